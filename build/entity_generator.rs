@@ -33,6 +33,10 @@ use crate::{
     XData,
     HatchPatternBoundaryData,
     HatchPatternLineData,
+    MLeaderArrowhead,
+    MLeaderBlockAttribute,
+    MLeaderContextData,
+    TableCell,
 };
 use crate::code_pair_put_back::CodePairPutBack;
 use crate::extension_data;
@@ -54,6 +58,13 @@ use crate::objects::*;
     generate_type_string(&mut fun, &element);
     generate_try_apply_code_pair(&mut fun, &element);
     generate_get_code_pairs(&mut fun, &element);
+    let types = element
+        .children
+        .iter()
+        .filter(|c| name(c) != "Entity" && name(c) != "DimensionBase")
+        .cloned()
+        .collect::<Vec<_>>();
+    fun.push_str(&methods_for_enum_pointers("EntityType", &types));
     fun.push_str("}\n");
 
     let mut file = File::create(generated_dir.join("entities.rs"))
@@ -156,9 +167,10 @@ fn generate_base_entity(fun: &mut String, element: &Element) {
             fun.push_str(&methods_for_pointer_access(p));
         }
     }
+    fun.push_str(&methods_for_pointers(&entity.children, &[]));
 
     ////////////////////////////////////////////////////// apply_individual_pair
-    fun.push_str("    pub(crate) fn apply_individual_pair(&mut self, pair: &CodePair, iter: &mut CodePairPutBack) -> DxfResult<()> {\n");
+    fun.push_str("    pub(crate) fn apply_individual_pair(&mut self, pair: &CodePair, iter: &mut CodePairPutBack) -> DxfResult<bool> {\n");
     fun.push_str("        match pair.code {\n");
     for c in &entity.children {
         if c.name == "Field" {
@@ -195,9 +207,9 @@ fn generate_base_entity(fun: &mut String, element: &Element) {
     fun.push_str("                let x = XData::read_item(pair.assert_string()?, iter)?;\n");
     fun.push_str("                self.x_data.push(x);\n");
     fun.push_str("            },\n");
-    fun.push_str("            _ => (), // unknown code, just ignore\n");
+    fun.push_str("            _ => return Ok(false), // unknown code\n");
     fun.push_str("        }\n");
-    fun.push_str("        Ok(())\n");
+    fun.push_str("        Ok(true)\n");
     fun.push_str("    }\n");
 
     ///////////////////////////////////////////////////////////// add_code_pairs
@@ -228,6 +240,15 @@ fn generate_entity_types(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str("    /// An entity type not otherwise supported by this library.  The code pairs specific to the\n");
+    fun.push_str("    /// entity are preserved as they were read so that the entity can be written back out unchanged.\n");
+    fun.push_str("    Unknown {\n");
+    fun.push_str("        type_string: String,\n");
+    fun.push_str("        pairs: Vec<CodePair>,\n");
+    fun.push_str("        /// The version of the drawing the entity was read from.  The entity is only written to\n");
+    fun.push_str("        /// drawings of this version or newer.\n");
+    fun.push_str("        version: AcadVersion,\n");
+    fun.push_str("    },\n");
     fun.push_str("}\n");
     fun.push('\n');
 
@@ -388,6 +409,9 @@ fn generate_implementation(fun: &mut String, element: &Element) {
             implementation.push_str(&methods_for_pointer_access(field));
         }
     }
+    if has_item_pointers(element) {
+        implementation.push_str(&methods_for_pointers(&element.children, &[]));
+    }
 
     if !implementation.is_empty() {
         fun.push_str(&format!("impl {typ} {{\n", typ = name(element)));
@@ -420,6 +444,7 @@ fn generate_is_supported_on_version(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            EntityType::Unknown { version: min_version, .. } => { version >= min_version },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -459,6 +484,7 @@ fn generate_type_string(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            EntityType::Unknown { ref type_string, .. } => { type_string },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -543,6 +569,7 @@ fn generate_try_apply_code_pair(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str("            EntityType::Unknown { .. } => { panic!(\"this case should have been covered in a custom reader\"); },\n");
     fun.push_str("        }\n");
     fun.push_str("        Ok(true)\n");
     fun.push_str("    }\n");
@@ -574,6 +601,7 @@ fn generate_get_code_pairs(fun: &mut String, element: &Element) {
             }
         }
     }
+    fun.push_str("            EntityType::Unknown { .. } => { panic!(\"this case should have been covered in a custom writer\"); },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -59,6 +59,13 @@ use chrono::{DateTime, Local};
     generate_type_string(&mut fun, &element);
     generate_try_apply_code_pair(&mut fun, &element);
     generate_write(&mut fun, &element);
+    let types = element
+        .children
+        .iter()
+        .filter(|c| name(c) != "Object")
+        .cloned()
+        .collect::<Vec<_>>();
+    fun.push_str(&methods_for_enum_pointers("ObjectType", &types));
     fun.push_str("}\n");
 
     let mut file = File::create(generated_dir.join("objects.rs")).ok().unwrap();
@@ -159,6 +166,7 @@ fn generate_base_object(fun: &mut String, element: &Element) {
             fun.push_str(&methods_for_pointer_access(p));
         }
     }
+    fun.push_str(&methods_for_pointers(&object.children, &[]));
 
     ////////////////////////////////////////////////////// apply_individual_pair
     fun.push_str("    pub(crate) fn apply_individual_pair(&mut self, pair: &CodePair, iter: &mut CodePairPutBack) -> DxfResult<bool> {\n");
@@ -234,6 +242,15 @@ fn generate_object_types(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str("    /// An object type not otherwise supported by this library.  The code pairs specific to the\n");
+    fun.push_str("    /// object are preserved as they were read so that the object can be written back out unchanged.\n");
+    fun.push_str("    Unknown {\n");
+    fun.push_str("        type_string: String,\n");
+    fun.push_str("        pairs: Vec<CodePair>,\n");
+    fun.push_str("        /// The version of the drawing the object was read from.  The object is only written to\n");
+    fun.push_str("        /// drawings of this version or newer.\n");
+    fun.push_str("        version: AcadVersion,\n");
+    fun.push_str("    },\n");
     fun.push_str("}\n");
     fun.push('\n');
 
@@ -376,6 +393,9 @@ fn generate_implementation(fun: &mut String, element: &Element) {
             implementation.push_str(&methods_for_pointer_access(field));
         }
     }
+    if has_item_pointers(element) {
+        implementation.push_str(&methods_for_pointers(&element.children, &[]));
+    }
 
     if !implementation.is_empty() {
         fun.push_str(&format!("impl {typ} {{\n", typ = name(element)));
@@ -411,6 +431,7 @@ fn generate_is_supported_on_version(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            ObjectType::Unknown { version: ref min_version, .. } => { version >= *min_version },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -446,6 +467,7 @@ fn generate_type_string(fun: &mut String, element: &Element) {
             ));
         }
     }
+    fun.push_str("            ObjectType::Unknown { ref type_string, .. } => { type_string },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 }
@@ -543,6 +565,7 @@ fn generate_try_apply_code_pair(fun: &mut String, element: &Element) {
         }
     }
 
+    fun.push_str("            ObjectType::Unknown { .. } => { panic!(\"this case should have been covered in a custom reader\"); },\n");
     fun.push_str("        }\n");
     fun.push_str("        Ok(true)\n");
     fun.push_str("    }\n");
@@ -588,6 +611,7 @@ fn generate_write(fun: &mut String, element: &Element) {
             }
         }
     }
+    fun.push_str("            ObjectType::Unknown { .. } => { panic!(\"this case should have been covered in a custom writer\"); },\n");
     fun.push_str("        }\n");
     fun.push_str("    }\n");
 
@@ -134,6 +134,10 @@ fn generate_table_items(fun: &mut String, element: &Element) {
         fun.push_str("    pub fn set_owner<'a>(&mut self, item: &'a mut DrawingItemMut, drawing: &'a mut Drawing) {\n");
         fun.push_str("        self.__owner_handle = drawing.assign_and_get_handle(item);\n");
         fun.push_str("    }\n");
+        fun.push_str(&methods_for_pointers(
+            &table_item.children,
+            &["__owner_handle"],
+        ));
         fun.push_str("}\n");
         fun.push('\n');
     }
@@ -336,6 +340,7 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
     fun.push_str("}\n");
     fun.push('\n');
 
+    let mut item_writers = String::new();
     for table in &element.children {
         let table_item = &table.children[0];
         fun.push_str("#[allow(clippy::cognitive_complexity)] // long function, no good way to simplify this\n");
@@ -367,6 +372,7 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
             "    for item in drawing.{collection}() {{\n",
             collection = attr(table, "Collection")
         ));
+        let body_start = fun.len();
         fun.push_str(&format!(
             "        pairs.push(CodePair::new_str(0, \"{type_string}\"));\n",
             type_string = attr(table, "TypeString")
@@ -491,12 +497,29 @@ fn generate_table_writer(fun: &mut String, element: &Element) {
         fun.push_str("            x.add_code_pairs(pairs, drawing.header.version);\n");
         fun.push_str("        }\n");
 
+        // the item's pairs are written by its own method
+        let body = fun
+            .split_off(body_start)
+            .replace("drawing.header.version", "version");
+        item_writers.push_str(&format!("impl {item_type} {{\n"));
+        item_writers.push_str("    pub(crate) fn add_code_pairs(&self, pairs: &mut Vec<CodePair>, version: AcadVersion, write_handles: bool) {\n");
+        item_writers.push_str("        let item = self;\n");
+        item_writers.push_str(&body);
+        item_writers.push_str("    }\n");
+        item_writers.push_str("}\n");
+        item_writers.push('\n');
+        fun.push_str(
+            "        item.add_code_pairs(pairs, drawing.header.version, write_handles);\n",
+        );
+
         fun.push_str("    }\n");
         fun.push('\n');
         fun.push_str("    pairs.push(CodePair::new_str(0, \"ENDTAB\"));\n");
         fun.push_str("}\n");
         fun.push('\n');
     }
+
+    fun.push_str(&item_writers);
 }
 
 fn load_xml() -> Element {
@@ -70,6 +70,92 @@ pub fn field_reader(element: &Element) -> String {
     }
 }
 
+/// Returns the pointers that refer to other items in the drawing.  Pointers with code 5 (sort
+/// handles) are excluded because they needn't refer to existing items.
+fn item_pointers(elements: &[Element]) -> Vec<&Element> {
+    let mut seen = vec![];
+    let mut pointers = vec![];
+    for element in elements {
+        if element.name == "Pointer" && code(element) != 5 && !seen.contains(&name(element)) {
+            seen.push(name(element));
+            pointers.push(element);
+        }
+    }
+    pointers
+}
+
+pub fn has_item_pointers(element: &Element) -> bool {
+    !item_pointers(&element.children).is_empty()
+}
+
+/// Generates the `pointers` and `pointers_mut` methods that return the handles referenced by the
+/// `extra_fields` and the pointers among `elements`.
+pub fn methods_for_pointers(elements: &[Element], extra_fields: &[&str]) -> String {
+    let pointers = item_pointers(elements);
+    let mut fun = String::new();
+    for (suffix, reference, single, multiple) in [
+        ("", "&self", "Handle", "self.{}.iter().copied()"),
+        ("_mut", "&mut self", "&mut Handle", "self.{}.iter_mut()"),
+    ] {
+        let borrow = if suffix.is_empty() { "" } else { "&mut " };
+        fun.push_str(&format!(
+            "    pub(crate) fn pointers{suffix}({reference}) -> Vec<{single}> {{\n"
+        ));
+        let mut singles = vec![];
+        let mut multiples = vec![];
+        for field in extra_fields {
+            singles.push(format!("{borrow}self.{field}"));
+        }
+        for pointer in &pointers {
+            let field = format!("__{}_handle", name(pointer));
+            if allow_multiples(pointer) {
+                multiples.push(multiple.replace("{}", &field));
+            } else {
+                singles.push(format!("{borrow}self.{field}"));
+            }
+        }
+        if multiples.is_empty() {
+            fun.push_str(&format!("        vec![{}]\n", singles.join(", ")));
+        } else {
+            fun.push_str(&format!(
+                "        let mut pointers = vec![{}];\n",
+                singles.join(", ")
+            ));
+            for multiple in &multiples {
+                fun.push_str(&format!("        pointers.extend({multiple});\n"));
+            }
+            fun.push_str("        pointers\n");
+        }
+        fun.push_str("    }\n");
+    }
+    fun
+}
+
+/// Generates the `pointers` and `pointers_mut` methods of the `enum_name` enum, which defer to the
+/// specific types in `types` that have pointers.
+pub fn methods_for_enum_pointers(enum_name: &str, types: &[Element]) -> String {
+    let mut fun = String::new();
+    for (suffix, reference, single, binding) in [
+        ("", "&self", "Handle", "ref"),
+        ("_mut", "&mut self", "&mut Handle", "ref mut"),
+    ] {
+        fun.push_str(&format!(
+            "    pub(crate) fn pointers{suffix}({reference}) -> Vec<{single}> {{\n"
+        ));
+        fun.push_str("        match *self {\n");
+        for typ in types.iter().filter(|t| has_item_pointers(t)) {
+            fun.push_str(&format!(
+                "            {enum_name}::{typ}({binding} item) => item.pointers{suffix}(),\n",
+                typ = name(typ)
+            ));
+        }
+        fun.push_str("            _ => vec![],\n");
+        fun.push_str("        }\n");
+        fun.push_str("    }\n");
+    }
+    fun
+}
+
 pub fn methods_for_pointer_access(pointer: &Element) -> String {
     let mut fun = String::new();
     let typ = attr(pointer, "Type");
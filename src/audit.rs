@@ -0,0 +1,899 @@
+// Structural validation of a `Drawing`
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::entities::*;
+use crate::enums::AcadVersion;
+use crate::objects::ObjectType;
+use crate::{Block, CodePair, Drawing, Handle, SplineError};
+
+/// A structural problem found by `Drawing::audit`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuditIssue {
+    /// More than one item has the specified handle.
+    DuplicateHandle(Handle),
+    /// `$HANDSEED` isn't greater than the largest handle in use.
+    HandleSeedTooLow {
+        handle_seed: Handle,
+        maximum_handle: Handle,
+    },
+    /// An item points to a handle that doesn't belong to any item.
+    DanglingPointer { item: Handle, pointer: Handle },
+    /// An entity or block is on a layer that doesn't exist.
+    MissingLayer { item: Handle, name: String },
+    /// An entity uses a line type that doesn't exist.
+    MissingLineType { item: Handle, name: String },
+    /// A text entity uses a text style that doesn't exist.
+    MissingTextStyle { item: Handle, name: String },
+    /// A dimension, leader, or tolerance uses a dimension style that doesn't exist.
+    MissingDimensionStyle { item: Handle, name: String },
+    /// An `Insert` names a block that doesn't exist.
+    MissingBlock { item: Handle, name: String },
+    /// A `Spline`'s control points, knots, and weights don't define a valid curve.
+    InvalidSpline { item: Handle, error: SplineError },
+    /// An entity type that isn't supported by `$ACADVER`, so it won't be saved.
+    UnsupportedEntity { item: Handle, type_string: String },
+    /// An object type that isn't supported by `$ACADVER`, so it won't be saved.
+    UnsupportedObject { item: Handle, type_string: String },
+    /// An entity property was set that can't be saved with `$ACADVER`.
+    UnsupportedField {
+        item: Handle,
+        field: &'static str,
+        minimum_version: AcadVersion,
+    },
+}
+
+impl AuditIssue {
+    /// Returns `true` if `Drawing::audit_and_fix` can fix the issue.  The others can only be
+    /// resolved by changing `$ACADVER` or the item.
+    pub fn is_fixable(&self) -> bool {
+        !matches!(
+            self,
+            AuditIssue::InvalidSpline { .. }
+                | AuditIssue::UnsupportedEntity { .. }
+                | AuditIssue::UnsupportedObject { .. }
+                | AuditIssue::UnsupportedField { .. }
+        )
+    }
+}
+
+impl fmt::Display for AuditIssue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AuditIssue::DuplicateHandle(h) => {
+                write!(
+                    formatter,
+                    "the handle {} is used more than once",
+                    h.as_string()
+                )
+            }
+            AuditIssue::HandleSeedTooLow {
+                handle_seed,
+                maximum_handle,
+            } => write!(
+                formatter,
+                "the handle seed {} isn't greater than the largest handle {}",
+                handle_seed.as_string(),
+                maximum_handle.as_string()
+            ),
+            AuditIssue::DanglingPointer { item, pointer } => write!(
+                formatter,
+                "item {} points to the missing item {}",
+                item.as_string(),
+                pointer.as_string()
+            ),
+            AuditIssue::MissingLayer { item, ref name } => {
+                write!(
+                    formatter,
+                    "item {} is on the missing layer '{name}'",
+                    item.as_string()
+                )
+            }
+            AuditIssue::MissingLineType { item, ref name } => write!(
+                formatter,
+                "item {} uses the missing line type '{name}'",
+                item.as_string()
+            ),
+            AuditIssue::MissingTextStyle { item, ref name } => write!(
+                formatter,
+                "item {} uses the missing text style '{name}'",
+                item.as_string()
+            ),
+            AuditIssue::MissingDimensionStyle { item, ref name } => write!(
+                formatter,
+                "item {} uses the missing dimension style '{name}'",
+                item.as_string()
+            ),
+            AuditIssue::MissingBlock { item, ref name } => write!(
+                formatter,
+                "item {} inserts the missing block '{name}'",
+                item.as_string()
+            ),
+            AuditIssue::InvalidSpline { item, ref error } => {
+                write!(formatter, "spline {} is invalid: {error}", item.as_string())
+            }
+            AuditIssue::UnsupportedEntity {
+                item,
+                ref type_string,
+            } => write!(
+                formatter,
+                "entity {} of type {type_string} isn't supported by the drawing version",
+                item.as_string()
+            ),
+            AuditIssue::UnsupportedObject {
+                item,
+                ref type_string,
+            } => write!(
+                formatter,
+                "object {} of type {type_string} isn't supported by the drawing version",
+                item.as_string()
+            ),
+            AuditIssue::UnsupportedField {
+                item,
+                field,
+                minimum_version,
+            } => write!(
+                formatter,
+                "entity {} sets {field}, which requires {minimum_version:?}",
+                item.as_string()
+            ),
+        }
+    }
+}
+
+impl Drawing {
+    /// Checks the `Drawing` for structural problems that would produce an invalid file, such as
+    /// duplicate handles, pointers to items that don't exist, and references by name to tables
+    /// entries or blocks that don't exist.
+    ///
+    /// Pointers are checked wherever they're stored, including the content of a `MLeader`, the
+    /// cells of a `Table`, and the code pairs of entities and objects that aren't otherwise
+    /// supported.  Pointers in extension data and XDATA aren't checked.
+    pub fn audit(&self) -> Vec<AuditIssue> {
+        let mut issues = vec![];
+
+        // handles
+        let handles = self.all_item_handles();
+        let mut seen = HashSet::new();
+        let mut duplicates = HashSet::new();
+        for &handle in &handles {
+            if !seen.insert(handle) && duplicates.insert(handle) {
+                issues.push(AuditIssue::DuplicateHandle(handle));
+            }
+        }
+        if let Some(&maximum_handle) = handles.iter().max_by_key(|h| h.0) {
+            if self.header.next_available_handle.0 <= maximum_handle.0 {
+                issues.push(AuditIssue::HandleSeedTooLow {
+                    handle_seed: self.header.next_available_handle,
+                    maximum_handle,
+                });
+            }
+        }
+
+        // pointers
+        for (item, pointers) in self.all_item_pointers() {
+            for pointer in pointers {
+                if !pointer.is_empty() && !seen.contains(&pointer) {
+                    issues.push(AuditIssue::DanglingPointer { item, pointer });
+                }
+            }
+        }
+
+        // references by name
+        for block in self.blocks() {
+            if !self.has_layer(&block.layer) {
+                issues.push(AuditIssue::MissingLayer {
+                    item: block.handle,
+                    name: block.layer.clone(),
+                });
+            }
+        }
+        for entity in self.all_entities() {
+            issues.extend(self.audit_entity(entity));
+        }
+
+        // version
+        let version = self.header.version;
+        for entity in self.all_entities() {
+            let item = entity.common.handle;
+            if !entity.specific.is_supported_on_version(version) {
+                issues.push(AuditIssue::UnsupportedEntity {
+                    item,
+                    type_string: String::from(entity.specific.to_type_string()),
+                });
+            }
+            for (field, minimum_version) in unsupported_fields(&entity.common, version) {
+                issues.push(AuditIssue::UnsupportedField {
+                    item,
+                    field,
+                    minimum_version,
+                });
+            }
+        }
+        for object in self.objects() {
+            if !object.specific.is_supported_on_version(version) {
+                issues.push(AuditIssue::UnsupportedObject {
+                    item: object.common.handle,
+                    type_string: String::from(object.specific.to_type_string()),
+                });
+            }
+        }
+
+        issues
+    }
+    /// Checks the `Drawing` for structural problems as with `audit()`, and fixes them where
+    /// possible; see `AuditIssue::is_fixable`.  All of the issues that were found are returned.
+    ///
+    /// Duplicate handles are replaced with new ones, `$HANDSEED` is raised past the largest
+    /// handle, dangling pointers are cleared, and missing table entries and blocks are added with
+    /// default values.
+    pub fn audit_and_fix(&mut self) -> Vec<AuditIssue> {
+        let issues = self.audit();
+        if !issues.iter().any(|i| i.is_fixable()) {
+            return issues;
+        }
+
+        // give every duplicate a new handle
+        let maximum_handle = self.all_item_handles().into_iter().max_by_key(|h| h.0);
+        let mut next_handle = match maximum_handle {
+            Some(h) if h.0 >= self.header.next_available_handle.0 => h.next_handle_value(),
+            _ => self.header.next_available_handle,
+        };
+        let mut seen = HashSet::new();
+        self.for_each_item_handle_mut(&mut |handle| {
+            if !seen.insert(*handle) {
+                *handle = next_handle;
+                seen.insert(next_handle);
+                next_handle = next_handle.next_handle_value();
+            }
+        });
+        self.header.next_available_handle = next_handle;
+
+        // clear dangling pointers
+        self.for_each_item_pointer_mut(&mut |pointer| {
+            if !pointer.is_empty() && !seen.contains(pointer) {
+                *pointer = Handle::empty();
+            }
+        });
+
+        // add whatever is missing
+        for issue in &issues {
+            match *issue {
+                AuditIssue::MissingLayer { ref name, .. } => self.ensure_layer_is_present(name),
+                AuditIssue::MissingLineType { ref name, .. } => {
+                    self.ensure_line_type_is_present(name)
+                }
+                AuditIssue::MissingTextStyle { ref name, .. } => {
+                    self.ensure_text_style_is_present(name)
+                }
+                AuditIssue::MissingDimensionStyle { ref name, .. } => {
+                    self.ensure_dimension_style_is_present(name)
+                }
+                // several inserts may name the same missing block
+                AuditIssue::MissingBlock { ref name, .. }
+                    if !self.blocks().any(|b| b.name.eq_ignore_ascii_case(name)) =>
+                {
+                    self.add_block(Block {
+                        name: name.clone(),
+                        ..Default::default()
+                    });
+                    self.ensure_block_record_is_present(name);
+                }
+                _ => (),
+            }
+        }
+
+        issues
+    }
+    fn has_layer(&self, name: &str) -> bool {
+        self.layers().any(|l| l.name.eq_ignore_ascii_case(name))
+    }
+    fn audit_entity(&self, entity: &Entity) -> Vec<AuditIssue> {
+        let mut issues = vec![];
+        let item = entity.common.handle;
+        if !self.has_layer(&entity.common.layer) {
+            issues.push(AuditIssue::MissingLayer {
+                item,
+                name: entity.common.layer.clone(),
+            });
+        }
+
+        let line_type_name = &entity.common.line_type_name;
+        if !line_type_name.is_empty()
+            && !line_type_name.eq_ignore_ascii_case("BYLAYER")
+            && !line_type_name.eq_ignore_ascii_case("BYBLOCK")
+            && !self
+                .line_types()
+                .any(|lt| lt.name.eq_ignore_ascii_case(line_type_name))
+        {
+            issues.push(AuditIssue::MissingLineType {
+                item,
+                name: line_type_name.clone(),
+            });
+        }
+
+        let text_style_name = match entity.specific {
+            EntityType::ArcAlignedText(ref e) => Some(&e.text_style_name),
+            EntityType::Attribute(ref e) => Some(&e.text_style_name),
+            EntityType::AttributeDefinition(ref e) => Some(&e.text_style_name),
+            EntityType::MText(ref e) => Some(&e.text_style_name),
+            EntityType::Text(ref e) => Some(&e.text_style_name),
+            _ => None,
+        };
+        if let Some(name) = text_style_name {
+            if !self.styles().any(|s| s.name.eq_ignore_ascii_case(name)) {
+                issues.push(AuditIssue::MissingTextStyle {
+                    item,
+                    name: name.clone(),
+                });
+            }
+        }
+
+        let dim_style_name = match entity.specific {
+            EntityType::RotatedDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
+            EntityType::RadialDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
+            EntityType::DiameterDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
+            EntityType::AngularThreePointDimension(ref d) => {
+                Some(&d.dimension_base.dimension_style_name)
+            }
+            EntityType::OrdinateDimension(ref d) => Some(&d.dimension_base.dimension_style_name),
+            EntityType::Leader(ref l) => Some(&l.dimension_style_name),
+            EntityType::Tolerance(ref t) => Some(&t.dimension_style_name),
+            _ => None,
+        };
+        if let Some(name) = dim_style_name {
+            if !self.dim_styles().any(|d| d.name.eq_ignore_ascii_case(name)) {
+                issues.push(AuditIssue::MissingDimensionStyle {
+                    item,
+                    name: name.clone(),
+                });
+            }
+        }
+
+        if let EntityType::Insert(ref insert) = entity.specific {
+            if !self
+                .blocks()
+                .any(|b| b.name.eq_ignore_ascii_case(&insert.name))
+            {
+                issues.push(AuditIssue::MissingBlock {
+                    item,
+                    name: insert.name.clone(),
+                });
+            }
+        }
+
+        // splines defined only by fit points have no control points to check
+        if let EntityType::Spline(ref spline) = entity.specific {
+            if !spline.control_points.is_empty() {
+                if let Err(error) = spline.validate() {
+                    issues.push(AuditIssue::InvalidSpline { item, error });
+                }
+            }
+        }
+
+        issues
+    }
+    /// Returns the entities of the drawing followed by the entities of every block.
+    fn all_entities(&self) -> impl Iterator<Item = &Entity> {
+        self.entities()
+            .chain(self.blocks().flat_map(|b| b.entities.iter()))
+    }
+    /// Returns the non-empty handles of every item, including the entities in blocks and the
+    /// attributes, vertices, and `Seqend`s of entities.
+    fn all_item_handles(&self) -> Vec<Handle> {
+        let mut handles = vec![];
+        handles.extend(self.app_ids().map(|i| i.handle));
+        handles.extend(self.block_records().map(|i| i.handle));
+        handles.extend(self.dim_styles().map(|i| i.handle));
+        handles.extend(self.layers().map(|i| i.handle));
+        handles.extend(self.line_types().map(|i| i.handle));
+        handles.extend(self.styles().map(|i| i.handle));
+        handles.extend(self.ucss().map(|i| i.handle));
+        handles.extend(self.views().map(|i| i.handle));
+        handles.extend(self.view_ports().map(|i| i.handle));
+        handles.extend(self.blocks().map(|b| b.handle));
+        for entity in self.all_entities() {
+            handles.push(entity.common.handle);
+            match entity.specific {
+                EntityType::Insert(ref insert) => {
+                    handles.extend(insert.__attributes_and_handles.iter().map(|(_, h)| *h));
+                    handles.push(insert.__seqend_handle);
+                }
+                EntityType::Polyline(ref poly) => {
+                    handles.extend(poly.__vertices_and_handles.iter().map(|(_, h)| *h));
+                    handles.push(poly.__seqend_handle);
+                }
+                _ => (),
+            }
+        }
+        handles.extend(self.objects().map(|o| o.common.handle));
+        handles.retain(|h| !h.is_empty());
+        handles
+    }
+    /// Calls `f` with the non-empty handles of every item in the same order as
+    /// `all_item_handles()`.
+    pub(crate) fn for_each_item_handle_mut(&mut self, f: &mut dyn FnMut(&mut Handle)) {
+        let mut visit = |handle: &mut Handle| {
+            if !handle.is_empty() {
+                f(handle);
+            }
+        };
+        self.app_ids_mut().for_each(|i| visit(&mut i.handle));
+        self.block_records_mut().for_each(|i| visit(&mut i.handle));
+        self.dim_styles_mut().for_each(|i| visit(&mut i.handle));
+        self.layers_mut().for_each(|i| visit(&mut i.handle));
+        self.line_types_mut().for_each(|i| visit(&mut i.handle));
+        self.styles_mut().for_each(|i| visit(&mut i.handle));
+        self.ucss_mut().for_each(|i| visit(&mut i.handle));
+        self.views_mut().for_each(|i| visit(&mut i.handle));
+        self.view_ports_mut().for_each(|i| visit(&mut i.handle));
+        self.blocks_mut().for_each(|b| visit(&mut b.handle));
+        let mut visit_entity = |entity: &mut Entity| {
+            visit(&mut entity.common.handle);
+            match entity.specific {
+                EntityType::Insert(ref mut insert) => {
+                    for (_, handle) in insert.__attributes_and_handles.iter_mut() {
+                        visit(handle);
+                    }
+                    visit(&mut insert.__seqend_handle);
+                }
+                EntityType::Polyline(ref mut poly) => {
+                    for (_, handle) in poly.__vertices_and_handles.iter_mut() {
+                        visit(handle);
+                    }
+                    visit(&mut poly.__seqend_handle);
+                }
+                _ => (),
+            }
+        };
+        self.entities_mut().for_each(&mut visit_entity);
+        self.blocks_mut()
+            .flat_map(|b| b.entities.iter_mut())
+            .for_each(&mut visit_entity);
+        self.objects_mut().for_each(|o| visit(&mut o.common.handle));
+    }
+    /// Returns the handle of every item that has pointers, along with the pointers.
+    fn all_item_pointers(&self) -> Vec<(Handle, Vec<Handle>)> {
+        let mut pointers = vec![];
+        pointers.extend(self.app_ids().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.block_records().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.dim_styles().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.layers().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.line_types().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.styles().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.ucss().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.views().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.view_ports().map(|i| (i.handle, i.pointers())));
+        pointers.extend(self.blocks().map(|b| (b.handle, vec![b.__owner_handle])));
+        for entity in self.all_entities() {
+            let mut entity_pointers = entity.common.pointers();
+            entity_pointers.extend(entity.specific.pointers());
+            entity_pointers.extend(custom_entity_pointers(&entity.specific));
+            pointers.push((entity.common.handle, entity_pointers));
+            if let EntityType::Insert(ref insert) = entity.specific {
+                for (attribute, handle) in &insert.__attributes_and_handles {
+                    pointers.push((*handle, attribute.pointers()));
+                }
+            }
+        }
+        for object in self.objects() {
+            let mut object_pointers = object.common.pointers();
+            object_pointers.extend(object.specific.pointers());
+            if let ObjectType::Unknown { ref pairs, .. } = object.specific {
+                object_pointers.extend(pair_pointers(pairs));
+            }
+            pointers.push((object.common.handle, object_pointers));
+        }
+        pointers
+    }
+    /// Calls `f` with every pointer in the same order as `all_item_pointers()`.
+    pub(crate) fn for_each_item_pointer_mut(&mut self, f: &mut dyn FnMut(&mut Handle)) {
+        self.app_ids_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.block_records_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.dim_styles_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.layers_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.line_types_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.styles_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.ucss_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.views_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.view_ports_mut()
+            .for_each(|i| i.pointers_mut().into_iter().for_each(&mut *f));
+        self.blocks_mut().for_each(|b| f(&mut b.__owner_handle));
+        let mut visit_entity = |entity: &mut Entity| {
+            entity.common.pointers_mut().into_iter().for_each(&mut *f);
+            if let EntityType::Insert(ref mut insert) = entity.specific {
+                for (attribute, _) in insert.__attributes_and_handles.iter_mut() {
+                    attribute.pointers_mut().into_iter().for_each(&mut *f);
+                }
+            }
+            entity.specific.pointers_mut().into_iter().for_each(&mut *f);
+            for_each_custom_entity_pointer_mut(&mut entity.specific, &mut *f);
+        };
+        self.entities_mut().for_each(&mut visit_entity);
+        self.blocks_mut()
+            .flat_map(|b| b.entities.iter_mut())
+            .for_each(&mut visit_entity);
+        self.objects_mut().for_each(|o| {
+            o.common.pointers_mut().into_iter().for_each(&mut *f);
+            o.specific.pointers_mut().into_iter().for_each(&mut *f);
+            if let ObjectType::Unknown { ref mut pairs, .. } = o.specific {
+                for_each_pair_pointer_mut(pairs, &mut *f);
+            }
+        });
+    }
+}
+
+/// Returns the pointers of an entity that aren't declared by its type, i.e., those of the content
+/// of a `MLeader`, of the cells of a `Table`, and in the code pairs of an unknown entity.
+fn custom_entity_pointers(specific: &EntityType) -> Vec<Handle> {
+    let mut pointers = vec![];
+    match *specific {
+        EntityType::MLeader(ref mleader) => {
+            let context = &mleader.context_data;
+            if let Some(ref text) = context.text_content {
+                pointers.push(text.__text_style_handle);
+            }
+            if let Some(ref block) = context.block_content {
+                pointers.push(block.__block_record_handle);
+            }
+            pointers.extend(
+                mleader
+                    .arrowhead_overrides
+                    .iter()
+                    .map(|a| a.__arrowhead_handle),
+            );
+            pointers.extend(
+                mleader
+                    .block_attributes
+                    .iter()
+                    .map(|a| a.__attribute_definition_handle),
+            );
+        }
+        EntityType::Table(ref table) => {
+            for cell in &table.cells {
+                pointers.push(cell.__field_handle);
+                pointers.push(cell.__block_record_handle);
+                pointers.extend(
+                    cell.attribute_values
+                        .iter()
+                        .map(|a| a.__attribute_definition_handle),
+                );
+                pointers.extend(pair_pointers(&cell.unknown_pairs));
+            }
+            pointers.extend(pair_pointers(&table.table_style_overrides));
+        }
+        EntityType::Unknown { ref pairs, .. } => pointers.extend(pair_pointers(pairs)),
+        _ => (),
+    }
+    pointers
+}
+
+/// Calls `f` with every pointer in the same order as `custom_entity_pointers()`.
+fn for_each_custom_entity_pointer_mut(specific: &mut EntityType, f: &mut dyn FnMut(&mut Handle)) {
+    match *specific {
+        EntityType::MLeader(ref mut mleader) => {
+            let context = &mut mleader.context_data;
+            if let Some(ref mut text) = context.text_content {
+                f(&mut text.__text_style_handle);
+            }
+            if let Some(ref mut block) = context.block_content {
+                f(&mut block.__block_record_handle);
+            }
+            for arrowhead in mleader.arrowhead_overrides.iter_mut() {
+                f(&mut arrowhead.__arrowhead_handle);
+            }
+            for attribute in mleader.block_attributes.iter_mut() {
+                f(&mut attribute.__attribute_definition_handle);
+            }
+        }
+        EntityType::Table(ref mut table) => {
+            for cell in table.cells.iter_mut() {
+                f(&mut cell.__field_handle);
+                f(&mut cell.__block_record_handle);
+                for attribute in cell.attribute_values.iter_mut() {
+                    f(&mut attribute.__attribute_definition_handle);
+                }
+                for_each_pair_pointer_mut(&mut cell.unknown_pairs, f);
+            }
+            for_each_pair_pointer_mut(&mut table.table_style_overrides, f);
+        }
+        EntityType::Unknown { ref mut pairs, .. } => for_each_pair_pointer_mut(pairs, f),
+        _ => (),
+    }
+}
+
+/// Returns `true` for the codes of handles that point to other items in the same drawing.  The
+/// handles of codes 320-329 and 1005 aren't translated when items are copied, so they're left
+/// alone.
+fn is_pointer_code(code: i32) -> bool {
+    matches!(code, 330..=369 | 390..=399 | 480..=481)
+}
+
+/// Returns the pointers in `pairs`, which were preserved as they were read.
+fn pair_pointers(pairs: &[CodePair]) -> impl Iterator<Item = Handle> + '_ {
+    pairs
+        .iter()
+        .filter(|p| is_pointer_code(p.code))
+        .filter_map(|p| p.as_handle().ok())
+}
+
+/// Calls `f` with every pointer in `pairs` in the same order as `pair_pointers()`, and replaces
+/// the pairs whose pointer was changed.
+fn for_each_pair_pointer_mut(pairs: &mut [CodePair], f: &mut dyn FnMut(&mut Handle)) {
+    for pair in pairs.iter_mut().filter(|p| is_pointer_code(p.code)) {
+        if let Ok(original) = pair.as_handle() {
+            let mut handle = original;
+            f(&mut handle);
+            if handle != original {
+                *pair = CodePair::new_string(pair.code, &handle.as_string());
+            }
+        }
+    }
+}
+
+/// Returns the entity properties that are set but can't be saved with `version`.
+pub(crate) fn unsupported_fields(
+    common: &EntityCommon,
+    version: AcadVersion,
+) -> Vec<(&'static str, AcadVersion)> {
+    let set_fields = [
+        (!common.is_visible, "is_visible", AcadVersion::R13),
+        (
+            common.line_type_scale != 1.0,
+            "line_type_scale",
+            AcadVersion::R13,
+        ),
+        (
+            common.lineweight_enum_value != 0,
+            "lineweight_enum_value",
+            AcadVersion::R2000,
+        ),
+        (common.color_24_bit != 0, "color_24_bit", AcadVersion::R2004),
+        (
+            !common.color_name.is_empty(),
+            "color_name",
+            AcadVersion::R2004,
+        ),
+        (common.transparency != 0, "transparency", AcadVersion::R2004),
+    ];
+    set_fields
+        .iter()
+        .filter(|(is_set, _, minimum_version)| *is_set && version < *minimum_version)
+        .map(|(_, field, minimum_version)| (*field, *minimum_version))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::objects::*;
+    use crate::*;
+
+    fn line() -> Entity {
+        Entity::new(EntityType::Line(Line::new(
+            Point::origin(),
+            Point::new(1.0, 1.0, 0.0),
+        )))
+    }
+
+    #[test]
+    fn new_drawing_has_no_issues() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        drawing.add_entity(line());
+        drawing.normalize();
+        assert_eq!(Vec::<AuditIssue>::new(), drawing.audit());
+    }
+
+    #[test]
+    fn duplicate_handles_are_replaced() {
+        let mut drawing = Drawing::new();
+        let first = drawing.add_entity(line()).common.handle;
+        drawing.add_entity(line());
+        drawing.entities_mut().nth(1).unwrap().common.handle = first;
+        let issues = drawing.audit_and_fix();
+        assert!(issues.contains(&AuditIssue::DuplicateHandle(first)));
+
+        let handles = drawing
+            .entities()
+            .map(|e| e.common.handle)
+            .collect::<Vec<_>>();
+        assert_eq!(first, handles[0]);
+        assert_ne!(first, handles[1]);
+        assert!(handles[1].0 < drawing.header.next_available_handle.0);
+        assert!(drawing.audit().is_empty());
+    }
+
+    #[test]
+    fn handle_seed_is_raised() {
+        let mut drawing = Drawing::new();
+        let handle = drawing.add_entity(line()).common.handle;
+        drawing.header.next_available_handle = Handle(1);
+        let issues = drawing.audit_and_fix();
+        assert!(issues.contains(&AuditIssue::HandleSeedTooLow {
+            handle_seed: Handle(1),
+            maximum_handle: handle,
+        }));
+        assert_eq!(
+            handle.next_handle_value(),
+            drawing.header.next_available_handle
+        );
+        assert!(drawing.audit().is_empty());
+    }
+
+    #[test]
+    fn dangling_pointers_are_cleared() {
+        let mut drawing = Drawing::new();
+        let mut entity = line();
+        entity.common.__material_handle = Handle(0xDEAD);
+        let item = drawing.add_entity(entity).common.handle;
+        let issues = drawing.audit_and_fix();
+        assert_eq!(
+            vec![AuditIssue::DanglingPointer {
+                item,
+                pointer: Handle(0xDEAD),
+            }],
+            issues
+        );
+        assert!(drawing
+            .entities()
+            .next()
+            .unwrap()
+            .common
+            .__material_handle
+            .is_empty());
+    }
+
+    #[test]
+    fn dangling_pointers_outside_of_the_spec_are_cleared() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        let mut mleader = MLeader::default();
+        mleader.arrowhead_overrides.push(MLeaderArrowhead {
+            index: 0,
+            __arrowhead_handle: Handle(0xDEAD),
+        });
+        let mleader = drawing
+            .add_entity(Entity::new(EntityType::MLeader(mleader)))
+            .common
+            .handle;
+        let mut table = Table::default();
+        table.cells.push(TableCell {
+            __block_record_handle: Handle(0xBEEF),
+            ..Default::default()
+        });
+        let table = drawing
+            .add_entity(Entity::new(EntityType::Table(table)))
+            .common
+            .handle;
+        let unknown = drawing
+            .add_object(Object::new(ObjectType::Unknown {
+                type_string: String::from("UNKNOWN_OBJECT"),
+                pairs: vec![CodePair::new_str(340, "CAFE")],
+                version: AcadVersion::R2018,
+            }))
+            .common
+            .handle;
+
+        let issues = drawing.audit_and_fix();
+        assert_eq!(
+            vec![
+                AuditIssue::DanglingPointer {
+                    item: mleader,
+                    pointer: Handle(0xDEAD),
+                },
+                AuditIssue::DanglingPointer {
+                    item: table,
+                    pointer: Handle(0xBEEF),
+                },
+                AuditIssue::DanglingPointer {
+                    item: unknown,
+                    pointer: Handle(0xCAFE),
+                },
+            ],
+            issues
+        );
+        assert!(drawing.audit().is_empty());
+        let entities = drawing.entities().collect::<Vec<_>>();
+        match entities[0].specific {
+            EntityType::MLeader(ref mleader) => {
+                assert!(mleader.arrowhead_overrides[0].__arrowhead_handle.is_empty())
+            }
+            _ => panic!("expected a multileader"),
+        }
+        match entities[1].specific {
+            EntityType::Table(ref table) => {
+                assert!(table.cells[0].__block_record_handle.is_empty())
+            }
+            _ => panic!("expected a table"),
+        }
+        match drawing.objects().last().unwrap().specific {
+            ObjectType::Unknown { ref pairs, .. } => {
+                assert_eq!(vec![CodePair::new_str(340, "0")], *pairs)
+            }
+            _ => panic!("expected an unknown object"),
+        }
+    }
+
+    #[test]
+    fn missing_references_are_added() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line());
+        drawing.add_entity(Entity::new(EntityType::Insert(Insert::default())));
+
+        // the entities are changed after being added so the references aren't added for them
+        for entity in drawing.entities_mut() {
+            entity.common.layer = String::from("new-layer");
+            entity.common.line_type_name = String::from("new-line-type");
+            if let EntityType::Insert(ref mut insert) = entity.specific {
+                insert.name = String::from("new-block");
+            }
+        }
+        let issues = drawing.audit_and_fix();
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, AuditIssue::MissingLayer { name, .. } if name == "new-layer")));
+        assert!(issues.iter().any(
+            |i| matches!(i, AuditIssue::MissingLineType { name, .. } if name == "new-line-type")
+        ));
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, AuditIssue::MissingBlock { name, .. } if name == "new-block")));
+
+        assert!(drawing.layers().any(|l| l.name == "new-layer"));
+        assert!(drawing.line_types().any(|l| l.name == "new-line-type"));
+        assert!(drawing.blocks().any(|b| b.name == "new-block"));
+        assert!(drawing.audit().is_empty());
+    }
+
+    #[test]
+    fn fields_unsupported_by_version_are_reported_but_not_fixed() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R14;
+        let mut entity = line();
+        entity.common.transparency = 50;
+        let item = drawing.add_entity(entity).common.handle;
+        let expected = vec![AuditIssue::UnsupportedField {
+            item,
+            field: "transparency",
+            minimum_version: AcadVersion::R2004,
+        }];
+        assert_eq!(expected, drawing.audit_and_fix());
+        assert_eq!(expected, drawing.audit());
+    }
+
+    #[test]
+    fn invalid_splines_are_reported() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        let spline = Spline {
+            degree_of_curve: 1,
+            control_points: vec![Point::origin(), Point::new(1.0, 0.0, 0.0)],
+            knot_values: vec![0.0, 0.0, 1.0],
+            ..Default::default()
+        };
+        let item = drawing
+            .add_entity(Entity::new(EntityType::Spline(spline)))
+            .common
+            .handle;
+        drawing.normalize();
+        let expected = vec![AuditIssue::InvalidSpline {
+            item,
+            error: SplineError::WrongKnotCount {
+                expected: 4,
+                actual: 3,
+            },
+        }];
+        assert_eq!(expected, drawing.audit_and_fix());
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{
-    CodePair, CodePairValue, Drawing, DrawingItem, DrawingItemMut, DxfError, DxfResult,
-    ExtensionGroup, Handle, Point, XData,
+    BoundingBox, CodePair, CodePairValue, Drawing, DrawingItem, DrawingItemMut, DxfError,
+    DxfResult, ExtensionGroup, Handle, Point, XData,
 };
 
+use crate::bounding_box::Bounds;
+
 use crate::code_pair_put_back::CodePairPutBack;
 use crate::entities::Entity;
 use crate::entity_iter::EntityIter;
@@ -91,6 +93,15 @@ impl Block {
     pub fn set_is_resolved_external_reference(&mut self, val: bool) {
         self.set_flag(64, val)
     }
+    /// Returns the extents of the block's entities in block coordinates, or `None` if the block
+    /// has no measurable geometry.  The `drawing` is used to resolve nested `Insert` entities.
+    pub fn bounding_box(&self, drawing: &Drawing) -> Option<BoundingBox> {
+        let mut bounds = Bounds::default();
+        for ent in &self.entities {
+            bounds.add_entity(ent, drawing);
+        }
+        bounds.result
+    }
     /// Ensure all values are valid.
     pub fn normalize(&mut self) {
         default_if_empty(&mut self.layer, "0");
@@ -154,10 +165,22 @@ impl Block {
                             }
                             break;
                         }
+                        CodePair {
+                            code: 0,
+                            value: CodePairValue::Str(ref s),
+                            ..
+                        } if s == "ENDSEC" || s == "EOF" => {
+                            // the block was never ended; leave the pair for the section reader
+                            iter.put_back(Ok(pair.clone()));
+                            return Err(DxfError::UnexpectedCodePair(
+                                pair,
+                                String::from("expected 0/ENDBLK"),
+                            ));
+                        }
                         CodePair { code: 0, .. } => {
                             // should be an entity
                             iter.put_back(Ok(pair));
-                            let mut iter = EntityIter { iter };
+                            let mut iter = EntityIter::new(iter);
                             iter.read_entities_into_vec(&mut current.entities)?;
                         }
                         _ => {
@@ -434,8 +457,14 @@ mod tests {
             CodePair::new_str(0, "UNSUPPORTED_ENTITY"),
             CodePair::new_str(0, "LINE"),
         ]);
-        assert_eq!(1, block.entities.len());
+        assert_eq!(2, block.entities.len());
         match block.entities[0].specific {
+            EntityType::Unknown {
+                ref type_string, ..
+            } => assert_eq!("UNSUPPORTED_ENTITY", type_string),
+            _ => panic!("expected an unknown entity"),
+        }
+        match block.entities[1].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
@@ -447,11 +476,17 @@ mod tests {
             CodePair::new_str(0, "LINE"),
             CodePair::new_str(0, "UNSUPPORTED_ENTITY"),
         ]);
-        assert_eq!(1, block.entities.len());
+        assert_eq!(2, block.entities.len());
         match block.entities[0].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
+        match block.entities[1].specific {
+            EntityType::Unknown {
+                ref type_string, ..
+            } => assert_eq!("UNSUPPORTED_ENTITY", type_string),
+            _ => panic!("expected an unknown entity"),
+        }
     }
 
     #[test]
@@ -461,12 +496,18 @@ mod tests {
             CodePair::new_str(0, "UNSUPPORTED_ENTITY"),
             CodePair::new_str(0, "CIRCLE"),
         ]);
-        assert_eq!(2, block.entities.len());
+        assert_eq!(3, block.entities.len());
         match block.entities[0].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
         match block.entities[1].specific {
+            EntityType::Unknown {
+                ref type_string, ..
+            } => assert_eq!("UNSUPPORTED_ENTITY", type_string),
+            _ => panic!("expected an unknown entity"),
+        }
+        match block.entities[2].specific {
             EntityType::Circle(_) => (),
             _ => panic!("expected a circle"),
         }
@@ -707,6 +748,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn round_trip_blocks_without_duplicating_block_records() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_block(Block {
+            name: String::from("block-name"),
+            ..Default::default()
+        });
+
+        let drawing_pairs = drawing.code_pairs().unwrap();
+        let reparsed = drawing_from_pairs(drawing_pairs);
+        assert_eq!(
+            1,
+            reparsed
+                .block_records()
+                .filter(|r| r.name == "block-name")
+                .count()
+        );
+    }
+
     /// Test case derived from <https://ezdxf.readthedocs.io/en/stable/dxfinternals/block_management.html>
     #[test]
     fn write_block_r12_compat() {
@@ -0,0 +1,1023 @@
+use std::f64::consts::PI;
+
+use crate::entities::*;
+use crate::enums::*;
+use crate::hatch::*;
+use crate::{ocs_to_wcs_matrix, wcs_to_ocs_matrix, Drawing, Point, TransformationMatrix, Vector};
+
+/// The approximate width of a character, relative to the text height.
+const CHARACTER_WIDTH_FACTOR: f64 = 0.6;
+
+/// The distance between lines of `MText` at a line spacing factor of 1.0, relative to the text
+/// height.
+pub(crate) const MTEXT_LINE_SPACING: f64 = 5.0 / 3.0;
+
+/// An axis-aligned box in world coordinates.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> Self {
+        BoundingBox { min, max }
+    }
+    /// Creates an empty box located at the specified point.
+    pub fn from_point(point: &Point) -> Self {
+        BoundingBox::new(point.clone(), point.clone())
+    }
+    /// Returns the smallest box containing all of the specified points, or `None` if there are
+    /// no points.
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Point>,
+    {
+        let mut result: Option<BoundingBox> = None;
+        for point in points {
+            match result {
+                Some(ref mut bbox) => bbox.add_point(point),
+                None => result = Some(BoundingBox::from_point(point)),
+            }
+        }
+        result
+    }
+    /// Grows the box to contain the specified point.
+    pub fn add_point(&mut self, point: &Point) {
+        self.min.x = self.min.x.min(point.x);
+        self.min.y = self.min.y.min(point.y);
+        self.min.z = self.min.z.min(point.z);
+        self.max.x = self.max.x.max(point.x);
+        self.max.y = self.max.y.max(point.y);
+        self.max.z = self.max.z.max(point.z);
+    }
+    /// Returns the smallest box containing both boxes.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let mut result = self.clone();
+        result.add_point(&other.min);
+        result.add_point(&other.max);
+        result
+    }
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+    pub fn depth(&self) -> f64 {
+        self.max.z - self.min.z
+    }
+    pub fn center(&self) -> Point {
+        Point::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+            (self.min.z + self.max.z) / 2.0,
+        )
+    }
+}
+
+impl Entity {
+    /// Returns the axis-aligned extents of the entity in world coordinates, or `None` if the entity
+    /// has no measurable geometry.
+    ///
+    /// Curves are bounded exactly, `Spline`s by their control points, and text by an estimate of
+    /// its rendered size.  The `drawing` is used to resolve the blocks of `Insert` entities.
+    /// Infinite entities (`Ray` and `XLine`) and opaque ACIS data are not included.
+    pub fn bounding_box(&self, drawing: &Drawing) -> Option<BoundingBox> {
+        let mut bounds = Bounds::default();
+        bounds.add_entity(self, drawing);
+        bounds.result
+    }
+}
+
+/// Accumulates the extents of geometry.
+#[derive(Default)]
+pub(crate) struct Bounds {
+    pub result: Option<BoundingBox>,
+}
+
+impl Bounds {
+    fn point(&mut self, point: &Point) {
+        match self.result {
+            Some(ref mut bbox) => bbox.add_point(point),
+            None => self.result = Some(BoundingBox::from_point(point)),
+        }
+    }
+    fn points<'a, I>(&mut self, points: I)
+    where
+        I: IntoIterator<Item = &'a Point>,
+    {
+        for point in points {
+            self.point(point);
+        }
+    }
+    pub(crate) fn bounding_box(&mut self, bbox: &BoundingBox) {
+        self.point(&bbox.min);
+        self.point(&bbox.max);
+    }
+    /// Adds the elliptical arc `center + u * cos(t) + v * sin(t)` for `t` from `start` counter-
+    /// clockwise to `end`, in radians.
+    fn ellipse(&mut self, center: &Point, u: &Vector, v: &Vector, start: f64, end: f64) {
+        let at = |t: f64| {
+            let (sin, cos) = t.sin_cos();
+            Point::new(
+                center.x + u.x * cos + v.x * sin,
+                center.y + u.y * cos + v.y * sin,
+                center.z + u.z * cos + v.z * sin,
+            )
+        };
+        let sweep = if (end - start).abs() >= PI * 2.0 {
+            PI * 2.0
+        } else {
+            (end - start).rem_euclid(PI * 2.0)
+        };
+        self.point(&at(start));
+        self.point(&at(start + sweep));
+
+        // each coordinate is at an extreme where its derivative is zero
+        for (a, b) in [(u.x, v.x), (u.y, v.y), (u.z, v.z)] {
+            let t = b.atan2(a);
+            for candidate in [t, t + PI] {
+                if (candidate - start).rem_euclid(PI * 2.0) <= sweep {
+                    self.point(&at(candidate));
+                }
+            }
+        }
+    }
+    fn circle(
+        &mut self,
+        ocs: &TransformationMatrix,
+        center: &Point,
+        radius: f64,
+        start: f64,
+        end: f64,
+    ) {
+        self.ellipse(
+            &ocs.transform_point(center),
+            &ocs.transform_vector(&Vector::new(radius, 0.0, 0.0)),
+            &ocs.transform_vector(&Vector::new(0.0, radius, 0.0)),
+            start,
+            end,
+        );
+    }
+    /// Adds a polyline segment in the object coordinate system that may be a bulged arc.
+    fn bulge_segment(
+        &mut self,
+        ocs: &TransformationMatrix,
+        start: &Point,
+        end: &Point,
+        bulge: f64,
+    ) {
+        self.point(&ocs.transform_point(start));
+        self.point(&ocs.transform_point(end));
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let chord = dx.hypot(dy);
+        if bulge == 0.0 || chord == 0.0 {
+            return;
+        }
+
+        // the center is offset from the midpoint of the chord to the left for counter-clockwise arcs
+        let offset = chord / 2.0 * (1.0 - bulge * bulge) / (2.0 * bulge);
+        let center = Point::new(
+            (start.x + end.x) / 2.0 - dy / chord * offset,
+            (start.y + end.y) / 2.0 + dx / chord * offset,
+            start.z,
+        );
+        let radius = (start.x - center.x).hypot(start.y - center.y);
+        let start_angle = (start.y - center.y).atan2(start.x - center.x);
+        let end_angle = (end.y - center.y).atan2(end.x - center.x);
+        if bulge > 0.0 {
+            self.circle(ocs, &center, radius, start_angle, end_angle);
+        } else {
+            self.circle(ocs, &center, radius, end_angle, start_angle);
+        }
+    }
+    /// Adds the corners of a rectangle of the specified size, rotated about `anchor` and offset so
+    /// that the anchor is at the relative position (`anchor_x`, `anchor_y`) within the rectangle.
+    #[allow(clippy::too_many_arguments)]
+    fn rectangle(
+        &mut self,
+        ocs: &TransformationMatrix,
+        anchor: &Point,
+        rotation: f64,
+        width: f64,
+        height: f64,
+        anchor_x: f64,
+        anchor_y: f64,
+    ) {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let left = -width * anchor_x;
+        let bottom = -height * anchor_y;
+        for (x, y) in [
+            (left, bottom),
+            (left + width, bottom),
+            (left, bottom + height),
+            (left + width, bottom + height),
+        ] {
+            self.point(&ocs.transform_point(&Point::new(
+                anchor.x + x * cos - y * sin,
+                anchor.y + x * sin + y * cos,
+                anchor.z,
+            )));
+        }
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn text(
+        &mut self,
+        normal: &Vector,
+        value: &str,
+        location: &Point,
+        second_alignment_point: &Point,
+        height: f64,
+        rotation: f64,
+        relative_x_scale_factor: f64,
+        horizontal: HorizontalTextJustification,
+        vertical: VerticalTextJustification,
+    ) {
+        let ocs = ocs_to_wcs_matrix(normal);
+        let mut width = value.chars().count() as f64
+            * height
+            * relative_x_scale_factor
+            * CHARACTER_WIDTH_FACTOR;
+        let (anchor, anchor_x) = match horizontal {
+            HorizontalTextJustification::Left => (location, 0.0),
+            HorizontalTextJustification::Center | HorizontalTextJustification::Middle => {
+                (second_alignment_point, 0.5)
+            }
+            HorizontalTextJustification::Right => (second_alignment_point, 1.0),
+            HorizontalTextJustification::Aligned | HorizontalTextJustification::Fit => {
+                // the text is stretched between the two alignment points
+                width = (second_alignment_point.x - location.x)
+                    .hypot(second_alignment_point.y - location.y);
+                (location, 0.0)
+            }
+        };
+        let anchor_y = match (horizontal, vertical) {
+            (HorizontalTextJustification::Middle, _) => 0.5,
+            (_, VerticalTextJustification::Baseline) | (_, VerticalTextJustification::Bottom) => {
+                0.0
+            }
+            (_, VerticalTextJustification::Middle) => 0.5,
+            (_, VerticalTextJustification::Top) => 1.0,
+        };
+        let anchor = if horizontal == HorizontalTextJustification::Left
+            && vertical != VerticalTextJustification::Baseline
+        {
+            second_alignment_point
+        } else {
+            anchor
+        };
+        self.rectangle(&ocs, anchor, rotation, width, height, anchor_x, anchor_y);
+    }
+    fn mtext(&mut self, mtext: &MText) {
+        let value = mtext.plain_text();
+        let lines = value.split('\n').collect::<Vec<_>>();
+        let longest_line = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = mtext.initial_text_height;
+        let width = if mtext.reference_rectangle_width > 0.0 {
+            mtext.reference_rectangle_width
+        } else {
+            longest_line as f64 * height * CHARACTER_WIDTH_FACTOR
+        };
+        let total_height = if mtext.vertical_height > 0.0 {
+            mtext.vertical_height
+        } else {
+            height
+                + (lines.len().max(1) - 1) as f64
+                    * height
+                    * MTEXT_LINE_SPACING
+                    * mtext.line_spacing_factor
+        };
+
+        let ocs = ocs_to_wcs_matrix(&mtext.extrusion_direction);
+        let x_axis = if mtext.x_axis_direction.length() == 0.0 {
+            let (sin, cos) = mtext.rotation_angle.to_radians().sin_cos();
+            ocs.transform_vector(&Vector::new(cos, sin, 0.0))
+        } else {
+            mtext.x_axis_direction.normalized()
+        };
+        let y_axis = mtext.extrusion_direction.normalized().cross(&x_axis);
+        let (anchor_x, anchor_y) = match mtext.attachment_point {
+            AttachmentPoint::TopLeft => (0.0, 1.0),
+            AttachmentPoint::TopCenter => (0.5, 1.0),
+            AttachmentPoint::TopRight => (1.0, 1.0),
+            AttachmentPoint::MiddleLeft => (0.0, 0.5),
+            AttachmentPoint::MiddleCenter => (0.5, 0.5),
+            AttachmentPoint::MiddleRight => (1.0, 0.5),
+            AttachmentPoint::BottomLeft => (0.0, 0.0),
+            AttachmentPoint::BottomCenter => (0.5, 0.0),
+            AttachmentPoint::BottomRight => (1.0, 0.0),
+        };
+        let left = -width * anchor_x;
+        let bottom = -total_height * anchor_y;
+        let origin = &mtext.insertion_point;
+        for (x, y) in [
+            (left, bottom),
+            (left + width, bottom),
+            (left, bottom + total_height),
+            (left + width, bottom + total_height),
+        ] {
+            self.point(&Point::new(
+                origin.x + x_axis.x * x + y_axis.x * y,
+                origin.y + x_axis.y * x + y_axis.y * y,
+                origin.z + x_axis.z * x + y_axis.z * y,
+            ));
+        }
+    }
+    /// Adds the box of the entity extruded by `thickness` along `normal`.
+    fn extruded<F>(&mut self, normal: &Vector, thickness: f64, add: F)
+    where
+        F: FnOnce(&mut Bounds),
+    {
+        let mut local = Bounds::default();
+        add(&mut local);
+        if let Some(bbox) = local.result {
+            self.bounding_box(&bbox);
+            if thickness != 0.0 {
+                let offset = normal.normalized();
+                let shift = |p: &Point| {
+                    Point::new(
+                        p.x + offset.x * thickness,
+                        p.y + offset.y * thickness,
+                        p.z + offset.z * thickness,
+                    )
+                };
+                self.point(&shift(&bbox.min));
+                self.point(&shift(&bbox.max));
+            }
+        }
+    }
+    fn hatch(&mut self, hatch: &Hatch) {
+        let z = hatch.elevation_point.z;
+        let identity = TransformationMatrix::identity();
+        for boundary in &hatch.pattern_boundary_data {
+            match boundary.path {
+                BoundaryPath::Polyline(ref poly) => {
+                    let count = poly.vertices.len();
+                    let segment_count = if poly.is_closed {
+                        count
+                    } else {
+                        count.saturating_sub(1)
+                    };
+                    for (i, vertex) in poly.vertices.iter().enumerate() {
+                        if i < segment_count {
+                            let next = &poly.vertices[(i + 1) % count];
+                            self.bulge_segment(
+                                &identity,
+                                &Point::new(vertex.x, vertex.y, z),
+                                &Point::new(next.x, next.y, z),
+                                vertex.bulge,
+                            );
+                        } else {
+                            self.point(&Point::new(vertex.x, vertex.y, z));
+                        }
+                    }
+                }
+                BoundaryPath::Edge(ref edges) => {
+                    for edge in &edges.edges {
+                        self.hatch_edge(edge, z);
+                    }
+                }
+            }
+        }
+    }
+    fn hatch_edge(&mut self, edge: &EdgePath, z: f64) {
+        let at_z = |p: &Point| Point::new(p.x, p.y, z);
+
+        // clockwise arcs store their angles mirrored across the X axis
+        let range = |start: f64, end: f64, is_counter_clockwise: bool| {
+            if is_counter_clockwise {
+                (start.to_radians(), end.to_radians())
+            } else {
+                (-end.to_radians(), -start.to_radians())
+            }
+        };
+        match edge {
+            EdgePath::Line(ref line) => {
+                self.point(&at_z(&line.p1));
+                self.point(&at_z(&line.p2));
+            }
+            EdgePath::CircularArc(ref arc) => {
+                let (start, end) = range(arc.start_angle, arc.end_angle, arc.is_counter_clockwise);
+                self.circle(
+                    &TransformationMatrix::identity(),
+                    &at_z(&arc.center),
+                    arc.radius,
+                    start,
+                    end,
+                );
+            }
+            EdgePath::EllipticArc(ref arc) => {
+                let (start, end) = range(arc.start_angle, arc.end_angle, arc.is_counter_clockwise);
+                let minor_axis = Vector::new(
+                    -arc.major_axis.y * arc.minor_axis_ratio,
+                    arc.major_axis.x * arc.minor_axis_ratio,
+                    0.0,
+                );
+                self.ellipse(&at_z(&arc.center), &arc.major_axis, &minor_axis, start, end);
+            }
+            EdgePath::Spline(ref spline) => {
+                for point in spline.control_points.iter().chain(spline.fit_points.iter()) {
+                    self.point(&at_z(point));
+                }
+            }
+        }
+    }
+    fn dimension_base(&mut self, dim: &DimensionBase) {
+        self.point(&dim.definition_point_1);
+        self.point(&dim.text_mid_point);
+    }
+    pub(crate) fn add_entity(&mut self, entity: &Entity, drawing: &Drawing) {
+        match entity.specific {
+            EntityType::Face3D(ref face) => self.points([
+                &face.first_corner,
+                &face.second_corner,
+                &face.third_corner,
+                &face.fourth_corner,
+            ]),
+            EntityType::Arc(ref arc) => self.extruded(&arc.normal, arc.thickness, |b| {
+                b.circle(
+                    &ocs_to_wcs_matrix(&arc.normal),
+                    &arc.center,
+                    arc.radius,
+                    arc.start_angle.to_radians(),
+                    arc.end_angle.to_radians(),
+                )
+            }),
+            EntityType::ArcAlignedText(ref text) => self.circle(
+                &ocs_to_wcs_matrix(&text.extrusion_direction),
+                &text.center_point,
+                text.arc_radius + text.text_height,
+                text.start_angle,
+                text.end_angle,
+            ),
+            EntityType::AttributeDefinition(ref att) => self.text(
+                &att.normal,
+                &att.text_tag,
+                &att.location,
+                &att.second_alignment_point,
+                att.text_height,
+                att.rotation,
+                att.relative_x_scale_factor,
+                att.horizontal_text_justification,
+                att.vertical_text_justification,
+            ),
+            EntityType::Attribute(ref att) => self.text(
+                &att.normal,
+                &att.value,
+                &att.location,
+                &att.second_alignment_point,
+                att.text_height,
+                att.rotation,
+                att.relative_x_scale_factor,
+                att.horizontal_text_justification,
+                att.vertical_text_justification,
+            ),
+            EntityType::Circle(ref circle) => {
+                self.extruded(&circle.normal, circle.thickness, |b| {
+                    b.circle(
+                        &ocs_to_wcs_matrix(&circle.normal),
+                        &circle.center,
+                        circle.radius,
+                        0.0,
+                        PI * 2.0,
+                    )
+                })
+            }
+            EntityType::RotatedDimension(ref dim) => {
+                self.dimension_base(&dim.dimension_base);
+                self.points([&dim.definition_point_2, &dim.definition_point_3]);
+            }
+            EntityType::RadialDimension(ref dim) => {
+                self.dimension_base(&dim.dimension_base);
+                self.point(&dim.definition_point_2);
+            }
+            EntityType::DiameterDimension(ref dim) => {
+                self.dimension_base(&dim.dimension_base);
+                self.point(&dim.definition_point_2);
+            }
+            EntityType::AngularThreePointDimension(ref dim) => {
+                self.dimension_base(&dim.dimension_base);
+                self.points([
+                    &dim.definition_point_2,
+                    &dim.definition_point_3,
+                    &dim.definition_point_4,
+                    &dim.definition_point_5,
+                ]);
+            }
+            EntityType::OrdinateDimension(ref dim) => {
+                self.dimension_base(&dim.dimension_base);
+                self.points([&dim.definition_point_2, &dim.definition_point_3]);
+            }
+            EntityType::Ellipse(ref ellipse) => {
+                let minor_axis = ellipse.normal.normalized().cross(&ellipse.major_axis);
+                let minor_axis = Vector::new(
+                    minor_axis.x * ellipse.minor_axis_ratio,
+                    minor_axis.y * ellipse.minor_axis_ratio,
+                    minor_axis.z * ellipse.minor_axis_ratio,
+                );
+                self.ellipse(
+                    &ellipse.center,
+                    &ellipse.major_axis,
+                    &minor_axis,
+                    ellipse.start_parameter,
+                    ellipse.end_parameter,
+                );
+            }
+            EntityType::Hatch(ref hatch) => self.hatch(hatch),
+            EntityType::Helix(ref helix) => {
+                let axis = helix.axis_vector.normalized();
+                let height = helix.number_of_turns * helix.turn_height;
+                let top = Point::new(
+                    helix.axis_base_point.x + axis.x * height,
+                    helix.axis_base_point.y + axis.y * height,
+                    helix.axis_base_point.z + axis.z * height,
+                );
+                for center in [&helix.axis_base_point, &top] {
+                    self.circle(
+                        &ocs_to_wcs_matrix(&axis),
+                        &wcs_to_ocs_matrix(&axis).transform_point(center),
+                        helix.radius,
+                        0.0,
+                        PI * 2.0,
+                    );
+                }
+            }
+            EntityType::Image(ref image) => self.image(
+                &image.location,
+                &image.u_vector,
+                &image.v_vector,
+                &image.image_size,
+            ),
+            EntityType::Insert(ref insert) => {
+                let mut exploded = vec![];
+                if drawing.explode_insert_into(
+                    insert,
+                    &TransformationMatrix::identity(),
+                    None,
+                    true,
+                    &mut vec![],
+                    &mut exploded,
+                ) {
+                    for ent in &exploded {
+                        self.add_entity(ent, drawing);
+                    }
+                } else {
+                    self.point(
+                        &ocs_to_wcs_matrix(&insert.extrusion_direction)
+                            .transform_point(&insert.location),
+                    );
+                }
+            }
+            EntityType::Leader(ref leader) => self.points(&leader.vertices),
+            EntityType::Light(ref light) => self.point(&light.position),
+            EntityType::Line(ref line) => {
+                self.extruded(&line.extrusion_direction, line.thickness, |b| {
+                    b.points([&line.p1, &line.p2])
+                })
+            }
+            EntityType::LwPolyline(ref poly) => {
+                let ocs = ocs_to_wcs_matrix(&poly.extrusion_direction);
+                let z = entity.common.elevation;
+                let vertices = poly
+                    .vertices
+                    .iter()
+                    .map(|v| (Point::new(v.x, v.y, z), v.bulge))
+                    .collect::<Vec<_>>();
+                self.extruded(&poly.extrusion_direction, poly.thickness, |b| {
+                    b.polyline(&ocs, &vertices, poly.is_closed())
+                });
+            }
+            EntityType::MLeader(ref mleader) => {
+                let context = &mleader.context_data;
+                self.point(&context.content_base_point);
+                if let Some(ref text) = context.text_content {
+                    self.point(&text.location);
+                }
+                if let Some(ref block) = context.block_content {
+                    self.point(&block.location);
+                }
+                for root in &context.leader_roots {
+                    self.point(&root.connection_point);
+                    for line in &root.leader_lines {
+                        self.points(&line.vertices);
+                    }
+                }
+            }
+            EntityType::MLine(ref mline) => self.points(&mline.vertices),
+            EntityType::MText(ref mtext) => self.mtext(mtext),
+            EntityType::Ole2Frame(ref ole) => {
+                self.points([&ole.upper_left_corner, &ole.lower_right_corner])
+            }
+            EntityType::ModelPoint(ref point) => {
+                self.extruded(&point.extrusion_direction, point.thickness, |b| {
+                    b.point(&point.location)
+                })
+            }
+            EntityType::Polyline(ref poly) => {
+                if poly.is_3d_polyline() || poly.is_3d_polygon_mesh() || poly.is_polyface_mesh() {
+                    for vertex in poly.vertices() {
+                        // polyface mesh face records don't have a location
+                        if !vertex.is_polyface_mesh_vertex() || vertex.is_3d_polygon_mesh() {
+                            self.point(&vertex.location);
+                        }
+                    }
+                } else {
+                    let ocs = ocs_to_wcs_matrix(&poly.normal);
+                    let vertices = poly
+                        .vertices()
+                        .map(|v| {
+                            (
+                                Point::new(v.location.x, v.location.y, poly.location.z),
+                                v.bulge,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    self.extruded(&poly.normal, poly.thickness, |b| {
+                        b.polyline(&ocs, &vertices, poly.is_closed())
+                    });
+                }
+            }
+            EntityType::RText(ref text) => self.point(
+                &ocs_to_wcs_matrix(&text.extrusion_direction)
+                    .transform_point(&text.insertion_point),
+            ),
+            EntityType::Section(ref section) => self.points(&section.vertices),
+            EntityType::Shape(ref shape) => self.rectangle(
+                &ocs_to_wcs_matrix(&shape.extrusion_direction),
+                &shape.location,
+                shape.rotation_angle,
+                shape.size * shape.relative_x_scale_factor,
+                shape.size,
+                0.0,
+                0.0,
+            ),
+            EntityType::Solid(ref solid) => {
+                let ocs = ocs_to_wcs_matrix(&solid.extrusion_direction);
+                self.extruded(&solid.extrusion_direction, solid.thickness, |b| {
+                    for corner in [
+                        &solid.first_corner,
+                        &solid.second_corner,
+                        &solid.third_corner,
+                        &solid.fourth_corner,
+                    ] {
+                        b.point(&ocs.transform_point(corner));
+                    }
+                })
+            }
+            EntityType::Spline(ref spline) => {
+                self.points(&spline.control_points);
+                self.points(&spline.fit_points);
+            }
+            EntityType::Table(ref table) => {
+                let x_axis = table.horizontal_direction.normalized();
+                let y_axis = Vector::z_axis().cross(&x_axis);
+                let width = table.column_widths.iter().sum::<f64>();
+                let height = table.row_heights.iter().sum::<f64>();
+                let origin = &table.insertion_point;
+
+                // rows extend downward from the insertion point
+                for (x, y) in [(0.0, 0.0), (width, 0.0), (0.0, -height), (width, -height)] {
+                    self.point(&Point::new(
+                        origin.x + x_axis.x * x + y_axis.x * y,
+                        origin.y + x_axis.y * x + y_axis.y * y,
+                        origin.z + x_axis.z * x + y_axis.z * y,
+                    ));
+                }
+            }
+            EntityType::Text(ref text) => self.extruded(&text.normal, text.thickness, |b| {
+                b.text(
+                    &text.normal,
+                    &text.value,
+                    &text.location,
+                    &text.second_alignment_point,
+                    text.text_height,
+                    text.rotation,
+                    text.relative_x_scale_factor,
+                    text.horizontal_text_justification,
+                    text.vertical_text_justification,
+                )
+            }),
+            EntityType::Tolerance(ref tol) => self.point(&tol.insertion_point),
+            EntityType::Trace(ref trace) => {
+                let ocs = ocs_to_wcs_matrix(&trace.extrusion_direction);
+                self.extruded(&trace.extrusion_direction, trace.thickness, |b| {
+                    for corner in [
+                        &trace.first_corner,
+                        &trace.second_corner,
+                        &trace.third_corner,
+                        &trace.fourth_corner,
+                    ] {
+                        b.point(&ocs.transform_point(corner));
+                    }
+                })
+            }
+            EntityType::DgnUnderlay(ref underlay) => self.underlay(
+                &underlay.insertion_point,
+                &underlay.normal,
+                underlay.rotation_angle,
+                underlay.x_scale,
+                underlay.y_scale,
+                &underlay.points,
+            ),
+            EntityType::DwfUnderlay(ref underlay) => self.underlay(
+                &underlay.insertion_point,
+                &underlay.normal,
+                underlay.rotation_angle,
+                underlay.x_scale,
+                underlay.y_scale,
+                &underlay.points,
+            ),
+            EntityType::PdfUnderlay(ref underlay) => self.underlay(
+                &underlay.insertion_point,
+                &underlay.normal,
+                underlay.rotation_angle,
+                underlay.x_scale,
+                underlay.y_scale,
+                &underlay.points,
+            ),
+            EntityType::Vertex(ref vertex) => self.point(&vertex.location),
+            EntityType::Viewport(ref viewport) => {
+                let half = Vector::new(viewport.width / 2.0, viewport.height / 2.0, 0.0);
+                self.points([&(&viewport.center - &half), &(&viewport.center + &half)]);
+            }
+            EntityType::Wipeout(ref wipeout) => self.image(
+                &wipeout.location,
+                &wipeout.u_vector,
+                &wipeout.v_vector,
+                &wipeout.image_size,
+            ),
+            // infinite, opaque, or non-geometric data
+            EntityType::Ray(_)
+            | EntityType::XLine(_)
+            | EntityType::Solid3D(_)
+            | EntityType::ProxyEntity(_)
+            | EntityType::Body(_)
+            | EntityType::OleFrame(_)
+            | EntityType::Region(_)
+            | EntityType::Seqend(_)
+            | EntityType::Unknown { .. } => (),
+        }
+    }
+    fn polyline(&mut self, ocs: &TransformationMatrix, vertices: &[(Point, f64)], is_closed: bool) {
+        for (i, (point, bulge)) in vertices.iter().enumerate() {
+            match vertices
+                .get(i + 1)
+                .or(if is_closed { vertices.first() } else { None })
+            {
+                Some((next, _)) => self.bulge_segment(ocs, point, next, *bulge),
+                None => self.point(&ocs.transform_point(point)),
+            }
+        }
+    }
+    fn image(&mut self, location: &Point, u: &Vector, v: &Vector, size: &Vector) {
+        for (x, y) in [(0.0, 0.0), (size.x, 0.0), (0.0, size.y), (size.x, size.y)] {
+            self.point(&Point::new(
+                location.x + u.x * x + v.x * y,
+                location.y + u.y * x + v.y * y,
+                location.z + u.z * x + v.z * y,
+            ));
+        }
+    }
+    fn underlay(
+        &mut self,
+        insertion_point: &Point,
+        normal: &Vector,
+        rotation: f64,
+        x_scale: f64,
+        y_scale: f64,
+        clipping_points: &[Point],
+    ) {
+        // the insertion point is in world coordinates and the clipping boundary is in the plane of
+        // the underlay
+        let ocs = ocs_to_wcs_matrix(normal);
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        self.point(insertion_point);
+        for point in clipping_points {
+            let (x, y) = (point.x * x_scale, point.y * y_scale);
+            let offset =
+                ocs.transform_vector(&Vector::new(x * cos - y * sin, x * sin + y * cos, 0.0));
+            self.point(&Point::new(
+                insertion_point.x + offset.x,
+                insertion_point.y + offset.y,
+                insertion_point.z + offset.z,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::helper_functions::tests::*;
+    use crate::*;
+
+    fn assert_box(expected: ((f64, f64, f64), (f64, f64, f64)), actual: Option<BoundingBox>) {
+        let actual = actual.expect("expected a bounding box");
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(
+            close((expected.0).0, actual.min.x)
+                && close((expected.0).1, actual.min.y)
+                && close((expected.0).2, actual.min.z)
+                && close((expected.1).0, actual.max.x)
+                && close((expected.1).1, actual.max.y)
+                && close((expected.1).2, actual.max.z),
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn line_bounding_box() {
+        let drawing = Drawing::new();
+        let line = Entity::new(EntityType::Line(Line::new(
+            Point::new(3.0, -1.0, 0.0),
+            Point::new(1.0, 2.0, 5.0),
+        )));
+        assert_box(
+            ((1.0, -1.0, 0.0), (3.0, 2.0, 5.0)),
+            line.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn arc_bounding_box_includes_quadrant_points() {
+        let drawing = Drawing::new();
+        let arc = Entity::new(EntityType::Arc(Arc::new(
+            Point::new(1.0, 1.0, 0.0),
+            2.0,
+            45.0,
+            180.0,
+        )));
+        let root_two = 2.0f64.sqrt();
+        assert_box(
+            ((-1.0, 1.0, 0.0), (1.0 + root_two, 3.0, 0.0)),
+            arc.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn arc_bounding_box_across_zero_degrees() {
+        let drawing = Drawing::new();
+        let arc = Entity::new(EntityType::Arc(Arc::new(Point::origin(), 1.0, 270.0, 90.0)));
+        assert_box(
+            ((0.0, -1.0, 0.0), (1.0, 1.0, 0.0)),
+            arc.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn circle_with_thickness_and_tilted_normal() {
+        let drawing = Drawing::new();
+        let circle = Entity::new(EntityType::Circle(Circle {
+            radius: 1.0,
+            thickness: 2.0,
+            normal: Vector::x_axis(),
+            ..Default::default()
+        }));
+        assert_box(
+            ((0.0, -1.0, -1.0), (2.0, 1.0, 1.0)),
+            circle.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn ellipse_bounding_box() {
+        let drawing = Drawing::new();
+        let ellipse = Entity::new(EntityType::Ellipse(Ellipse {
+            major_axis: Vector::new(0.0, 4.0, 0.0),
+            minor_axis_ratio: 0.5,
+            ..Default::default()
+        }));
+        assert_box(
+            ((-2.0, -4.0, 0.0), (2.0, 4.0, 0.0)),
+            ellipse.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn bulged_lw_polyline_bounding_box() {
+        let drawing = Drawing::new();
+        let mut poly = LwPolyline::default();
+        // a counter-clockwise half circle from (1, 0) to (-1, 0) through (0, 1)
+        poly.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            y: 0.0,
+            bulge: 1.0,
+            ..Default::default()
+        });
+        poly.vertices.push(LwPolylineVertex {
+            x: -1.0,
+            y: 0.0,
+            ..Default::default()
+        });
+        let mut ent = Entity::new(EntityType::LwPolyline(poly));
+        ent.common.elevation = 3.0;
+        assert_box(
+            ((-1.0, 0.0, 3.0), (1.0, 1.0, 3.0)),
+            ent.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn text_bounding_box_is_estimated() {
+        let drawing = Drawing::new();
+        let text = Entity::new(EntityType::Text(Text {
+            location: Point::new(1.0, 1.0, 0.0),
+            text_height: 2.0,
+            value: String::from("abcde"),
+            ..Default::default()
+        }));
+        let bbox = text.bounding_box(&drawing).unwrap();
+        assert_eq!(Point::new(1.0, 1.0, 0.0), bbox.min);
+        assert!((bbox.height() - 2.0).abs() < 1e-9);
+        assert!(bbox.width() > 2.0);
+    }
+
+    #[test]
+    fn insert_bounding_box_uses_block() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(Block {
+            name: String::from("b"),
+            entities: vec![Entity::new(EntityType::Circle(Circle::new(
+                Point::origin(),
+                1.0,
+            )))],
+            ..Default::default()
+        });
+        let block = drawing.blocks().find(|b| b.name == "b").unwrap();
+        assert_box(
+            ((-1.0, -1.0, 0.0), (1.0, 1.0, 0.0)),
+            block.bounding_box(&drawing),
+        );
+
+        let insert = Entity::new(EntityType::Insert(Insert {
+            name: String::from("b"),
+            location: Point::new(10.0, 0.0, 0.0),
+            x_scale_factor: 2.0,
+            y_scale_factor: 2.0,
+            ..Default::default()
+        }));
+        assert_box(
+            ((8.0, -2.0, 0.0), (12.0, 2.0, 0.0)),
+            insert.bounding_box(&drawing),
+        );
+    }
+
+    #[test]
+    fn drawing_extents_ignore_paper_space() {
+        let mut drawing = Drawing::new();
+        assert!(drawing.extents().is_none());
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 1.0, 0.0),
+        ))));
+        let mut paper_space_line = Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(100.0, 100.0, 0.0),
+        )));
+        paper_space_line.common.is_in_paper_space = true;
+        drawing.add_entity(paper_space_line);
+        assert_box(((0.0, 0.0, 0.0), (1.0, 1.0, 0.0)), drawing.extents());
+    }
+
+    #[test]
+    fn extents_are_written_when_requested() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(-1.0, -2.0, 0.0),
+            Point::new(3.0, 4.0, 0.0),
+        ))));
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$EXTMAX"),
+                CodePair::new_f64(10, 0.0),
+                CodePair::new_f64(20, 0.0),
+            ],
+        );
+
+        drawing.update_extents_on_save = true;
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(9, "$EXTMIN"),
+                CodePair::new_f64(10, -1.0),
+                CodePair::new_f64(20, -2.0),
+                CodePair::new_f64(30, 0.0),
+                CodePair::new_str(9, "$EXTMAX"),
+                CodePair::new_f64(10, 3.0),
+                CodePair::new_f64(20, 4.0),
+            ],
+        );
+        assert_eq!(Point::origin(), drawing.header.maximum_drawing_extents);
+
+        drawing.update_extents();
+        assert_eq!(
+            Point::new(3.0, 4.0, 0.0),
+            drawing.header.maximum_drawing_extents
+        );
+    }
+}
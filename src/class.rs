@@ -125,7 +125,11 @@ impl Class {
                                 iter.put_back(Ok(pair));
                                 break;
                             }
-                            typ => Class::read_class(typ, drawing, iter)?,
+                            typ => {
+                                if let Err(e) = Class::read_class(typ, drawing, iter) {
+                                    iter.recover(e)?;
+                                }
+                            }
                         }
                     }
                 }
@@ -192,10 +196,8 @@ impl Class {
                             class.class_name = pair.assert_string()?;
                         }
                     }
-                    3 => {
-                        if drawing.header.version >= AcadVersion::R14 {
-                            class.application_name = pair.assert_string()?;
-                        }
+                    3 if drawing.header.version >= AcadVersion::R14 => {
+                        class.application_name = pair.assert_string()?;
                     }
                     90 => {
                         if drawing.header.version <= AcadVersion::R13 {
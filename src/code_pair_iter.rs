@@ -7,20 +7,27 @@ use std::io::{Cursor, Read};
 
 pub(crate) trait CodePairIter: Iterator<Item = DxfResult<CodePair>> {
     fn read_as_utf8(&mut self);
+    /// Returns `true` if the code pair offsets are line numbers instead of byte offsets.
+    fn offsets_are_lines(&self) -> bool {
+        false
+    }
 }
 
 /// Directly returns code pairs; primarily used in tests.
+#[cfg(test)]
 pub(crate) struct DirectCodePairIter {
     pairs: Vec<CodePair>,
     offset: usize,
 }
 
+#[cfg(test)]
 impl CodePairIter for DirectCodePairIter {
     fn read_as_utf8(&mut self) {
         // noop
     }
 }
 
+#[cfg(test)]
 impl Iterator for DirectCodePairIter {
     type Item = DxfResult<CodePair>;
     fn next(&mut self) -> Option<DxfResult<CodePair>> {
@@ -54,6 +61,9 @@ impl<T: Read> CodePairIter for TextCodePairIter<T> {
     fn read_as_utf8(&mut self) {
         self.string_encoding = encoding_rs::UTF_8;
     }
+    fn offsets_are_lines(&self) -> bool {
+        true
+    }
 }
 
 impl<T: Read> Iterator for TextCodePairIter<T> {
@@ -99,6 +109,8 @@ impl<T: Read> TextCodePairIter<T> {
             return None;
         }
 
+        // only the code line is consumed when it's malformed, e.g., when a line is missing, so
+        // that reading can resume with the next line
         let code_offset = self.offset;
         let code = try_into_option!(parse_i32(String::from(code_line), code_offset));
 
@@ -296,24 +308,35 @@ where
 {
     let mut bytes = vec![];
     reader.read_to_end(&mut bytes)?;
-    let mut cursor = Cursor::new(bytes);
-    let iter: Box<dyn CodePairIter> = match &*first_line {
+    new_code_pair_iter_from_stream(Cursor::new(bytes), string_encoding, first_line)
+}
+
+/// Creates a `CodePairIter` that reads from `reader` as code pairs are requested.
+pub(crate) fn new_code_pair_iter_from_stream<'a, T>(
+    mut reader: T,
+    string_encoding: &'static Encoding,
+    first_line: String,
+) -> DxfResult<Box<dyn CodePairIter + 'a>>
+where
+    T: Read + 'a,
+{
+    let iter: Box<dyn CodePairIter + 'a> = match &*first_line {
         "AutoCAD Binary DXF" => {
             // swallow 0x1A,0x00
             assert_or_err!(
-                try_option_io_result_into_err!(read_u8(&mut cursor)),
+                try_option_io_result_into_err!(read_u8(&mut reader)),
                 0x1A,
                 18
             );
             assert_or_err!(
-                try_option_io_result_into_err!(read_u8(&mut cursor)),
+                try_option_io_result_into_err!(read_u8(&mut reader)),
                 0x00,
                 19
             );
-            Box::new(BinaryCodePairIter::new(cursor, 20))
+            Box::new(BinaryCodePairIter::new(reader, 20))
         }
         _ => Box::new(TextCodePairIter::new(
-            cursor,
+            reader,
             string_encoding,
             first_line,
             1,
@@ -1,15 +1,32 @@
 use crate::code_pair_iter::CodePairIter;
 use crate::dxf_result::DxfResult;
-use crate::CodePair;
+use crate::enums::AcadVersion;
+use crate::{CodePair, CodePairValue, DxfError, ErrorContext, Handle, LoadDiagnostic};
 
-pub(crate) struct CodePairPutBack {
+pub(crate) struct CodePairPutBack<'a> {
     top: Vec<DxfResult<CodePair>>,
-    iter: Box<dyn CodePairIter>,
+    iter: Box<dyn CodePairIter + 'a>,
+    /// The diagnostics gathered so far, if errors are being recovered from.
+    diagnostics: Option<Vec<LoadDiagnostic>>,
+    section: String,
+    item_type: Option<String>,
+    handle: Option<Handle>,
+    offset: usize,
+    version: AcadVersion,
 }
 
-impl CodePairPutBack {
-    pub fn from_code_pair_iter(iter: Box<dyn CodePairIter>) -> Self {
-        CodePairPutBack { top: vec![], iter }
+impl<'a> CodePairPutBack<'a> {
+    pub fn from_code_pair_iter(iter: Box<dyn CodePairIter + 'a>) -> Self {
+        CodePairPutBack {
+            top: vec![],
+            iter,
+            diagnostics: None,
+            section: String::new(),
+            item_type: None,
+            handle: None,
+            offset: 0,
+            version: AcadVersion::R12,
+        }
     }
     pub fn put_back(&mut self, item: DxfResult<CodePair>) {
         self.top.push(item);
@@ -17,22 +34,147 @@ impl CodePairPutBack {
     pub fn read_as_utf8(&mut self) {
         self.iter.read_as_utf8()
     }
+    /// Causes `recover()` to record errors instead of returning them, and malformed code pairs to
+    /// be skipped.
+    pub fn set_lenient(&mut self) {
+        self.diagnostics = Some(vec![]);
+    }
+    /// Returns the diagnostics that have been recorded.
+    pub fn take_diagnostics(&mut self) -> Vec<LoadDiagnostic> {
+        self.diagnostics.take().unwrap_or_default()
+    }
+    /// Returns the version of the drawing being read, as set by `set_version()`.
+    pub fn version(&self) -> AcadVersion {
+        self.version
+    }
+    /// Records the version of the drawing being read, once the header has been read.
+    pub fn set_version(&mut self, version: AcadVersion) {
+        self.version = version;
+    }
+    /// Records the name of the section being read.
+    pub fn set_section(&mut self, section: &str) {
+        self.section = section.to_string();
+        self.item_type = None;
+        self.handle = None;
+    }
+    /// Adds the section, item, and line that were being read to `error`.
+    pub fn add_context(&self, error: DxfError) -> DxfError {
+        if let DxfError::WithContext(..) = error {
+            return error;
+        }
+
+        let context = ErrorContext {
+            section: Some(self.section.clone()).filter(|s| !s.is_empty()),
+            item_type: self.item_type.clone(),
+            handle: self.handle,
+            line: if self.iter.offsets_are_lines() {
+                Some(error.offset().unwrap_or(self.offset))
+            } else {
+                None
+            },
+        };
+        if context == ErrorContext::default() {
+            return error;
+        }
+
+        DxfError::WithContext(Box::new(error), Box::new(context))
+    }
+    /// Returns `Ok(())` if errors are being recovered from, after recording `error`; otherwise
+    /// returns `error`.  Once the input has ended, only the first error saying so is recorded.
+    pub fn recover(&mut self, error: DxfError) -> DxfResult<()> {
+        match self.diagnostics {
+            Some(ref mut diagnostics) => {
+                let has_ended = |e: &DxfError| matches!(e.inner(), DxfError::UnexpectedEndOfInput);
+                if has_ended(&error) && diagnostics.iter().any(|d| has_ended(&d.error)) {
+                    return Ok(());
+                }
+                diagnostics.push(LoadDiagnostic {
+                    section: self.section.clone(),
+                    handle: self.handle,
+                    offset: error.offset().unwrap_or(self.offset),
+                    error,
+                });
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+    /// Skips code pairs until one with code 0 and one of the specified `values` (or any value if
+    /// `values` is empty), which is put back, and returns `false` if the input ended first.  Used
+    /// to find a place to resume reading after an error has been recovered from.
+    pub fn skip_to(&mut self, values: &[&str]) -> DxfResult<bool> {
+        loop {
+            match self.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    let is_match = match pair.value {
+                        CodePairValue::Str(ref s) => {
+                            values.is_empty() || values.contains(&s.as_str())
+                        }
+                        _ => false,
+                    };
+                    if is_match {
+                        self.put_back(Ok(pair));
+                        return Ok(true);
+                    }
+                }
+                Some(Ok(_)) => (),
+                Some(Err(e)) => return Err(e),
+                None => return Ok(false),
+            }
+        }
+    }
+    fn track(&mut self, pair: &CodePair) {
+        self.offset = pair.offset;
+        match pair.code {
+            0 => {
+                self.item_type = match pair.value {
+                    CodePairValue::Str(ref s) if !matches!(&**s, "SECTION" | "ENDSEC" | "EOF") => {
+                        Some(s.clone())
+                    }
+                    _ => None,
+                };
+                self.handle = None;
+            }
+            5 | 105 if self.section != "HEADER" => self.handle = pair.as_handle().ok(),
+            _ => (),
+        }
+    }
 }
 
-impl Iterator for CodePairPutBack {
+/// Returns `true` for errors that only affect a single code pair, which can then be skipped.
+fn is_malformed_pair(error: &DxfError) -> bool {
+    matches!(
+        error,
+        DxfError::ParseFloatError(..)
+            | DxfError::ParseIntError(..)
+            | DxfError::ParseError(_)
+            | DxfError::UnexpectedEnumValue(_)
+            | DxfError::MalformedString
+    )
+}
+
+impl Iterator for CodePairPutBack<'_> {
     type Item = DxfResult<CodePair>;
 
     fn next(&mut self) -> Option<DxfResult<CodePair>> {
-        if self.top.is_empty() {
+        let pair = if self.top.is_empty() {
             loop {
-                let pair = self.iter.next();
-                match pair {
+                match self.iter.next() {
                     Some(Ok(CodePair { code: 999, .. })) => (), // a 999 comment code, try again
-                    _ => return pair,
+                    Some(Err(e)) if self.diagnostics.is_some() && is_malformed_pair(&e) => {
+                        // the rest of the pair was consumed, so skip it
+                        let _ = self.recover(e);
+                    }
+                    pair => break pair,
                 }
             }
         } else {
-            Some(self.top.pop().unwrap())
+            self.top.pop()
+        };
+        if let Some(Ok(ref pair)) = pair {
+            self.track(pair);
         }
+
+        pair
     }
 }
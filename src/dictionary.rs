@@ -0,0 +1,317 @@
+// Navigation of the named object dictionary
+
+use std::collections::HashMap;
+
+use crate::objects::*;
+use crate::{
+    CodePair, Drawing, DrawingItem, DrawingItemMut, DxfError, DxfResult, ExtensionGroup,
+    ExtensionGroupItem, Handle,
+};
+
+pub(crate) const REACTORS_GROUP: &str = "ACAD_REACTORS";
+
+impl Drawing {
+    /// Returns the root dictionary of named objects, which contains dictionaries like
+    /// `ACAD_GROUP`, `ACAD_LAYOUT`, and `ACAD_MLINESTYLE`.  This is the first `Dictionary` without
+    /// an owner.
+    pub fn named_object_dictionary(&self) -> Option<&Object> {
+        self.objects().find(|o| {
+            matches!(o.specific, ObjectType::Dictionary(_)) && o.common.__owner_handle.is_empty()
+        })
+    }
+    /// Returns the handle of the named object dictionary, adding an empty one if it doesn't exist.
+    pub fn ensure_named_object_dictionary(&mut self) -> Handle {
+        match self.named_object_dictionary() {
+            Some(dictionary) => dictionary.common.handle,
+            None => {
+                self.add_object(Object::new(ObjectType::Dictionary(Dictionary::default())))
+                    .common
+                    .handle
+            }
+        }
+    }
+    /// Follows `path` through nested dictionaries, starting at the named object dictionary, and
+    /// returns the object at the end, e.g., `&["ACAD_LAYOUT", "Layout1"]` returns the `Layout`
+    /// named `Layout1`.  An empty path returns the named object dictionary.  Entry names that
+    /// don't match exactly are compared case-insensitively.
+    pub fn dictionary_path(&self, path: &[&str]) -> Option<&Object> {
+        let mut object = self.named_object_dictionary()?;
+        for name in path {
+            let handle = dictionary_entry(object, name)?;
+            object = match self.item_by_handle(handle)? {
+                DrawingItem::Object(o) => o,
+                _ => return None,
+            };
+        }
+        Some(object)
+    }
+    /// Same as `dictionary_path` but returns a mutable object.
+    pub fn dictionary_path_mut(&mut self, path: &[&str]) -> Option<&mut Object> {
+        let handle = self.dictionary_path(path)?.common.handle;
+        match self.item_by_handle_mut(handle)? {
+            DrawingItemMut::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+    /// Follows `path` through nested dictionaries like `dictionary_path`, adding empty
+    /// dictionaries for any entries that don't exist, and returns the handle of the last
+    /// dictionary.  Returns `DxfError::WrongItemType` if an entry on the path isn't a dictionary.
+    pub fn ensure_dictionary_path(&mut self, path: &[&str]) -> DxfResult<Handle> {
+        let mut dictionary = self.ensure_named_object_dictionary();
+        for name in path {
+            let existing = match self.item_by_handle(dictionary) {
+                Some(DrawingItem::Object(o)) => dictionary_entry(o, name),
+                _ => None,
+            };
+            dictionary = match existing {
+                Some(handle) => match self.item_by_handle(handle) {
+                    Some(DrawingItem::Object(o)) if dictionary_entries(o).is_some() => handle,
+                    _ => return Err(DxfError::WrongItemType),
+                },
+                None => {
+                    let child = Object::new(ObjectType::Dictionary(Dictionary::default()));
+                    self.add_dictionary_entry(dictionary, name, child)?
+                        .common
+                        .handle
+                }
+            };
+        }
+        Ok(dictionary)
+    }
+    /// Adds `obj` to the drawing as the entry `name` of the dictionary with the specified handle,
+    /// making the dictionary its owner and a reactor.  An existing entry whose name matches `name`
+    /// case-insensitively is replaced, and its object is removed if it was owned by the
+    /// dictionary.  Returns `DxfError::WrongItemType` if
+    /// the handle isn't a dictionary.
+    pub fn add_dictionary_entry(
+        &mut self,
+        dictionary: Handle,
+        name: &str,
+        mut obj: Object,
+    ) -> DxfResult<&Object> {
+        // names are matched case-insensitively, so a differently cased entry is replaced too
+        let previous = match self.item_by_handle(dictionary) {
+            Some(DrawingItem::Object(o)) if dictionary_entries(o).is_some() => {
+                dictionary_key(o, name)
+            }
+            _ => return Err(DxfError::WrongItemType),
+        };
+        let previous_index = previous.as_ref().and_then(|&(_, previous)| {
+            self.objects()
+                .position(|o| o.common.handle == previous && o.common.__owner_handle == dictionary)
+        });
+        if let Some(index) = previous_index {
+            self.remove_object(index);
+        }
+
+        obj.common.__owner_handle = dictionary;
+        obj.common.add_reactor(dictionary);
+        let handle = self.add_object(obj).common.handle;
+        if let Some(DrawingItemMut::Object(o)) = self.item_by_handle_mut(dictionary) {
+            if let Some(entries) = dictionary_entries_mut(o) {
+                if let Some((ref key, _)) = previous {
+                    entries.remove(key);
+                }
+                entries.insert(String::from(name), handle);
+            }
+        }
+
+        match self.item_by_handle(handle) {
+            Some(DrawingItem::Object(o)) => Ok(o),
+            _ => Err(DxfError::WrongItemType),
+        }
+    }
+}
+
+impl ObjectCommon {
+    /// Adds `handle` to the `ACAD_REACTORS` extension data group if it isn't already there.
+    pub(crate) fn add_reactor(&mut self, handle: Handle) {
+        let pair = CodePair::new_string(330, &handle.as_string());
+        match self
+            .extension_data_groups
+            .iter_mut()
+            .find(|g| g.application_name == REACTORS_GROUP)
+        {
+            Some(group) => {
+                let item = ExtensionGroupItem::CodePair(pair);
+                if !group.items.contains(&item) {
+                    group.items.push(item);
+                }
+            }
+            None => self.extension_data_groups.push(ExtensionGroup {
+                application_name: String::from(REACTORS_GROUP),
+                items: vec![ExtensionGroupItem::CodePair(pair)],
+            }),
+        }
+    }
+}
+
+/// Returns the entries of a `Dictionary` or `DictionaryWithDefault`.
+pub(crate) fn dictionary_entries(obj: &Object) -> Option<&HashMap<String, Handle>> {
+    match obj.specific {
+        ObjectType::Dictionary(ref d) => Some(&d.value_handles),
+        ObjectType::DictionaryWithDefault(ref d) => Some(&d.value_handles),
+        _ => None,
+    }
+}
+
+pub(crate) fn dictionary_entries_mut(obj: &mut Object) -> Option<&mut HashMap<String, Handle>> {
+    match obj.specific {
+        ObjectType::Dictionary(ref mut d) => Some(&mut d.value_handles),
+        ObjectType::DictionaryWithDefault(ref mut d) => Some(&mut d.value_handles),
+        _ => None,
+    }
+}
+
+fn dictionary_entry(obj: &Object, name: &str) -> Option<Handle> {
+    dictionary_key(obj, name).map(|(_, handle)| handle)
+}
+
+/// Returns the key that matches `name`, preferring an exact match, along with its value.
+fn dictionary_key(obj: &Object, name: &str) -> Option<(String, Handle)> {
+    let entries = dictionary_entries(obj)?;
+    match entries.get_key_value(name) {
+        Some((key, handle)) => Some((key.clone(), *handle)),
+        None => entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(key, handle)| (key.clone(), *handle)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::*;
+    use crate::*;
+
+    fn reactors(obj: &Object) -> Vec<Handle> {
+        obj.common
+            .extension_data_groups
+            .iter()
+            .filter(|g| g.application_name == "ACAD_REACTORS")
+            .flat_map(|g| g.items.iter())
+            .filter_map(|item| match item {
+                ExtensionGroupItem::CodePair(pair) => pair.as_handle().ok(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn named_object_dictionary_is_added_once() {
+        let mut drawing = Drawing::new();
+        assert!(drawing.named_object_dictionary().is_none());
+        let root = drawing.ensure_named_object_dictionary();
+        assert_eq!(root, drawing.ensure_named_object_dictionary());
+        assert_eq!(1, drawing.objects().count());
+        assert_eq!(root, drawing.dictionary_path(&[]).unwrap().common.handle);
+    }
+
+    #[test]
+    fn entries_are_found_by_path() {
+        let mut drawing = Drawing::new();
+        let layouts = drawing.ensure_dictionary_path(&["ACAD_LAYOUT"]).unwrap();
+        let layout = Object::new(ObjectType::Layout(Layout {
+            layout_name: String::from("Layout1"),
+            ..Default::default()
+        }));
+        let handle = drawing
+            .add_dictionary_entry(layouts, "Layout1", layout)
+            .unwrap()
+            .common
+            .handle;
+
+        let found = drawing
+            .dictionary_path(&["ACAD_LAYOUT", "Layout1"])
+            .unwrap();
+        assert_eq!(handle, found.common.handle);
+        assert_eq!(layouts, found.common.__owner_handle);
+        assert_eq!(vec![layouts], reactors(found));
+        assert!(drawing
+            .dictionary_path(&["acad_layout", "LAYOUT1"])
+            .is_some());
+        assert!(drawing
+            .dictionary_path(&["ACAD_LAYOUT", "Layout2"])
+            .is_none());
+
+        // intermediate dictionaries are owned by their parents
+        let root = drawing.named_object_dictionary().unwrap().common.handle;
+        let dictionary = drawing.dictionary_path(&["ACAD_LAYOUT"]).unwrap();
+        assert_eq!(root, dictionary.common.__owner_handle);
+        assert_eq!(vec![root], reactors(dictionary));
+        assert_eq!(
+            layouts,
+            drawing.ensure_dictionary_path(&["ACAD_LAYOUT"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn replaced_entries_are_removed() {
+        let mut drawing = Drawing::new();
+        let groups = drawing.ensure_dictionary_path(&["ACAD_GROUP"]).unwrap();
+        for description in ["first", "second"] {
+            let group = Object::new(ObjectType::Group(Group {
+                description: String::from(description),
+                ..Default::default()
+            }));
+            drawing.add_dictionary_entry(groups, "G", group).unwrap();
+        }
+
+        assert_eq!(3, drawing.objects().count());
+        match drawing
+            .dictionary_path(&["ACAD_GROUP", "G"])
+            .unwrap()
+            .specific
+        {
+            ObjectType::Group(ref g) => assert_eq!("second", g.description),
+            _ => panic!("expected a group"),
+        }
+    }
+
+    #[test]
+    fn entries_are_replaced_regardless_of_case() {
+        let mut drawing = Drawing::new();
+        let root = drawing.ensure_named_object_dictionary();
+        for name in ["MY_APP", "my_app"] {
+            let child = Object::new(ObjectType::Dictionary(Dictionary::default()));
+            drawing.add_dictionary_entry(root, name, child).unwrap();
+        }
+
+        assert_eq!(2, drawing.objects().count());
+        let root = drawing.named_object_dictionary().unwrap();
+        match root.specific {
+            ObjectType::Dictionary(ref d) => {
+                assert_eq!(vec!["my_app"], d.value_handles.keys().collect::<Vec<_>>())
+            }
+            _ => panic!("expected a dictionary"),
+        }
+    }
+
+    #[test]
+    fn paths_through_other_objects_are_errors() {
+        let mut drawing = Drawing::new();
+        let root = drawing.ensure_named_object_dictionary();
+        drawing
+            .add_dictionary_entry(
+                root,
+                "GROUP",
+                Object::new(ObjectType::Group(Group::default())),
+            )
+            .unwrap();
+        let group = drawing.dictionary_path(&["GROUP"]).unwrap().common.handle;
+
+        assert!(drawing.dictionary_path(&["GROUP", "X"]).is_none());
+        assert!(matches!(
+            drawing.ensure_dictionary_path(&["GROUP", "X"]),
+            Err(DxfError::WrongItemType)
+        ));
+        assert!(matches!(
+            drawing.add_dictionary_entry(
+                group,
+                "X",
+                Object::new(ObjectType::Group(Group::default()))
+            ),
+            Err(DxfError::WrongItemType)
+        ));
+    }
+}
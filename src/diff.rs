@@ -0,0 +1,812 @@
+// Semantic comparison of two `Drawing`s
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::enums::AcadVersion;
+use crate::{CodePair, CodePairValue, Drawing, Handle};
+
+/// The tolerance used by `diff` when comparing floating-point values.
+pub const DEFAULT_DIFF_TOLERANCE: f64 = 1e-9;
+
+// LCS tables larger than this fall back to a positional comparison.
+const MAX_LCS_CELLS: usize = 1_000_000;
+
+/// Compares two drawings using `DEFAULT_DIFF_TOLERANCE`.  See `diff_with_tolerance`.
+pub fn diff(old: &Drawing, new: &Drawing) -> DrawingDiff {
+    diff_with_tolerance(old, new, DEFAULT_DIFF_TOLERANCE)
+}
+
+/// Compares two drawings and returns the header variables, table entries, blocks, entities, and
+/// objects that were added, removed, or modified.
+///
+/// Items are matched by handle.  Table entries and blocks that aren't matched by handle are then
+/// matched by name, and entities and objects are matched by content, ignoring handles and
+/// pointers.  Any remaining entities are matched by geometry so that an entity that was moved
+/// to another layer in a drawing with regenerated handles is reported as modified instead of
+/// removed and re-added.  Floating-point values are considered equal if they're within
+/// `tolerance` of each other.
+pub fn diff_with_tolerance(old: &Drawing, new: &Drawing, tolerance: f64) -> DrawingDiff {
+    let version = if old.header.version >= new.header.version {
+        old.header.version
+    } else {
+        new.header.version
+    };
+    DrawingDiff {
+        header_changes: diff_header(old, new, tolerance),
+        table_changes: match_items(
+            &table_items(old, version),
+            &table_items(new, version),
+            tolerance,
+            Fallback::Name,
+        ),
+        block_changes: match_items(
+            &block_items(old, version),
+            &block_items(new, version),
+            tolerance,
+            Fallback::Name,
+        ),
+        entity_changes: match_items(
+            &entity_items(old, version),
+            &entity_items(new, version),
+            tolerance,
+            Fallback::Geometry,
+        ),
+        object_changes: match_items(
+            &object_items(old, version),
+            &object_items(new, version),
+            tolerance,
+            Fallback::Content,
+        ),
+    }
+}
+
+/// The differences between two drawings, as returned by `diff`.  The `Display` implementation
+/// produces a human-readable report.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DrawingDiff {
+    pub header_changes: Vec<HeaderChange>,
+    pub table_changes: Vec<ItemChange>,
+    pub block_changes: Vec<ItemChange>,
+    pub entity_changes: Vec<ItemChange>,
+    pub object_changes: Vec<ItemChange>,
+}
+
+impl DrawingDiff {
+    /// Returns `true` if the drawings are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.header_changes.is_empty()
+            && self.table_changes.is_empty()
+            && self.block_changes.is_empty()
+            && self.entity_changes.is_empty()
+            && self.object_changes.is_empty()
+    }
+}
+
+impl fmt::Display for DrawingDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(formatter, "no differences");
+        }
+
+        for change in &self.header_changes {
+            writeln!(formatter, "{change}")?;
+        }
+
+        for (section, changes) in [
+            ("table", &self.table_changes),
+            ("block", &self.block_changes),
+            ("entity", &self.entity_changes),
+            ("object", &self.object_changes),
+        ] {
+            for change in changes {
+                write!(formatter, "{section} {change}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A header variable whose value differs between two drawings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaderChange {
+    /// The variable name, e.g., `$ANGBASE`.
+    pub variable: String,
+    /// The values in the old drawing; empty if the variable isn't written for its version.
+    pub old: Vec<CodePair>,
+    /// The values in the new drawing; empty if the variable isn't written for its version.
+    pub new: Vec<CodePair>,
+}
+
+impl fmt::Display for HeaderChange {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "header {}: {} -> {}",
+            self.variable,
+            format_pairs(&self.old),
+            format_pairs(&self.new)
+        )
+    }
+}
+
+/// How an item differs between two drawings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A table entry, block, entity, or object that differs between two drawings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemChange {
+    pub kind: ChangeKind,
+    /// The type of the item as written to a DXF file, e.g., `LINE` or `LAYER`.
+    pub item_type: String,
+    /// The name of the item, e.g., the layer or block name; empty if the item isn't named.
+    pub name: String,
+    /// The handle of the item in the old drawing; `None` if it was added.
+    pub old_handle: Option<Handle>,
+    /// The handle of the item in the new drawing; `None` if it was removed.
+    pub new_handle: Option<Handle>,
+    /// The code pairs that differ when the item was modified, in the order they're written.
+    pub pair_changes: Vec<PairChange>,
+}
+
+impl fmt::Display for ItemChange {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Modified => '~',
+        };
+        write!(formatter, "{symbol} {}", self.item_type)?;
+        if !self.name.is_empty() {
+            write!(formatter, " \"{}\"", self.name)?;
+        }
+
+        match (self.old_handle, self.new_handle) {
+            (Some(old), Some(new)) if old != new => {
+                write!(formatter, " {} -> {}", old.as_string(), new.as_string())?
+            }
+            (Some(h), _) | (None, Some(h)) => write!(formatter, " {}", h.as_string())?,
+            (None, None) => (),
+        }
+
+        writeln!(formatter)?;
+        for change in &self.pair_changes {
+            writeln!(formatter, "    {change}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A code pair that differs between two versions of an item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairChange {
+    /// The pair in the old item; `None` if it was added.
+    pub old: Option<CodePair>,
+    /// The pair in the new item; `None` if it was removed.
+    pub new: Option<CodePair>,
+}
+
+impl fmt::Display for PairChange {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(
+                formatter,
+                "{} -> {}",
+                format_pair(old),
+                format_value(&new.value)
+            ),
+            (Some(old), None) => write!(formatter, "- {}", format_pair(old)),
+            (None, Some(new)) => write!(formatter, "+ {}", format_pair(new)),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+fn format_value(value: &CodePairValue) -> String {
+    value.to_string().trim().to_string()
+}
+
+fn format_pair(pair: &CodePair) -> String {
+    format!("{}/{}", pair.code, format_value(&pair.value))
+}
+
+fn format_pairs(pairs: &[CodePair]) -> String {
+    if pairs.is_empty() {
+        String::from("(none)")
+    } else {
+        pairs.iter().map(format_pair).collect::<Vec<_>>().join(", ")
+    }
+}
+
+fn diff_header(old: &Drawing, new: &Drawing, tolerance: f64) -> Vec<HeaderChange> {
+    fn variables(drawing: &Drawing) -> Vec<(String, Vec<CodePair>)> {
+        let mut pairs = vec![];
+        drawing.header.add_code_pairs_internal(&mut pairs);
+        let mut variables: Vec<(String, Vec<CodePair>)> = vec![];
+        for pair in pairs {
+            match (pair.code, variables.last_mut()) {
+                (9, _) => variables.push((pair.assert_string().unwrap_or_default(), vec![])),
+                (_, Some((_, values))) => values.push(pair),
+                (_, None) => (),
+            }
+        }
+        variables
+    }
+
+    let old_variables = variables(old);
+    let new_variables = variables(new);
+    let new_values: HashMap<&str, &Vec<CodePair>> = new_variables
+        .iter()
+        .map(|(name, values)| (name.as_str(), values))
+        .collect();
+    let old_names: HashSet<&str> = old_variables.iter().map(|(n, _)| n.as_str()).collect();
+
+    let mut changes = vec![];
+    for (name, old_values) in &old_variables {
+        let new_values = new_values.get(name.as_str()).copied().cloned();
+        let new_values = new_values.unwrap_or_default();
+        if !pairs_equal(old_values, &new_values, tolerance, false) {
+            changes.push(HeaderChange {
+                variable: name.clone(),
+                old: old_values.clone(),
+                new: new_values,
+            });
+        }
+    }
+
+    for (name, new_values) in &new_variables {
+        if !old_names.contains(name.as_str()) {
+            changes.push(HeaderChange {
+                variable: name.clone(),
+                old: vec![],
+                new: new_values.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+// An item prepared for comparison.
+struct Item {
+    handle: Handle,
+    item_type: String,
+    name: String,
+    // all pairs except for the item's type and handle
+    pairs: Vec<CodePair>,
+}
+
+impl Item {
+    fn new<F>(handle: Handle, add_code_pairs: F) -> Self
+    where
+        F: FnOnce(&mut Vec<CodePair>),
+    {
+        let mut pairs = vec![];
+        add_code_pairs(&mut pairs);
+        let item_type = match pairs.first() {
+            Some(pair) if pair.code == 0 => pairs.remove(0).assert_string().unwrap_or_default(),
+            _ => String::new(),
+        };
+        pairs.retain(|p| p.code != 5 && p.code != 105);
+        let name = pairs
+            .iter()
+            .find(|p| p.code == 2)
+            .and_then(|p| p.assert_string().ok())
+            .unwrap_or_default();
+        Item {
+            handle,
+            item_type,
+            name,
+            pairs,
+        }
+    }
+}
+
+fn table_items(drawing: &Drawing, version: AcadVersion) -> Vec<Item> {
+    let mut items = vec![];
+    for item in drawing.app_ids() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.block_records() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.dim_styles() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.layers() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.line_types() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.styles() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.ucss() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.views() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    for item in drawing.view_ports() {
+        items.push(Item::new(item.handle, |p| {
+            item.add_code_pairs(p, version, true)
+        }));
+    }
+    items
+}
+
+fn block_items(drawing: &Drawing, version: AcadVersion) -> Vec<Item> {
+    drawing
+        .blocks()
+        .map(|b| Item::new(b.handle, |p| b.add_code_pairs(p, version, true)))
+        .collect()
+}
+
+fn entity_items(drawing: &Drawing, version: AcadVersion) -> Vec<Item> {
+    drawing
+        .entities()
+        .map(|e| Item::new(e.common.handle, |p| e.add_code_pairs(p, version, true)))
+        .collect()
+}
+
+fn object_items(drawing: &Drawing, version: AcadVersion) -> Vec<Item> {
+    drawing
+        .objects()
+        .map(|o| Item::new(o.common.handle, |p| o.add_code_pairs(p, version)))
+        .collect()
+}
+
+// How items that aren't matched by handle are matched.
+#[derive(Clone, Copy, PartialEq)]
+enum Fallback {
+    Name,
+    Content,
+    Geometry,
+}
+
+impl Fallback {
+    fn is_match(self, old: &Item, new: &Item, tolerance: f64) -> bool {
+        match self {
+            Fallback::Name => !old.name.is_empty() && old.name.eq_ignore_ascii_case(&new.name),
+            Fallback::Content => pairs_equal(&old.pairs, &new.pairs, tolerance, true),
+            Fallback::Geometry => geometry_equal(&old.pairs, &new.pairs, tolerance),
+        }
+    }
+}
+
+fn match_items(old: &[Item], new: &[Item], tolerance: f64, fallback: Fallback) -> Vec<ItemChange> {
+    let mut old_to_new: Vec<Option<usize>> = vec![None; old.len()];
+    let mut new_matched = vec![false; new.len()];
+
+    // by handle
+    let new_by_handle: HashMap<u64, usize> = new
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !item.handle.is_empty())
+        .map(|(i, item)| (item.handle.0, i))
+        .collect();
+    for (old_index, item) in old.iter().enumerate() {
+        if item.handle.is_empty() {
+            continue;
+        }
+        if let Some(&new_index) = new_by_handle.get(&item.handle.0) {
+            if !new_matched[new_index] && new[new_index].item_type == item.item_type {
+                old_to_new[old_index] = Some(new_index);
+                new_matched[new_index] = true;
+            }
+        }
+    }
+
+    // by name, content, and then geometry
+    let passes: &[Fallback] = match fallback {
+        Fallback::Geometry => &[Fallback::Content, Fallback::Geometry],
+        _ => &[fallback],
+    };
+    for &pass in passes {
+        for (old_index, old_item) in old.iter().enumerate() {
+            if old_to_new[old_index].is_some() {
+                continue;
+            }
+            let candidate = (0..new.len()).find(|&new_index| {
+                !new_matched[new_index]
+                    && new[new_index].item_type == old_item.item_type
+                    && pass.is_match(old_item, &new[new_index], tolerance)
+            });
+            if let Some(new_index) = candidate {
+                old_to_new[old_index] = Some(new_index);
+                new_matched[new_index] = true;
+            }
+        }
+    }
+
+    let mut changes = vec![];
+    for (old_item, new_index) in old.iter().zip(&old_to_new) {
+        match new_index {
+            Some(new_index) => {
+                let new_item = &new[*new_index];
+                let pair_changes = diff_pairs(&old_item.pairs, &new_item.pairs, tolerance);
+                if !pair_changes.is_empty() || old_item.handle != new_item.handle {
+                    changes.push(ItemChange {
+                        kind: ChangeKind::Modified,
+                        item_type: new_item.item_type.clone(),
+                        name: new_item.name.clone(),
+                        old_handle: Some(old_item.handle),
+                        new_handle: Some(new_item.handle),
+                        pair_changes,
+                    });
+                }
+            }
+            None => changes.push(ItemChange {
+                kind: ChangeKind::Removed,
+                item_type: old_item.item_type.clone(),
+                name: old_item.name.clone(),
+                old_handle: Some(old_item.handle),
+                new_handle: None,
+                pair_changes: vec![],
+            }),
+        }
+    }
+
+    for (new_item, _) in new.iter().zip(&new_matched).filter(|(_, m)| !**m) {
+        changes.push(ItemChange {
+            kind: ChangeKind::Added,
+            item_type: new_item.item_type.clone(),
+            name: new_item.name.clone(),
+            old_handle: None,
+            new_handle: Some(new_item.handle),
+            pair_changes: vec![],
+        });
+    }
+
+    changes
+}
+
+fn is_pointer_code(code: i32) -> bool {
+    matches!(code, 320..=369 | 390..=399 | 480..=481 | 1005)
+}
+
+fn pair_equal(a: &CodePair, b: &CodePair, tolerance: f64) -> bool {
+    if a.code != b.code {
+        return false;
+    }
+
+    match (&a.value, &b.value) {
+        (CodePairValue::Double(a), CodePairValue::Double(b)) => (a - b).abs() <= tolerance,
+        (a, b) => a == b,
+    }
+}
+
+fn pairs_equal(a: &[CodePair], b: &[CodePair], tolerance: f64, ignore_pointers: bool) -> bool {
+    let mut a = a
+        .iter()
+        .filter(|p| !ignore_pointers || !is_pointer_code(p.code));
+    let mut b = b
+        .iter()
+        .filter(|p| !ignore_pointers || !is_pointer_code(p.code));
+    loop {
+        match (a.next(), b.next()) {
+            (Some(a), Some(b)) if pair_equal(a, b, tolerance) => (),
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+fn geometry_equal(a: &[CodePair], b: &[CodePair], tolerance: f64) -> bool {
+    fn coordinates(pairs: &[CodePair]) -> impl Iterator<Item = &CodePair> {
+        pairs
+            .iter()
+            .filter(|p| matches!(p.value, CodePairValue::Double(_)))
+    }
+
+    coordinates(a).next().is_some()
+        && coordinates(a).count() == coordinates(b).count()
+        && coordinates(a)
+            .zip(coordinates(b))
+            .all(|(a, b)| pair_equal(a, b, tolerance))
+}
+
+// Returns the pairs that differ, aligned with the longest common subsequence so that an inserted
+// pair doesn't cause every following pair to be reported.
+fn diff_pairs(old: &[CodePair], new: &[CodePair], tolerance: f64) -> Vec<PairChange> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| pair_equal(a, b, tolerance))
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| pair_equal(a, b, tolerance))
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    let mut changes = vec![];
+    if old.len().saturating_mul(new.len()) > MAX_LCS_CELLS {
+        for i in 0..old.len().max(new.len()) {
+            push_change(&mut changes, old.get(i), new.get(i), tolerance);
+        }
+        return changes;
+    }
+
+    // lengths[i][j] is the length of the LCS of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if pair_equal(&old[i], &new[j], tolerance) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    // walk the table, pairing up a removal followed by an addition of the same code
+    let mut removed: Vec<&CodePair> = vec![];
+    let mut added: Vec<&CodePair> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && pair_equal(&old[i], &new[j], tolerance) {
+            flush_changes(&mut changes, &mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            added.push(&new[j]);
+            j += 1;
+        } else {
+            removed.push(&old[i]);
+            i += 1;
+        }
+    }
+    flush_changes(&mut changes, &mut removed, &mut added);
+
+    changes
+}
+
+fn flush_changes(
+    changes: &mut Vec<PairChange>,
+    removed: &mut Vec<&CodePair>,
+    added: &mut Vec<&CodePair>,
+) {
+    for k in 0..removed.len().max(added.len()) {
+        let old = removed.get(k).copied();
+        let new = added.get(k).copied();
+        match (old, new) {
+            (Some(o), Some(n)) if o.code != n.code => {
+                changes.push(PairChange {
+                    old: Some(o.clone()),
+                    new: None,
+                });
+                changes.push(PairChange {
+                    old: None,
+                    new: Some(n.clone()),
+                });
+            }
+            _ => changes.push(PairChange {
+                old: old.cloned(),
+                new: new.cloned(),
+            }),
+        }
+    }
+    removed.clear();
+    added.clear();
+}
+
+fn push_change(
+    changes: &mut Vec<PairChange>,
+    old: Option<&CodePair>,
+    new: Option<&CodePair>,
+    tolerance: f64,
+) {
+    match (old, new) {
+        (Some(o), Some(n)) if pair_equal(o, n, tolerance) => (),
+        _ => changes.push(PairChange {
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::tables::*;
+    use crate::*;
+
+    fn line(p1: Point, p2: Point) -> Entity {
+        Entity::new(EntityType::Line(Line::new(p1, p2)))
+    }
+
+    #[test]
+    fn identical_drawings_have_no_differences() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line(Point::origin(), Point::new(1.0, 1.0, 0.0)));
+        let result = diff(&drawing, &drawing.clone());
+        assert!(result.is_empty(), "{result}");
+        assert_eq!("no differences\n", result.to_string());
+    }
+
+    #[test]
+    fn header_variable_changes_are_reported() {
+        let old = Drawing::new();
+        let mut new = old.clone();
+        new.header.angle_zero_direction = 55.0;
+        let result = diff(&old, &new);
+        assert_eq!(1, result.header_changes.len());
+        let change = &result.header_changes[0];
+        assert_eq!("$ANGBASE", change.variable);
+        assert_eq!(vec![CodePair::new_f64(50, 0.0)], change.old);
+        assert_eq!(vec![CodePair::new_f64(50, 55.0)], change.new);
+        assert_eq!("header $ANGBASE: 50/0.0 -> 50/55.0\n", result.to_string());
+    }
+
+    #[test]
+    fn entities_are_matched_by_handle() {
+        let mut old = Drawing::new();
+        old.add_entity(line(Point::origin(), Point::new(1.0, 1.0, 0.0)));
+        old.add_entity(line(Point::origin(), Point::new(2.0, 2.0, 0.0)));
+        let mut new = old.clone();
+        match new.entities_mut().next().unwrap().specific {
+            EntityType::Line(ref mut line) => line.p2 = Point::new(3.0, 1.0, 0.0),
+            _ => unreachable!(),
+        }
+
+        let result = diff(&old, &new);
+        assert_eq!(1, result.entity_changes.len());
+        let change = &result.entity_changes[0];
+        assert_eq!(ChangeKind::Modified, change.kind);
+        assert_eq!("LINE", change.item_type);
+        assert_eq!(change.old_handle, change.new_handle);
+        assert_eq!(
+            vec![PairChange {
+                old: Some(CodePair::new_f64(11, 1.0)),
+                new: Some(CodePair::new_f64(11, 3.0)),
+            }],
+            change.pair_changes
+        );
+    }
+
+    #[test]
+    fn added_and_removed_entities() {
+        let mut old = Drawing::new();
+        old.add_entity(line(Point::origin(), Point::new(1.0, 1.0, 0.0)));
+        let mut new = Drawing::new();
+        new.add_entity(Entity::new(EntityType::Circle(Circle::new(
+            Point::origin(),
+            1.0,
+        ))));
+
+        let result = diff(&old, &new);
+        let kinds: Vec<_> = result
+            .entity_changes
+            .iter()
+            .map(|c| (c.kind, c.item_type.as_str()))
+            .collect();
+        assert_eq!(
+            vec![(ChangeKind::Removed, "LINE"), (ChangeKind::Added, "CIRCLE")],
+            kinds
+        );
+    }
+
+    #[test]
+    fn regenerated_handles_are_matched_by_geometry() {
+        let mut old = Drawing::new();
+        old.add_entity(line(Point::origin(), Point::new(1.0, 1.0, 0.0)));
+        old.add_entity(line(Point::origin(), Point::new(2.0, 2.0, 0.0)));
+
+        // same entities in reverse order with different handles
+        let mut new = Drawing::new();
+        for name in ["other", "unused"] {
+            new.add_layer(Layer {
+                name: String::from(name),
+                ..Default::default()
+            });
+        }
+        let mut moved = line(Point::origin(), Point::new(2.0, 2.0, 0.0));
+        moved.common.layer = String::from("other");
+        new.add_entity(moved);
+        new.add_entity(line(Point::origin(), Point::new(1.0 + 1e-12, 1.0, 0.0)));
+
+        let result = diff(&old, &new);
+        let entity_changes: Vec<_> = result
+            .entity_changes
+            .iter()
+            .filter(|c| !c.pair_changes.is_empty())
+            .collect();
+        assert_eq!(1, entity_changes.len());
+        let change = entity_changes[0];
+        assert_eq!(ChangeKind::Modified, change.kind);
+        assert_ne!(change.old_handle, change.new_handle);
+        assert_eq!(
+            vec![PairChange {
+                old: Some(CodePair::new_str(8, "0")),
+                new: Some(CodePair::new_str(8, "other")),
+            }],
+            change.pair_changes
+        );
+        assert!(result
+            .entity_changes
+            .iter()
+            .all(|c| c.kind == ChangeKind::Modified));
+    }
+
+    #[test]
+    fn table_entries_are_matched_by_name() {
+        let mut old = Drawing::new();
+        old.add_layer(Layer {
+            name: String::from("walls"),
+            ..Default::default()
+        });
+        // the new drawing has different handles
+        let mut new = Drawing::new();
+        new.add_app_id(AppId {
+            name: String::from("APP"),
+            ..Default::default()
+        });
+        new.add_layer(Layer {
+            name: String::from("WALLS"),
+            is_layer_on: false,
+            ..Default::default()
+        });
+        new.add_layer(Layer {
+            name: String::from("doors"),
+            ..Default::default()
+        });
+
+        let result = diff(&old, &new);
+        let layer_changes: Vec<_> = result
+            .table_changes
+            .iter()
+            .filter(|c| c.item_type == "LAYER")
+            .map(|c| (c.kind, c.name.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (ChangeKind::Modified, "WALLS"),
+                (ChangeKind::Added, "doors")
+            ],
+            layer_changes
+        );
+    }
+
+    #[test]
+    fn text_report() {
+        let mut old = Drawing::new();
+        old.add_entity(line(Point::origin(), Point::new(1.0, 1.0, 0.0)));
+        let mut new = old.clone();
+        match new.entities_mut().next().unwrap().specific {
+            EntityType::Line(ref mut line) => line.p1 = Point::new(0.5, 0.0, 0.0),
+            _ => unreachable!(),
+        }
+
+        let handle = old.entities().next().unwrap().common.handle.as_string();
+        let expected = format!("entity ~ LINE {handle}\n    10/0.0 -> 0.5\n");
+        assert_eq!(expected, diff(&old, &new).to_string());
+    }
+}
@@ -10,11 +10,17 @@ use crate::header::*;
 use crate::objects::*;
 use crate::tables::*;
 
-use crate::{CodePair, CodePairValue, DxfError, DxfResult, Handle};
+use crate::{
+    ocs_to_wcs_matrix, BoundingBox, CodePair, CodePairValue, DxbWriteOptions, DxfError, DxfResult,
+    Handle, LoadDiagnostic, Point, TransformationMatrix, Vector,
+};
+
+use crate::bounding_box::Bounds;
 
 use crate::dxb_reader::DxbReader;
 use crate::dxb_writer::DxbWriter;
 use crate::entity_iter::EntityIter;
+use crate::handle_index::{HandleIndex, ItemLocation};
 use crate::helper_functions::*;
 use crate::object_iter::ObjectIter;
 
@@ -29,8 +35,7 @@ use crate::thumbnail;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Cursor, Read, Write};
 
-use itertools::put_back;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::iter::Iterator;
 use std::path::Path;
 
@@ -72,10 +77,24 @@ pub struct Drawing {
     __entities: Vec<Entity>,
     /// Internal collection of objects.
     __objects: Vec<Object>,
+    /// Internal index of item handles.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    __handle_index: HandleIndex,
 
     /// The thumbnail image preview of the drawing.
     #[cfg_attr(feature = "serialize", serde(skip))]
     pub thumbnail: Option<DynamicImage>,
+
+    /// When `true`, the `$EXTMIN` and `$EXTMAX` header values are recomputed from the model space
+    /// entities as the drawing is saved.  This is a save setting, so it isn't serialized.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub update_extents_on_save: bool,
+
+    /// When set to a `(width, height)`, the thumbnail is rendered from the model space entities at
+    /// that size as the drawing is saved, instead of writing `thumbnail`.  This is a save setting,
+    /// so it isn't serialized.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub update_thumbnail_on_save: Option<(u32, u32)>,
 }
 
 // public implementation
@@ -97,7 +116,10 @@ impl Drawing {
             __blocks: vec![],
             __entities: vec![],
             __objects: vec![],
+            __handle_index: HandleIndex::default(),
             thumbnail: None,
+            update_extents_on_save: false,
+            update_thumbnail_on_save: None,
         };
         drawing.normalize();
         drawing
@@ -128,23 +150,17 @@ impl Drawing {
     }
     /// Loads a `Drawing` from the specified `CodePairIter`.
     pub(crate) fn load_from_iter(iter: Box<dyn CodePairIter>) -> DxfResult<Drawing> {
+        let mut iter = CodePairPutBack::from_code_pair_iter(iter);
+        Drawing::load_from_put_back(&mut iter)
+    }
+    fn load_from_put_back(iter: &mut CodePairPutBack) -> DxfResult<Drawing> {
         let mut drawing = Drawing::new();
         drawing.clear();
-        let mut iter = CodePairPutBack::from_code_pair_iter(iter);
-        Drawing::read_sections(&mut drawing, &mut iter)?;
-        match iter.next() {
-            Some(Ok(CodePair {
-                code: 0,
-                value: CodePairValue::Str(ref s),
-                ..
-            })) if s == "EOF" => Ok(drawing),
-            Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
-                pair,
-                String::from("expected 0/EOF"),
-            )),
-            Some(Err(e)) => Err(e),
-            None => Ok(drawing),
+        Drawing::read_sections(&mut drawing, iter).map_err(|e| iter.add_context(e))?;
+        if let Err(e) = Drawing::read_eof(iter) {
+            iter.recover(e).map_err(|e| iter.add_context(e))?;
         }
+        Ok(drawing)
     }
     /// Loads a `Drawing` from disk, using a `BufReader`.
     pub fn load_file(path: impl AsRef<Path>) -> DxfResult<Drawing> {
@@ -159,6 +175,73 @@ impl Drawing {
         let mut buf_reader = BufReader::new(file);
         Drawing::load_with_encoding(&mut buf_reader, encoding)
     }
+    /// Loads a `Drawing` from anything that implements the `Read` trait, recovering from malformed
+    /// content instead of failing.
+    ///
+    /// Malformed code pairs are skipped, items that can't be completely read are either kept with
+    /// the values that could be read or dropped, and reading resumes with the next item or
+    /// section.  Each problem is described by a `LoadDiagnostic`.  An error is only returned if
+    /// reading can't continue, e.g., when the underlying reader fails.  DXB files are loaded the
+    /// same as with `Drawing::load`.
+    ///
+    /// ``` rust
+    /// # fn ex() -> dxf::DxfResult<()> {
+    /// use dxf::Drawing;
+    ///
+    /// let mut file = std::fs::File::open("path/to/file.dxf")?;
+    /// let (drawing, diagnostics) = Drawing::load_lenient(&mut file)?;
+    /// for diagnostic in &diagnostics {
+    ///     println!("skipped: {}", diagnostic);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_lenient<T>(reader: &mut T) -> DxfResult<(Drawing, Vec<LoadDiagnostic>)>
+    where
+        T: Read + ?Sized,
+    {
+        Drawing::load_lenient_with_encoding(reader, encoding_rs::WINDOWS_1252)
+    }
+    /// Loads a `Drawing` from anything that implements the `Read` trait using the specified text
+    /// encoding, recovering from malformed content instead of failing.  See
+    /// `Drawing::load_lenient`.
+    pub fn load_lenient_with_encoding<T>(
+        reader: &mut T,
+        encoding: &'static Encoding,
+    ) -> DxfResult<(Drawing, Vec<LoadDiagnostic>)>
+    where
+        T: Read + ?Sized,
+    {
+        let first_line = read_line(reader, true, encoding)?;
+        match &*first_line {
+            "AutoCAD DXB 1.0" => {
+                let mut reader = DxbReader::new(reader);
+                Ok((reader.load()?, vec![]))
+            }
+            _ => {
+                let iter = new_code_pair_iter_from_reader(reader, encoding, first_line)?;
+                let mut iter = CodePairPutBack::from_code_pair_iter(iter);
+                iter.set_lenient();
+                let drawing = Drawing::load_from_put_back(&mut iter)?;
+                Ok((drawing, iter.take_diagnostics()))
+            }
+        }
+    }
+    /// Loads a `Drawing` from disk, using a `BufReader`, recovering from malformed content instead
+    /// of failing.  See `Drawing::load_lenient`.
+    pub fn load_file_lenient(path: impl AsRef<Path>) -> DxfResult<(Drawing, Vec<LoadDiagnostic>)> {
+        Drawing::load_file_lenient_with_encoding(path, encoding_rs::WINDOWS_1252)
+    }
+    /// Loads a `Drawing` from disk, using a `BufReader` with the specified text encoding,
+    /// recovering from malformed content instead of failing.  See `Drawing::load_lenient`.
+    pub fn load_file_lenient_with_encoding(
+        path: impl AsRef<Path>,
+        encoding: &'static Encoding,
+    ) -> DxfResult<(Drawing, Vec<LoadDiagnostic>)> {
+        let file = File::open(&path)?;
+        let mut buf_reader = BufReader::new(file);
+        Drawing::load_lenient_with_encoding(&mut buf_reader, encoding)
+    }
     /// Writes a `Drawing` to anything that implements the `Write` trait.
     pub fn save<T>(&self, writer: &mut T) -> DxfResult<()>
     where
@@ -177,7 +260,16 @@ impl Drawing {
     pub(crate) fn code_pairs(&self) -> DxfResult<Vec<CodePair>> {
         let write_handles = self.header.version >= AcadVersion::R13 || self.header.handles_enabled;
         let mut pairs = Vec::new();
-        self.header.add_code_pairs(&mut pairs);
+        if self.update_extents_on_save {
+            let mut header = self.header.clone();
+            if let Some(extents) = self.extents() {
+                header.minimum_drawing_extents = extents.min;
+                header.maximum_drawing_extents = extents.max;
+            }
+            header.add_code_pairs(&mut pairs);
+        } else {
+            self.header.add_code_pairs(&mut pairs);
+        }
         self.add_classes_pairs(&mut pairs);
         self.add_tables_pairs(&mut pairs, write_handles);
         self.add_blocks_pairs(&mut pairs, write_handles);
@@ -219,14 +311,40 @@ impl Drawing {
     where
         T: Write + ?Sized,
     {
-        let mut writer = DxbWriter::new(writer);
+        self.save_dxb_with_options(writer, &DxbWriteOptions::default())
+    }
+    /// Writes a `Drawing` as DXB to anything that implements the `Write` trait.
+    ///
+    /// DXB only supports simple 2D entities along with `Face3D`s and 3D `Line`s.  `Ellipse`s and
+    /// `Spline`s are written as line segments within `options.tolerance`, `Insert`s are replaced
+    /// with the contents of their blocks, and other entities are skipped.
+    ///
+    /// A DXB file holds at most one block, named by `options.block_name`, so each block must be
+    /// written with its own call.
+    pub fn save_dxb_with_options<T>(
+        &self,
+        writer: &mut T,
+        options: &DxbWriteOptions,
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        let mut writer = DxbWriter::new(writer, options);
         writer.write(self)
     }
     /// Writes a `Drawing` as DXB to disk, using a `BufWriter`.
     pub fn save_file_dxb(&self, path: impl AsRef<Path>) -> DxfResult<()> {
+        self.save_file_dxb_with_options(path, &DxbWriteOptions::default())
+    }
+    /// Writes a `Drawing` as DXB to disk, using a `BufWriter`.
+    pub fn save_file_dxb_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: &DxbWriteOptions,
+    ) -> DxfResult<()> {
         let file = File::create(&path)?;
         let mut buf_writer = BufWriter::new(file);
-        self.save_dxb(&mut buf_writer)
+        self.save_dxb_with_options(&mut buf_writer, options)
     }
     /// Returns an iterator for all app ids.
     pub fn app_ids(&self) -> impl Iterator<Item = &AppId> {
@@ -234,6 +352,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable app ids.
     pub fn app_ids_mut(&mut self) -> impl Iterator<Item = &mut AppId> {
+        self.__handle_index.invalidate();
         self.__app_ids.iter_mut()
     }
     /// Adds an app id to the `Drawing`.
@@ -243,7 +362,14 @@ impl Drawing {
     }
     /// Removes the specified `AppId` from the `Drawing`.
     pub fn remove_app_id(&mut self, index: usize) -> Option<AppId> {
-        Drawing::remove_item(&mut self.__app_ids, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__app_ids,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::AppId,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all block records.
     pub fn block_records(&self) -> impl Iterator<Item = &BlockRecord> {
@@ -251,6 +377,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable block records.
     pub fn block_records_mut(&mut self) -> impl Iterator<Item = &mut BlockRecord> {
+        self.__handle_index.invalidate();
         self.__block_records.iter_mut()
     }
     /// Adds a block record to the `Drawing`.
@@ -260,7 +387,14 @@ impl Drawing {
     }
     /// Removes the specified `BlockRecord` from the `Drawing`.
     pub fn remove_block_record(&mut self, index: usize) -> Option<BlockRecord> {
-        Drawing::remove_item(&mut self.__block_records, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__block_records,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::BlockRecord,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all dimension styles.
     pub fn dim_styles(&self) -> impl Iterator<Item = &DimStyle> {
@@ -268,6 +402,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable dimension styles.
     pub fn dim_styles_mut(&mut self) -> impl Iterator<Item = &mut DimStyle> {
+        self.__handle_index.invalidate();
         self.__dim_styles.iter_mut()
     }
     /// Adds a dimension style to the `Drawing`.
@@ -277,7 +412,14 @@ impl Drawing {
     }
     /// Removes the specified `DimStyle` from the `Drawing`.
     pub fn remove_dim_style(&mut self, index: usize) -> Option<DimStyle> {
-        Drawing::remove_item(&mut self.__dim_styles, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__dim_styles,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::DimStyle,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all layers.
     pub fn layers(&self) -> impl Iterator<Item = &Layer> {
@@ -285,6 +427,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable layers.
     pub fn layers_mut(&mut self) -> impl Iterator<Item = &mut Layer> {
+        self.__handle_index.invalidate();
         self.__layers.iter_mut()
     }
     /// Adds a layer to the `Drawing`.
@@ -294,7 +437,14 @@ impl Drawing {
     }
     /// Removes the specified `Layer` from the `Drawing`.
     pub fn remove_layer(&mut self, index: usize) -> Option<Layer> {
-        Drawing::remove_item(&mut self.__layers, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__layers,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::Layer,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all line types.
     pub fn line_types(&self) -> impl Iterator<Item = &LineType> {
@@ -302,6 +452,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable line types.
     pub fn line_types_mut(&mut self) -> impl Iterator<Item = &mut LineType> {
+        self.__handle_index.invalidate();
         self.__line_types.iter_mut()
     }
     /// Adds a line type to the `Drawing`.
@@ -311,7 +462,14 @@ impl Drawing {
     }
     /// Removes the specified `LineType` from the `Drawing`.
     pub fn remove_line_type(&mut self, index: usize) -> Option<LineType> {
-        Drawing::remove_item(&mut self.__line_types, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__line_types,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::LineType,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all styles.
     pub fn styles(&self) -> impl Iterator<Item = &Style> {
@@ -319,6 +477,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable styles.
     pub fn styles_mut(&mut self) -> impl Iterator<Item = &mut Style> {
+        self.__handle_index.invalidate();
         self.__styles.iter_mut()
     }
     /// Adds a style to the `Drawing`.
@@ -328,7 +487,14 @@ impl Drawing {
     }
     /// Removes the specified `Style` from the `Drawing`.
     pub fn remove_style(&mut self, index: usize) -> Option<Style> {
-        Drawing::remove_item(&mut self.__styles, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__styles,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::Style,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all ucss.
     pub fn ucss(&self) -> impl Iterator<Item = &Ucs> {
@@ -336,6 +502,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable ucss.
     pub fn ucss_mut(&mut self) -> impl Iterator<Item = &mut Ucs> {
+        self.__handle_index.invalidate();
         self.__ucss.iter_mut()
     }
     /// Add a ucs to the `Drawing`.
@@ -345,7 +512,14 @@ impl Drawing {
     }
     /// Removes the specified `Ucs` from the `Drawing`.
     pub fn remove_ucs(&mut self, index: usize) -> Option<Ucs> {
-        Drawing::remove_item(&mut self.__ucss, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__ucss,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::Ucs,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all views.
     pub fn views(&self) -> impl Iterator<Item = &View> {
@@ -353,6 +527,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable views.
     pub fn views_mut(&mut self) -> impl Iterator<Item = &mut View> {
+        self.__handle_index.invalidate();
         self.__views.iter_mut()
     }
     /// Add a view to the `Drawing`.
@@ -362,7 +537,14 @@ impl Drawing {
     }
     /// Removes the specified `View` from the `Drawing`.
     pub fn remove_view(&mut self, index: usize) -> Option<View> {
-        Drawing::remove_item(&mut self.__views, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__views,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::View,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all view ports.
     pub fn view_ports(&self) -> impl Iterator<Item = &ViewPort> {
@@ -370,6 +552,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable view ports.
     pub fn view_ports_mut(&mut self) -> impl Iterator<Item = &mut ViewPort> {
+        self.__handle_index.invalidate();
         self.__view_ports.iter_mut()
     }
     /// Add a view port to the `Drawing`.
@@ -379,7 +562,14 @@ impl Drawing {
     }
     /// Removes the specified `ViewPort` from the `Drawing`.
     pub fn remove_view_port(&mut self, index: usize) -> Option<ViewPort> {
-        Drawing::remove_item(&mut self.__view_ports, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__view_ports,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::ViewPort,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all blocks.
     pub fn blocks(&self) -> impl Iterator<Item = &Block> {
@@ -387,6 +577,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable blocks.
     pub fn blocks_mut(&mut self) -> impl Iterator<Item = &mut Block> {
+        self.__handle_index.invalidate();
         self.__blocks.iter_mut()
     }
     /// Add a block to the `Drawing`.
@@ -396,7 +587,14 @@ impl Drawing {
     }
     /// Removes the specified `Block` from the `Drawing`.
     pub fn remove_block(&mut self, index: usize) -> Option<Block> {
-        Drawing::remove_item(&mut self.__blocks, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__blocks,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::Block,
+            |item| item.handle,
+        )
     }
     /// Returns an iterator for all contained entities.
     pub fn entities(&self) -> impl Iterator<Item = &Entity> {
@@ -404,10 +602,19 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable entities.
     pub fn entities_mut(&mut self) -> impl Iterator<Item = &mut Entity> {
+        self.__handle_index.invalidate();
         self.__entities.iter_mut()
     }
     /// Adds an entity to the `Drawing`.
     pub fn add_entity(&mut self, mut entity: Entity) -> &Entity {
+        self.set_entity_handles(&mut entity);
+
+        // ensure invariants
+        self.add_entity_no_handle_set(entity)
+    }
+    /// Assigns a new handle to the entity, and to the `Seqend` and any attributes or vertices that
+    /// are waiting for one.
+    pub(crate) fn set_entity_handles(&mut self, entity: &mut Entity) {
         entity.common.handle = self.next_handle();
 
         // set child handles
@@ -430,13 +637,17 @@ impl Drawing {
             }
             _ => (),
         }
-
-        // ensure invariants
-        self.add_entity_no_handle_set(entity)
     }
     /// Removes the specified `Entity` from the `Drawing`.
     pub fn remove_entity(&mut self, index: usize) -> Option<Entity> {
-        Drawing::remove_item(&mut self.__entities, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__entities,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::Entity,
+            |item| item.common.handle,
+        )
     }
     /// Returns an iterator for all contained objects.
     pub fn objects(&self) -> impl Iterator<Item = &Object> {
@@ -444,6 +655,7 @@ impl Drawing {
     }
     /// Returns an iterator for all mutable objects.
     pub fn objects_mut(&mut self) -> impl Iterator<Item = &mut Object> {
+        self.__handle_index.invalidate();
         self.__objects.iter_mut()
     }
     /// Adds an object to the `Drawing`.
@@ -457,7 +669,14 @@ impl Drawing {
     }
     /// Removes the specified `Object` from the `Drawing`.
     pub fn remove_object(&mut self, index: usize) -> Option<Object> {
-        Drawing::remove_item(&mut self.__objects, index)
+        self.settle_handle_index();
+        Drawing::remove_item(
+            &mut self.__objects,
+            index,
+            &mut self.__handle_index,
+            ItemLocation::Object,
+            |item| item.common.handle,
+        )
     }
     /// Clears all items from the `Drawing`.
     pub fn clear(&mut self) {
@@ -474,6 +693,7 @@ impl Drawing {
         self.__blocks.clear();
         self.__entities.clear();
         self.__objects.clear();
+        self.__handle_index.invalidate();
         self.thumbnail = None;
 
         self.header.next_available_handle = Handle(1);
@@ -507,136 +727,105 @@ impl Drawing {
         self.__ucss.sort_by(|a, b| a.name.cmp(&b.name));
         self.__views.sort_by(|a, b| a.name.cmp(&b.name));
         self.__view_ports.sort_by(|a, b| a.name.cmp(&b.name));
+        self.__handle_index.invalidate();
     }
     /// Gets a `DrawingItem` with the appropriate handle or `None`.
     pub fn item_by_handle(&'_ self, handle: Handle) -> Option<DrawingItem<'_>> {
-        for item in &self.__app_ids {
-            if item.handle == handle {
-                return Some(DrawingItem::AppId(item));
-            }
-        }
-        for item in &self.__blocks {
-            if item.handle == handle {
-                return Some(DrawingItem::Block(item));
-            }
-        }
-        for item in &self.__block_records {
-            if item.handle == handle {
-                return Some(DrawingItem::BlockRecord(item));
-            }
-        }
-        for item in &self.__dim_styles {
-            if item.handle == handle {
-                return Some(DrawingItem::DimStyle(item));
-            }
-        }
-        for item in &self.__entities {
-            if item.common.handle == handle {
-                return Some(DrawingItem::Entity(item));
-            }
-        }
-        for item in &self.__layers {
-            if item.handle == handle {
-                return Some(DrawingItem::Layer(item));
-            }
-        }
-        for item in &self.__line_types {
-            if item.handle == handle {
-                return Some(DrawingItem::LineType(item));
-            }
-        }
-        for item in &self.__objects {
-            if item.common.handle == handle {
-                return Some(DrawingItem::Object(item));
-            }
-        }
-        for item in &self.__styles {
-            if item.handle == handle {
-                return Some(DrawingItem::Style(item));
-            }
-        }
-        for item in &self.__ucss {
-            if item.handle == handle {
-                return Some(DrawingItem::Ucs(item));
-            }
-        }
-        for item in &self.__views {
-            if item.handle == handle {
-                return Some(DrawingItem::View(item));
-            }
-        }
-        for item in &self.__view_ports {
-            if item.handle == handle {
-                return Some(DrawingItem::ViewPort(item));
-            }
-        }
-
-        None
+        let index = self
+            .__handle_index
+            .get_or_build(|| self.build_handle_index());
+        if let Some(item) = index
+            .get(&handle)
+            .and_then(|location| self.item_at(*location))
+            .filter(|item| item.handle() == handle)
+        {
+            return Some(item);
+        }
+
+        // the last item returned from `item_by_handle_mut` may have been given a new handle
+        self.__handle_index
+            .unchecked
+            .and_then(|(_, location)| self.item_at(location))
+            .filter(|item| item.handle() == handle)
     }
     /// Gets a `DrawingItemMut` with the appropriate handle or `None`.
     pub fn item_by_handle_mut(&'_ mut self, handle: Handle) -> Option<DrawingItemMut<'_>> {
-        for item in &mut self.__app_ids {
-            if item.handle == handle {
-                return Some(DrawingItemMut::AppId(item));
-            }
-        }
-        for item in &mut self.__blocks {
-            if item.handle == handle {
-                return Some(DrawingItemMut::Block(item));
-            }
-        }
-        for item in &mut self.__block_records {
-            if item.handle == handle {
-                return Some(DrawingItemMut::BlockRecord(item));
-            }
-        }
-        for item in &mut self.__dim_styles {
-            if item.handle == handle {
-                return Some(DrawingItemMut::DimStyle(item));
-            }
-        }
-        for item in &mut self.__entities {
-            if item.common.handle == handle {
-                return Some(DrawingItemMut::Entity(item));
-            }
-        }
-        for item in &mut self.__layers {
-            if item.handle == handle {
-                return Some(DrawingItemMut::Layer(item));
-            }
-        }
-        for item in &mut self.__line_types {
-            if item.handle == handle {
-                return Some(DrawingItemMut::LineType(item));
-            }
-        }
-        for item in &mut self.__objects {
-            if item.common.handle == handle {
-                return Some(DrawingItemMut::Object(item));
-            }
-        }
-        for item in &mut self.__styles {
-            if item.handle == handle {
-                return Some(DrawingItemMut::Style(item));
-            }
-        }
-        for item in &mut self.__ucss {
-            if item.handle == handle {
-                return Some(DrawingItemMut::Ucs(item));
-            }
-        }
-        for item in &mut self.__views {
-            if item.handle == handle {
-                return Some(DrawingItemMut::View(item));
-            }
-        }
-        for item in &mut self.__view_ports {
-            if item.handle == handle {
-                return Some(DrawingItemMut::ViewPort(item));
+        self.settle_handle_index();
+        let location = *self
+            .__handle_index
+            .get_or_build(|| self.build_handle_index())
+            .get(&handle)?;
+        self.__handle_index.unchecked = Some((handle, location));
+        self.item_at_mut(location)
+    }
+    /// Returns the extents of the model space entities, or `None` if there is no measurable
+    /// geometry.  See `Entity::bounding_box()` for how each entity is measured.
+    pub fn extents(&self) -> Option<BoundingBox> {
+        let mut bounds = Bounds::default();
+        for ent in self.entities().filter(|e| !e.common.is_in_paper_space) {
+            bounds.add_entity(ent, self);
+        }
+        bounds.result
+    }
+    /// Sets the `$EXTMIN` and `$EXTMAX` header values to the extents of the model space entities.
+    pub fn update_extents(&mut self) {
+        if let Some(extents) = self.extents() {
+            self.header.minimum_drawing_extents = extents.min;
+            self.header.maximum_drawing_extents = extents.max;
+        }
+    }
+    /// Returns copies of the entities in the block referenced by `insert`, transformed into the
+    /// coordinate system of the `Insert`.
+    ///
+    /// One copy of the block is returned for each cell of the column/row array and the visible
+    /// `Attribute`s of the `Insert` are returned as `Text`.  Nested `Insert` entities are
+    /// transformed but not expanded; see `flatten_inserts()`.  If the block can't be found, an
+    /// empty collection is returned.
+    pub fn explode_insert(&self, insert: &Insert) -> Vec<Entity> {
+        let mut result = vec![];
+        self.explode_insert_into(
+            insert,
+            &TransformationMatrix::identity(),
+            None,
+            false,
+            &mut vec![],
+            &mut result,
+        );
+        result
+    }
+    /// Replaces every `Insert` in the `Drawing` with the geometry of the block it references,
+    /// recursively expanding nested blocks.
+    ///
+    /// Block entities on layer `0` or with a `BYBLOCK` color or line type take those values from
+    /// the `Insert` that placed them.  An `Insert` that references a missing block is left as-is.
+    pub fn flatten_inserts(&mut self) {
+        let entities = self.take_entities();
+        for entity in entities {
+            let mut exploded = vec![];
+            let is_exploded = match entity.specific {
+                EntityType::Insert(ref insert) => self.explode_insert_into(
+                    insert,
+                    &TransformationMatrix::identity(),
+                    Some(&entity.common),
+                    true,
+                    &mut vec![],
+                    &mut exploded,
+                ),
+                _ => false,
+            };
+            if is_exploded {
+                for ent in exploded {
+                    self.add_entity(ent);
+                }
+            } else {
+                self.add_entity_no_handle_set(entity);
             }
         }
-
-        None
+    }
+    /// Removes and returns all entities.
+    pub(crate) fn take_entities(&mut self) -> Vec<Entity> {
+        self.__handle_index.invalidate();
+        std::mem::take(&mut self.__entities)
     }
     pub(crate) fn assign_and_get_handle(&mut self, item: &mut DrawingItemMut) -> Handle {
         if item.handle().is_empty() {
@@ -656,40 +845,355 @@ impl Drawing {
         self.header.next_available_handle = self.header.next_available_handle.next_handle_value();
         result
     }
-    fn remove_item<T>(collection: &mut Vec<T>, index: usize) -> Option<T> {
+    fn remove_item<T>(
+        collection: &mut Vec<T>,
+        index: usize,
+        handle_index: &mut HandleIndex,
+        location: fn(usize) -> ItemLocation,
+        handle: fn(&T) -> Handle,
+    ) -> Option<T> {
         if index < collection.len() {
-            Some(collection.remove(index))
+            let item = collection.remove(index);
+            if let Some(map) = handle_index.get_mut() {
+                if map.get(&handle(&item)) == Some(&location(index)) {
+                    map.remove(&handle(&item));
+                }
+
+                // every following item moved down by one
+                for (i, moved) in collection.iter().enumerate().skip(index) {
+                    if let Some(l) = map.get_mut(&handle(moved)) {
+                        if *l == location(i + 1) {
+                            *l = location(i);
+                        }
+                    }
+                }
+            }
+
+            Some(item)
         } else {
             None
         }
     }
+    fn build_handle_index(&self) -> HashMap<Handle, ItemLocation> {
+        // when handles are duplicated, the first item is found, in the same order as the
+        // collections are searched
+        let mut index = HashMap::new();
+        let mut add = |handle: Handle, location: ItemLocation| {
+            index.entry(handle).or_insert(location);
+        };
+        for (i, item) in self.__app_ids.iter().enumerate() {
+            add(item.handle, ItemLocation::AppId(i));
+        }
+        for (i, item) in self.__blocks.iter().enumerate() {
+            add(item.handle, ItemLocation::Block(i));
+        }
+        for (i, item) in self.__block_records.iter().enumerate() {
+            add(item.handle, ItemLocation::BlockRecord(i));
+        }
+        for (i, item) in self.__dim_styles.iter().enumerate() {
+            add(item.handle, ItemLocation::DimStyle(i));
+        }
+        for (i, item) in self.__entities.iter().enumerate() {
+            add(item.common.handle, ItemLocation::Entity(i));
+        }
+        for (i, item) in self.__layers.iter().enumerate() {
+            add(item.handle, ItemLocation::Layer(i));
+        }
+        for (i, item) in self.__line_types.iter().enumerate() {
+            add(item.handle, ItemLocation::LineType(i));
+        }
+        for (i, item) in self.__objects.iter().enumerate() {
+            add(item.common.handle, ItemLocation::Object(i));
+        }
+        for (i, item) in self.__styles.iter().enumerate() {
+            add(item.handle, ItemLocation::Style(i));
+        }
+        for (i, item) in self.__ucss.iter().enumerate() {
+            add(item.handle, ItemLocation::Ucs(i));
+        }
+        for (i, item) in self.__views.iter().enumerate() {
+            add(item.handle, ItemLocation::View(i));
+        }
+        for (i, item) in self.__view_ports.iter().enumerate() {
+            add(item.handle, ItemLocation::ViewPort(i));
+        }
+
+        index
+    }
+    /// Updates the handle index for the last item returned from `item_by_handle_mut`.
+    fn settle_handle_index(&mut self) {
+        if let Some((old_handle, location)) = self.__handle_index.unchecked.take() {
+            let new_handle = match self.item_at(location) {
+                Some(item) => item.handle(),
+                None => return,
+            };
+            if new_handle != old_handle {
+                if let Some(map) = self.__handle_index.get_mut() {
+                    if map.get(&old_handle) == Some(&location) {
+                        map.remove(&old_handle);
+                    }
+                    map.entry(new_handle).or_insert(location);
+                }
+            }
+        }
+    }
+    fn item_at(&self, location: ItemLocation) -> Option<DrawingItem<'_>> {
+        match location {
+            ItemLocation::AppId(i) => self.__app_ids.get(i).map(DrawingItem::AppId),
+            ItemLocation::Block(i) => self.__blocks.get(i).map(DrawingItem::Block),
+            ItemLocation::BlockRecord(i) => {
+                self.__block_records.get(i).map(DrawingItem::BlockRecord)
+            }
+            ItemLocation::DimStyle(i) => self.__dim_styles.get(i).map(DrawingItem::DimStyle),
+            ItemLocation::Entity(i) => self.__entities.get(i).map(DrawingItem::Entity),
+            ItemLocation::Layer(i) => self.__layers.get(i).map(DrawingItem::Layer),
+            ItemLocation::LineType(i) => self.__line_types.get(i).map(DrawingItem::LineType),
+            ItemLocation::Object(i) => self.__objects.get(i).map(DrawingItem::Object),
+            ItemLocation::Style(i) => self.__styles.get(i).map(DrawingItem::Style),
+            ItemLocation::Ucs(i) => self.__ucss.get(i).map(DrawingItem::Ucs),
+            ItemLocation::View(i) => self.__views.get(i).map(DrawingItem::View),
+            ItemLocation::ViewPort(i) => self.__view_ports.get(i).map(DrawingItem::ViewPort),
+        }
+    }
+    fn item_at_mut(&mut self, location: ItemLocation) -> Option<DrawingItemMut<'_>> {
+        match location {
+            ItemLocation::AppId(i) => self.__app_ids.get_mut(i).map(DrawingItemMut::AppId),
+            ItemLocation::Block(i) => self.__blocks.get_mut(i).map(DrawingItemMut::Block),
+            ItemLocation::BlockRecord(i) => self
+                .__block_records
+                .get_mut(i)
+                .map(DrawingItemMut::BlockRecord),
+            ItemLocation::DimStyle(i) => self.__dim_styles.get_mut(i).map(DrawingItemMut::DimStyle),
+            ItemLocation::Entity(i) => self.__entities.get_mut(i).map(DrawingItemMut::Entity),
+            ItemLocation::Layer(i) => self.__layers.get_mut(i).map(DrawingItemMut::Layer),
+            ItemLocation::LineType(i) => self.__line_types.get_mut(i).map(DrawingItemMut::LineType),
+            ItemLocation::Object(i) => self.__objects.get_mut(i).map(DrawingItemMut::Object),
+            ItemLocation::Style(i) => self.__styles.get_mut(i).map(DrawingItemMut::Style),
+            ItemLocation::Ucs(i) => self.__ucss.get_mut(i).map(DrawingItemMut::Ucs),
+            ItemLocation::View(i) => self.__views.get_mut(i).map(DrawingItemMut::View),
+            ItemLocation::ViewPort(i) => self.__view_ports.get_mut(i).map(DrawingItemMut::ViewPort),
+        }
+    }
     pub(crate) fn add_block_no_handle_set(&mut self, mut block: Block) -> &Block {
         self.ensure_layer_is_present_for_block(&block);
         self.ensure_line_type_is_present_for_block(&block);
         self.ensure_block_record_is_present_for_block(&mut block);
         self.ensure_block_entity_handles_are_set(&mut block);
+        self.settle_handle_index();
+        self.__handle_index
+            .insert(block.handle, ItemLocation::Block(self.__blocks.len()));
         self.__blocks.push(block);
         self.__blocks.last().unwrap()
     }
-    fn add_entity_no_handle_set(&mut self, entity: Entity) -> &Entity {
+    /// Appends the exploded contents of `insert` to `result`; returns `false` if the block
+    /// doesn't exist.
+    pub(crate) fn explode_insert_into(
+        &self,
+        insert: &Insert,
+        parent: &TransformationMatrix,
+        owner: Option<&EntityCommon>,
+        recursive: bool,
+        block_stack: &mut Vec<String>,
+        result: &mut Vec<Entity>,
+    ) -> bool {
+        let block = match self
+            .blocks()
+            .find(|b| b.name.eq_ignore_ascii_case(&insert.name))
+        {
+            Some(block) => block,
+            None => return false,
+        };
+
+        // guard against blocks that (indirectly) contain themselves
+        if !block_stack
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(&block.name))
+        {
+            block_stack.push(block.name.clone());
+            for column in 0..insert.column_count.max(1) {
+                for row in 0..insert.row_count.max(1) {
+                    let matrix = Drawing::insert_matrix(insert, &block.base_point, column, row)
+                        .compose(parent);
+                    for ent in &block.entities {
+                        let mut copy = match ent.specific {
+                            // the displayed values come from the attributes of the insert
+                            EntityType::AttributeDefinition(ref att) if !att.is_constant() => {
+                                continue
+                            }
+                            EntityType::AttributeDefinition(ref att) => Entity {
+                                common: ent.common.clone(),
+                                specific: EntityType::Text(
+                                    Drawing::text_from_attribute_definition(att),
+                                ),
+                            },
+                            EntityType::Insert(ref nested) if recursive => {
+                                let mut common = ent.common.clone();
+                                Drawing::inherit_block_properties(&mut common, owner);
+                                if self.explode_insert_into(
+                                    nested,
+                                    &matrix,
+                                    Some(&common),
+                                    recursive,
+                                    block_stack,
+                                    result,
+                                ) {
+                                    continue;
+                                }
+                                ent.clone()
+                            }
+                            _ => ent.clone(),
+                        };
+                        copy.transform(&matrix);
+                        Drawing::inherit_block_properties(&mut copy.common, owner);
+                        Drawing::reset_exploded_handles(&mut copy);
+                        result.push(copy);
+                    }
+                }
+            }
+            block_stack.pop();
+        }
+
+        for att in insert.attributes().filter(|att| !att.is_invisible()) {
+            let mut text = Entity::new(EntityType::Text(Drawing::text_from_attribute(att)));
+            if let Some(owner) = owner {
+                text.common.layer = owner.layer.clone();
+                text.common.color = owner.color.clone();
+                text.common.is_in_paper_space = owner.is_in_paper_space;
+            }
+            text.transform(parent);
+            result.push(text);
+        }
+
+        true
+    }
+    /// Maps block coordinates into the coordinates of the specified array cell of an `Insert`.
+    fn insert_matrix(
+        insert: &Insert,
+        base_point: &Point,
+        column: i16,
+        row: i16,
+    ) -> TransformationMatrix {
+        let cell_offset = Vector::new(
+            f64::from(column) * insert.column_spacing,
+            f64::from(row) * insert.row_spacing,
+            0.0,
+        );
+        TransformationMatrix::translation(&Vector::new(-base_point.x, -base_point.y, -base_point.z))
+            .compose(&TransformationMatrix::scale(
+                insert.x_scale_factor,
+                insert.y_scale_factor,
+                insert.z_scale_factor,
+            ))
+            .compose(&TransformationMatrix::translation(&cell_offset))
+            .compose(&TransformationMatrix::rotation(
+                &Vector::z_axis(),
+                insert.rotation,
+            ))
+            .compose(&TransformationMatrix::translation(&Vector::new(
+                insert.location.x,
+                insert.location.y,
+                insert.location.z,
+            )))
+            .compose(&ocs_to_wcs_matrix(&insert.extrusion_direction))
+    }
+    fn inherit_block_properties(common: &mut EntityCommon, owner: Option<&EntityCommon>) {
+        if let Some(owner) = owner {
+            if common.layer == "0" {
+                common.layer = owner.layer.clone();
+            }
+            if common.color.is_by_block() {
+                common.color = owner.color.clone();
+            }
+            if common.line_type_name.eq_ignore_ascii_case("BYBLOCK") {
+                common.line_type_name = owner.line_type_name.clone();
+            }
+            common.is_in_paper_space = owner.is_in_paper_space;
+        }
+    }
+    /// Clears the handles copied from the block so new ones are assigned when the entity is added.
+    fn reset_exploded_handles(entity: &mut Entity) {
+        entity.common.handle = Handle::empty();
+        entity.common.__owner_handle = Handle::empty();
+        match entity.specific {
+            EntityType::Insert(ref mut ins) => {
+                for a in ins.__attributes_and_handles.iter_mut() {
+                    a.1 = AUTO_REPLACE_HANDLE;
+                }
+            }
+            EntityType::Polyline(ref mut poly) => {
+                for v in poly.__vertices_and_handles.iter_mut() {
+                    v.1 = AUTO_REPLACE_HANDLE;
+                }
+            }
+            _ => (),
+        }
+    }
+    fn text_from_attribute(att: &Attribute) -> Text {
+        Text {
+            thickness: att.thickness,
+            location: att.location.clone(),
+            text_height: att.text_height,
+            value: att.value.clone(),
+            rotation: att.rotation,
+            relative_x_scale_factor: att.relative_x_scale_factor,
+            oblique_angle: att.oblique_angle,
+            text_style_name: att.text_style_name.clone(),
+            text_generation_flags: att.text_generation_flags,
+            horizontal_text_justification: att.horizontal_text_justification,
+            second_alignment_point: att.second_alignment_point.clone(),
+            normal: att.normal.clone(),
+            vertical_text_justification: att.vertical_text_justification,
+        }
+    }
+    fn text_from_attribute_definition(att: &AttributeDefinition) -> Text {
+        Text {
+            thickness: att.thickness,
+            location: att.location.clone(),
+            text_height: att.text_height,
+            value: att.value.clone(),
+            rotation: att.rotation,
+            relative_x_scale_factor: att.relative_x_scale_factor,
+            oblique_angle: att.oblique_angle,
+            text_style_name: att.text_style_name.clone(),
+            text_generation_flags: att.text_generation_flags,
+            horizontal_text_justification: att.horizontal_text_justification,
+            second_alignment_point: att.second_alignment_point.clone(),
+            normal: att.normal.clone(),
+            vertical_text_justification: att.vertical_text_justification,
+        }
+    }
+    pub(crate) fn add_entity_no_handle_set(&mut self, entity: Entity) -> &Entity {
         self.ensure_mline_style_is_present_for_entity(&entity);
         self.ensure_dimension_style_is_present_for_entity(&entity);
         self.ensure_layer_is_present(&entity.common.layer);
         self.ensure_line_type_is_present(&entity.common.line_type_name);
         self.ensure_text_style_is_present_for_entity(&entity);
+        self.settle_handle_index();
+        self.__handle_index.insert(
+            entity.common.handle,
+            ItemLocation::Entity(self.__entities.len()),
+        );
         self.__entities.push(entity);
         self.__entities.last().unwrap()
     }
-    fn add_object_no_handle_set(&mut self, obj: Object) -> &Object {
+    pub(crate) fn add_object_no_handle_set(&mut self, obj: Object) -> &Object {
         self.ensure_layer_is_present_for_object(&obj);
         self.ensure_line_type_is_present_for_object(&obj);
         self.ensure_text_style_is_present_for_object(&obj);
         self.ensure_view_is_present(&obj);
+        self.settle_handle_index();
+        self.__handle_index.insert(
+            obj.common.handle,
+            ItemLocation::Object(self.__objects.len()),
+        );
         self.__objects.push(obj);
         self.__objects.last().unwrap()
     }
     pub(crate) fn add_app_id_no_handle_set(&mut self, app_id: AppId) -> &AppId {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index
+            .insert(app_id.handle, ItemLocation::AppId(self.__app_ids.len()));
         self.__app_ids.push(app_id);
         self.__app_ids.last().unwrap()
     }
@@ -698,41 +1202,73 @@ impl Drawing {
         block_record: BlockRecord,
     ) -> &BlockRecord {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index.insert(
+            block_record.handle,
+            ItemLocation::BlockRecord(self.__block_records.len()),
+        );
         self.__block_records.push(block_record);
         self.__block_records.last().unwrap()
     }
     pub(crate) fn add_dim_style_no_handle_set(&mut self, dim_style: DimStyle) -> &DimStyle {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index.insert(
+            dim_style.handle,
+            ItemLocation::DimStyle(self.__dim_styles.len()),
+        );
         self.__dim_styles.push(dim_style);
         self.__dim_styles.last().unwrap()
     }
     pub(crate) fn add_layer_no_handle_set(&mut self, layer: Layer) -> &Layer {
         self.ensure_line_type_is_present(&layer.line_type_name);
+        self.settle_handle_index();
+        self.__handle_index
+            .insert(layer.handle, ItemLocation::Layer(self.__layers.len()));
         self.__layers.push(layer);
         self.__layers.last().unwrap()
     }
     pub(crate) fn add_line_type_no_handle_set(&mut self, line_type: LineType) -> &LineType {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index.insert(
+            line_type.handle,
+            ItemLocation::LineType(self.__line_types.len()),
+        );
         self.__line_types.push(line_type);
         self.__line_types.last().unwrap()
     }
     pub(crate) fn add_style_no_handle_set(&mut self, style: Style) -> &Style {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index
+            .insert(style.handle, ItemLocation::Style(self.__styles.len()));
         self.__styles.push(style);
         self.__styles.last().unwrap()
     }
     pub(crate) fn add_ucs_no_handle_set(&mut self, ucs: Ucs) -> &Ucs {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index
+            .insert(ucs.handle, ItemLocation::Ucs(self.__ucss.len()));
         self.__ucss.push(ucs);
         self.__ucss.last().unwrap()
     }
     pub(crate) fn add_view_no_handle_set(&mut self, view: View) -> &View {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index
+            .insert(view.handle, ItemLocation::View(self.__views.len()));
         self.__views.push(view);
         self.__views.last().unwrap()
     }
     pub(crate) fn add_view_port_no_handle_set(&mut self, view_port: ViewPort) -> &ViewPort {
         // TODO: ensure invariants
+        self.settle_handle_index();
+        self.__handle_index.insert(
+            view_port.handle,
+            ItemLocation::ViewPort(self.__view_ports.len()),
+        );
         self.__view_ports.push(view_port);
         self.__view_ports.last().unwrap()
     }
@@ -744,8 +1280,11 @@ impl Drawing {
             });
         }
     }
-    fn ensure_block_record_is_present(&mut self, name: &str) {
-        if !self.block_records().any(|b| b.name == name) {
+    pub(crate) fn ensure_block_record_is_present(&mut self, name: &str) {
+        if !self
+            .block_records()
+            .any(|b| b.name.eq_ignore_ascii_case(name))
+        {
             self.add_block_record(BlockRecord {
                 name: String::from(name),
                 ..Default::default()
@@ -783,7 +1322,7 @@ impl Drawing {
             self.ensure_dimension_style_is_present(dim_style_name);
         }
     }
-    fn ensure_dimension_style_is_present(&mut self, dim_style_name: &str) {
+    pub(crate) fn ensure_dimension_style_is_present(&mut self, dim_style_name: &str) {
         if !self.dim_styles().any(|d| d.name == dim_style_name) {
             self.add_dim_style(DimStyle {
                 name: String::from(dim_style_name),
@@ -812,7 +1351,7 @@ impl Drawing {
             _ => (),
         }
     }
-    fn ensure_layer_is_present(&mut self, layer_name: &str) {
+    pub(crate) fn ensure_layer_is_present(&mut self, layer_name: &str) {
         if !self.layers().any(|l| l.name == *layer_name) {
             self.add_layer(Layer {
                 name: String::from(layer_name),
@@ -826,10 +1365,7 @@ impl Drawing {
         }
     }
     fn ensure_block_record_is_present_for_block(&mut self, block: &mut Block) {
-        self.add_block_record(BlockRecord {
-            name: String::from(&block.name),
-            ..Default::default()
-        });
+        self.ensure_block_record_is_present(&block.name);
     }
     fn ensure_block_entity_handles_are_set(&mut self, block: &mut Block) {
         for ent in &mut block.entities {
@@ -841,7 +1377,7 @@ impl Drawing {
             self.ensure_line_type_is_present(&style.style_name);
         }
     }
-    fn ensure_line_type_is_present(&mut self, line_type_name: &str) {
+    pub(crate) fn ensure_line_type_is_present(&mut self, line_type_name: &str) {
         if !self.line_types().any(|lt| lt.name == *line_type_name) {
             self.add_line_type(LineType {
                 name: String::from(line_type_name),
@@ -867,7 +1403,7 @@ impl Drawing {
             self.ensure_text_style_is_present(&o.style_name);
         }
     }
-    fn ensure_text_style_is_present(&mut self, text_style_name: &str) {
+    pub(crate) fn ensure_text_style_is_present(&mut self, text_style_name: &str) {
         if !self.styles().any(|s| s.name == text_style_name) {
             self.add_style(Style {
                 name: String::from(text_style_name),
@@ -955,7 +1491,10 @@ impl Drawing {
     }
     pub(crate) fn add_thumbnail_pairs(&self, pairs: &mut Vec<CodePair>) -> DxfResult<()> {
         if self.header.version >= AcadVersion::R2000 {
-            if let Some(ref img) = self.thumbnail {
+            let rendered = self
+                .update_thumbnail_on_save
+                .map(|(width, height)| self.render_thumbnail(width, height));
+            if let Some(img) = rendered.as_ref().or(self.thumbnail.as_ref()) {
                 pairs.push(CodePair::new_str(0, "SECTION"));
                 pairs.push(CodePair::new_str(2, "THUMBNAILIMAGE"));
                 let mut data = vec![];
@@ -973,80 +1512,126 @@ impl Drawing {
     }
     fn read_sections(drawing: &mut Drawing, iter: &mut CodePairPutBack) -> DxfResult<()> {
         loop {
-            match iter.next() {
-                Some(Ok(pair @ CodePair { code: 0, .. })) => match &*pair.assert_string()? {
-                    "EOF" => {
-                        iter.put_back(Ok(pair));
-                        break;
+            let section_name = match Drawing::read_section_start(iter) {
+                Ok(Some(section_name)) => section_name,
+                Ok(None) => break,
+                Err(e) => {
+                    // resume with the next section
+                    iter.recover(e)?;
+                    if iter.skip_to(&["SECTION", "EOF"])? {
+                        continue;
                     }
-                    "SECTION" => match iter.next() {
-                        Some(Ok(CodePair {
-                            code: 2,
-                            value: CodePairValue::Str(s),
-                            ..
-                        })) => {
-                            match &*s {
-                                "HEADER" => drawing.header = Header::read(iter)?,
-                                "CLASSES" => Class::read_classes(drawing, iter)?,
-                                "TABLES" => {
-                                    drawing.read_section_item(iter, "TABLE", read_specific_table)?
-                                }
-                                "BLOCKS" => {
-                                    drawing.read_section_item(iter, "BLOCK", Block::read_block)?
-                                }
-                                "ENTITIES" => drawing.read_entities(iter)?,
-                                "OBJECTS" => drawing.read_objects(iter)?,
-                                "THUMBNAILIMAGE" => {
-                                    drawing.thumbnail = thumbnail::read_thumbnail(iter)?;
-                                }
-                                _ => Drawing::swallow_section(iter)?,
-                            }
+                    break;
+                }
+            };
+            iter.set_section(&section_name);
+            if let Err(e) = Drawing::read_section(drawing, iter, &section_name) {
+                iter.recover(e)?;
+                iter.skip_to(&["ENDSEC", "SECTION", "EOF"])?;
+            }
+            if let Err(e) = Drawing::read_section_end(iter) {
+                iter.recover(e)?;
+                iter.skip_to(&["SECTION", "EOF"])?;
+            }
+            iter.set_section("");
+        }
 
-                            match iter.next() {
-                                Some(Ok(CodePair {
-                                    code: 0,
-                                    value: CodePairValue::Str(ref s),
-                                    ..
-                                })) if s == "ENDSEC" => (),
-                                Some(Ok(pair)) => {
-                                    return Err(DxfError::UnexpectedCodePair(
-                                        pair,
-                                        String::from("expected 0/ENDSEC"),
-                                    ))
-                                }
-                                Some(Err(e)) => return Err(e),
-                                None => return Err(DxfError::UnexpectedEndOfInput),
-                            }
-                        }
-                        Some(Ok(pair)) => {
-                            return Err(DxfError::UnexpectedCodePair(
-                                pair,
-                                String::from("expected 2/<section-name>"),
-                            ))
-                        }
-                        Some(Err(e)) => return Err(e),
-                        None => return Err(DxfError::UnexpectedEndOfInput),
-                    },
-                    _ => {
-                        return Err(DxfError::UnexpectedCodePair(
-                            pair,
-                            String::from("expected 0/SECTION"),
-                        ))
-                    }
-                },
-                Some(Ok(pair)) => {
-                    return Err(DxfError::UnexpectedCodePair(
-                        pair,
-                        String::from("expected 0/SECTION or 0/EOF"),
-                    ))
+        Ok(())
+    }
+    /// Reads the `0/SECTION` and `2/<section-name>` pairs and returns the section name, or `None`
+    /// if there are no more sections.
+    pub(crate) fn read_section_start(iter: &mut CodePairPutBack) -> DxfResult<Option<String>> {
+        match iter.next() {
+            Some(Ok(pair @ CodePair { code: 0, .. })) => match &*pair.assert_string()? {
+                "EOF" => {
+                    iter.put_back(Ok(pair));
+                    Ok(None)
                 }
-                Some(Err(e)) => return Err(e),
-                None => break, // ideally should have been 0/EOF
+                "SECTION" => match iter.next() {
+                    Some(Ok(CodePair {
+                        code: 2,
+                        value: CodePairValue::Str(s),
+                        ..
+                    })) => Ok(Some(s)),
+                    Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
+                        pair,
+                        String::from("expected 2/<section-name>"),
+                    )),
+                    Some(Err(e)) => Err(e),
+                    None => Err(DxfError::UnexpectedEndOfInput),
+                },
+                _ => Err(DxfError::UnexpectedCodePair(
+                    pair,
+                    String::from("expected 0/SECTION"),
+                )),
+            },
+            Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
+                pair,
+                String::from("expected 0/SECTION or 0/EOF"),
+            )),
+            Some(Err(e)) => Err(e),
+            None => Ok(None), // ideally should have been 0/EOF
+        }
+    }
+    /// Reads the contents of the named section, up to but not including `0/ENDSEC`.
+    pub(crate) fn read_section(
+        drawing: &mut Drawing,
+        iter: &mut CodePairPutBack,
+        section_name: &str,
+    ) -> DxfResult<()> {
+        match section_name {
+            "HEADER" => {
+                drawing.header = Header::read(iter)?;
+                iter.set_version(drawing.header.version);
             }
+            "CLASSES" => Class::read_classes(drawing, iter)?,
+            "TABLES" => drawing.read_section_item(iter, "TABLE", read_specific_table)?,
+            "BLOCKS" => drawing.read_section_item(iter, "BLOCK", Block::read_block)?,
+            "ENTITIES" => drawing.read_entities(iter)?,
+            "OBJECTS" => drawing.read_objects(iter)?,
+            "THUMBNAILIMAGE" => {
+                drawing.thumbnail = thumbnail::read_thumbnail(iter)?;
+            }
+            _ => Drawing::swallow_section(iter)?,
         }
 
         Ok(())
     }
+    pub(crate) fn read_section_end(iter: &mut CodePairPutBack) -> DxfResult<()> {
+        match iter.next() {
+            Some(Ok(CodePair {
+                code: 0,
+                value: CodePairValue::Str(ref s),
+                ..
+            })) if s == "ENDSEC" => Ok(()),
+            Some(Ok(pair)) => {
+                // put the pair back so reading can resume there when recovering from errors
+                iter.put_back(Ok(pair.clone()));
+                Err(DxfError::UnexpectedCodePair(
+                    pair,
+                    String::from("expected 0/ENDSEC"),
+                ))
+            }
+            Some(Err(e)) => Err(e),
+            None => Err(DxfError::UnexpectedEndOfInput),
+        }
+    }
+    /// Reads the `0/EOF` pair that ends the file, if present.
+    pub(crate) fn read_eof(iter: &mut CodePairPutBack) -> DxfResult<()> {
+        match iter.next() {
+            Some(Ok(CodePair {
+                code: 0,
+                value: CodePairValue::Str(ref s),
+                ..
+            })) if s == "EOF" => Ok(()),
+            Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
+                pair,
+                String::from("expected 0/EOF"),
+            )),
+            Some(Err(e)) => Err(e),
+            None => Ok(()),
+        }
+    }
     fn swallow_section(iter: &mut CodePairPutBack) -> DxfResult<()> {
         loop {
             match iter.next() {
@@ -1064,7 +1649,7 @@ impl Drawing {
         Ok(())
     }
     fn read_entities(&mut self, iter: &mut CodePairPutBack) -> DxfResult<()> {
-        let mut iter = EntityIter { iter };
+        let mut iter = EntityIter::new(iter);
         let mut entities = vec![];
         iter.read_entities_into_vec(&mut entities)?;
         for e in entities {
@@ -1077,8 +1662,8 @@ impl Drawing {
         Ok(())
     }
     fn read_objects(&mut self, iter: &mut CodePairPutBack) -> DxfResult<()> {
-        let iter = put_back(ObjectIter { iter });
-        for o in iter {
+        let mut iter = ObjectIter::new(iter);
+        for o in iter.by_ref() {
             if o.common.handle.is_empty() {
                 self.add_object(o);
             } else {
@@ -1086,7 +1671,10 @@ impl Drawing {
             }
         }
 
-        Ok(())
+        match iter.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
     fn read_section_item<F>(
         &mut self,
@@ -1108,14 +1696,23 @@ impl Drawing {
                             }
                             val => {
                                 if val == item_type {
-                                    callback(self, iter)?;
+                                    if let Err(e) = callback(self, iter) {
+                                        // resume with the next item
+                                        iter.recover(e)?;
+                                        iter.skip_to(&[item_type, "ENDSEC"])?;
+                                    }
                                 } else {
-                                    return Err(DxfError::UnexpectedCodePair(pair, String::new()));
+                                    iter.recover(DxfError::UnexpectedCodePair(
+                                        pair,
+                                        String::new(),
+                                    ))?;
+                                    iter.skip_to(&[item_type, "ENDSEC"])?;
                                 }
                             }
                         }
                     } else {
-                        return Err(DxfError::UnexpectedCodePair(pair, String::new()));
+                        iter.recover(DxfError::UnexpectedCodePair(pair, String::new()))?;
+                        iter.skip_to(&[item_type, "ENDSEC"])?;
                     }
                 }
                 Some(Err(e)) => return Err(e),
@@ -1328,6 +1925,88 @@ mod tests {
         assert_ne!(Handle(0), layer.handle);
     }
 
+    fn line_entity() -> Entity {
+        Entity::new(EntityType::Line(Default::default()))
+    }
+
+    fn entity_handle(item: Option<DrawingItem>) -> Option<Handle> {
+        match item {
+            Some(DrawingItem::Entity(ent)) => Some(ent.common.handle),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn item_by_handle_after_removal() {
+        let mut drawing = Drawing::new();
+        let handles = (0..3)
+            .map(|_| drawing.add_entity(line_entity()).common.handle)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Some(handles[2]),
+            entity_handle(drawing.item_by_handle(handles[2]))
+        );
+
+        drawing.remove_entity(0);
+        assert!(drawing.item_by_handle(handles[0]).is_none());
+        assert_eq!(
+            Some(handles[1]),
+            entity_handle(drawing.item_by_handle(handles[1]))
+        );
+        assert_eq!(
+            Some(handles[2]),
+            entity_handle(drawing.item_by_handle(handles[2]))
+        );
+
+        let added = drawing.add_entity(line_entity()).common.handle;
+        assert_eq!(Some(added), entity_handle(drawing.item_by_handle(added)));
+    }
+
+    #[test]
+    fn item_by_handle_after_handle_is_changed() {
+        let mut drawing = Drawing::new();
+        let handle = drawing.add_entity(line_entity()).common.handle;
+        assert!(drawing.item_by_handle(handle).is_some());
+
+        drawing
+            .item_by_handle_mut(handle)
+            .unwrap()
+            .set_handle(Handle(0x1234));
+        assert!(drawing.item_by_handle(handle).is_none());
+        assert!(drawing.item_by_handle(Handle(0x1234)).is_some());
+
+        for ent in drawing.entities_mut() {
+            ent.common.handle = Handle(0x5678);
+        }
+        assert!(drawing.item_by_handle(Handle(0x1234)).is_none());
+        assert!(drawing.item_by_handle(Handle(0x5678)).is_some());
+    }
+
+    #[test]
+    fn item_by_handle_after_clear_and_normalize() {
+        let mut drawing = Drawing::new();
+        let layer_handle = drawing
+            .add_layer(Layer {
+                name: String::from("!moved"),
+                ..Default::default()
+            })
+            .handle;
+        assert!(matches!(
+            drawing.item_by_handle(layer_handle),
+            Some(DrawingItem::Layer(l)) if l.name == "!moved"
+        ));
+
+        // sorting moves the layer
+        drawing.normalize();
+        assert!(matches!(
+            drawing.item_by_handle(layer_handle),
+            Some(DrawingItem::Layer(l)) if l.name == "!moved"
+        ));
+
+        drawing.clear();
+        assert!(drawing.item_by_handle(layer_handle).is_none());
+    }
+
     #[test]
     fn objects_section_is_not_written_on_r12() {
         let mut drawing = Drawing::new();
@@ -2096,4 +2775,476 @@ mod tests {
         let views = drawing.views().filter(|&v| v.name == "some-view");
         assert_eq!(1, views.count());
     }
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-9,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    fn line_block(name: &str, base_point: Point) -> Block {
+        Block {
+            name: String::from(name),
+            base_point,
+            entities: vec![Entity::new(EntityType::Line(Line::new(
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(2.0, 0.0, 0.0),
+            )))],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn explode_insert_applies_base_point_scale_rotation_and_location() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(line_block("b", Point::new(1.0, 0.0, 0.0)));
+        let insert = Insert {
+            name: String::from("b"),
+            location: Point::new(10.0, 10.0, 0.0),
+            x_scale_factor: 2.0,
+            y_scale_factor: 2.0,
+            rotation: 90.0,
+            ..Default::default()
+        };
+        let exploded = drawing.explode_insert(&insert);
+        assert_eq!(1, exploded.len());
+        match exploded[0].specific {
+            EntityType::Line(ref line) => {
+                assert_close(10.0, line.p1.x);
+                assert_close(10.0, line.p1.y);
+                assert_close(10.0, line.p2.x);
+                assert_close(12.0, line.p2.y);
+            }
+            _ => panic!("expected a line"),
+        }
+        assert!(exploded[0].common.handle.is_empty());
+    }
+
+    #[test]
+    fn explode_insert_respects_arrays() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(line_block("b", Point::origin()));
+        let insert = Insert {
+            name: String::from("b"),
+            rotation: 90.0,
+            column_count: 2,
+            row_count: 3,
+            column_spacing: 5.0,
+            row_spacing: 7.0,
+            ..Default::default()
+        };
+        let exploded = drawing.explode_insert(&insert);
+        assert_eq!(6, exploded.len());
+        match exploded[5].specific {
+            // column 1, row 2, rotated with the insert
+            EntityType::Line(ref line) => {
+                assert_close(-14.0, line.p1.x);
+                assert_close(6.0, line.p1.y);
+            }
+            _ => panic!("expected a line"),
+        }
+    }
+
+    #[test]
+    fn explode_insert_converts_attributes_to_text() {
+        let mut drawing = Drawing::new();
+        let mut block = line_block("b", Point::origin());
+        block
+            .entities
+            .push(Entity::new(EntityType::AttributeDefinition(
+                AttributeDefinition {
+                    text_tag: String::from("TAG"),
+                    ..Default::default()
+                },
+            )));
+        drawing.add_block(block);
+        let mut insert = Insert {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        insert.add_attribute(
+            &mut drawing,
+            Attribute {
+                value: String::from("attribute-value"),
+                location: Point::new(3.0, 4.0, 0.0),
+                ..Default::default()
+            },
+        );
+        insert.add_attribute(
+            &mut drawing,
+            Attribute {
+                value: String::from("hidden-value"),
+                flags: 1, // invisible
+                ..Default::default()
+            },
+        );
+        let exploded = drawing.explode_insert(&insert);
+        assert_eq!(2, exploded.len());
+        match exploded[1].specific {
+            EntityType::Text(ref text) => {
+                assert_eq!("attribute-value", text.value);
+                assert_eq!(Point::new(3.0, 4.0, 0.0), text.location);
+            }
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn explode_insert_of_missing_block_is_empty() {
+        let drawing = Drawing::new();
+        let insert = Insert {
+            name: String::from("not-a-block"),
+            ..Default::default()
+        };
+        assert!(drawing.explode_insert(&insert).is_empty());
+    }
+
+    #[test]
+    fn flatten_nested_inserts() {
+        let mut drawing = Drawing::new();
+        drawing.add_block(line_block("inner", Point::origin()));
+        drawing.add_block(Block {
+            name: String::from("outer"),
+            entities: vec![Entity::new(EntityType::Insert(Insert {
+                name: String::from("inner"),
+                location: Point::new(5.0, 0.0, 0.0),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        });
+        let mut insert = Entity::new(EntityType::Insert(Insert {
+            name: String::from("outer"),
+            location: Point::new(0.0, 10.0, 0.0),
+            ..Default::default()
+        }));
+        insert.common.layer = String::from("insert-layer");
+        drawing.add_entity(insert);
+        drawing.add_entity(Entity::new(EntityType::Insert(Insert {
+            name: String::from("missing"),
+            ..Default::default()
+        })));
+
+        drawing.flatten_inserts();
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(2, entities.len());
+        match entities[0].specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(6.0, 10.0, 0.0), line.p1);
+                assert_eq!(Point::new(7.0, 10.0, 0.0), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+        assert_eq!("insert-layer", entities[0].common.layer);
+        assert!(!entities[0].common.handle.is_empty());
+        match entities[1].specific {
+            EntityType::Insert(ref insert) => assert_eq!("missing", insert.name),
+            _ => panic!("expected an insert"),
+        }
+    }
+
+    #[test]
+    fn flatten_self_referencing_insert() {
+        let mut drawing = Drawing::new();
+        let mut block = line_block("b", Point::origin());
+        block.entities.push(Entity::new(EntityType::Insert(Insert {
+            name: String::from("b"),
+            ..Default::default()
+        })));
+        drawing.add_block(block);
+        drawing.add_entity(Entity::new(EntityType::Insert(Insert {
+            name: String::from("b"),
+            ..Default::default()
+        })));
+        drawing.flatten_inserts();
+        assert_eq!(1, drawing.entities().count());
+    }
+
+    #[test]
+    fn lenient_load_skips_malformed_values() {
+        let text = [
+            "0",
+            "SECTION",
+            "2",
+            "ENTITIES", //
+            "0",
+            "LINE",
+            "5",
+            "AB",
+            "10",
+            "not-a-number",
+            "20",
+            "2.0", //
+            "0",
+            "CIRCLE",
+            "40",
+            "3.0", //
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ]
+        .join("\n");
+        assert!(Drawing::load(&mut text.as_bytes()).is_err());
+
+        let (drawing, diagnostics) = Drawing::load_lenient(&mut text.as_bytes()).unwrap();
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(2, entities.len());
+        match entities[0].specific {
+            EntityType::Line(ref line) => assert_eq!(Point::new(0.0, 2.0, 0.0), line.p1),
+            _ => panic!("expected a line"),
+        }
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("ENTITIES", diagnostics[0].section);
+        assert_eq!(Some(Handle(0xAB)), diagnostics[0].handle);
+        assert_eq!(10, diagnostics[0].offset);
+        assert!(matches!(
+            diagnostics[0].error,
+            DxfError::ParseFloatError(..)
+        ));
+    }
+
+    #[test]
+    fn lenient_load_skips_unreadable_items() {
+        let text = [
+            "0", "SECTION", "2", "TABLES", //
+            "0", "TABLE", "2", "LAYER", //
+            "0", "LAYER", "2", "broken", "62", "1", //
+            "0", "ENDTAB", //
+            "0", "BOGUS", "1", "ignored", //
+            "0", "TABLE", "2", "STYLE", //
+            "0", "STYLE", "2", "kept", //
+            "0", "ENDTAB", //
+            "0", "ENDSEC", //
+            "0", "SECTION", "2", "ENTITIES", //
+            "0", "LINE", "10", "1.0", //
+            "0", "ENDSEC", "0", "EOF",
+        ]
+        .join("\n");
+        assert!(Drawing::load(&mut text.as_bytes()).is_err());
+
+        let (drawing, diagnostics) = Drawing::load_lenient(&mut text.as_bytes()).unwrap();
+        assert!(drawing.styles().any(|s| s.name == "kept"));
+        assert_eq!(1, drawing.entities().count());
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("TABLES", diagnostics[0].section);
+        assert!(matches!(
+            diagnostics[0].error,
+            DxfError::UnexpectedCodePair(..)
+        ));
+    }
+
+    #[test]
+    fn lenient_load_of_truncated_file() {
+        let text = [
+            "0", "SECTION", "2", "ENTITIES", //
+            "0", "LINE", "10", "1.0", //
+            "0", "CIRCLE", "40",
+        ]
+        .join("\n");
+        let (drawing, diagnostics) = Drawing::load_lenient(&mut text.as_bytes()).unwrap();
+        // the line is recovered, but the circle ends before its radius
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        assert!(matches!(entities[0].specific, EntityType::Line(_)));
+        assert_eq!(2, diagnostics.len(), "{diagnostics:?}");
+        assert_eq!("ENTITIES", diagnostics[0].section);
+        assert_eq!(12, diagnostics[0].offset);
+        assert!(matches!(
+            diagnostics[0].error,
+            DxfError::ParseFloatError(_, 12)
+        ));
+        assert_eq!("ENTITIES", diagnostics[1].section);
+        assert_eq!(9, diagnostics[1].offset);
+        assert!(matches!(
+            diagnostics[1].error,
+            DxfError::UnexpectedEndOfInput
+        ));
+    }
+
+    #[test]
+    fn lenient_load_of_file_with_missing_line() {
+        let mut drawing = Drawing::new();
+        for i in 0..3 {
+            let y = f64::from(i);
+            drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+                Point::new(0.0, y, 0.0),
+                Point::new(5.5, y, 0.0),
+            ))));
+        }
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let mut lines = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        // remove the code of the end point's x value from the second line
+        let entities = lines.iter().position(|l| l == "ENTITIES").unwrap();
+        let second_line = lines
+            .iter()
+            .enumerate()
+            .skip(entities)
+            .filter(|(_, l)| *l == "LINE")
+            .nth(1)
+            .unwrap()
+            .0;
+        let code = second_line
+            + lines[second_line..]
+                .iter()
+                .position(|l| l == " 11")
+                .unwrap();
+        lines.remove(code);
+        let text = lines.join("\n");
+
+        let (drawing, diagnostics) = Drawing::load_lenient(&mut text.as_bytes()).unwrap();
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(3, entities.len());
+        assert_eq!(1, drawing.layers().count());
+        match entities[1].specific {
+            EntityType::Line(ref line) => {
+                assert_eq!(Point::new(0.0, 1.0, 0.0), line.p1);
+                assert_eq!(Point::new(0.0, 1.0, 0.0), line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+        match entities[2].specific {
+            EntityType::Line(ref line) => assert_eq!(Point::new(5.5, 2.0, 0.0), line.p2),
+            _ => panic!("expected a line"),
+        }
+        assert_eq!(1, diagnostics.len(), "{diagnostics:?}");
+        assert_eq!("ENTITIES", diagnostics[0].section);
+        assert_eq!(code + 1, diagnostics[0].offset);
+        assert!(matches!(diagnostics[0].error, DxfError::ParseIntError(..)));
+    }
+
+    #[test]
+    fn lenient_load_of_out_of_range_date() {
+        let text = [
+            "0",
+            "SECTION",
+            "2",
+            "HEADER", //
+            "9",
+            "$ACADVER",
+            "1",
+            "AC1015", //
+            "9",
+            "$TDUCREATE",
+            "40",
+            "1e308", //
+            "9",
+            "$LTSCALE",
+            "40",
+            "2.0", //
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ]
+        .join("\n");
+        assert!(Drawing::load(&mut text.as_bytes()).is_err());
+
+        let (drawing, diagnostics) = Drawing::load_lenient(&mut text.as_bytes()).unwrap();
+        assert_eq!(2.0, drawing.header.line_type_scale);
+        assert_eq!(1, diagnostics.len(), "{diagnostics:?}");
+        assert_eq!("HEADER", diagnostics[0].section);
+        assert_eq!(11, diagnostics[0].offset);
+        assert!(matches!(
+            diagnostics[0].error,
+            DxfError::DateOutOfRange(_, 11)
+        ));
+    }
+
+    #[test]
+    fn lenient_load_of_block_without_endblk() {
+        let text = [
+            "0", "SECTION", "2", "BLOCKS", //
+            "0", "BLOCK", "2", "blk", //
+            "0", "ENDSEC", //
+            "0", "SECTION", "2", "ENTITIES", //
+            "0", "LINE", //
+            "0", "ENDSEC", //
+            "0", "EOF",
+        ]
+        .join("\n");
+        assert!(Drawing::load(&mut text.as_bytes()).is_err());
+
+        let (drawing, diagnostics) = Drawing::load_lenient(&mut text.as_bytes()).unwrap();
+        assert_eq!(0, drawing.blocks().count());
+        assert_eq!(1, drawing.entities().count());
+        assert_eq!(1, diagnostics.len(), "{diagnostics:?}");
+        assert_eq!("BLOCKS", diagnostics[0].section);
+        assert_eq!(9, diagnostics[0].offset);
+        assert!(matches!(
+            diagnostics[0].error,
+            DxfError::UnexpectedCodePair(..)
+        ));
+    }
+
+    #[test]
+    fn lenient_load_of_valid_file_has_no_diagnostics() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let (drawing, diagnostics) = Drawing::load_lenient(&mut buf.as_slice()).unwrap();
+        assert_eq!(1, drawing.entities().count());
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    }
+
+    #[test]
+    fn load_errors_have_context() {
+        let text = [
+            "0",
+            "SECTION",
+            "2",
+            "ENTITIES", //
+            "0",
+            "LINE",
+            "5",
+            "AB",
+            "10",
+            "not-a-number", //
+            "0",
+            "ENDSEC",
+            "0",
+            "EOF",
+        ]
+        .join("\n");
+        let error = Drawing::load(&mut text.as_bytes()).unwrap_err();
+        assert_eq!(
+            Some(&ErrorContext {
+                section: Some(String::from("ENTITIES")),
+                item_type: Some(String::from("LINE")),
+                handle: Some(Handle(0xAB)),
+                line: Some(10),
+            }),
+            error.context()
+        );
+        assert!(matches!(error.inner(), DxfError::ParseFloatError(..)));
+        assert!(error
+            .to_string()
+            .ends_with("(in the ENTITIES section, while reading LINE with handle AB, at line 10)"));
+
+        // the inner error is part of the message, so its source comes next in the chain
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.is::<std::num::ParseFloatError>());
+    }
+
+    #[test]
+    fn binary_load_errors_have_no_line_number() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+        let mut buf = vec![];
+        drawing.save_binary(&mut buf).unwrap();
+        buf.truncate(buf.len() - 40);
+        let error = Drawing::load(&mut buf.as_slice()).unwrap_err();
+        let context = error.context().unwrap();
+        assert!(context.section.is_some());
+        assert_eq!(None, context.line);
+    }
 }
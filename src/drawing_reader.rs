@@ -0,0 +1,401 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use encoding_rs::Encoding;
+
+use crate::code_pair_iter::new_code_pair_iter_from_stream;
+use crate::code_pair_put_back::CodePairPutBack;
+use crate::dxb_reader::DxbReader;
+use crate::entities::Entity;
+use crate::entity_iter::CombinedEntityIter;
+use crate::helper_functions::*;
+use crate::{Drawing, DxfError, DxfResult, Header};
+
+/// Reads a drawing incrementally so that the entities of very large files don't all have to be
+/// held in memory at once.
+///
+/// Creating the reader reads everything that precedes the `ENTITIES` section (the header, classes,
+/// tables, and blocks), which is then available from `drawing()`.  The entities are then read one
+/// at a time from `entities()`, and finally `finish()` reads any remaining sections.
+///
+/// ``` rust
+/// # fn ex() -> dxf::DxfResult<()> {
+/// use dxf::DrawingReader;
+/// use dxf::entities::*;
+///
+/// let mut reader = DrawingReader::from_file("path/to/file.dxf")?;
+/// println!("version: {:?}", reader.header().version);
+/// let mut line_count = 0;
+/// for entity in reader.entities() {
+///     if let EntityType::Line(_) = entity?.specific {
+///         line_count += 1;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DrawingReader<'a> {
+    drawing: Drawing,
+    source: EntitySource<'a>,
+}
+
+enum EntitySource<'a> {
+    CodePairs {
+        entities: Box<CombinedEntityIter<RawEntities<'a>>>,
+        state: ReadState,
+    },
+    /// DXB files aren't organized into sections, so they're read all at once.
+    Loaded(std::vec::IntoIter<Entity>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ReadState {
+    InEntities,
+    AfterEntities,
+    Failed,
+}
+
+impl<'a> DrawingReader<'a> {
+    /// Starts reading a drawing from anything that implements the `Read` trait, e.g., a `File`,
+    /// a `&mut File`, or a `&[u8]`.
+    ///
+    /// The reader isn't buffered; wrap it in a `BufReader` if necessary.
+    pub fn new<T>(reader: T) -> DxfResult<DrawingReader<'a>>
+    where
+        T: Read + 'a,
+    {
+        DrawingReader::with_encoding(reader, encoding_rs::WINDOWS_1252)
+    }
+    /// Starts reading a drawing from anything that implements the `Read` trait using the specified
+    /// text encoding.
+    pub fn with_encoding<T>(
+        mut reader: T,
+        encoding: &'static Encoding,
+    ) -> DxfResult<DrawingReader<'a>>
+    where
+        T: Read + 'a,
+    {
+        let first_line = read_line(&mut reader, true, encoding)?;
+        if first_line == "AutoCAD DXB 1.0" {
+            let mut drawing = DxbReader::new(reader).load()?;
+            let entities = drawing.take_entities();
+            return Ok(DrawingReader {
+                drawing,
+                source: EntitySource::Loaded(entities.into_iter()),
+            });
+        }
+
+        let iter = new_code_pair_iter_from_stream(reader, encoding, first_line)?;
+        let mut iter = CodePairPutBack::from_code_pair_iter(iter);
+        let mut drawing = Drawing::new();
+        drawing.clear();
+        let state = DrawingReader::read_sections_before_entities(&mut drawing, &mut iter)
+            .map_err(|e| iter.add_context(e))?;
+        Ok(DrawingReader {
+            drawing,
+            source: EntitySource::CodePairs {
+                entities: Box::new(CombinedEntityIter::new(RawEntities { iter, error: None })),
+                state,
+            },
+        })
+    }
+    fn read_sections_before_entities(
+        drawing: &mut Drawing,
+        iter: &mut CodePairPutBack,
+    ) -> DxfResult<ReadState> {
+        while let Some(section_name) = Drawing::read_section_start(iter)? {
+            if section_name == "ENTITIES" {
+                iter.set_section(&section_name);
+                return Ok(ReadState::InEntities);
+            }
+
+            iter.set_section(&section_name);
+            Drawing::read_section(drawing, iter, &section_name)?;
+            Drawing::read_section_end(iter)?;
+            iter.set_section("");
+        }
+
+        Ok(ReadState::AfterEntities)
+    }
+    /// Starts reading a drawing from disk, using a `BufReader`.
+    pub fn from_file(path: impl AsRef<Path>) -> DxfResult<DrawingReader<'a>> {
+        DrawingReader::from_file_with_encoding(path, encoding_rs::WINDOWS_1252)
+    }
+    /// Starts reading a drawing from disk, using a `BufReader` with the specified text encoding.
+    pub fn from_file_with_encoding(
+        path: impl AsRef<Path>,
+        encoding: &'static Encoding,
+    ) -> DxfResult<DrawingReader<'a>> {
+        let file = File::open(&path)?;
+        DrawingReader::with_encoding(BufReader::new(file), encoding)
+    }
+    /// The header of the drawing.
+    pub fn header(&self) -> &Header {
+        &self.drawing.header
+    }
+    /// The parts of the drawing that have been read so far.  This contains no entities.
+    pub fn drawing(&self) -> &Drawing {
+        &self.drawing
+    }
+    /// Returns the entities of the drawing as they're read.
+    ///
+    /// `Attribute`s and `Vertex`s are gathered into their `Insert` or `Polyline` as they are when
+    /// loading a `Drawing`, but no handles are assigned; use `Drawing::add_entity` for that.
+    /// Reading stops after the first error.
+    pub fn entities(&mut self) -> impl Iterator<Item = DxfResult<Entity>> + use<'_, 'a> {
+        StreamedEntities {
+            source: &mut self.source,
+        }
+    }
+    /// Reads the remainder of the drawing, skipping any entities that haven't been read, and
+    /// returns everything but the entities.  If reading the entities failed, the sections that
+    /// follow them can't be read, so the error is returned, or `DxfError::IncompleteDrawing` if
+    /// it was already returned from `entities()`.
+    pub fn finish(mut self) -> DxfResult<Drawing> {
+        for entity in self.entities() {
+            entity?;
+        }
+
+        if let EntitySource::CodePairs { entities, state } = self.source {
+            match state {
+                ReadState::AfterEntities => {
+                    let mut iter = entities.iter.iter;
+                    iter.set_section("");
+                    DrawingReader::read_remaining_sections(&mut self.drawing, &mut iter)
+                        .map_err(|e| iter.add_context(e))?;
+                }
+                ReadState::Failed => return Err(DxfError::IncompleteDrawing),
+                ReadState::InEntities => (),
+            }
+        }
+
+        Ok(self.drawing)
+    }
+    fn read_remaining_sections(drawing: &mut Drawing, iter: &mut CodePairPutBack) -> DxfResult<()> {
+        while let Some(section_name) = Drawing::read_section_start(iter)? {
+            iter.set_section(&section_name);
+            Drawing::read_section(drawing, iter, &section_name)?;
+            Drawing::read_section_end(iter)?;
+            iter.set_section("");
+        }
+
+        Drawing::read_eof(iter)
+    }
+}
+
+/// Reads single entities, recording the error that stopped reading.
+struct RawEntities<'a> {
+    iter: CodePairPutBack<'a>,
+    error: Option<DxfError>,
+}
+
+impl Iterator for RawEntities<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        match Entity::read(&mut self.iter) {
+            Ok(entity) => entity,
+            Err(e) => {
+                self.error = Some(self.iter.add_context(e));
+                None
+            }
+        }
+    }
+}
+
+struct StreamedEntities<'a, 'b> {
+    source: &'a mut EntitySource<'b>,
+}
+
+impl Iterator for StreamedEntities<'_, '_> {
+    type Item = DxfResult<Entity>;
+
+    fn next(&mut self) -> Option<DxfResult<Entity>> {
+        let (entities, state) = match self.source {
+            EntitySource::CodePairs {
+                ref mut entities,
+                ref mut state,
+            } => (entities, state),
+            EntitySource::Loaded(ref mut entities) => return entities.next().map(Ok),
+        };
+        if *state != ReadState::InEntities {
+            return None;
+        }
+
+        if let Some(entity) = entities.next() {
+            return Some(Ok(entity));
+        }
+
+        let result = match entities.iter.error.take() {
+            Some(e) => Err(e),
+            None => {
+                let iter = &mut entities.iter.iter;
+                Drawing::read_section_end(iter).map_err(|e| iter.add_context(e))
+            }
+        };
+        match result {
+            Ok(()) => {
+                *state = ReadState::AfterEntities;
+                None
+            }
+            Err(e) => {
+                *state = ReadState::Failed;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::tables::Layer;
+    use crate::*;
+
+    fn test_drawing() -> Drawing {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_layer(Layer {
+            name: String::from("streamed"),
+            ..Default::default()
+        });
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 2.0, 3.0),
+            Point::new(4.0, 5.0, 6.0),
+        ))));
+        let mut poly = Polyline::default();
+        poly.add_vertex(&mut drawing, Vertex::new(Point::new(0.0, 0.0, 0.0)));
+        poly.add_vertex(&mut drawing, Vertex::new(Point::new(1.0, 1.0, 0.0)));
+        drawing.add_entity(Entity::new(EntityType::Polyline(poly)));
+        drawing.add_entity(Entity::new(EntityType::Circle(Circle::new(
+            Point::origin(),
+            2.0,
+        ))));
+        drawing
+    }
+
+    fn reader_for(drawing: &Drawing) -> DrawingReader<'static> {
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        DrawingReader::new(Cursor::new(buf)).unwrap()
+    }
+
+    #[test]
+    fn tables_are_read_before_entities() {
+        let reader = reader_for(&test_drawing());
+        assert_eq!(AcadVersion::R2000, reader.header().version);
+        assert!(reader.drawing().layers().any(|l| l.name == "streamed"));
+        assert_eq!(0, reader.drawing().entities().count());
+    }
+
+    #[test]
+    fn entities_are_streamed() {
+        let mut reader = reader_for(&test_drawing());
+        let entities = reader.entities().collect::<DxfResult<Vec<_>>>().unwrap();
+        assert_eq!(3, entities.len());
+        match entities[0].specific {
+            EntityType::Line(ref line) => assert_eq!(Point::new(4.0, 5.0, 6.0), line.p2),
+            _ => panic!("expected a line"),
+        }
+        match entities[1].specific {
+            EntityType::Polyline(ref poly) => assert_eq!(2, poly.vertices().count()),
+            _ => panic!("expected a polyline"),
+        }
+        match entities[2].specific {
+            EntityType::Circle(ref circle) => assert_eq!(2.0, circle.radius),
+            _ => panic!("expected a circle"),
+        }
+    }
+
+    #[test]
+    fn entities_can_be_read_in_pieces() {
+        let mut reader = reader_for(&test_drawing());
+        assert_eq!(2, reader.entities().take(2).count());
+        let rest = reader.entities().collect::<Vec<_>>();
+        assert_eq!(1, rest.len());
+        assert!(matches!(
+            rest[0],
+            Ok(Entity {
+                specific: EntityType::Circle(_),
+                ..
+            })
+        ));
+        assert_eq!(0, reader.entities().count());
+    }
+
+    #[test]
+    fn finish_reads_the_remaining_sections() {
+        let drawing = test_drawing();
+        let mut reader = reader_for(&drawing);
+        assert_eq!(1, reader.entities().take(1).count());
+        let finished = reader.finish().unwrap();
+        assert_eq!(0, finished.entities().count());
+        assert_eq!(drawing.objects().count(), finished.objects().count());
+    }
+
+    #[test]
+    fn binary_files_are_streamed() {
+        let mut buf = vec![];
+        test_drawing().save_binary(&mut buf).unwrap();
+        let mut reader = DrawingReader::new(Cursor::new(buf)).unwrap();
+        assert_eq!(3, reader.entities().filter(|e| e.is_ok()).count());
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn dxb_files_are_streamed() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(3.0, 4.0, 0.0),
+        ))));
+        let mut buf = vec![];
+        drawing.save_dxb(&mut buf).unwrap();
+        let mut reader = DrawingReader::new(Cursor::new(buf)).unwrap();
+        assert_eq!(1, reader.entities().count());
+    }
+
+    #[test]
+    fn entity_errors_are_returned() {
+        let text = "0\nSECTION\n2\nENTITIES\n0\nLINE\n10\nnot-a-number\n0\nENDSEC\n0\nEOF";
+        let mut reader = DrawingReader::new(Cursor::new(text.as_bytes())).unwrap();
+        let entities = reader.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        assert!(entities[0].is_err());
+        assert!(matches!(reader.finish(), Err(DxfError::IncompleteDrawing)));
+
+        // the error is returned from `finish()` if it wasn't read from `entities()`
+        let reader = DrawingReader::new(text.as_bytes()).unwrap();
+        match reader.finish() {
+            Err(e) => assert!(matches!(e.inner(), DxfError::ParseFloatError(..))),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn unterminated_blocks_are_errors() {
+        let text = "0\nSECTION\n2\nBLOCKS\n0\nBLOCK\n2\nblk\n0\nENDSEC\n0\nEOF";
+        match DrawingReader::new(text.as_bytes()) {
+            Err(e) => assert!(matches!(e.inner(), DxfError::UnexpectedCodePair(..))),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn borrowed_readers_are_streamed() {
+        let mut buf = vec![];
+        test_drawing().save(&mut buf).unwrap();
+        let mut slice = buf.as_slice();
+        let mut reader = DrawingReader::new(&mut slice).unwrap();
+        assert_eq!(3, reader.entities().count());
+        reader.finish().unwrap();
+    }
+}
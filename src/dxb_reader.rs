@@ -18,6 +18,7 @@ pub(crate) struct DxbReader<T: Read> {
     last_line_point: Point,
     last_trace_p3: Point,
     last_trace_p4: Point,
+    width: (f64, f64),
     offset: usize,
 }
 
@@ -32,6 +33,7 @@ impl<T: Read> DxbReader<T> {
             last_line_point: Point::origin(),
             last_trace_p3: Point::origin(),
             last_trace_p4: Point::origin(),
+            width: (0.0, 0.0),
             offset: 0,
         }
     }
@@ -137,15 +139,23 @@ impl<T: Read> DxbReader<T> {
                     self.is_integer_mode = self.read_w()? == 0;
                 }
                 DxbItemType::Width => {
-                    let starting_width = self.read_n()?;
-                    let ending_width = self.read_n()?;
+                    // the width applies to the last vertex and the ones that follow it, or is the
+                    // default when directly after the polyline
+                    self.width = (self.read_n()?, self.read_n()?);
                     match vec_last!(entities) {
                         Entity {
                             specific: EntityType::Vertex(ref mut v),
                             ..
                         } => {
-                            v.starting_width = starting_width;
-                            v.ending_width = ending_width;
+                            v.starting_width = self.width.0;
+                            v.ending_width = self.width.1;
+                        }
+                        Entity {
+                            specific: EntityType::Polyline(ref mut p),
+                            ..
+                        } => {
+                            p.default_starting_width = self.width.0;
+                            p.default_ending_width = self.width.1;
                         }
                         _ => return Err(DxfError::UnexpectedEnumValue(self.offset)),
                     }
@@ -227,6 +237,7 @@ impl<T: Read> DxbReader<T> {
         let is_closed = self.read_w()? != 0;
         let mut poly = Polyline::default();
         poly.set_is_closed(is_closed);
+        self.width = (0.0, 0.0);
         Ok(self.wrap_common_values(EntityType::Polyline(poly)))
     }
     fn read_seqend(&mut self) -> DxfResult<Entity> {
@@ -265,7 +276,9 @@ impl<T: Read> DxbReader<T> {
     }
     fn read_vertex(&mut self) -> DxfResult<Entity> {
         let location = Point::new(self.read_n()?, self.read_n()?, 0.0);
-        let vertex = Vertex::new(location);
+        let mut vertex = Vertex::new(location);
+        vertex.starting_width = self.width.0;
+        vertex.ending_width = self.width.1;
         Ok(self.wrap_common_values(EntityType::Vertex(vertex)))
     }
     fn wrap_common_values(&self, specific: EntityType) -> Entity {
@@ -288,7 +301,8 @@ impl<T: Read> DxbReader<T> {
     }
     fn read_a(&mut self) -> DxfResult<f64> {
         let value = if self.is_integer_mode {
-            f64::from(read_i32(&mut self.reader)?) * self.scale_factor / 1_000_000.0
+            // millionths of a degree
+            f64::from(read_i32(&mut self.reader)?) / 1_000_000.0
         } else {
             f64::from(read_f32(&mut self.reader)?)
         };
@@ -313,7 +327,8 @@ impl<T: Read> DxbReader<T> {
     }
     fn read_u(&mut self) -> DxfResult<f64> {
         let value = if self.is_integer_mode {
-            f64::from(read_i32(&mut self.reader)?) * 65536.0 * self.scale_factor
+            // 65536ths
+            f64::from(read_i32(&mut self.reader)?) / 65536.0
         } else {
             f64::from(read_f32(&mut self.reader)?)
         };
@@ -321,7 +336,7 @@ impl<T: Read> DxbReader<T> {
         Ok(value)
     }
     fn read_w(&mut self) -> DxfResult<i32> {
-        let value = (f64::from(read_i16(&mut self.reader)?) * self.scale_factor) as i32;
+        let value = i32::from(read_i16(&mut self.reader)?);
         self.advance_offset(2);
         Ok(value)
     }
@@ -0,0 +1,48 @@
+/// How numbers are stored in a DXB file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DxbNumberMode {
+    /// Coordinates are 16-bit integers that are multiplied by the scale factor when read, angles
+    /// are millionths of a degree, and bulges are 65536ths.  Compact, but only as precise as the
+    /// scale factor allows.
+    Integer,
+    /// All numbers are 32-bit floats.
+    Float,
+}
+
+/// How entities are assigned to layers in a DXB file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DxbLayerStrategy {
+    /// Entities are written on their own layers.
+    LayerName,
+    /// Entities are written on a layer named after their color number, e.g., `1`, resolving
+    /// `BYLAYER` to the color of their layer.
+    Color,
+}
+
+/// Options for `Drawing::save_dxb_with_options`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DxbWriteOptions {
+    pub number_mode: DxbNumberMode,
+    /// The size of one integer unit when `number_mode` is `DxbNumberMode::Integer`; values that
+    /// don't fit in 16 bits at this scale are clamped.  Ignored for floats.
+    pub scale_factor: f64,
+    pub layer_strategy: DxbLayerStrategy,
+    /// The name of the block to write as a DXB block at its base point, instead of the entities
+    /// of the drawing.
+    pub block_name: Option<String>,
+    /// How far the line segments written in place of `Ellipse`s and `Spline`s can be from the
+    /// curves.
+    pub tolerance: f64,
+}
+
+impl Default for DxbWriteOptions {
+    fn default() -> Self {
+        DxbWriteOptions {
+            number_mode: DxbNumberMode::Float,
+            scale_factor: 1.0,
+            layer_strategy: DxbLayerStrategy::LayerName,
+            block_name: None,
+            tolerance: 0.01,
+        }
+    }
+}
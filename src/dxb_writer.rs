@@ -2,20 +2,35 @@ use std::io::Write;
 
 use byteorder::{LittleEndian, WriteBytesExt};
 
-use crate::{Drawing, DxfResult};
+use crate::{
+    Drawing, DxbLayerStrategy, DxbNumberMode, DxbWriteOptions, DxfResult, Point,
+    TransformationMatrix,
+};
 
 use crate::dxb_item_type::DxbItemType;
 use crate::entities::*;
 
-use itertools::Itertools;
-
-pub(crate) struct DxbWriter<T: Write> {
+pub(crate) struct DxbWriter<'a, T: Write> {
     writer: T,
+    options: &'a DxbWriteOptions,
+    layer_name: Option<String>,
+    color: Option<i16>,
+    last_line_point: Option<Point>,
+    last_trace_corners: Option<(Point, Point)>,
+    width: (f64, f64),
 }
 
-impl<T: Write> DxbWriter<T> {
-    pub fn new(writer: T) -> Self {
-        DxbWriter { writer }
+impl<'a, T: Write> DxbWriter<'a, T> {
+    pub fn new(writer: T, options: &'a DxbWriteOptions) -> Self {
+        DxbWriter {
+            writer,
+            options,
+            layer_name: None,
+            color: None,
+            last_line_point: None,
+            last_trace_corners: None,
+            width: (0.0, 0.0),
+        }
     }
     pub fn write(&mut self, drawing: &Drawing) -> DxfResult<()> {
         // write sentinel
@@ -23,42 +38,51 @@ impl<T: Write> DxbWriter<T> {
         self.writer.write_u8(0x1A)?;
         self.writer.write_u8(0x00)?;
 
-        let writing_block = drawing.entities().any(|_| true) && drawing.blocks().any(|_| true);
-        if writing_block {
-            // write block header
-            self.write_item_type(DxbItemType::BlockBase)?;
-            let block = drawing.blocks().next().unwrap();
-            self.write_n(block.base_point.x)?;
-            self.write_n(block.base_point.y)?;
-        }
-
-        // force all numbers to be floats
         self.write_item_type(DxbItemType::NumberMode)?;
-        self.write_w(1)?;
+        match self.options.number_mode {
+            DxbNumberMode::Integer => {
+                self.write_w(0)?;
+                if self.options.scale_factor != 1.0 {
+                    self.write_item_type(DxbItemType::ScaleFactor)?;
+                    self.writer
+                        .write_f64::<LittleEndian>(self.options.scale_factor)?;
+                }
+            }
+            DxbNumberMode::Float => self.write_w(1)?,
+        }
 
-        // write color
-        let mut last_color = 0i16;
-        self.write_item_type(DxbItemType::NewColor)?;
-        self.write_w(last_color)?;
+        let entities = match self.options.block_name {
+            Some(ref name) => match drawing.blocks().find(|b| b.name.eq_ignore_ascii_case(name)) {
+                Some(block) => {
+                    // the block base must precede the entities
+                    self.write_item_type(DxbItemType::BlockBase)?;
+                    self.write_n(block.base_point.x)?;
+                    self.write_n(block.base_point.y)?;
+                    block.entities.iter().collect::<Vec<_>>()
+                }
+                None => vec![],
+            },
+            None => drawing.entities().collect(),
+        };
 
-        if writing_block {
-            self.write_entities(&drawing.blocks().next().unwrap().entities)?;
-        } else {
-            let groups = drawing.entities().chunk_by(|&e| e.common.layer.clone());
-            for (layer, entities) in &groups {
-                self.write_item_type(DxbItemType::NewLayer)?;
-                self.write_null_terminated_string(&layer)?;
-                for entity in entities {
-                    match entity.common.color.raw_value() {
-                        c if c == last_color => (), // same color, do nothing
-                        c => {
-                            last_color = c;
-                            self.write_item_type(DxbItemType::NewColor)?;
-                            self.write_w(last_color)?;
-                        }
+        for entity in entities {
+            match entity.specific {
+                EntityType::Insert(ref insert) => {
+                    // write the contents of blocks in place of their inserts
+                    let mut exploded = vec![];
+                    drawing.explode_insert_into(
+                        insert,
+                        &TransformationMatrix::identity(),
+                        Some(&entity.common),
+                        true,
+                        &mut vec![],
+                        &mut exploded,
+                    );
+                    for entity in &exploded {
+                        self.write_entity(drawing, entity)?;
                     }
-                    self.write_entity(entity)?;
                 }
+                _ => self.write_entity(drawing, entity)?,
             }
         }
 
@@ -66,13 +90,63 @@ impl<T: Write> DxbWriter<T> {
         self.writer.write_u8(0)?;
         Ok(())
     }
-    fn write_entities(&mut self, entities: &[Entity]) -> DxfResult<()> {
-        for entity in entities.iter() {
-            self.write_entity(entity)?;
+    fn write_entity(&mut self, drawing: &Drawing, entity: &Entity) -> DxfResult<()> {
+        // curves that DXB can't represent are written as line segments
+        let polylines = match entity.specific {
+            EntityType::Ellipse(_) | EntityType::Spline(_) => {
+                entity.tessellate(self.options.tolerance)
+            }
+            _ => vec![],
+        };
+        let is_writable = match entity.specific {
+            EntityType::Arc(_)
+            | EntityType::Circle(_)
+            | EntityType::Face3D(_)
+            | EntityType::Line(_)
+            | EntityType::LwPolyline(_)
+            | EntityType::ModelPoint(_)
+            | EntityType::Polyline(_)
+            | EntityType::Seqend(_)
+            | EntityType::Solid(_)
+            | EntityType::Trace(_)
+            | EntityType::Vertex(_) => true,
+            EntityType::Ellipse(_) | EntityType::Spline(_) => {
+                polylines.iter().any(|points| points.len() > 1)
+            }
+            _ => false,
+        };
+        if !is_writable {
+            // don't leave a layer or color change without anything after it
+            return Ok(());
         }
-        Ok(())
-    }
-    fn write_entity(&mut self, entity: &Entity) -> DxfResult<()> {
+
+        let layer_name = match self.options.layer_strategy {
+            DxbLayerStrategy::LayerName => entity.common.layer.clone(),
+            DxbLayerStrategy::Color => {
+                let color = if entity.common.color.is_by_layer() {
+                    drawing
+                        .layers()
+                        .find(|l| l.name.eq_ignore_ascii_case(&entity.common.layer))
+                        .map(|l| l.color.raw_value().abs())
+                        .unwrap_or(7)
+                } else {
+                    entity.common.color.raw_value()
+                };
+                color.to_string()
+            }
+        };
+        if self.layer_name.as_ref() != Some(&layer_name) {
+            self.write_item_type(DxbItemType::NewLayer)?;
+            self.write_null_terminated_string(&layer_name)?;
+            self.layer_name = Some(layer_name);
+        }
+        let color = entity.common.color.raw_value();
+        if self.color != Some(color) {
+            self.write_item_type(DxbItemType::NewColor)?;
+            self.write_w(color)?;
+            self.color = Some(color);
+        }
+
         match &entity.specific {
             EntityType::Arc(ref arc) => {
                 self.write_arc(arc)?;
@@ -86,6 +160,9 @@ impl<T: Write> DxbWriter<T> {
             EntityType::Line(ref line) => {
                 self.write_line(line)?;
             }
+            EntityType::LwPolyline(ref poly) => {
+                self.write_lw_polyline(poly)?;
+            }
             EntityType::ModelPoint(ref point) => {
                 self.write_point(point)?;
             }
@@ -104,6 +181,13 @@ impl<T: Write> DxbWriter<T> {
             EntityType::Vertex(ref vertex) => {
                 self.write_vertex(vertex)?;
             }
+            EntityType::Ellipse(_) | EntityType::Spline(_) => {
+                for points in &polylines {
+                    for segment in points.windows(2) {
+                        self.write_line(&Line::new(segment[0].clone(), segment[1].clone()))?;
+                    }
+                }
+            }
             _ => (),
         }
         Ok(())
@@ -113,8 +197,8 @@ impl<T: Write> DxbWriter<T> {
         self.write_n(arc.center.x)?;
         self.write_n(arc.center.y)?;
         self.write_n(arc.radius)?;
-        self.write_n(arc.start_angle)?;
-        self.write_n(arc.end_angle)?;
+        self.write_a(arc.start_angle)?;
+        self.write_a(arc.end_angle)?;
         Ok(())
     }
     fn write_circle(&mut self, circle: &Circle) -> DxfResult<()> {
@@ -141,13 +225,43 @@ impl<T: Write> DxbWriter<T> {
         Ok(())
     }
     fn write_line(&mut self, line: &Line) -> DxfResult<()> {
-        self.write_item_type(DxbItemType::Line)?;
-        self.write_n(line.p1.x)?;
-        self.write_n(line.p1.y)?;
-        self.write_n(line.p1.z)?;
-        self.write_n(line.p2.x)?;
-        self.write_n(line.p2.y)?;
-        self.write_n(line.p2.z)?;
+        if self.last_line_point.as_ref() == Some(&line.p1) {
+            // continue from the end of the last line
+            if line.p2.z == 0.0 {
+                self.write_item_type(DxbItemType::LineExtension)?;
+                self.write_n(line.p2.x)?;
+                self.write_n(line.p2.y)?;
+            } else {
+                self.write_item_type(DxbItemType::LineExtension3D)?;
+                self.write_n(line.p2.x)?;
+                self.write_n(line.p2.y)?;
+                self.write_n(line.p2.z)?;
+            }
+        } else {
+            self.write_item_type(DxbItemType::Line)?;
+            self.write_n(line.p1.x)?;
+            self.write_n(line.p1.y)?;
+            self.write_n(line.p1.z)?;
+            self.write_n(line.p2.x)?;
+            self.write_n(line.p2.y)?;
+            self.write_n(line.p2.z)?;
+        }
+        self.last_line_point = Some(line.p2.clone());
+        Ok(())
+    }
+    fn write_lw_polyline(&mut self, poly: &LwPolyline) -> DxfResult<()> {
+        self.write_item_type(DxbItemType::Polyline)?;
+        self.write_w(if poly.is_closed() { 1 } else { 0 })?;
+        self.width = (0.0, 0.0);
+        for vertex in &poly.vertices {
+            let width = if poly.constant_width != 0.0 {
+                (poly.constant_width, poly.constant_width)
+            } else {
+                (vertex.starting_width, vertex.ending_width)
+            };
+            self.write_vertex_values(vertex.x, vertex.y, vertex.bulge, width)?;
+        }
+        self.write_seqend()?;
         Ok(())
     }
     fn write_point(&mut self, point: &ModelPoint) -> DxfResult<()> {
@@ -159,6 +273,12 @@ impl<T: Write> DxbWriter<T> {
     fn write_polyline(&mut self, poly: &Polyline) -> DxfResult<()> {
         self.write_item_type(DxbItemType::Polyline)?;
         self.write_w(if poly.is_closed() { 1 } else { 0 })?;
+        self.width = (0.0, 0.0);
+        let default_width = (poly.default_starting_width, poly.default_ending_width);
+        if default_width != self.width {
+            // a width directly after the polyline is its default
+            self.write_width(default_width)?;
+        }
         for vertex in poly.vertices() {
             self.write_vertex(vertex)?;
         }
@@ -182,21 +302,62 @@ impl<T: Write> DxbWriter<T> {
         Ok(())
     }
     fn write_trace(&mut self, trace: &Trace) -> DxfResult<()> {
-        self.write_item_type(DxbItemType::Trace)?;
-        self.write_n(trace.first_corner.x)?;
-        self.write_n(trace.first_corner.y)?;
-        self.write_n(trace.second_corner.x)?;
-        self.write_n(trace.second_corner.y)?;
+        let is_extension = match self.last_trace_corners {
+            Some((ref third, ref fourth)) => {
+                *third == trace.first_corner && *fourth == trace.second_corner
+            }
+            None => false,
+        };
+        if is_extension {
+            // continue from the end of the last trace
+            self.write_item_type(DxbItemType::TraceExtension)?;
+        } else {
+            self.write_item_type(DxbItemType::Trace)?;
+            self.write_n(trace.first_corner.x)?;
+            self.write_n(trace.first_corner.y)?;
+            self.write_n(trace.second_corner.x)?;
+            self.write_n(trace.second_corner.y)?;
+        }
         self.write_n(trace.third_corner.x)?;
         self.write_n(trace.third_corner.y)?;
         self.write_n(trace.fourth_corner.x)?;
         self.write_n(trace.fourth_corner.y)?;
+        self.last_trace_corners = Some((trace.third_corner.clone(), trace.fourth_corner.clone()));
         Ok(())
     }
     fn write_vertex(&mut self, vertex: &Vertex) -> DxfResult<()> {
+        self.write_vertex_values(
+            vertex.location.x,
+            vertex.location.y,
+            vertex.bulge,
+            (vertex.starting_width, vertex.ending_width),
+        )
+    }
+    fn write_vertex_values(
+        &mut self,
+        x: f64,
+        y: f64,
+        bulge: f64,
+        width: (f64, f64),
+    ) -> DxfResult<()> {
         self.write_item_type(DxbItemType::Vertex)?;
-        self.write_n(vertex.location.x)?;
-        self.write_n(vertex.location.y)?;
+        self.write_n(x)?;
+        self.write_n(y)?;
+        if bulge != 0.0 {
+            self.write_item_type(DxbItemType::Bulge)?;
+            self.write_u(bulge)?;
+        }
+        if width != self.width {
+            self.write_width(width)?;
+        }
+        Ok(())
+    }
+    /// Writes the width of the last vertex, which also applies to the rest of the polyline.
+    fn write_width(&mut self, width: (f64, f64)) -> DxfResult<()> {
+        self.write_item_type(DxbItemType::Width)?;
+        self.write_n(width.0)?;
+        self.write_n(width.1)?;
+        self.width = width;
         Ok(())
     }
     fn write_string(&mut self, value: &str) -> DxfResult<()> {
@@ -210,8 +371,33 @@ impl<T: Write> DxbWriter<T> {
         self.writer.write_u8(0)?;
         Ok(())
     }
+    fn write_a(&mut self, degrees: f64) -> DxfResult<()> {
+        match self.options.number_mode {
+            DxbNumberMode::Integer => self
+                .writer
+                .write_i32::<LittleEndian>((degrees * 1_000_000.0).round() as i32)?,
+            DxbNumberMode::Float => self.writer.write_f32::<LittleEndian>(degrees as f32)?,
+        }
+        Ok(())
+    }
     fn write_n(&mut self, d: f64) -> DxfResult<()> {
-        self.writer.write_f32::<LittleEndian>(d as f32)?;
+        match self.options.number_mode {
+            DxbNumberMode::Integer => {
+                // `as` saturates at the bounds of the type
+                let value = (d / self.options.scale_factor).round() as i16;
+                self.writer.write_i16::<LittleEndian>(value)?;
+            }
+            DxbNumberMode::Float => self.writer.write_f32::<LittleEndian>(d as f32)?,
+        }
+        Ok(())
+    }
+    fn write_u(&mut self, bulge: f64) -> DxfResult<()> {
+        match self.options.number_mode {
+            DxbNumberMode::Integer => self
+                .writer
+                .write_i32::<LittleEndian>((bulge * 65536.0).round() as i32)?,
+            DxbNumberMode::Float => self.writer.write_f32::<LittleEndian>(bulge as f32)?,
+        }
         Ok(())
     }
     fn write_w(&mut self, s: i16) -> DxfResult<()> {
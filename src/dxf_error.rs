@@ -3,7 +3,7 @@ use std::fmt;
 use std::io;
 use std::num;
 
-use crate::CodePair;
+use crate::{CodePair, Handle};
 
 #[derive(Debug)]
 pub enum DxfError {
@@ -23,6 +23,61 @@ pub enum DxfError {
     InvalidBinaryFile,
     MalformedString,
     WrongItemType,
+    /// A code pair with the specified code was required but not present.
+    MissingCode(i32),
+    /// A `102` group with the specified name was required but not present.
+    MissingGroup(String),
+    /// No layout with the specified name exists.
+    LayoutNotFound(String),
+    /// A layout with the specified name already exists.
+    DuplicateLayoutName(String),
+    /// The `Model` layout can't be renamed or removed.
+    CannotModifyModelLayout,
+    /// A date at the specified line/offset is later than can be represented.
+    DateOutOfRange(f64, usize),
+    /// The entities of a drawing couldn't all be read, so the sections that follow them weren't
+    /// read either.
+    IncompleteDrawing,
+    /// Another error along with where in the file it occurred.  Errors from loading a drawing are
+    /// wrapped in this; use `inner()` to match on the original error.
+    WithContext(Box<DxfError>, Box<ErrorContext>),
+}
+
+/// Describes what was being read when an error occurred.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The name of the section being read, e.g., `ENTITIES`.
+    pub section: Option<String>,
+    /// The type of the entity, object, table entry, or class being read, e.g., `LINE`.
+    pub item_type: Option<String>,
+    /// The handle of the item being read, if it had been read.
+    pub handle: Option<Handle>,
+    /// The line number where the error occurred, for text files.
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = vec![];
+        if let Some(ref section) = self.section {
+            parts.push(format!("in the {section} section"));
+        }
+        match (&self.item_type, self.handle) {
+            (Some(item_type), Some(handle)) => parts.push(format!(
+                "while reading {item_type} with handle {}",
+                handle.as_string()
+            )),
+            (Some(item_type), None) => parts.push(format!("while reading {item_type}")),
+            (None, Some(handle)) => {
+                parts.push(format!("while reading handle {}", handle.as_string()))
+            }
+            (None, None) => (),
+        }
+        if let Some(line) = self.line {
+            parts.push(format!("at line {line}"));
+        }
+        write!(formatter, "{}", parts.join(", "))
+    }
 }
 
 impl From<io::Error> for DxfError {
@@ -37,6 +92,40 @@ impl From<::image::ImageError> for DxfError {
     }
 }
 
+impl DxfError {
+    /// Returns where in the file the error occurred, if known.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match *self {
+            DxfError::WithContext(_, ref context) => Some(context),
+            _ => None,
+        }
+    }
+    /// Returns the error without any `ErrorContext`.
+    pub fn inner(&self) -> &DxfError {
+        match *self {
+            DxfError::WithContext(ref e, _) => e.inner(),
+            _ => self,
+        }
+    }
+    /// The line number or byte offset where the error occurred, if known.
+    pub(crate) fn offset(&self) -> Option<usize> {
+        match *self {
+            DxfError::ParseFloatError(_, o)
+            | DxfError::ParseIntError(_, o)
+            | DxfError::ParseError(o)
+            | DxfError::UnexpectedCode(_, o)
+            | DxfError::UnexpectedByte(_, o)
+            | DxfError::UnexpectedEnumValue(o)
+            | DxfError::ExpectedTableType(o)
+            | DxfError::WrongValueType(o)
+            | DxfError::DateOutOfRange(_, o) => Some(o),
+            DxfError::UnexpectedCodePair(ref cp, _) => Some(cp.offset),
+            DxfError::WithContext(ref e, _) => e.offset(),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for DxfError {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -83,17 +172,46 @@ impl fmt::Display for DxfError {
             DxfError::InvalidBinaryFile => write!(formatter, "the binary file is invalid"),
             DxfError::MalformedString => write!(formatter, "the string is malformed"),
             DxfError::WrongItemType => write!(formatter, "the specified item type is not correct"),
+            DxfError::MissingCode(c) => {
+                write!(
+                    formatter,
+                    "a code pair with code '{c}' was expected but not found"
+                )
+            }
+            DxfError::MissingGroup(ref name) => {
+                write!(formatter, "the group '{name}' was expected but not found")
+            }
+            DxfError::LayoutNotFound(ref name) => {
+                write!(formatter, "the layout '{name}' does not exist")
+            }
+            DxfError::DuplicateLayoutName(ref name) => {
+                write!(formatter, "a layout named '{name}' already exists")
+            }
+            DxfError::CannotModifyModelLayout => {
+                write!(formatter, "the model layout can't be renamed or removed")
+            }
+            DxfError::DateOutOfRange(d, o) => write!(
+                formatter,
+                "the date '{d}' is out of range at line/offset {o}"
+            ),
+            DxfError::IncompleteDrawing => write!(
+                formatter,
+                "the drawing is incomplete because its entities couldn't be read"
+            ),
+            DxfError::WithContext(ref e, ref context) => write!(formatter, "{e} ({context})"),
         }
     }
 }
 
 impl error::Error for DxfError {
-    fn cause(&self) -> Option<&dyn error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             DxfError::IoError(ref e) => Some(e),
             DxfError::ImageError(ref e) => Some(e),
             DxfError::ParseFloatError(ref e, _) => Some(e),
             DxfError::ParseIntError(ref e, _) => Some(e),
+            // the message of the inner error is already part of this one
+            DxfError::WithContext(ref e, _) => e.source(),
             _ => None,
         }
     }
@@ -4,7 +4,8 @@ use enum_primitive::FromPrimitive;
 
 use crate::{
     CodePair, Color, DxfError, DxfResult, Handle, HatchPatternBoundaryData, HatchPatternLineData,
-    Point, Vector,
+    MLeaderArrowhead, MLeaderBlockAttribute, MLeaderContextData, Point, TableCell, TableCellRange,
+    Vector,
 };
 
 use crate::code_pair_put_back::CodePairPutBack;
@@ -200,6 +201,55 @@ impl Solid {
     }
 }
 
+//------------------------------------------------------------------------------
+//                                                                         Table
+//------------------------------------------------------------------------------
+impl Table {
+    pub fn row_count(&self) -> usize {
+        self.row_heights.len()
+    }
+    pub fn column_count(&self) -> usize {
+        self.column_widths.len()
+    }
+    /// Gets the cell at the specified zero-based row and column.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&TableCell> {
+        if column >= self.column_count() {
+            return None;
+        }
+        self.cells.get(row * self.column_count() + column)
+    }
+    /// Gets the cell at the specified zero-based row and column.
+    pub fn cell_mut(&mut self, row: usize, column: usize) -> Option<&mut TableCell> {
+        if column >= self.column_count() {
+            return None;
+        }
+        let column_count = self.column_count();
+        self.cells.get_mut(row * column_count + column)
+    }
+    /// Returns the ranges of cells that have been merged, each anchored at its top-left cell.
+    pub fn merged_ranges(&self) -> Vec<TableCellRange> {
+        let column_count = self.column_count();
+        if column_count == 0 {
+            return vec![];
+        }
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.column_span > 1 || cell.row_span > 1)
+            .map(|(i, cell)| {
+                let first_row = i / column_count;
+                let first_column = i % column_count;
+                TableCellRange {
+                    first_row,
+                    first_column,
+                    last_row: first_row + cell.row_span.max(1) as usize - 1,
+                    last_column: first_column + cell.column_span.max(1) as usize - 1,
+                }
+            })
+            .collect()
+    }
+}
+
 //------------------------------------------------------------------------------
 //                                                                         Trace
 //------------------------------------------------------------------------------
@@ -629,7 +679,11 @@ impl Entity {
                                                     break;
                                                 }
                                                 Some(Ok(pair)) => {
-                                                    entity.apply_code_pair(&pair, iter)?
+                                                    if let Err(e) =
+                                                        entity.apply_code_pair(&pair, iter)
+                                                    {
+                                                        iter.recover(e)?;
+                                                    }
                                                 }
                                                 Some(Err(e)) => return Err(e),
                                                 None => return Err(DxfError::UnexpectedEndOfInput),
@@ -642,19 +696,9 @@ impl Entity {
                                     return Ok(Some(entity));
                                 }
                                 None => {
-                                    // swallow unsupported entity
-                                    loop {
-                                        match iter.next() {
-                                            Some(Ok(pair @ CodePair { code: 0, .. })) => {
-                                                // found another entity or ENDSEC
-                                                iter.put_back(Ok(pair));
-                                                break;
-                                            }
-                                            Some(Ok(_)) => (), // part of the unsupported entity
-                                            Some(Err(e)) => return Err(e),
-                                            None => return Err(DxfError::UnexpectedEndOfInput),
-                                        }
-                                    }
+                                    // preserve unsupported entity
+                                    let entity = Entity::read_unknown(type_string, iter)?;
+                                    return Ok(Some(entity));
                                 }
                             }
                         }
@@ -671,6 +715,48 @@ impl Entity {
             }
         }
     }
+    fn read_unknown(type_string: String, iter: &mut CodePairPutBack) -> DxfResult<Entity> {
+        let mut common = EntityCommon::default();
+        let mut pairs = vec![];
+        let mut reading_common = true;
+        loop {
+            match iter.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    // new entity or ENDSEC
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+                Some(Ok(pair @ CodePair { code: 100, .. })) => {
+                    // `AcDbEntity` is re-written with the common values, any other subclass marker
+                    // starts the entity-specific values
+                    if !reading_common || pair.assert_string()? != "AcDbEntity" {
+                        reading_common = false;
+                        pairs.push(pair);
+                    }
+                }
+                Some(Ok(pair @ CodePair { code: 1001, .. })) => {
+                    // XDATA always trails the entity
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+                Some(Ok(pair)) => {
+                    if !reading_common || !common.apply_individual_pair(&pair, iter)? {
+                        pairs.push(pair);
+                    }
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Err(DxfError::UnexpectedEndOfInput),
+            }
+        }
+
+        Ok(Entity {
+            common,
+            specific: EntityType::Unknown {
+                type_string,
+                pairs,
+                version: iter.version(),
+            },
+        })
+    }
     fn apply_code_pair(&mut self, pair: &CodePair, iter: &mut CodePairPutBack) -> DxfResult<()> {
         if !self.specific.try_apply_code_pair(pair)? {
             self.common.apply_individual_pair(pair, iter)?;
@@ -805,6 +891,12 @@ impl Entity {
             EntityType::Hatch(ref mut hatch) => {
                 Entity::apply_custom_reader_hatch(&mut self.common, hatch, iter)
             }
+            EntityType::MLeader(ref mut mleader) => {
+                Entity::apply_custom_reader_mleader(&mut self.common, mleader, iter)
+            }
+            EntityType::Table(ref mut table) => {
+                Entity::apply_custom_reader_table(&mut self.common, table, iter)
+            }
             _ => Ok(false), // no custom reader
         }
     }
@@ -1395,6 +1487,193 @@ impl Entity {
             }
         }
     }
+    fn apply_custom_reader_mleader(
+        common: &mut EntityCommon,
+        mleader: &mut MLeader,
+        iter: &mut CodePairPutBack,
+    ) -> DxfResult<bool> {
+        // some codes (e.g., 92 and 330) are shared with the common values
+        let mut reading_specific = false;
+        loop {
+            let pair = next_pair!(iter);
+            match pair.code {
+                100 => {
+                    reading_specific = pair.assert_string()? == "AcDbMLeader";
+                }
+                270 if reading_specific => mleader.version = pair.assert_i16()?,
+                300 if reading_specific => {
+                    mleader.context_data = MLeaderContextData::read(iter)?;
+                }
+                340 if reading_specific => mleader.__style_handle = pair.as_handle()?,
+                90 if reading_specific => mleader.property_override_flags = pair.assert_i32()?,
+                170 if reading_specific => mleader.leader_type = pair.assert_i16()?,
+                91 if reading_specific => mleader.leader_line_color = pair.assert_i32()?,
+                341 if reading_specific => mleader.__leader_line_type_handle = pair.as_handle()?,
+                171 if reading_specific => mleader.leader_line_weight = pair.assert_i16()?,
+                290 if reading_specific => mleader.enable_landing = pair.assert_bool()?,
+                291 if reading_specific => mleader.enable_dogleg = pair.assert_bool()?,
+                41 if reading_specific => mleader.dogleg_length = pair.assert_f64()?,
+                342 if reading_specific => mleader.__arrowhead_handle = pair.as_handle()?,
+                42 if reading_specific => mleader.arrowhead_size = pair.assert_f64()?,
+                172 if reading_specific => mleader.content_type = pair.assert_i16()?,
+                343 if reading_specific => mleader.__text_style_handle = pair.as_handle()?,
+                173 if reading_specific => mleader.text_left_attachment_type = pair.assert_i16()?,
+                95 if reading_specific => mleader.text_right_attachment_type = pair.assert_i32()?,
+                174 if reading_specific => mleader.text_angle_type = pair.assert_i16()?,
+                175 if reading_specific => mleader.text_alignment_type = pair.assert_i16()?,
+                92 if reading_specific => mleader.text_color = pair.assert_i32()?,
+                292 if reading_specific => mleader.enable_frame_text = pair.assert_bool()?,
+                344 if reading_specific => mleader.__block_content_handle = pair.as_handle()?,
+                93 if reading_specific => mleader.block_content_color = pair.assert_i32()?,
+                10 if reading_specific => mleader.block_content_scale.x = pair.assert_f64()?,
+                20 if reading_specific => mleader.block_content_scale.y = pair.assert_f64()?,
+                30 if reading_specific => mleader.block_content_scale.z = pair.assert_f64()?,
+                43 if reading_specific => mleader.block_content_rotation = pair.assert_f64()?,
+                176 if reading_specific => {
+                    mleader.block_content_connection_type = pair.assert_i16()?;
+                }
+                293 if reading_specific => mleader.enable_annotation_scale = pair.assert_bool()?,
+                94 if reading_specific => {
+                    mleader.arrowhead_overrides.push(MLeaderArrowhead {
+                        index: pair.assert_i32()?,
+                        __arrowhead_handle: Handle::empty(),
+                    });
+                }
+                345 if reading_specific => {
+                    if let Some(arrowhead) = mleader.arrowhead_overrides.last_mut() {
+                        arrowhead.__arrowhead_handle = pair.as_handle()?;
+                    }
+                }
+                330 if reading_specific => {
+                    mleader.block_attributes.push(MLeaderBlockAttribute {
+                        __attribute_definition_handle: pair.as_handle()?,
+                        ..Default::default()
+                    });
+                }
+                177 if reading_specific => {
+                    if let Some(att) = mleader.block_attributes.last_mut() {
+                        att.index = pair.assert_i16()?;
+                    }
+                }
+                44 if reading_specific => {
+                    if let Some(att) = mleader.block_attributes.last_mut() {
+                        att.width = pair.assert_f64()?;
+                    }
+                }
+                302 if reading_specific => {
+                    if let Some(att) = mleader.block_attributes.last_mut() {
+                        att.text = pair.assert_string()?;
+                    }
+                }
+                294 if reading_specific => {
+                    mleader.is_text_direction_negative = pair.assert_bool()?;
+                }
+                178 if reading_specific => mleader.text_align_in_ipe = pair.assert_i16()?,
+                179 if reading_specific => mleader.text_attachment_point = pair.assert_i16()?,
+                271 if reading_specific => {
+                    mleader.text_attachment_direction = enum_from_number!(
+                        TextAttachmentDirection,
+                        Horizontal,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                272 if reading_specific => {
+                    mleader.bottom_text_attachment_direction = enum_from_number!(
+                        BottomTextAttachmentDirection,
+                        Center,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                273 if reading_specific => {
+                    mleader.top_text_attachment_direction = enum_from_number!(
+                        TopTextAttachmentDirection,
+                        Center,
+                        from_i16,
+                        pair.assert_i16()?
+                    );
+                }
+                _ => {
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+            }
+        }
+    }
+    fn apply_custom_reader_table(
+        common: &mut EntityCommon,
+        table: &mut Table,
+        iter: &mut CodePairPutBack,
+    ) -> DxfResult<bool> {
+        // codes like 2, 7, 91, and 92 mean different things before the table subclass marker, in the
+        // table header, and within a cell
+        let mut reading_table = false;
+        let mut reading_overrides = false;
+        let mut row_count = 0;
+        let mut column_count = 0;
+        loop {
+            let pair = next_pair!(iter);
+            if reading_table && !reading_overrides && pair.code != 171 {
+                let is_last_cell = table.cells.len() >= row_count * column_count;
+                if let Some(cell) = table.cells.last_mut() {
+                    // the table style overrides follow the last cell and start with a value that
+                    // only applies to the whole table
+                    if is_last_cell && matches!(pair.code, 40 | 41 | 280 | 281 | 282) {
+                        reading_overrides = true;
+                    } else if pair.code == 301 && pair.assert_string()? == "CELL_VALUE" {
+                        cell.read_cell_value(iter)?;
+                        continue;
+                    } else if cell.apply_code_pair(&pair)? {
+                        continue;
+                    } else if pair.code != 1001 {
+                        cell.unknown_pairs.push(pair);
+                        continue;
+                    }
+                }
+            }
+            match pair.code {
+                100 => {
+                    reading_table = pair.assert_string()? == "AcDbTable";
+                }
+                2 if !reading_table => table.block_name = pair.assert_string()?,
+                10 if !reading_table => table.insertion_point.x = pair.assert_f64()?,
+                20 if !reading_table => table.insertion_point.y = pair.assert_f64()?,
+                30 if !reading_table => table.insertion_point.z = pair.assert_f64()?,
+                1001 => {
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+                _ if reading_overrides => table.table_style_overrides.push(pair),
+                280 if reading_table => table.version = pair.assert_i16()?,
+                342 if reading_table => table.__table_style_handle = pair.as_handle()?,
+                343 if reading_table => table.__owning_block_handle = pair.as_handle()?,
+                11 if reading_table => table.horizontal_direction.x = pair.assert_f64()?,
+                21 if reading_table => table.horizontal_direction.y = pair.assert_f64()?,
+                31 if reading_table => table.horizontal_direction.z = pair.assert_f64()?,
+                90 if reading_table => table.value_flags = pair.assert_i32()?,
+                // row and column counts; also implied by the collections
+                91 if reading_table => row_count = pair.assert_i32()?.max(0) as usize,
+                92 if reading_table => column_count = pair.assert_i32()?.max(0) as usize,
+                93 if reading_table => table.override_flags = pair.assert_i32()?,
+                94 if reading_table => table.border_color_override_flags = pair.assert_i32()?,
+                95 if reading_table => {
+                    table.border_lineweight_override_flags = pair.assert_i32()?;
+                }
+                96 if reading_table => {
+                    table.border_visibility_override_flags = pair.assert_i32()?;
+                }
+                141 if reading_table => table.row_heights.push(pair.assert_f64()?),
+                142 if reading_table => table.column_widths.push(pair.assert_f64()?),
+                171 if reading_table => {
+                    table
+                        .cells
+                        .push(TableCell::new_from_type(pair.assert_i16()?));
+                }
+                _ => {
+                    common.apply_individual_pair(&pair, iter)?;
+                }
+            }
+        }
+    }
     pub(crate) fn add_code_pairs(
         &self,
         pairs: &mut Vec<CodePair>,
@@ -1440,6 +1719,18 @@ impl Entity {
             EntityType::Hatch(ref hatch) => {
                 Entity::add_custom_code_pairs_hatch(pairs, hatch, version);
             }
+            EntityType::MLeader(ref mleader) => {
+                Entity::add_custom_code_pairs_mleader(pairs, mleader);
+            }
+            EntityType::Table(ref table) => {
+                Entity::add_custom_code_pairs_table(pairs, table);
+            }
+            EntityType::Unknown {
+                pairs: ref unknown_pairs,
+                ..
+            } => {
+                pairs.extend(unknown_pairs.iter().cloned());
+            }
             _ => return false, // no custom code pairs
         }
 
@@ -1640,7 +1931,7 @@ impl Entity {
         }
         true
     }
-    #[allow(unused_variables)]
+    #[allow(unused_variables, clippy::ptr_arg)]
     fn add_custom_code_pairs_hatch(
         pairs: &mut Vec<CodePair>,
         hatch: &Hatch,
@@ -1649,6 +1940,136 @@ impl Entity {
         //TODO
         true
     }
+    fn add_custom_code_pairs_mleader(pairs: &mut Vec<CodePair>, mleader: &MLeader) {
+        pairs.push(CodePair::new_str(100, "AcDbMLeader"));
+        pairs.push(CodePair::new_i16(270, mleader.version));
+        mleader.context_data.add_code_pairs(pairs);
+        pairs.push(CodePair::new_string(
+            340,
+            &mleader.__style_handle.as_string(),
+        ));
+        pairs.push(CodePair::new_i32(90, mleader.property_override_flags));
+        pairs.push(CodePair::new_i16(170, mleader.leader_type));
+        pairs.push(CodePair::new_i32(91, mleader.leader_line_color));
+        pairs.push(CodePair::new_string(
+            341,
+            &mleader.__leader_line_type_handle.as_string(),
+        ));
+        pairs.push(CodePair::new_i16(171, mleader.leader_line_weight));
+        pairs.push(CodePair::new_bool(290, mleader.enable_landing));
+        pairs.push(CodePair::new_bool(291, mleader.enable_dogleg));
+        pairs.push(CodePair::new_f64(41, mleader.dogleg_length));
+        if !mleader.__arrowhead_handle.is_empty() {
+            pairs.push(CodePair::new_string(
+                342,
+                &mleader.__arrowhead_handle.as_string(),
+            ));
+        }
+        pairs.push(CodePair::new_f64(42, mleader.arrowhead_size));
+        pairs.push(CodePair::new_i16(172, mleader.content_type));
+        pairs.push(CodePair::new_string(
+            343,
+            &mleader.__text_style_handle.as_string(),
+        ));
+        pairs.push(CodePair::new_i16(173, mleader.text_left_attachment_type));
+        pairs.push(CodePair::new_i32(95, mleader.text_right_attachment_type));
+        pairs.push(CodePair::new_i16(174, mleader.text_angle_type));
+        pairs.push(CodePair::new_i16(175, mleader.text_alignment_type));
+        pairs.push(CodePair::new_i32(92, mleader.text_color));
+        pairs.push(CodePair::new_bool(292, mleader.enable_frame_text));
+        if !mleader.__block_content_handle.is_empty() {
+            pairs.push(CodePair::new_string(
+                344,
+                &mleader.__block_content_handle.as_string(),
+            ));
+        }
+        pairs.push(CodePair::new_i32(93, mleader.block_content_color));
+        pairs.push(CodePair::new_f64(10, mleader.block_content_scale.x));
+        pairs.push(CodePair::new_f64(20, mleader.block_content_scale.y));
+        pairs.push(CodePair::new_f64(30, mleader.block_content_scale.z));
+        pairs.push(CodePair::new_f64(43, mleader.block_content_rotation));
+        pairs.push(CodePair::new_i16(
+            176,
+            mleader.block_content_connection_type,
+        ));
+        pairs.push(CodePair::new_bool(293, mleader.enable_annotation_scale));
+        for arrowhead in &mleader.arrowhead_overrides {
+            pairs.push(CodePair::new_i32(94, arrowhead.index));
+            pairs.push(CodePair::new_string(
+                345,
+                &arrowhead.__arrowhead_handle.as_string(),
+            ));
+        }
+        for att in &mleader.block_attributes {
+            pairs.push(CodePair::new_string(
+                330,
+                &att.__attribute_definition_handle.as_string(),
+            ));
+            pairs.push(CodePair::new_i16(177, att.index));
+            pairs.push(CodePair::new_f64(44, att.width));
+            pairs.push(CodePair::new_string(302, &att.text));
+        }
+        pairs.push(CodePair::new_bool(294, mleader.is_text_direction_negative));
+        pairs.push(CodePair::new_i16(178, mleader.text_align_in_ipe));
+        pairs.push(CodePair::new_i16(179, mleader.text_attachment_point));
+        pairs.push(CodePair::new_i16(
+            271,
+            mleader.text_attachment_direction as i16,
+        ));
+        pairs.push(CodePair::new_i16(
+            272,
+            mleader.bottom_text_attachment_direction as i16,
+        ));
+        pairs.push(CodePair::new_i16(
+            273,
+            mleader.top_text_attachment_direction as i16,
+        ));
+    }
+    fn add_custom_code_pairs_table(pairs: &mut Vec<CodePair>, table: &Table) {
+        pairs.push(CodePair::new_str(100, "AcDbBlockReference"));
+        pairs.push(CodePair::new_string(2, &table.block_name));
+        pairs.push(CodePair::new_f64(10, table.insertion_point.x));
+        pairs.push(CodePair::new_f64(20, table.insertion_point.y));
+        pairs.push(CodePair::new_f64(30, table.insertion_point.z));
+        pairs.push(CodePair::new_str(100, "AcDbTable"));
+        pairs.push(CodePair::new_i16(280, table.version));
+        pairs.push(CodePair::new_string(
+            342,
+            &table.__table_style_handle.as_string(),
+        ));
+        if !table.__owning_block_handle.is_empty() {
+            pairs.push(CodePair::new_string(
+                343,
+                &table.__owning_block_handle.as_string(),
+            ));
+        }
+        pairs.push(CodePair::new_f64(11, table.horizontal_direction.x));
+        pairs.push(CodePair::new_f64(21, table.horizontal_direction.y));
+        pairs.push(CodePair::new_f64(31, table.horizontal_direction.z));
+        pairs.push(CodePair::new_i32(90, table.value_flags));
+        pairs.push(CodePair::new_i32(91, table.row_heights.len() as i32));
+        pairs.push(CodePair::new_i32(92, table.column_widths.len() as i32));
+        pairs.push(CodePair::new_i32(93, table.override_flags));
+        pairs.push(CodePair::new_i32(94, table.border_color_override_flags));
+        pairs.push(CodePair::new_i32(
+            95,
+            table.border_lineweight_override_flags,
+        ));
+        pairs.push(CodePair::new_i32(
+            96,
+            table.border_visibility_override_flags,
+        ));
+        for height in &table.row_heights {
+            pairs.push(CodePair::new_f64(141, *height));
+        }
+        for width in &table.column_widths {
+            pairs.push(CodePair::new_f64(142, *width));
+        }
+        for cell in &table.cells {
+            cell.add_code_pairs(pairs);
+        }
+        pairs.extend(table.table_style_overrides.iter().cloned());
+    }
     fn add_post_code_pairs(
         &self,
         pairs: &mut Vec<CodePair>,
@@ -1778,8 +2199,143 @@ mod tests {
             CodePair::new_str(0, "ENDSEC"),
             CodePair::new_str(0, "EOF"),
         ]);
-        let entities = drawing.entities();
-        assert_eq!(0, entities.count());
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Unknown {
+                ref type_string,
+                ref pairs,
+                ..
+            } => {
+                assert_eq!("UNSUPPORTED_ENTITY", type_string);
+                assert_eq!(vec![CodePair::new_str(1, "unsupported string")], *pairs);
+            }
+            _ => panic!("expected an unknown entity"),
+        }
+    }
+
+    #[test]
+    fn read_unsupported_entity_common_and_specific_values() {
+        let ent = read_entity(
+            "UNSUPPORTED_ENTITY",
+            vec![
+                CodePair::new_str(5, "42"),
+                CodePair::new_str(100, "AcDbEntity"),
+                CodePair::new_str(8, "layer"),
+                CodePair::new_str(100, "AcDbUnsupported"),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_str(8, "not-a-layer"),
+                CodePair::new_str(1001, "APP"),
+                CodePair::new_str(1000, "x data"),
+            ],
+        );
+        assert_eq!(Handle(0x42), ent.common.handle);
+        assert_eq!("layer", ent.common.layer);
+        assert_eq!(1, ent.common.x_data.len());
+        match ent.specific {
+            EntityType::Unknown { ref pairs, .. } => {
+                assert_eq!(
+                    vec![
+                        CodePair::new_str(100, "AcDbUnsupported"),
+                        CodePair::new_f64(10, 1.0),
+                        CodePair::new_str(8, "not-a-layer"),
+                    ],
+                    *pairs
+                );
+            }
+            _ => panic!("expected an unknown entity"),
+        }
+    }
+
+    #[test]
+    fn write_unsupported_entity() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let ent = Entity {
+            common: EntityCommon {
+                layer: String::from("layer"),
+                ..Default::default()
+            },
+            specific: EntityType::Unknown {
+                type_string: String::from("UNSUPPORTED_ENTITY"),
+                pairs: vec![
+                    CodePair::new_str(100, "AcDbUnsupported"),
+                    CodePair::new_f64(10, 1.0),
+                ],
+                version: AcadVersion::R2000,
+            },
+        };
+        let ent = drawing.add_entity(ent);
+        let handle = ent.common.handle;
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(0, "UNSUPPORTED_ENTITY"),
+                CodePair::new_string(5, &handle.as_string()),
+                CodePair::new_str(100, "AcDbEntity"),
+                CodePair::new_str(8, "layer"),
+            ],
+        );
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(100, "AcDbUnsupported"),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_str(0, "ENDSEC"),
+            ],
+        );
+    }
+
+    #[test]
+    fn round_trip_unsupported_entity() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Unknown {
+            type_string: String::from("UNSUPPORTED_ENTITY"),
+            pairs: vec![
+                CodePair::new_str(100, "AcDbUnsupported"),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_str(1, "unsupported string"),
+            ],
+            version: AcadVersion::R2000,
+        }));
+        let drawing = parse_drawing(&to_test_string(&drawing));
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Unknown {
+                ref type_string,
+                ref pairs,
+                version,
+            } => {
+                assert_eq!(AcadVersion::R2000, version);
+                assert_eq!("UNSUPPORTED_ENTITY", type_string);
+                assert_eq!(
+                    vec![
+                        CodePair::new_str(100, "AcDbUnsupported"),
+                        CodePair::new_f64(10, 1.0),
+                        CodePair::new_str(1, "unsupported string"),
+                    ],
+                    *pairs
+                );
+            }
+            _ => panic!("expected an unknown entity"),
+        }
+    }
+
+    #[test]
+    fn unsupported_entity_is_not_written_to_older_versions() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R12;
+        drawing.add_entity(Entity::new(EntityType::Unknown {
+            type_string: String::from("UNSUPPORTED_ENTITY"),
+            pairs: vec![CodePair::new_str(1, "unsupported string")],
+            version: AcadVersion::R2000,
+        }));
+        assert_not_contains_pairs(&drawing, vec![CodePair::new_str(0, "UNSUPPORTED_ENTITY")]);
+
+        drawing.header.version = AcadVersion::R2004;
+        assert_contains_pairs(&drawing, vec![CodePair::new_str(0, "UNSUPPORTED_ENTITY")]);
     }
 
     #[test]
@@ -1795,12 +2351,16 @@ mod tests {
             CodePair::new_str(0, "EOF"),
         ]);
         let entities = drawing.entities().collect::<Vec<_>>();
-        assert_eq!(2, entities.len());
+        assert_eq!(3, entities.len());
         match entities[0].specific {
             EntityType::Line(_) => (),
             _ => panic!("expected a line"),
         }
         match entities[1].specific {
+            EntityType::Unknown { .. } => (),
+            _ => panic!("expected an unknown entity"),
+        }
+        match entities[2].specific {
             EntityType::Circle(_) => (),
             _ => panic!("expected a circle"),
         }
@@ -3392,6 +3952,382 @@ mod tests {
         }
     }
 
+    #[test]
+    fn read_mleader() {
+        let ent = read_entity(
+            "MULTILEADER",
+            vec![
+                CodePair::new_str(100, "AcDbEntity"),
+                CodePair::new_str(8, "layer"),
+                CodePair::new_str(100, "AcDbMLeader"),
+                CodePair::new_i16(270, 2),
+                CodePair::new_str(300, "CONTEXT_DATA{"),
+                CodePair::new_f64(40, 2.0),
+                CodePair::new_bool(290, true),
+                CodePair::new_str(304, "leader text"),
+                CodePair::new_f64(12, 1.0),
+                CodePair::new_f64(22, 2.0),
+                CodePair::new_f64(32, 3.0),
+                CodePair::new_str(302, "LEADER{"),
+                CodePair::new_f64(10, 4.0),
+                CodePair::new_f64(20, 5.0),
+                CodePair::new_f64(30, 6.0),
+                CodePair::new_str(304, "LEADER_LINE{"),
+                CodePair::new_f64(10, 7.0),
+                CodePair::new_f64(20, 8.0),
+                CodePair::new_f64(30, 9.0),
+                CodePair::new_f64(10, 10.0),
+                CodePair::new_f64(20, 11.0),
+                CodePair::new_f64(30, 12.0),
+                CodePair::new_i32(91, 3),
+                CodePair::new_str(305, "}"),
+                CodePair::new_str(303, "}"),
+                CodePair::new_str(301, "}"),
+                CodePair::new_i32(92, 42),
+                CodePair::new_bool(292, true),
+            ],
+        );
+        assert_eq!("layer", ent.common.layer);
+        match ent.specific {
+            EntityType::MLeader(ref ml) => {
+                assert_eq!(2, ml.version);
+                assert_eq!(42, ml.text_color);
+                assert!(ml.enable_frame_text);
+                let context = &ml.context_data;
+                assert_eq!(2.0, context.content_scale);
+                let text = context.text_content.as_ref().unwrap();
+                assert_eq!("leader text", text.text);
+                assert_eq!(Point::new(1.0, 2.0, 3.0), text.location);
+                assert_eq!(1, context.leader_roots.len());
+                let root = &context.leader_roots[0];
+                assert_eq!(Point::new(4.0, 5.0, 6.0), root.connection_point);
+                assert_eq!(1, root.leader_lines.len());
+                let line = &root.leader_lines[0];
+                assert_eq!(
+                    vec![Point::new(7.0, 8.0, 9.0), Point::new(10.0, 11.0, 12.0)],
+                    line.vertices
+                );
+                assert_eq!(3, line.leader_line_index);
+            }
+            _ => panic!("expected an MLEADER"),
+        }
+    }
+
+    #[test]
+    fn round_trip_mleader() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        let mut mleader = MLeader {
+            text_color: 42,
+            ..Default::default()
+        };
+        mleader.context_data.text_content = Some(MLeaderTextContent {
+            text: String::from("leader text"),
+            location: Point::new(1.0, 2.0, 3.0),
+            ..Default::default()
+        });
+        mleader.context_data.leader_roots.push(MLeaderRoot {
+            connection_point: Point::new(4.0, 5.0, 6.0),
+            leader_lines: vec![MLeaderLine {
+                vertices: vec![Point::new(7.0, 8.0, 9.0), Point::new(10.0, 11.0, 12.0)],
+                leader_line_index: 3,
+            }],
+            ..Default::default()
+        });
+        mleader.arrowhead_overrides.push(MLeaderArrowhead {
+            index: 1,
+            __arrowhead_handle: Handle(0x42),
+        });
+        let expected = mleader.clone();
+        drawing.add_entity(Entity::new(EntityType::MLeader(mleader)));
+
+        let drawing = parse_drawing(&to_test_string(&drawing));
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::MLeader(ref ml) => assert_eq!(expected, *ml),
+            _ => panic!("expected an MLEADER"),
+        }
+    }
+
+    #[test]
+    fn mleader_is_not_written_before_r2007() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        drawing.add_entity(Entity::new(EntityType::MLeader(MLeader::default())));
+        let contents = to_test_string(&drawing);
+        assert!(!contents.contains("MULTILEADER"));
+    }
+
+    #[test]
+    fn read_table() {
+        let ent = read_entity(
+            "ACAD_TABLE",
+            vec![
+                CodePair::new_str(100, "AcDbEntity"),
+                CodePair::new_str(8, "layer"),
+                CodePair::new_str(100, "AcDbBlockReference"),
+                CodePair::new_str(2, "*T1"),
+                CodePair::new_f64(10, 1.0),
+                CodePair::new_f64(20, 2.0),
+                CodePair::new_f64(30, 3.0),
+                CodePair::new_str(100, "AcDbTable"),
+                CodePair::new_i16(280, 0),
+                CodePair::new_str(342, "42"),
+                CodePair::new_i32(91, 1),
+                CodePair::new_i32(92, 2),
+                CodePair::new_f64(141, 0.5),
+                CodePair::new_f64(142, 3.0),
+                CodePair::new_f64(142, 4.0),
+                // text cell
+                CodePair::new_i16(171, 1),
+                CodePair::new_i16(175, 2),
+                CodePair::new_i16(176, 1),
+                CodePair::new_i32(91, 262_144),
+                CodePair::new_str(2, "long "),
+                CodePair::new_str(1, "text"),
+                CodePair::new_str(7, "Standard"),
+                // block cell
+                CodePair::new_i16(171, 2),
+                CodePair::new_str(340, "43"),
+                CodePair::new_f64(144, 2.0),
+                CodePair::new_i16(179, 1),
+                CodePair::new_str(331, "44"),
+                CodePair::new_str(300, "attribute value"),
+                CodePair::new_i32(92, 0),
+                // table style overrides
+                CodePair::new_i16(280, 1),
+                CodePair::new_str(7, "override style"),
+            ],
+        );
+        assert_eq!("layer", ent.common.layer);
+        match ent.specific {
+            EntityType::Table(ref table) => {
+                assert_eq!("*T1", table.block_name);
+                assert_eq!(Point::new(1.0, 2.0, 3.0), table.insertion_point);
+                assert_eq!(Handle(0x42), table.__table_style_handle);
+                assert_eq!(1, table.row_count());
+                assert_eq!(2, table.column_count());
+                assert_eq!(vec![3.0, 4.0], table.column_widths);
+                assert_eq!(2, table.cells.len());
+
+                let text = table.cell(0, 0).unwrap();
+                assert_eq!(TableCellType::Text, text.cell_type);
+                assert_eq!(2, text.column_span);
+                assert_eq!(262_144, text.override_flags);
+                assert_eq!("long text", text.text);
+                assert_eq!("Standard", text.text_style_name);
+
+                let block = table.cell(0, 1).unwrap();
+                assert_eq!(TableCellType::Block, block.cell_type);
+                assert_eq!(Handle(0x43), block.__block_record_handle);
+                assert_eq!(2.0, block.block_scale);
+                assert_eq!(1, block.attribute_values.len());
+                assert_eq!(
+                    Handle(0x44),
+                    block.attribute_values[0].__attribute_definition_handle
+                );
+                assert_eq!("attribute value", block.attribute_values[0].value);
+                assert_eq!(vec![CodePair::new_i32(92, 0)], block.unknown_pairs);
+                assert_eq!(
+                    vec![
+                        CodePair::new_i16(280, 1),
+                        CodePair::new_str(7, "override style"),
+                    ],
+                    table.table_style_overrides
+                );
+            }
+            _ => panic!("expected a TABLE"),
+        }
+    }
+
+    #[test]
+    fn read_and_write_table_cells_with_unknown_values() {
+        let mut pairs = vec![
+            CodePair::new_str(100, "AcDbTable"),
+            CodePair::new_i32(91, 2),
+            CodePair::new_i32(92, 2),
+            CodePair::new_f64(141, 1.0),
+            CodePair::new_f64(141, 1.0),
+            CodePair::new_f64(142, 1.0),
+            CodePair::new_f64(142, 1.0),
+        ];
+        for i in 0..4 {
+            pairs.push(CodePair::new_i16(171, 1));
+            pairs.push(CodePair::new_i32(92, 0));
+            pairs.push(CodePair::new_string(1, &format!("cell {i}")));
+            pairs.push(CodePair::new_i16(177, 0));
+            pairs.push(CodePair::new_i16(170, 5));
+        }
+        pairs.push(CodePair::new_i16(280, 0));
+        pairs.push(CodePair::new_i16(63, 7));
+        let ent = read_entity("ACAD_TABLE", pairs);
+        let table = match ent.specific {
+            EntityType::Table(ref table) => table.clone(),
+            _ => panic!("expected a TABLE"),
+        };
+        assert_eq!(4, table.cells.len());
+        for (i, cell) in table.cells.iter().enumerate() {
+            assert_eq!(format!("cell {i}"), cell.text);
+            assert_eq!(
+                vec![
+                    CodePair::new_i32(92, 0),
+                    CodePair::new_i16(177, 0),
+                    CodePair::new_i16(170, 5),
+                ],
+                cell.unknown_pairs
+            );
+        }
+        assert_eq!(
+            vec![CodePair::new_i16(280, 0), CodePair::new_i16(63, 7)],
+            table.table_style_overrides
+        );
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        drawing.add_entity(Entity::new(EntityType::Table(table.clone())));
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(1, "cell 3"),
+                CodePair::new_str(7, ""),
+                CodePair::new_i32(92, 0),
+                CodePair::new_i16(177, 0),
+                CodePair::new_i16(170, 5),
+                CodePair::new_i16(280, 0),
+                CodePair::new_i16(63, 7),
+            ],
+        );
+        let drawing = parse_drawing(&to_test_string(&drawing));
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Table(ref reparsed) => assert_eq!(table, *reparsed),
+            _ => panic!("expected a TABLE"),
+        }
+    }
+
+    #[test]
+    fn read_table_cell_text_from_cell_value() {
+        let ent = read_entity(
+            "ACAD_TABLE",
+            vec![
+                CodePair::new_str(100, "AcDbTable"),
+                CodePair::new_f64(141, 1.0),
+                CodePair::new_f64(142, 1.0),
+                CodePair::new_i16(171, 1),
+                CodePair::new_str(301, "CELL_VALUE"),
+                CodePair::new_i32(93, 2),
+                CodePair::new_i32(90, 4),
+                CodePair::new_str(1, "cell text"),
+                CodePair::new_str(302, "formatted"),
+                CodePair::new_str(304, "ACVALUE_END"),
+            ],
+        );
+        match ent.specific {
+            EntityType::Table(ref table) => {
+                assert_eq!(1, table.cells.len());
+                assert_eq!("cell text", table.cells[0].text);
+            }
+            _ => panic!("expected a TABLE"),
+        }
+    }
+
+    #[test]
+    fn round_trip_table() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2004;
+        let block_cell = TableCell {
+            cell_type: TableCellType::Block,
+            __block_record_handle: Handle(0x43),
+            block_scale: 2.0,
+            attribute_values: vec![TableCellAttribute {
+                __attribute_definition_handle: Handle(0x44),
+                value: String::from("attribute value"),
+            }],
+            ..Default::default()
+        };
+        let table = Table {
+            block_name: String::from("*T1"),
+            row_heights: vec![0.5, 0.5],
+            column_widths: vec![3.0, 4.0],
+            cells: vec![
+                TableCell {
+                    column_span: 2,
+                    is_merged: true,
+                    ..TableCell::new_text(&"x".repeat(600))
+                },
+                TableCell::new_text(""),
+                TableCell::new_text("text"),
+                block_cell,
+            ],
+            ..Default::default()
+        };
+        let expected = table.clone();
+        drawing.add_entity(Entity::new(EntityType::Table(table)));
+
+        let contents = to_test_string(&drawing);
+        assert!(contents.contains("ACAD_TABLE"));
+        let drawing = parse_drawing(&contents);
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Table(ref table) => assert_eq!(expected, *table),
+            _ => panic!("expected a TABLE"),
+        }
+    }
+
+    #[test]
+    fn table_is_not_written_before_r2004() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(Entity::new(EntityType::Table(Table::default())));
+        let contents = to_test_string(&drawing);
+        assert!(!contents.contains("ACAD_TABLE"));
+    }
+
+    #[test]
+    fn table_merged_ranges() {
+        let table = Table {
+            row_heights: vec![1.0, 1.0, 1.0],
+            column_widths: vec![1.0, 1.0],
+            cells: vec![
+                TableCell {
+                    column_span: 2,
+                    ..Default::default()
+                },
+                TableCell::default(),
+                TableCell {
+                    row_span: 2,
+                    ..Default::default()
+                },
+                TableCell::default(),
+                TableCell::default(),
+                TableCell::default(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![
+                TableCellRange {
+                    first_row: 0,
+                    first_column: 0,
+                    last_row: 0,
+                    last_column: 1,
+                },
+                TableCellRange {
+                    first_row: 1,
+                    first_column: 0,
+                    last_row: 2,
+                    last_column: 0,
+                },
+            ],
+            table.merged_ranges()
+        );
+        assert!(table.cell(0, 2).is_none());
+        assert!(table.cell(3, 0).is_none());
+    }
+
     #[test]
     fn normalize_mline_styles() {
         let mut file = Drawing::new();
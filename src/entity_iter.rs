@@ -1,28 +1,51 @@
 use crate::code_pair_put_back::CodePairPutBack;
 use crate::drawing::AUTO_REPLACE_HANDLE;
 use crate::entities::*;
-use crate::DxfResult;
+use crate::{DxfError, DxfResult};
 
-use itertools::{put_back, PutBack};
+pub(crate) struct EntityIter<'a, 'b> {
+    iter: &'a mut CodePairPutBack<'b>,
+    /// The error that stopped reading.
+    error: Option<DxfError>,
+}
 
-pub(crate) struct EntityIter<'a> {
-    pub iter: &'a mut CodePairPutBack,
+impl<'a, 'b> EntityIter<'a, 'b> {
+    pub(crate) fn new(iter: &'a mut CodePairPutBack<'b>) -> Self {
+        EntityIter { iter, error: None }
+    }
 }
 
-impl Iterator for EntityIter<'_> {
+impl Iterator for EntityIter<'_, '_> {
     type Item = Entity;
 
     fn next(&mut self) -> Option<Entity> {
-        match Entity::read(self.iter) {
-            Ok(Some(e)) => Some(e),
-            Ok(None) | Err(_) => None,
+        loop {
+            match Entity::read(self.iter) {
+                Ok(Some(e)) => return Some(e),
+                Ok(None) => return None,
+                Err(e) => {
+                    // when recovering, skip the rest of the item and continue with the next one
+                    match self.iter.recover(e).and_then(|_| self.iter.skip_to(&[])) {
+                        Ok(true) => (),
+                        Ok(false) => return None,
+                        Err(e) => {
+                            self.error = Some(e);
+                            return None;
+                        }
+                    }
+                }
+            }
         }
     }
 }
 
-impl EntityIter<'_> {
+impl EntityIter<'_, '_> {
     pub(crate) fn read_entities_into_vec(&mut self, entities: &mut Vec<Entity>) -> DxfResult<()> {
-        collect_entities(self, entities)
+        collect_entities(self, entities)?;
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
 }
 
@@ -30,85 +53,98 @@ pub(crate) fn collect_entities<I>(iter: &mut I, entities: &mut Vec<Entity>) -> D
 where
     I: Iterator<Item = Entity>,
 {
-    fn swallow_seqend<I>(iter: &mut PutBack<I>) -> DxfResult<()>
-    where
-        I: Iterator<Item = Entity>,
-    {
-        match iter.next() {
+    entities.extend(CombinedEntityIter::new(iter));
+    Ok(())
+}
+
+/// Gathers the `Attribute`s, `Vertex`s, `MText`s, and `Seqend`s that follow an entity into it as
+/// they're read.
+pub(crate) struct CombinedEntityIter<I>
+where
+    I: Iterator<Item = Entity>,
+{
+    pub iter: I,
+    put_back: Option<Entity>,
+}
+
+impl<I> CombinedEntityIter<I>
+where
+    I: Iterator<Item = Entity>,
+{
+    pub(crate) fn new(iter: I) -> Self {
+        CombinedEntityIter {
+            iter,
+            put_back: None,
+        }
+    }
+    fn next_raw(&mut self) -> Option<Entity> {
+        self.put_back.take().or_else(|| self.iter.next())
+    }
+    fn swallow_seqend(&mut self) {
+        match self.next_raw() {
             Some(Entity {
                 specific: EntityType::Seqend(_),
                 ..
             }) => (),
-            Some(ent) => {
-                iter.put_back(ent);
-            }
+            Some(ent) => self.put_back = Some(ent),
             None => (),
         }
-
-        Ok(())
     }
-
-    fn mtext<I>(iter: &mut PutBack<I>) -> DxfResult<Option<MText>>
-    where
-        I: Iterator<Item = Entity>,
-    {
-        let m_text = match iter.next() {
+    fn mtext(&mut self) -> Option<MText> {
+        match self.next_raw() {
             Some(Entity {
                 specific: EntityType::MText(m),
                 ..
             }) => Some(m),
             Some(ent) => {
-                iter.put_back(ent);
+                self.put_back = Some(ent);
                 None
             }
             None => None,
-        };
-
-        Ok(m_text)
+        }
     }
+}
 
-    let mut iter = put_back(iter);
-    loop {
-        match iter.next() {
-            Some(Entity {
-                ref common,
-                specific: EntityType::Attribute(ref att),
-            }) => {
-                let mut att = att.clone(); // 27 fields
-                match mtext(&mut iter) {
-                    Ok(Some(m_text)) => att.m_text = m_text,
-                    Ok(None) => (),
-                    Err(e) => return Err(e),
+impl<I> Iterator for CombinedEntityIter<I>
+where
+    I: Iterator<Item = Entity>,
+{
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        match self.next_raw()? {
+            Entity {
+                common,
+                specific: EntityType::Attribute(mut att),
+            } => {
+                if let Some(m_text) = self.mtext() {
+                    att.m_text = m_text;
                 }
 
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
+                Some(Entity {
+                    common,
                     specific: EntityType::Attribute(att),
-                });
+                })
             }
-            Some(Entity {
-                ref common,
-                specific: EntityType::AttributeDefinition(ref att),
-            }) => {
-                let mut att = att.clone(); // 27 fields
-                match mtext(&mut iter) {
-                    Ok(Some(m_text)) => att.m_text = m_text,
-                    Ok(None) => (),
-                    Err(e) => return Err(e),
+            Entity {
+                common,
+                specific: EntityType::AttributeDefinition(mut att),
+            } => {
+                if let Some(m_text) = self.mtext() {
+                    att.m_text = m_text;
                 }
 
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
+                Some(Entity {
+                    common,
                     specific: EntityType::AttributeDefinition(att),
-                });
+                })
             }
-            Some(Entity {
-                ref common,
-                specific: EntityType::Insert(ref ins),
-            }) if ins.__has_attributes => {
-                let mut ins = ins.clone(); // 12 fields
+            Entity {
+                common,
+                specific: EntityType::Insert(mut ins),
+            } if ins.__has_attributes => {
                 loop {
-                    match iter.next() {
+                    match self.next_raw() {
                         Some(Entity {
                             specific: EntityType::Attribute(att),
                             ..
@@ -117,28 +153,27 @@ where
                             .push((att, AUTO_REPLACE_HANDLE)),
                         Some(ent) => {
                             // stop gathering on any non-ATTRIBUTE
-                            iter.put_back(ent);
+                            self.put_back = Some(ent);
                             break;
                         }
                         None => break,
                     }
                 }
 
-                swallow_seqend(&mut iter)?;
+                self.swallow_seqend();
 
                 // and finally keep the INSERT
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
+                Some(Entity {
+                    common,
                     specific: EntityType::Insert(ins),
-                });
+                })
             }
-            Some(Entity {
+            Entity {
                 common,
-                specific: EntityType::Polyline(poly),
-            }) => {
-                let mut poly = poly.clone(); // 13 fields
+                specific: EntityType::Polyline(mut poly),
+            } => {
                 loop {
-                    match iter.next() {
+                    match self.next_raw() {
                         Some(Entity {
                             specific: EntityType::Vertex(vertex),
                             ..
@@ -147,25 +182,22 @@ where
                             .push((vertex, AUTO_REPLACE_HANDLE)),
                         Some(ent) => {
                             // stop gathering on any non-VERTEX
-                            iter.put_back(ent);
+                            self.put_back = Some(ent);
                             break;
                         }
                         None => break,
                     }
                 }
 
-                swallow_seqend(&mut iter)?;
+                self.swallow_seqend();
 
                 // and finally keep the POLYLINE
-                entities.push(Entity {
-                    common: common.clone(), // 18 fields
+                Some(Entity {
+                    common,
                     specific: EntityType::Polyline(poly),
-                });
+                })
             }
-            Some(entity) => entities.push(entity),
-            None => break,
+            entity => Some(entity),
         }
     }
-
-    Ok(())
 }
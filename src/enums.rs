@@ -1087,6 +1087,15 @@ pub enum SubentityType
 }
 }
 
+enum_from_primitive! {
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TableCellType {
+    Text = 1,
+    Block = 2,
+}
+}
+
 enum_from_primitive! {
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::Handle;
+
+/// The collection and position of an item in a `Drawing`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ItemLocation {
+    AppId(usize),
+    Block(usize),
+    BlockRecord(usize),
+    DimStyle(usize),
+    Entity(usize),
+    Layer(usize),
+    LineType(usize),
+    Object(usize),
+    Style(usize),
+    Ucs(usize),
+    View(usize),
+    ViewPort(usize),
+}
+
+/// Maps handles to the location of their items in a `Drawing`.
+///
+/// The map is built on first use and then kept up to date as items are added and removed.  Changes
+/// that can't be tracked, like mutable iteration or sorting, discard the map so it's rebuilt on
+/// the next lookup.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HandleIndex {
+    map: OnceLock<HashMap<Handle, ItemLocation>>,
+    /// An item that was handed out mutably by handle, and its handle at the time, which may have
+    /// since been changed.
+    pub unchecked: Option<(Handle, ItemLocation)>,
+}
+
+impl HandleIndex {
+    /// Returns the map, building it with `build` if necessary.
+    pub fn get_or_build<F>(&self, build: F) -> &HashMap<Handle, ItemLocation>
+    where
+        F: FnOnce() -> HashMap<Handle, ItemLocation>,
+    {
+        self.map.get_or_init(build)
+    }
+    /// Returns the map if it has been built.
+    pub fn get_mut(&mut self) -> Option<&mut HashMap<Handle, ItemLocation>> {
+        self.map.get_mut()
+    }
+    /// Records a newly added item.
+    pub fn insert(&mut self, handle: Handle, location: ItemLocation) {
+        if let Some(map) = self.map.get_mut() {
+            map.entry(handle).or_insert(location);
+        }
+    }
+    /// Discards the map.
+    pub fn invalidate(&mut self) {
+        self.map = OnceLock::new();
+        self.unchecked = None;
+    }
+}
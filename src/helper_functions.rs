@@ -14,6 +14,7 @@ use uuid::Uuid;
 
 use enum_primitive::FromPrimitive;
 
+use crate::code_pair_put_back::CodePairPutBack;
 use crate::enums::*;
 use crate::tables::Layer;
 use crate::{CodePair, Color, DxfError, DxfResult, RGB};
@@ -47,13 +48,20 @@ where
     timezone.with_ymd_and_hms(1900, 1, 1, 0, 0, 0).unwrap()
 }
 
-fn as_datetime<T>(timezone: &T, date: f64) -> DateTime<T>
+/// The Julian date of December 31, 9999, the latest date that's read.
+const MAXIMUM_JULIAN_DATE: f64 = 5_373_484.5;
+
+fn as_datetime<T>(timezone: &T, date: f64, offset: usize) -> DxfResult<DateTime<T>>
 where
     T: TimeZone,
 {
     // dates are represented as the fractional number of days elapsed since December 31, 1899.
+    // later dates than can be represented are errors, but earlier dates are the epoch
+    if date.is_nan() || date > MAXIMUM_JULIAN_DATE {
+        return Err(DxfError::DateOutOfRange(date, offset));
+    }
     let epoch = epoch(timezone);
-    let duration = if date == 0.0 {
+    let duration = if date <= 2_415_020.0 {
         ChronoDuration::seconds(0)
     } else {
         let duration = f64_to_adjusted_duration(date);
@@ -62,15 +70,15 @@ where
             _ => ChronoDuration::seconds(0),
         }
     };
-    epoch + duration
+    Ok(epoch + duration)
 }
 
-pub(crate) fn as_datetime_local(date: f64) -> DateTime<Local> {
-    as_datetime(&Local, date)
+pub(crate) fn as_datetime_local(date: f64, offset: usize) -> DxfResult<DateTime<Local>> {
+    as_datetime(&Local, date, offset)
 }
 
-pub(crate) fn as_datetime_utc(date: f64) -> DateTime<Utc> {
-    as_datetime(&Utc, date)
+pub(crate) fn as_datetime_utc(date: f64, offset: usize) -> DxfResult<DateTime<Utc>> {
+    as_datetime(&Utc, date, offset)
 }
 
 #[test]
@@ -78,7 +86,7 @@ fn as_datetime_conversion_test() {
     // from AutoDesk spec: 2451544.91568287 = 31 December 1999, 9:58:35PM
     assert_eq!(
         Local.with_ymd_and_hms(1999, 12, 31, 21, 58, 35).unwrap(),
-        as_datetime_local(2_451_544.915_682_87)
+        as_datetime_local(2_451_544.915_682_87, 0).unwrap()
     );
 }
 
@@ -89,11 +97,25 @@ fn datetime_out_of_bounds_test() {
     for value in values {
         assert_eq!(
             Local.with_ymd_and_hms(1900, 1, 1, 0, 0, 0).unwrap(),
-            as_datetime_local(value)
+            as_datetime_local(value, 0).unwrap()
         )
     }
 }
 
+#[test]
+fn datetime_too_late_test() {
+    for value in [f64::NAN, 1e308, 5_373_485.0] {
+        assert!(matches!(
+            as_datetime_utc(value, 7),
+            Err(DxfError::DateOutOfRange(_, 7))
+        ));
+    }
+    assert_eq!(
+        Utc.with_ymd_and_hms(1900, 1, 1, 0, 0, 0).unwrap(),
+        as_datetime_utc(-1e308, 0).unwrap()
+    );
+}
+
 fn as_double<T>(timezone: &T, date: DateTime<T>) -> f64
 where
     T: TimeZone,
@@ -166,6 +188,20 @@ pub(crate) fn as_i16(b: bool) -> i16 {
     }
 }
 
+// returns the next `CodePair` within a `{`-delimited block, or fails on a 0 code
+pub(crate) fn next_block_pair(iter: &mut CodePairPutBack) -> DxfResult<CodePair> {
+    match iter.next() {
+        Some(Ok(pair @ CodePair { code: 0, .. })) => {
+            let offset = pair.offset;
+            iter.put_back(Ok(pair));
+            Err(DxfError::UnexpectedCode(0, offset))
+        }
+        Some(Ok(pair)) => Ok(pair),
+        Some(Err(e)) => Err(e),
+        None => Err(DxfError::UnexpectedEndOfInput),
+    }
+}
+
 pub(crate) fn uuid_string(u: &Uuid) -> String {
     format!("{u}")
 }
@@ -308,6 +344,7 @@ where
 {
     let mut bytes = vec![];
     let mut skipping_bom = false;
+    #[allow(clippy::unbuffered_bytes)]
     let reader_bytes = reader.bytes();
     for (i, b) in reader_bytes.enumerate() {
         let b = match b {
@@ -516,7 +553,7 @@ pub(crate) fn parse_hex_string(data: &str, bytes: &mut Vec<u8>, offset: usize) -
         Ok(value)
     }
 
-    let mut complete_byte = data.len() % 2 != 0; // handles strings with an odd number of bytes
+    let mut complete_byte = !data.len().is_multiple_of(2); // handles strings with an odd number of bytes
     let mut current_byte = 0u8;
     for c in data.chars() {
         let value = char_to_value(c, offset)?;
@@ -655,6 +692,17 @@ pub mod tests {
         assert!(actual_index.is_some());
     }
 
+    pub fn assert_point(expected: (f64, f64, f64), actual: &Point) {
+        assert!(
+            (expected.0 - actual.x).abs() < 1e-9
+                && (expected.1 - actual.y).abs() < 1e-9
+                && (expected.2 - actual.z).abs() < 1e-9,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
     pub fn assert_not_contains(drawing: &Drawing, contents: String) {
         let actual = to_test_string(drawing);
         assert!(!actual.contains(&contents));
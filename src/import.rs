@@ -0,0 +1,777 @@
+// Copying the contents of one `Drawing` into another
+
+use std::collections::{HashMap, HashSet};
+
+use crate::dictionary::{dictionary_entries, REACTORS_GROUP};
+use crate::entities::*;
+use crate::objects::*;
+use crate::{CodePair, Drawing, ExtensionGroup, ExtensionGroupItem, Handle};
+
+/// How `Drawing::import` resolves a table entry or block whose name is already used by the
+/// drawing being imported into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportNameCollision {
+    /// The existing entry is kept and imported items refer to it instead.
+    KeepExisting,
+    /// The imported entry is renamed by appending `_1`, `_2`, etc. and imported items are updated
+    /// to refer to the new name.
+    Rename,
+}
+
+/// Options for `Drawing::import`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportOptions {
+    /// How name collisions are resolved.  The layer `0`, the line types `BYLAYER`, `BYBLOCK`,
+    /// and `CONTINUOUS`, the text and dimension styles `STANDARD` and `ANNOTATIVE`, and the
+    /// model and paper space blocks are always merged with the existing entries.
+    pub name_collision: ImportNameCollision,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions {
+            name_collision: ImportNameCollision::KeepExisting,
+        }
+    }
+}
+
+// New names of renamed items, keyed by their upper-case original name.
+#[derive(Default)]
+struct Renames {
+    layers: HashMap<String, String>,
+    line_types: HashMap<String, String>,
+    styles: HashMap<String, String>,
+    dim_styles: HashMap<String, String>,
+    blocks: HashMap<String, String>,
+}
+
+fn rename(renames: &HashMap<String, String>, name: &mut String) {
+    if let Some(new_name) = renames.get(&name.to_uppercase()) {
+        *name = new_name.clone();
+    }
+}
+
+impl Drawing {
+    /// Copies the entities, blocks, layers, line types, text styles, dimension styles, and
+    /// objects of `other` into this drawing.
+    ///
+    /// Every copied item is given a new handle and every pointer to a copied item is updated to
+    /// match.  Pointers to items that aren't copied, such as UCSs and views, are cleared.  Table
+    /// entries and blocks whose names are already used are resolved according to `options`, and
+    /// the root dictionary of `other` is merged into the root dictionary of this drawing.
+    pub fn import(&mut self, other: &Drawing, options: &ImportOptions) {
+        let mut source = other.clone();
+        let mut handles: HashMap<Handle, Handle> = HashMap::new();
+        let mut renames = Renames::default();
+        let resolution = options.name_collision;
+
+        // UCSs, views, and view ports describe the other drawing's display, not its content
+        while source.remove_ucs(0).is_some() {}
+        while source.remove_view(0).is_some() {}
+        while source.remove_view_port(0).is_some() {}
+
+        // application ids are always merged
+        let existing: Vec<_> = self.app_ids().map(|i| (i.name.clone(), i.handle)).collect();
+        let imported: Vec<_> = source.app_ids().map(|i| i.name.clone()).collect();
+        let decisions = resolve_names(&imported, &existing, |_| true, resolution);
+        apply_decisions(
+            &imported,
+            &decisions,
+            &mut handles,
+            &mut HashMap::new(),
+            |i| {
+                let handle = source.app_ids().nth(i).map(|a| a.handle);
+                source.remove_app_id(i);
+                handle
+            },
+        );
+
+        macro_rules! resolve_table {
+            ($items:ident, $items_mut:ident, $remove:ident, $is_reserved:expr, $renames:expr) => {
+                let existing: Vec<_> = self.$items().map(|i| (i.name.clone(), i.handle)).collect();
+                let imported: Vec<_> = source.$items().map(|i| i.name.clone()).collect();
+                let decisions = resolve_names(&imported, &existing, $is_reserved, resolution);
+                apply_decisions(&imported, &decisions, &mut handles, $renames, |i| {
+                    let handle = source.$items().nth(i).map(|item| item.handle);
+                    source.$remove(i);
+                    handle
+                });
+                for item in source.$items_mut() {
+                    rename($renames, &mut item.name);
+                }
+            };
+        }
+
+        resolve_table!(
+            layers,
+            layers_mut,
+            remove_layer,
+            |name| name == "0",
+            &mut renames.layers
+        );
+        resolve_table!(
+            line_types,
+            line_types_mut,
+            remove_line_type,
+            |name| matches!(name, "BYLAYER" | "BYBLOCK" | "CONTINUOUS"),
+            &mut renames.line_types
+        );
+        resolve_table!(
+            styles,
+            styles_mut,
+            remove_style,
+            |name| matches!(name, "STANDARD" | "ANNOTATIVE"),
+            &mut renames.styles
+        );
+        resolve_table!(
+            dim_styles,
+            dim_styles_mut,
+            remove_dim_style,
+            |name| matches!(name, "STANDARD" | "ANNOTATIVE"),
+            &mut renames.dim_styles
+        );
+        resolve_table!(
+            block_records,
+            block_records_mut,
+            remove_block_record,
+            is_layout_block,
+            &mut renames.blocks
+        );
+
+        // blocks follow the decisions made for their block records
+        let existing: HashMap<String, Handle> = self
+            .blocks()
+            .map(|b| (b.name.to_uppercase(), b.handle))
+            .collect();
+        let existing_records: HashSet<String> = self
+            .block_records()
+            .map(|r| r.name.to_uppercase())
+            .collect();
+        let kept: Vec<(usize, Handle, String)> = source
+            .blocks()
+            .enumerate()
+            .map(|(i, b)| {
+                let mut name = b.name.clone();
+                rename(&renames.blocks, &mut name);
+                (i, b.handle, name.to_uppercase())
+            })
+            .filter(|(_, _, name)| existing.contains_key(name) || existing_records.contains(name))
+            .collect();
+        for (index, handle, name) in kept.into_iter().rev() {
+            if let Some(&existing_handle) = existing.get(&name) {
+                handles.insert(handle, existing_handle);
+            }
+            source.remove_block(index);
+        }
+
+        // update references by name
+        for block in source.blocks_mut() {
+            rename(&renames.blocks, &mut block.name);
+            rename(&renames.layers, &mut block.layer);
+        }
+        for layer in source.layers_mut() {
+            rename(&renames.line_types, &mut layer.line_type_name);
+        }
+        for entity in source.entities_mut() {
+            rename_entity_references(entity, &renames);
+        }
+        for entity in source.blocks_mut().flat_map(|b| b.entities.iter_mut()) {
+            rename_entity_references(entity, &renames);
+        }
+        for object in source.objects_mut() {
+            match object.specific {
+                ObjectType::LayerFilter(ref mut f) => {
+                    f.layer_names
+                        .iter_mut()
+                        .for_each(|n| rename(&renames.layers, n));
+                }
+                ObjectType::LayerIndex(ref mut i) => {
+                    i.layer_names
+                        .iter_mut()
+                        .for_each(|n| rename(&renames.layers, n));
+                }
+                _ => (),
+            }
+        }
+
+        // merge the root dictionaries
+        let mut new_entries = vec![];
+        if let (Some(existing_root), Some(imported_root)) = (
+            named_object_dictionary(self),
+            named_object_dictionary(&source),
+        ) {
+            let mut merged = vec![];
+            self.merge_dictionary(
+                &source,
+                existing_root,
+                imported_root,
+                resolution,
+                &mut handles,
+                &mut merged,
+                &mut new_entries,
+            );
+            let merged: Vec<usize> = source
+                .objects()
+                .enumerate()
+                .filter(|(_, o)| merged.contains(&o.common.handle))
+                .map(|(i, _)| i)
+                .collect();
+            for index in merged.into_iter().rev() {
+                source.remove_object(index);
+            }
+        }
+
+        // assign new handles and update every pointer
+        source.for_each_item_handle_mut(&mut |handle| {
+            let new_handle = self.next_handle();
+            handles.insert(*handle, new_handle);
+            *handle = new_handle;
+        });
+        source.for_each_item_pointer_mut(&mut |handle| {
+            if !handle.is_empty() {
+                *handle = handles.get(handle).copied().unwrap_or_else(Handle::empty);
+            }
+        });
+        for object in source.objects_mut() {
+            remap_reactors(&mut object.common.extension_data_groups, &handles);
+            match object.specific {
+                ObjectType::Dictionary(ref mut d) => remap_values(&mut d.value_handles, &handles),
+                ObjectType::DictionaryWithDefault(ref mut d) => {
+                    remap_values(&mut d.value_handles, &handles)
+                }
+                _ => (),
+            }
+        }
+        for entity in source.entities_mut() {
+            remap_reactors(&mut entity.common.extension_data_groups, &handles);
+        }
+        for entity in source.blocks_mut().flat_map(|b| b.entities.iter_mut()) {
+            remap_reactors(&mut entity.common.extension_data_groups, &handles);
+        }
+        for (dictionary, key, value) in new_entries {
+            let value = handles.get(&value).copied().unwrap_or_else(Handle::empty);
+            let dictionary = self.objects_mut().find(|o| o.common.handle == dictionary);
+            if let Some(Object {
+                specific: ObjectType::Dictionary(ref mut d),
+                ..
+            }) = dictionary
+            {
+                if !value.is_empty() {
+                    d.value_handles.insert(key, value);
+                }
+            }
+        }
+
+        // move the items
+        for item in source.app_ids() {
+            self.add_app_id_no_handle_set(item.clone());
+        }
+        for item in source.line_types() {
+            self.add_line_type_no_handle_set(item.clone());
+        }
+        for item in source.layers() {
+            self.add_layer_no_handle_set(item.clone());
+        }
+        for item in source.styles() {
+            self.add_style_no_handle_set(item.clone());
+        }
+        for item in source.dim_styles() {
+            self.add_dim_style_no_handle_set(item.clone());
+        }
+        for item in source.block_records() {
+            self.add_block_record_no_handle_set(item.clone());
+        }
+        for block in source.blocks() {
+            self.add_block_no_handle_set(block.clone());
+        }
+        for entity in source.take_entities() {
+            self.add_entity_no_handle_set(entity);
+        }
+        for object in source.objects() {
+            self.add_object_no_handle_set(object.clone());
+        }
+    }
+    // Merges the imported dictionary into the existing one.  Entries that are dictionaries in
+    // both are merged recursively, and the other imported entries are added to `new_entries`
+    // after name collisions are resolved.
+    #[allow(clippy::too_many_arguments)]
+    fn merge_dictionary(
+        &self,
+        source: &Drawing,
+        existing: Handle,
+        imported: Handle,
+        resolution: ImportNameCollision,
+        handles: &mut HashMap<Handle, Handle>,
+        merged: &mut Vec<Handle>,
+        new_entries: &mut Vec<(Handle, String, Handle)>,
+    ) {
+        let (existing_values, imported_values) = match (
+            dictionary_values(self, existing),
+            dictionary_values(source, imported),
+        ) {
+            (Some(e), Some(i)) => (e, i),
+            _ => return,
+        };
+        handles.insert(imported, existing);
+        merged.push(imported);
+
+        let mut keys: Vec<_> = imported_values.keys().collect();
+        keys.sort();
+        let mut used: HashSet<String> = existing_values.keys().cloned().collect();
+        for key in keys {
+            let imported_value = imported_values[key];
+            match existing_values.get(key) {
+                None => new_entries.push((existing, key.clone(), imported_value)),
+                Some(&existing_value)
+                    if dictionary_values(self, existing_value).is_some()
+                        && dictionary_values(source, imported_value).is_some() =>
+                {
+                    self.merge_dictionary(
+                        source,
+                        existing_value,
+                        imported_value,
+                        resolution,
+                        handles,
+                        merged,
+                        new_entries,
+                    );
+                }
+                Some(&existing_value) => match resolution {
+                    ImportNameCollision::KeepExisting => {
+                        handles.insert(imported_value, existing_value);
+                        merged.push(imported_value);
+                    }
+                    ImportNameCollision::Rename => {
+                        let new_key = unique_name(key, |k| used.contains(k));
+                        used.insert(new_key.clone());
+                        new_entries.push((existing, new_key, imported_value));
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn is_layout_block(name: &str) -> bool {
+    name == "*MODEL_SPACE" || name.starts_with("*PAPER_SPACE")
+}
+
+// What to do with an imported table entry.
+enum Decision {
+    /// Import the entry with the specified name.
+    Import(String),
+    /// Drop the entry and refer to the existing entry with the specified handle and name instead.
+    Merge(Handle, String),
+}
+
+// Decides what to do with each imported item.
+fn resolve_names(
+    imported: &[String],
+    existing: &[(String, Handle)],
+    is_reserved: impl Fn(&str) -> bool,
+    resolution: ImportNameCollision,
+) -> Vec<Decision> {
+    let mut used: HashSet<String> = existing.iter().map(|(n, _)| n.to_uppercase()).collect();
+    used.extend(imported.iter().map(|n| n.to_uppercase()));
+    imported
+        .iter()
+        .map(|name| {
+            let upper = name.to_uppercase();
+            match existing.iter().find(|(n, _)| n.to_uppercase() == upper) {
+                Some((existing_name, handle))
+                    if is_reserved(&upper) || resolution == ImportNameCollision::KeepExisting =>
+                {
+                    Decision::Merge(*handle, existing_name.clone())
+                }
+                Some(_) => {
+                    let new_name = unique_name(name, |n| used.contains(&n.to_uppercase()));
+                    used.insert(new_name.to_uppercase());
+                    Decision::Import(new_name)
+                }
+                None => Decision::Import(name.clone()),
+            }
+        })
+        .collect()
+}
+
+// Removes the imported items that are merged with existing items, recording the existing handles
+// they're replaced by and any names that references need to be updated to.
+fn apply_decisions(
+    imported: &[String],
+    decisions: &[Decision],
+    handles: &mut HashMap<Handle, Handle>,
+    renames: &mut HashMap<String, String>,
+    mut remove: impl FnMut(usize) -> Option<Handle>,
+) {
+    for (index, decision) in decisions.iter().enumerate().rev() {
+        let (new_name, existing_handle) = match decision {
+            Decision::Import(new_name) => (new_name, None),
+            Decision::Merge(handle, existing_name) => (existing_name, Some(*handle)),
+        };
+        if *new_name != imported[index] {
+            renames.insert(imported[index].to_uppercase(), new_name.clone());
+        }
+        if let Some(existing_handle) = existing_handle {
+            if let Some(imported_handle) = remove(index) {
+                handles.insert(imported_handle, existing_handle);
+            }
+        }
+    }
+}
+
+fn unique_name(name: &str, is_used: impl Fn(&str) -> bool) -> String {
+    (1..)
+        .map(|n| format!("{name}_{n}"))
+        .find(|candidate| !is_used(candidate))
+        .unwrap()
+}
+
+fn rename_entity_references(entity: &mut Entity, renames: &Renames) {
+    rename(&renames.layers, &mut entity.common.layer);
+    rename(&renames.line_types, &mut entity.common.line_type_name);
+    match entity.specific {
+        EntityType::ArcAlignedText(ref mut e) => rename(&renames.styles, &mut e.text_style_name),
+        EntityType::Attribute(ref mut e) => rename(&renames.styles, &mut e.text_style_name),
+        EntityType::AttributeDefinition(ref mut e) => {
+            rename(&renames.styles, &mut e.text_style_name)
+        }
+        EntityType::MText(ref mut e) => rename(&renames.styles, &mut e.text_style_name),
+        EntityType::RText(ref mut e) => rename(&renames.styles, &mut e.text_style),
+        EntityType::Text(ref mut e) => rename(&renames.styles, &mut e.text_style_name),
+        EntityType::RotatedDimension(ref mut d) => rename_dimension(&mut d.dimension_base, renames),
+        EntityType::RadialDimension(ref mut d) => rename_dimension(&mut d.dimension_base, renames),
+        EntityType::DiameterDimension(ref mut d) => {
+            rename_dimension(&mut d.dimension_base, renames)
+        }
+        EntityType::AngularThreePointDimension(ref mut d) => {
+            rename_dimension(&mut d.dimension_base, renames)
+        }
+        EntityType::OrdinateDimension(ref mut d) => {
+            rename_dimension(&mut d.dimension_base, renames)
+        }
+        EntityType::Leader(ref mut l) => rename(&renames.dim_styles, &mut l.dimension_style_name),
+        EntityType::Tolerance(ref mut t) => {
+            rename(&renames.dim_styles, &mut t.dimension_style_name)
+        }
+        EntityType::Insert(ref mut i) => {
+            rename(&renames.blocks, &mut i.name);
+            for (attribute, _) in i.__attributes_and_handles.iter_mut() {
+                rename(&renames.styles, &mut attribute.text_style_name);
+            }
+        }
+        EntityType::Table(ref mut t) => rename(&renames.blocks, &mut t.block_name),
+        _ => (),
+    }
+}
+
+fn rename_dimension(dimension: &mut DimensionBase, renames: &Renames) {
+    rename(&renames.dim_styles, &mut dimension.dimension_style_name);
+    rename(&renames.blocks, &mut dimension.block_name);
+}
+
+fn named_object_dictionary(drawing: &Drawing) -> Option<Handle> {
+    drawing.named_object_dictionary().map(|o| o.common.handle)
+}
+
+fn dictionary_values(drawing: &Drawing, handle: Handle) -> Option<HashMap<String, Handle>> {
+    drawing
+        .objects()
+        .find(|o| o.common.handle == handle)
+        .and_then(|o| dictionary_entries(o).cloned())
+}
+
+// Reactors are stored as extension data, so they aren't updated with the other pointers.
+fn remap_reactors(groups: &mut Vec<ExtensionGroup>, handles: &HashMap<Handle, Handle>) {
+    for group in groups.iter_mut() {
+        if group.application_name != REACTORS_GROUP {
+            continue;
+        }
+        group.items.retain_mut(|item| match item {
+            ExtensionGroupItem::CodePair(ref mut pair) => match pair.as_handle() {
+                Ok(handle) => match handles.get(&handle) {
+                    Some(new_handle) => {
+                        *pair = CodePair::new_string(pair.code, &new_handle.as_string());
+                        true
+                    }
+                    None => false,
+                },
+                Err(_) => true,
+            },
+            ExtensionGroupItem::Group(_) => true,
+        });
+    }
+    groups.retain(|g| g.application_name != REACTORS_GROUP || !g.items.is_empty());
+}
+
+fn remap_values(values: &mut HashMap<String, Handle>, handles: &HashMap<Handle, Handle>) {
+    values.retain(|_, value| match handles.get(value) {
+        Some(&new_value) => {
+            *value = new_value;
+            true
+        }
+        None => false,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::AcadVersion;
+    use crate::objects::*;
+    use crate::tables::*;
+    use crate::*;
+    use std::collections::HashMap;
+
+    fn line_on(layer: &str) -> Entity {
+        let mut line = Entity::new(EntityType::Line(Line::new(
+            Point::origin(),
+            Point::new(1.0, 1.0, 0.0),
+        )));
+        line.common.layer = String::from(layer);
+        line
+    }
+
+    fn add_layer(drawing: &mut Drawing, name: &str, color: u8) {
+        drawing.add_layer(Layer {
+            name: String::from(name),
+            color: Color::from_index(color),
+            ..Default::default()
+        });
+    }
+
+    fn add_block(drawing: &mut Drawing, name: &str) {
+        let mut block = Block {
+            name: String::from(name),
+            ..Default::default()
+        };
+        block.entities.push(line_on("0"));
+        drawing.add_block(block);
+        let insert = Insert {
+            name: String::from(name),
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::Insert(insert)));
+    }
+
+    fn layer_color(drawing: &Drawing, name: &str) -> Option<u8> {
+        drawing
+            .layers()
+            .find(|l| l.name == name)
+            .and_then(|l| l.color.index())
+    }
+
+    #[test]
+    fn imported_items_get_new_handles_and_pointers() {
+        let mut source = Drawing::new();
+        source.add_entity(line_on("walls"));
+        let mut group = Group::default();
+        group.add_entities(source.entities().next().unwrap());
+        source.add_object(Object::new(ObjectType::Group(group)));
+        add_block(&mut source, "door");
+
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line_on("0"));
+        drawing.import(&source, &ImportOptions::default());
+
+        drawing.header.version = AcadVersion::R2018;
+        drawing.normalize();
+        assert_eq!(Vec::<AuditIssue>::new(), drawing.audit());
+        assert_eq!(3, drawing.entities().count());
+        assert!(drawing.layers().any(|l| l.name == "walls"));
+        assert!(drawing.blocks().any(|b| b.name == "door"));
+
+        // the group points to the imported line, not to the line that was already there
+        let imported_line = drawing.entities().nth(1).unwrap();
+        assert_eq!("walls", imported_line.common.layer);
+        match drawing.objects().next().unwrap().specific {
+            ObjectType::Group(ref group) => {
+                assert_eq!(vec![imported_line.common.handle], group.__entities_handle)
+            }
+            _ => panic!("expected a group"),
+        };
+    }
+
+    #[test]
+    fn multileader_and_table_pointers_are_remapped() {
+        let mut source = Drawing::new();
+        source.add_entity(line_on("0"));
+        let arrow = source
+            .add_block_record(BlockRecord {
+                name: String::from("arrow"),
+                ..Default::default()
+            })
+            .handle;
+        let notes = source
+            .add_style(Style {
+                name: String::from("notes"),
+                ..Default::default()
+            })
+            .handle;
+        let mut mleader = MLeader::default();
+        mleader.context_data.text_content = Some(MLeaderTextContent {
+            __text_style_handle: notes,
+            ..Default::default()
+        });
+        mleader.arrowhead_overrides.push(MLeaderArrowhead {
+            index: 0,
+            __arrowhead_handle: arrow,
+        });
+        source.add_entity(Entity::new(EntityType::MLeader(mleader)));
+        let mut table = Table::default();
+        table.cells.push(TableCell {
+            __block_record_handle: arrow,
+            unknown_pairs: vec![CodePair::new_string(340, &notes.as_string())],
+            ..Default::default()
+        });
+        source.add_entity(Entity::new(EntityType::Table(table)));
+
+        // the existing style is kept, but the block record is copied
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line_on("0"));
+        let existing_notes = drawing
+            .add_style(Style {
+                name: String::from("NOTES"),
+                ..Default::default()
+            })
+            .handle;
+        drawing.import(&source, &ImportOptions::default());
+
+        let imported_arrow = drawing
+            .block_records()
+            .find(|r| r.name == "arrow")
+            .unwrap()
+            .handle;
+        assert_ne!(arrow, imported_arrow);
+        assert_ne!(notes, existing_notes);
+        let entities = drawing.entities().collect::<Vec<_>>();
+        match entities[2].specific {
+            EntityType::MLeader(ref mleader) => {
+                let text = mleader.context_data.text_content.as_ref().unwrap();
+                assert_eq!(existing_notes, text.__text_style_handle);
+                assert_eq!(
+                    imported_arrow,
+                    mleader.arrowhead_overrides[0].__arrowhead_handle
+                );
+            }
+            _ => panic!("expected a multileader"),
+        }
+        match entities[3].specific {
+            EntityType::Table(ref table) => {
+                assert_eq!(imported_arrow, table.cells[0].__block_record_handle);
+                assert_eq!(
+                    vec![CodePair::new_string(340, &existing_notes.as_string())],
+                    table.cells[0].unknown_pairs
+                );
+            }
+            _ => panic!("expected a table"),
+        }
+    }
+
+    #[test]
+    fn name_collisions_keep_existing_entries() {
+        let mut source = Drawing::new();
+        add_layer(&mut source, "walls", 3);
+        source.add_entity(line_on("walls"));
+        add_block(&mut source, "door");
+
+        let mut drawing = Drawing::new();
+        add_layer(&mut drawing, "WALLS", 1);
+        add_block(&mut drawing, "DOOR");
+        drawing.import(&source, &ImportOptions::default());
+
+        assert_eq!(
+            1,
+            drawing
+                .layers()
+                .filter(|l| l.name.eq_ignore_ascii_case("walls"))
+                .count()
+        );
+        assert_eq!(Some(1), layer_color(&drawing, "WALLS"));
+        assert!(drawing.entities().all(|e| e.common.layer != "walls"));
+        assert_eq!(1, drawing.blocks().count());
+        let records = drawing
+            .block_records()
+            .filter(|r| r.name.eq_ignore_ascii_case("door"))
+            .count();
+        assert_eq!(1, records);
+        let inserts = drawing
+            .entities()
+            .filter(|e| matches!(e.specific, EntityType::Insert(ref i) if i.name == "DOOR"))
+            .count();
+        assert_eq!(2, inserts);
+    }
+
+    #[test]
+    fn name_collisions_can_be_renamed() {
+        let mut source = Drawing::new();
+        add_layer(&mut source, "walls", 3);
+        source.add_entity(line_on("walls"));
+        add_block(&mut source, "door");
+
+        let mut drawing = Drawing::new();
+        add_layer(&mut drawing, "walls", 1);
+        add_block(&mut drawing, "door");
+        drawing.import(
+            &source,
+            &ImportOptions {
+                name_collision: ImportNameCollision::Rename,
+            },
+        );
+
+        assert_eq!(Some(1), layer_color(&drawing, "walls"));
+        assert_eq!(Some(3), layer_color(&drawing, "walls_1"));
+        assert!(drawing.entities().any(|e| e.common.layer == "walls_1"));
+        assert!(drawing.blocks().any(|b| b.name == "door_1"));
+        assert!(drawing.block_records().any(|r| r.name == "door_1"));
+        assert!(drawing
+            .entities()
+            .any(|e| matches!(e.specific, EntityType::Insert(ref i) if i.name == "door_1")));
+
+        // standard entries are always merged
+        assert_eq!(1, drawing.layers().filter(|l| l.name == "0").count());
+        assert_eq!(1, drawing.styles().filter(|s| s.name == "STANDARD").count());
+    }
+
+    #[test]
+    fn root_dictionaries_are_merged() {
+        fn add_root(drawing: &mut Drawing, entries: &[&str]) -> Handle {
+            let root = drawing
+                .add_object(Object::new(ObjectType::Dictionary(Dictionary::default())))
+                .common
+                .handle;
+            let mut values = HashMap::new();
+            for entry in entries {
+                let mut child = Object::new(ObjectType::Dictionary(Dictionary::default()));
+                child.common.__owner_handle = root;
+                let handle = drawing.add_object(child).common.handle;
+                values.insert(String::from(*entry), handle);
+            }
+            if let ObjectType::Dictionary(ref mut d) =
+                drawing.objects_mut().next().unwrap().specific
+            {
+                d.value_handles = values;
+            }
+            root
+        }
+
+        let mut source = Drawing::new();
+        add_root(&mut source, &["ACAD_GROUP", "CUSTOM"]);
+        let mut drawing = Drawing::new();
+        let root = add_root(&mut drawing, &["ACAD_GROUP"]);
+        drawing.import(&source, &ImportOptions::default());
+
+        // the imported root and its ACAD_GROUP dictionary are merged with the existing ones
+        assert_eq!(3, drawing.objects().count());
+        let root = drawing.objects().find(|o| o.common.handle == root).unwrap();
+        match root.specific {
+            ObjectType::Dictionary(ref d) => {
+                let mut keys: Vec<_> = d.value_handles.keys().cloned().collect();
+                keys.sort();
+                assert_eq!(vec!["ACAD_GROUP", "CUSTOM"], keys);
+                let custom = d.value_handles["CUSTOM"];
+                assert!(drawing.item_by_handle(custom).is_some());
+            }
+            _ => panic!("expected a dictionary"),
+        }
+    }
+}
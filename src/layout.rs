@@ -0,0 +1,603 @@
+// Layouts and the model and paper space entities they contain
+
+use std::collections::HashSet;
+
+use crate::dictionary::dictionary_entries_mut;
+use crate::entities::*;
+use crate::objects::*;
+use crate::tables::BlockRecord;
+use crate::{Block, Drawing, DrawingItemMut, DxfError, DxfResult, Handle};
+
+const LAYOUT_DICTIONARY: &str = "ACAD_LAYOUT";
+const MODEL_LAYOUT: &str = "Model";
+const MODEL_SPACE: &str = "*Model_Space";
+const PAPER_SPACE: &str = "*Paper_Space";
+
+/// Where the entities of a layout are stored.
+enum LayoutSpace {
+    /// Entities in the `ENTITIES` section without the paper space flag.
+    Model,
+    /// Entities in the `ENTITIES` section with the paper space flag; the active paper space layout.
+    Paper,
+    /// Entities of the named `*Paper_SpaceN` block; the other paper space layouts.
+    Block(String),
+}
+
+impl Drawing {
+    /// Returns the `Layout` objects in tab order.
+    pub fn layouts(&self) -> Vec<&Object> {
+        let mut layouts = self
+            .objects()
+            .filter(|o| matches!(o.specific, ObjectType::Layout(_)))
+            .collect::<Vec<_>>();
+        layouts.sort_by_key(|o| layout_of(o).map_or(0, |l| l.tab_order));
+        layouts
+    }
+    /// Returns the `Layout` object with the specified name, compared case-insensitively.
+    pub fn layout(&self, name: &str) -> Option<&Object> {
+        self.objects()
+            .find(|o| layout_of(o).is_some_and(|l| l.layout_name.eq_ignore_ascii_case(name)))
+    }
+    /// Returns the block record of the specified layout, e.g., `*Model_Space` for the `Model`
+    /// layout and `*Paper_Space` or `*Paper_SpaceN` for paper space layouts.
+    pub fn layout_block_record(&self, name: &str) -> Option<&BlockRecord> {
+        match self.layout(name) {
+            Some(layout) => self.block_record_of_layout(layout),
+            None if name.eq_ignore_ascii_case(MODEL_LAYOUT) => self.block_record(MODEL_SPACE),
+            None => None,
+        }
+    }
+    /// Returns the entities drawn on the specified layout.  The entities of the `Model` and
+    /// active paper space layouts are in the `ENTITIES` section; the entities of other paper space
+    /// layouts are in the layout's `*Paper_SpaceN` block.
+    pub fn layout_entities(&self, name: &str) -> Vec<&Entity> {
+        match self.layout_space(name) {
+            Some((LayoutSpace::Model, _)) => self
+                .entities()
+                .filter(|e| !e.common.is_in_paper_space)
+                .collect(),
+            Some((LayoutSpace::Paper, _)) => self
+                .entities()
+                .filter(|e| e.common.is_in_paper_space)
+                .collect(),
+            Some((LayoutSpace::Block(block_name), _)) => self
+                .blocks()
+                .find(|b| b.name.eq_ignore_ascii_case(&block_name))
+                .map(|b| b.entities.iter().collect())
+                .unwrap_or_default(),
+            None => vec![],
+        }
+    }
+    /// Returns the `Viewport` entities of the specified paper space layout.  The viewport with an
+    /// `id` of 1 is the view of the paper itself.
+    pub fn layout_viewports(&self, name: &str) -> Vec<&Viewport> {
+        self.layout_entities(name)
+            .into_iter()
+            .filter_map(|e| match e.specific {
+                EntityType::Viewport(ref v) => Some(v),
+                _ => None,
+            })
+            .collect()
+    }
+    /// Returns the `Layout` object that the entity with the specified handle is drawn on.
+    pub fn entity_layout(&self, entity: Handle) -> Option<&Object> {
+        let record_name = match self.entities().find(|e| e.common.handle == entity) {
+            Some(e) if e.common.is_in_paper_space => PAPER_SPACE,
+            Some(_) => MODEL_SPACE,
+            None => self
+                .blocks()
+                .find(|b| {
+                    is_paper_space_block(&b.name)
+                        && b.entities.iter().any(|e| e.common.handle == entity)
+                })?
+                .name
+                .as_str(),
+        };
+        let record = self.block_record(record_name)?.handle;
+        self.layouts().into_iter().find(|layout| {
+            self.block_record_of_layout(layout)
+                .is_some_and(|r| r.handle == record)
+        })
+    }
+    /// Adds a paper space layout with the specified name and returns the handle of its `Layout`
+    /// object.  The layout uses the `*Paper_Space` block record if no other layout does, otherwise
+    /// a new `*Paper_SpaceN` block record and block are added.  The `Model` layout is added if it
+    /// doesn't exist.  `Layout` objects are only saved for `AcadVersion::R2000` and later.
+    pub fn add_layout(&mut self, name: &str) -> DxfResult<Handle> {
+        if name.eq_ignore_ascii_case(MODEL_LAYOUT) || self.layout(name).is_some() {
+            return Err(DxfError::DuplicateLayoutName(String::from(name)));
+        }
+
+        self.ensure_model_layout()?;
+        let record_name = self.next_paper_space_name();
+        let record = self.ensure_layout_block_record(&record_name);
+        if is_paper_space_block(&record_name) && !record_name.eq_ignore_ascii_case(PAPER_SPACE) {
+            self.ensure_layout_block(&record_name, record);
+        }
+
+        let tab_order = self
+            .layouts()
+            .into_iter()
+            .filter_map(layout_of)
+            .map(|l| l.tab_order + 1)
+            .max()
+            .unwrap_or(1);
+        self.add_layout_object(name, tab_order, record)
+    }
+    /// Renames the specified layout and its `ACAD_LAYOUT` dictionary entry.  The `Model` layout
+    /// can't be renamed.
+    pub fn rename_layout(&mut self, name: &str, new_name: &str) -> DxfResult<()> {
+        if name.eq_ignore_ascii_case(MODEL_LAYOUT) {
+            return Err(DxfError::CannotModifyModelLayout);
+        }
+
+        let (handle, owner) = match self.layout(name) {
+            Some(layout) => (layout.common.handle, layout.common.__owner_handle),
+            None => return Err(DxfError::LayoutNotFound(String::from(name))),
+        };
+        let is_duplicate = self
+            .layout(new_name)
+            .is_some_and(|o| o.common.handle != handle);
+        if new_name.eq_ignore_ascii_case(MODEL_LAYOUT) || is_duplicate {
+            return Err(DxfError::DuplicateLayoutName(String::from(new_name)));
+        }
+
+        if let Some(DrawingItemMut::Object(o)) = self.item_by_handle_mut(handle) {
+            if let ObjectType::Layout(ref mut layout) = o.specific {
+                layout.layout_name = String::from(new_name);
+            }
+        }
+        self.set_layout_dictionary_entry(owner, handle, Some(new_name));
+        Ok(())
+    }
+    /// Removes the specified layout along with its entities, block, and block record.  If it was
+    /// the active paper space layout, the next paper space layout in tab order becomes the active
+    /// one.  The `Model` layout can't be removed.
+    pub fn remove_layout(&mut self, name: &str) -> DxfResult<()> {
+        if name.eq_ignore_ascii_case(MODEL_LAYOUT) {
+            return Err(DxfError::CannotModifyModelLayout);
+        }
+
+        let (handle, owner) = match self.layout(name) {
+            Some(layout) => (layout.common.handle, layout.common.__owner_handle),
+            None => return Err(DxfError::LayoutNotFound(String::from(name))),
+        };
+        match self.layout_space(name) {
+            Some((LayoutSpace::Model, _)) => return Err(DxfError::CannotModifyModelLayout),
+            Some((LayoutSpace::Paper, record)) => {
+                let indices = self
+                    .entities()
+                    .enumerate()
+                    .filter(|(_, e)| e.common.is_in_paper_space)
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+                for index in indices.into_iter().rev() {
+                    self.remove_entity(index);
+                }
+                if let Some(r) = self.block_records_mut().find(|r| r.handle == record) {
+                    r.__layout_handle = Handle::empty();
+                }
+
+                let next = self
+                    .layouts()
+                    .into_iter()
+                    .filter(|o| o.common.handle != handle)
+                    .find_map(|o| match self.block_record_of_layout(o) {
+                        Some(r) if is_paper_space_block(&r.name) => {
+                            Some((o.common.handle, r.name.clone()))
+                        }
+                        _ => None,
+                    });
+                if let Some((next, block_name)) = next {
+                    self.activate_layout(next, &block_name, record);
+                }
+            }
+            Some((LayoutSpace::Block(block_name), record)) => {
+                self.remove_layout_block(&block_name);
+                let index = self.block_records().position(|r| r.handle == record);
+                if let Some(index) = index {
+                    self.remove_block_record(index);
+                }
+            }
+            None => (),
+        }
+
+        let index = self.objects().position(|o| o.common.handle == handle);
+        if let Some(index) = index {
+            self.remove_object(index);
+        }
+        self.set_layout_dictionary_entry(owner, handle, None);
+        Ok(())
+    }
+    /// Moves the model or paper space entity with the specified handle to the specified layout,
+    /// updating its paper space flag and owner.  Returns `DxfError::WrongItemType` if the handle
+    /// isn't a model or paper space entity.
+    pub fn move_entity_to_layout(&mut self, entity: Handle, layout: &str) -> DxfResult<()> {
+        let (space, record) = self
+            .layout_space(layout)
+            .ok_or_else(|| DxfError::LayoutNotFound(String::from(layout)))?;
+        let mut entity = self
+            .take_layout_entity(entity)
+            .ok_or(DxfError::WrongItemType)?;
+        entity.common.is_in_paper_space = !matches!(space, LayoutSpace::Model);
+        entity.common.__owner_handle = record;
+        match space {
+            LayoutSpace::Model | LayoutSpace::Paper => {
+                self.add_entity_no_handle_set(entity);
+            }
+            LayoutSpace::Block(block_name) => {
+                self.ensure_layout_block(&block_name, record);
+                if let Some(block) = self
+                    .blocks_mut()
+                    .find(|b| b.name.eq_ignore_ascii_case(&block_name))
+                {
+                    block.entities.push(entity);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// private implementation
+impl Drawing {
+    fn block_record(&self, name: &str) -> Option<&BlockRecord> {
+        self.block_records()
+            .find(|r| r.name.eq_ignore_ascii_case(name))
+    }
+    /// The block record a `Layout` points to, or else the one that points to the `Layout`.
+    fn block_record_of_layout(&self, layout: &Object) -> Option<&BlockRecord> {
+        let pointer = layout_of(layout)?.__viewport_handle;
+        self.block_records()
+            .find(|r| !pointer.is_empty() && r.handle == pointer)
+            .or_else(|| {
+                self.block_records()
+                    .find(|r| r.__layout_handle == layout.common.handle)
+            })
+    }
+    fn layout_space(&self, name: &str) -> Option<(LayoutSpace, Handle)> {
+        let record = self.layout_block_record(name);
+        match record {
+            Some(r) if r.name.eq_ignore_ascii_case(MODEL_SPACE) => {
+                Some((LayoutSpace::Model, r.handle))
+            }
+            Some(r) if r.name.eq_ignore_ascii_case(PAPER_SPACE) => {
+                Some((LayoutSpace::Paper, r.handle))
+            }
+            Some(r) => Some((LayoutSpace::Block(r.name.clone()), r.handle)),
+            None if name.eq_ignore_ascii_case(MODEL_LAYOUT) => {
+                Some((LayoutSpace::Model, Handle::empty()))
+            }
+            None => None,
+        }
+    }
+    fn ensure_model_layout(&mut self) -> DxfResult<()> {
+        if self.layout(MODEL_LAYOUT).is_none() {
+            let record = self.ensure_layout_block_record(MODEL_SPACE);
+            self.add_layout_object(MODEL_LAYOUT, 0, record)?;
+        }
+        Ok(())
+    }
+    fn add_layout_object(
+        &mut self,
+        name: &str,
+        tab_order: i32,
+        record: Handle,
+    ) -> DxfResult<Handle> {
+        let layout = Object::new(ObjectType::Layout(Layout {
+            layout_name: String::from(name),
+            tab_order,
+            __viewport_handle: record,
+            ..Default::default()
+        }));
+        let dictionary = self.ensure_dictionary_path(&[LAYOUT_DICTIONARY])?;
+        let handle = self
+            .add_dictionary_entry(dictionary, name, layout)?
+            .common
+            .handle;
+        if let Some(r) = self.block_records_mut().find(|r| r.handle == record) {
+            r.__layout_handle = handle;
+        }
+        Ok(handle)
+    }
+    /// `*Paper_Space` if no layout uses it, otherwise the first unused `*Paper_SpaceN`.
+    fn next_paper_space_name(&self) -> String {
+        let used = self
+            .layouts()
+            .into_iter()
+            .filter_map(|l| self.block_record_of_layout(l))
+            .map(|r| r.name.to_uppercase())
+            .collect::<HashSet<_>>();
+        if !used.contains(&PAPER_SPACE.to_uppercase()) {
+            return match self.block_record(PAPER_SPACE) {
+                Some(r) => r.name.clone(),
+                None => String::from(PAPER_SPACE),
+            };
+        }
+
+        (0..)
+            .map(|i| format!("{PAPER_SPACE}{i}"))
+            .find(|name| {
+                self.block_record(name).is_none()
+                    && !self.blocks().any(|b| b.name.eq_ignore_ascii_case(name))
+            })
+            .unwrap_or_default()
+    }
+    fn ensure_layout_block_record(&mut self, name: &str) -> Handle {
+        match self.block_record(name) {
+            Some(r) => r.handle,
+            None => {
+                self.add_block_record(BlockRecord {
+                    name: String::from(name),
+                    ..Default::default()
+                })
+                .handle
+            }
+        }
+    }
+    fn ensure_layout_block(&mut self, name: &str, record: Handle) {
+        if !self.blocks().any(|b| b.name.eq_ignore_ascii_case(name)) {
+            self.add_block(Block {
+                name: String::from(name),
+                is_in_paperspace: true,
+                __owner_handle: record,
+                ..Default::default()
+            });
+        }
+    }
+    fn remove_layout_block(&mut self, name: &str) -> Option<Block> {
+        let index = self
+            .blocks()
+            .position(|b| b.name.eq_ignore_ascii_case(name))?;
+        self.remove_block(index)
+    }
+    /// Makes `layout` the active paper space layout by moving the entities of its block to the
+    /// `ENTITIES` section and pointing it at the `*Paper_Space` block record.
+    fn activate_layout(&mut self, layout: Handle, block_name: &str, paper_space: Handle) {
+        if let Some(block) = self.remove_layout_block(block_name) {
+            for mut entity in block.entities {
+                entity.common.is_in_paper_space = true;
+                entity.common.__owner_handle = paper_space;
+                self.add_entity_no_handle_set(entity);
+            }
+        }
+        let index = self
+            .block_records()
+            .position(|r| r.name.eq_ignore_ascii_case(block_name));
+        if let Some(index) = index {
+            self.remove_block_record(index);
+        }
+
+        if let Some(DrawingItemMut::Object(o)) = self.item_by_handle_mut(layout) {
+            if let ObjectType::Layout(ref mut l) = o.specific {
+                l.__viewport_handle = paper_space;
+            }
+        }
+        if let Some(r) = self.block_records_mut().find(|r| r.handle == paper_space) {
+            r.__layout_handle = layout;
+        }
+    }
+    /// Removes the entity with the specified handle from the `ENTITIES` section or a
+    /// `*Paper_SpaceN` block.
+    fn take_layout_entity(&mut self, handle: Handle) -> Option<Entity> {
+        let index = self.entities().position(|e| e.common.handle == handle);
+        if let Some(index) = index {
+            return self.remove_entity(index);
+        }
+
+        self.blocks_mut()
+            .filter(|b| is_paper_space_block(&b.name))
+            .find_map(|b| {
+                let index = b.entities.iter().position(|e| e.common.handle == handle)?;
+                Some(b.entities.remove(index))
+            })
+    }
+    /// Renames, or removes when `name` is `None`, the entry for `layout` in `dictionary`.
+    fn set_layout_dictionary_entry(
+        &mut self,
+        dictionary: Handle,
+        layout: Handle,
+        name: Option<&str>,
+    ) {
+        if let Some(DrawingItemMut::Object(o)) = self.item_by_handle_mut(dictionary) {
+            if let Some(entries) = dictionary_entries_mut(o) {
+                entries.retain(|_, handle| *handle != layout);
+                if let Some(name) = name {
+                    entries.insert(String::from(name), layout);
+                }
+            }
+        }
+    }
+}
+
+fn layout_of(obj: &Object) -> Option<&Layout> {
+    match obj.specific {
+        ObjectType::Layout(ref layout) => Some(layout),
+        _ => None,
+    }
+}
+
+fn is_paper_space_block(name: &str) -> bool {
+    name.to_uppercase().starts_with(&PAPER_SPACE.to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::AcadVersion;
+    use crate::objects::*;
+    use crate::*;
+
+    fn line(drawing: &mut Drawing, is_in_paper_space: bool) -> Handle {
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.is_in_paper_space = is_in_paper_space;
+        drawing.add_entity(line).common.handle
+    }
+
+    fn layout_names(drawing: &Drawing) -> Vec<String> {
+        drawing
+            .layouts()
+            .into_iter()
+            .map(|o| match o.specific {
+                ObjectType::Layout(ref l) => l.layout_name.clone(),
+                _ => panic!("expected a layout"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entities_are_listed_by_layout() {
+        let mut drawing = Drawing::new();
+        let model = line(&mut drawing, false);
+        let paper = line(&mut drawing, true);
+
+        // without any layout objects, only model space is known
+        assert_eq!(1, drawing.layout_entities("Model").len());
+        assert!(drawing.layout_entities("Layout1").is_empty());
+
+        drawing.add_layout("Layout1").unwrap();
+        drawing.add_layout("Layout2").unwrap();
+        assert_eq!(vec!["Model", "Layout1", "Layout2"], layout_names(&drawing));
+        assert_eq!(
+            "*MODEL_SPACE",
+            drawing.layout_block_record("model").unwrap().name
+        );
+        assert_eq!(
+            "*PAPER_SPACE",
+            drawing.layout_block_record("Layout1").unwrap().name
+        );
+        assert_eq!(
+            "*Paper_Space0",
+            drawing.layout_block_record("Layout2").unwrap().name
+        );
+        assert_eq!(model, drawing.layout_entities("Model")[0].common.handle);
+        assert_eq!(paper, drawing.layout_entities("Layout1")[0].common.handle);
+        assert!(drawing.layout_entities("Layout2").is_empty());
+
+        let layout = drawing.layout("Layout1").unwrap().common.handle;
+        assert_eq!(layout, drawing.entity_layout(paper).unwrap().common.handle);
+        assert_eq!(
+            layout,
+            drawing
+                .dictionary_path(&["ACAD_LAYOUT", "Layout1"])
+                .unwrap()
+                .common
+                .handle
+        );
+        assert!(matches!(
+            drawing.add_layout("LAYOUT1"),
+            Err(DxfError::DuplicateLayoutName(_))
+        ));
+    }
+
+    #[test]
+    fn entities_are_moved_between_layouts() {
+        let mut drawing = Drawing::new();
+        drawing.add_layout("Layout1").unwrap();
+        drawing.add_layout("Layout2").unwrap();
+        let handle = line(&mut drawing, false);
+
+        drawing.move_entity_to_layout(handle, "Layout2").unwrap();
+        assert!(drawing.layout_entities("Model").is_empty());
+        let entities = drawing.layout_entities("Layout2");
+        assert_eq!(handle, entities[0].common.handle);
+        assert!(entities[0].common.is_in_paper_space);
+        assert_eq!(
+            drawing.layout_block_record("Layout2").unwrap().handle,
+            entities[0].common.__owner_handle
+        );
+
+        drawing.move_entity_to_layout(handle, "Layout1").unwrap();
+        assert!(drawing.layout_entities("Layout2").is_empty());
+        assert_eq!(1, drawing.layout_entities("Layout1").len());
+
+        drawing.move_entity_to_layout(handle, "Model").unwrap();
+        assert!(!drawing.layout_entities("Model")[0].common.is_in_paper_space);
+        assert!(matches!(
+            drawing.move_entity_to_layout(Handle(0xFFFF), "Model"),
+            Err(DxfError::WrongItemType)
+        ));
+        assert!(matches!(
+            drawing.move_entity_to_layout(handle, "Layout3"),
+            Err(DxfError::LayoutNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn layouts_are_renamed_and_removed() {
+        let mut drawing = Drawing::new();
+        drawing.add_layout("Layout1").unwrap();
+        drawing.add_layout("Layout2").unwrap();
+        let first = line(&mut drawing, true);
+        let second = line(&mut drawing, false);
+        drawing.move_entity_to_layout(second, "Layout2").unwrap();
+
+        drawing.rename_layout("Layout2", "Sheet").unwrap();
+        assert_eq!(vec!["Model", "Layout1", "Sheet"], layout_names(&drawing));
+        assert!(drawing.dictionary_path(&["ACAD_LAYOUT", "Sheet"]).is_some());
+        assert!(drawing
+            .dictionary_path(&["ACAD_LAYOUT", "Layout2"])
+            .is_none());
+        assert!(matches!(
+            drawing.rename_layout("Sheet", "Layout1"),
+            Err(DxfError::DuplicateLayoutName(_))
+        ));
+        assert!(matches!(
+            drawing.rename_layout("Model", "Other"),
+            Err(DxfError::CannotModifyModelLayout)
+        ));
+
+        // removing the active layout makes the next one active
+        drawing.remove_layout("Layout1").unwrap();
+        assert_eq!(vec!["Model", "Sheet"], layout_names(&drawing));
+        assert!(drawing
+            .dictionary_path(&["ACAD_LAYOUT", "Layout1"])
+            .is_none());
+        assert!(drawing.entities().all(|e| e.common.handle != first));
+        assert_eq!(
+            "*PAPER_SPACE",
+            drawing.layout_block_record("Sheet").unwrap().name
+        );
+        assert_eq!(second, drawing.layout_entities("Sheet")[0].common.handle);
+        assert!(drawing.blocks().next().is_none());
+        assert!(drawing.block_records().all(|r| r.name != "*Paper_Space0"));
+
+        drawing.add_layout("Layout3").unwrap();
+        drawing.remove_layout("Layout3").unwrap();
+        assert!(drawing.blocks().next().is_none());
+        assert!(matches!(
+            drawing.remove_layout("Model"),
+            Err(DxfError::CannotModifyModelLayout)
+        ));
+    }
+
+    #[test]
+    fn viewports_round_trip() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_layout("Layout1").unwrap();
+        drawing.add_layout("Layout2").unwrap();
+        for (id, layout) in [(1, "Layout1"), (1, "Layout2"), (2, "Layout2")] {
+            let mut viewport = Entity::new(EntityType::Viewport(Viewport {
+                id,
+                width: 10.0,
+                ..Default::default()
+            }));
+            viewport.common.is_in_paper_space = true;
+            let handle = drawing.add_entity(viewport).common.handle;
+            drawing.move_entity_to_layout(handle, layout).unwrap();
+        }
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let drawing = Drawing::load(&mut buf.as_slice()).unwrap();
+        assert_eq!(vec!["Model", "Layout1", "Layout2"], layout_names(&drawing));
+        assert_eq!(1, drawing.layout_viewports("Layout1").len());
+        let viewports = drawing.layout_viewports("Layout2");
+        assert_eq!(
+            vec![1, 2],
+            viewports.iter().map(|v| v.id).collect::<Vec<_>>()
+        );
+        assert_eq!(10.0, viewports[0].width);
+    }
+}
@@ -19,6 +19,13 @@
 //! > Note that `serde` support is intended to aid in debugging and since the serialized format is heavily
 //! > dependent on the layout of the structures, it may change at any time.
 //!
+//! To render drawings as SVG with `Drawing::save_svg`, enable the `svg` feature:
+//!
+//! ``` toml
+//! [dependencies]
+//! dxf = { version = "0.5.0", features = ["svg"] }
+//! ```
+//!
 //! # Examples
 //!
 //! Open a DXF file from disk:
@@ -129,6 +136,8 @@ mod helper_functions;
 
 mod dxb_item_type;
 mod dxb_reader;
+mod dxb_write_options;
+pub use crate::dxb_write_options::{DxbLayerStrategy, DxbNumberMode, DxbWriteOptions};
 mod dxb_writer;
 
 mod extension_data;
@@ -137,6 +146,9 @@ pub use crate::extension_data::*;
 mod x_data;
 pub use crate::x_data::*;
 
+mod xrecord;
+pub use crate::xrecord::{XRecordBuilder, XRecordData, XRecordGroup, XRecordItem, XRecordReader};
+
 mod table;
 mod thumbnail;
 
@@ -152,6 +164,9 @@ pub use crate::section_geometry_settings::SectionGeometrySettings;
 mod section_type_settings;
 pub use crate::section_type_settings::SectionTypeSettings;
 
+mod table_cell;
+pub use crate::table_cell::{TableCell, TableCellAttribute, TableCellRange};
+
 mod table_cell_style;
 pub use crate::table_cell_style::TableCellStyle;
 
@@ -163,6 +178,12 @@ pub mod enums;
 pub mod hatch;
 pub use crate::hatch::{HatchPatternBoundaryData, HatchPatternLineData};
 
+mod mleader;
+pub use crate::mleader::{
+    MLeaderArrowhead, MLeaderBlockAttribute, MLeaderBlockContent, MLeaderContextData, MLeaderLine,
+    MLeaderRoot, MLeaderTextContent,
+};
+
 mod color;
 pub use crate::color::{Color, RGB, RGBA};
 
@@ -204,16 +225,62 @@ pub use crate::line_weight::LineWeight;
 mod entity;
 pub use crate::entity::LwPolylineVertex;
 
+mod ocs;
+pub use crate::ocs::{ocs_to_wcs_matrix, wcs_to_ocs_matrix};
+
+mod spline;
+pub use crate::spline::SplineError;
+
+mod mtext;
+pub use crate::mtext::{MTextBuilder, MTextFormat, MTextRun, MTextStackType};
+
+mod tessellate;
+mod transform;
+
+mod bounding_box;
+pub use crate::bounding_box::BoundingBox;
+
+mod audit;
+pub use crate::audit::AuditIssue;
+
+mod import;
+pub use crate::import::{ImportNameCollision, ImportOptions};
+
+mod dictionary;
+
+mod layout;
+
+mod diff;
+pub use crate::diff::{
+    diff, diff_with_tolerance, ChangeKind, DrawingDiff, HeaderChange, ItemChange, PairChange,
+    DEFAULT_DIFF_TOLERANCE,
+};
+
+mod version_conversion;
+pub use crate::version_conversion::ConversionLoss;
+
+mod raster;
+
+#[cfg(feature = "svg")]
+mod svg;
+
 mod object;
 pub use crate::object::{GeoMeshPoint, MLineStyleElement};
 
 mod dxf_error;
-pub use crate::dxf_error::DxfError;
+pub use crate::dxf_error::{DxfError, ErrorContext};
 
 mod dxf_result;
 pub use crate::dxf_result::DxfResult;
 
+mod load_diagnostic;
+pub use crate::load_diagnostic::LoadDiagnostic;
+
+mod drawing_reader;
+pub use crate::drawing_reader::DrawingReader;
+
 mod entity_iter;
+mod handle_index;
 mod object_iter;
 
 //------------------------------- test imports ---------------------------------
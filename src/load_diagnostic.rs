@@ -0,0 +1,31 @@
+use std::fmt;
+
+use crate::{DxfError, Handle};
+
+/// A problem that was skipped over or repaired while loading a drawing with
+/// `Drawing::load_lenient`.
+#[derive(Debug)]
+pub struct LoadDiagnostic {
+    /// The name of the section that was being read, e.g., `ENTITIES`, or an empty string if the
+    /// problem was outside of any section.
+    pub section: String,
+    /// The handle of the item that was being read, if it had been read before the problem.
+    pub handle: Option<Handle>,
+    /// The line number in text files or the byte offset in binary files where the problem was
+    /// found.
+    pub offset: usize,
+    /// The error that was encountered.
+    pub error: DxfError,
+}
+
+impl fmt::Display for LoadDiagnostic {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if !self.section.is_empty() {
+            write!(formatter, "{} section: ", self.section)?;
+        }
+        if let Some(handle) = self.handle {
+            write!(formatter, "item {}: ", handle.as_string())?;
+        }
+        write!(formatter, "{}", self.error)
+    }
+}
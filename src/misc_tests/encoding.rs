@@ -418,6 +418,297 @@ fn read_dxb_after_writing() {
     }
 }
 
+fn dxb_round_trip(drawing: &Drawing, options: &DxbWriteOptions) -> (Vec<u8>, Drawing) {
+    let mut buf = vec![];
+    drawing.save_dxb_with_options(&mut buf, options).unwrap();
+    let loaded = unwrap_drawing(Drawing::load(&mut buf.as_slice()));
+    (buf, loaded)
+}
+
+#[test]
+fn dxb_integer_mode_with_scale_factor() {
+    let mut drawing = Drawing::new();
+    drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+        Point::new(1.5, 2.0, 3.0),
+        Point::new(4.0, 5.5, 6.0),
+    ))));
+    drawing.add_entity(Entity::new(EntityType::Arc(Arc::new(
+        Point::new(1.0, 1.0, 0.0),
+        2.0,
+        12.5,
+        90.25,
+    ))));
+    let options = DxbWriteOptions {
+        number_mode: DxbNumberMode::Integer,
+        scale_factor: 0.5,
+        ..Default::default()
+    };
+    let (_, drawing) = dxb_round_trip(&drawing, &options);
+    let entities = drawing.entities().collect::<Vec<_>>();
+    assert_eq!(2, entities.len());
+    match entities[0].specific {
+        EntityType::Line(ref line) => {
+            assert_eq!(Point::new(1.5, 2.0, 3.0), line.p1);
+            assert_eq!(Point::new(4.0, 5.5, 6.0), line.p2);
+        }
+        _ => panic!("expected a line"),
+    }
+    match entities[1].specific {
+        EntityType::Arc(ref arc) => {
+            assert_eq!(Point::new(1.0, 1.0, 0.0), arc.center);
+            assert_eq!(2.0, arc.radius);
+            assert_eq!(12.5, arc.start_angle);
+            assert_eq!(90.25, arc.end_angle);
+        }
+        _ => panic!("expected an arc"),
+    }
+}
+
+#[test]
+fn dxb_bulged_polyline_with_widths() {
+    let mut drawing = Drawing::new();
+    let mut lw = LwPolyline::default();
+    lw.vertices.push(LwPolylineVertex {
+        x: 0.0,
+        y: 0.0,
+        bulge: 0.5,
+        starting_width: 1.0,
+        ending_width: 2.0,
+        ..Default::default()
+    });
+    lw.vertices.push(LwPolylineVertex {
+        x: 4.0,
+        y: 0.0,
+        starting_width: 1.0,
+        ending_width: 2.0,
+        ..Default::default()
+    });
+    lw.vertices.push(LwPolylineVertex {
+        x: 4.0,
+        y: 4.0,
+        ..Default::default()
+    });
+    drawing.add_entity(Entity::new(EntityType::LwPolyline(lw)));
+    for number_mode in [DxbNumberMode::Float, DxbNumberMode::Integer] {
+        let options = DxbWriteOptions {
+            number_mode,
+            scale_factor: 0.25,
+            ..Default::default()
+        };
+        let (_, drawing) = dxb_round_trip(&drawing, &options);
+        let entities = drawing.entities().collect::<Vec<_>>();
+        assert_eq!(1, entities.len());
+        match entities[0].specific {
+            EntityType::Polyline(ref poly) => {
+                let vertices = poly.vertices().collect::<Vec<_>>();
+                assert_eq!(3, vertices.len());
+                assert_eq!(0.5, vertices[0].bulge);
+                assert_eq!(0.0, vertices[1].bulge);
+                assert_eq!(
+                    (1.0, 2.0),
+                    (vertices[0].starting_width, vertices[0].ending_width)
+                );
+                assert_eq!(
+                    (1.0, 2.0),
+                    (vertices[1].starting_width, vertices[1].ending_width)
+                );
+                assert_eq!(
+                    (0.0, 0.0),
+                    (vertices[2].starting_width, vertices[2].ending_width)
+                );
+                assert_eq!(Point::new(4.0, 4.0, 0.0), vertices[2].location);
+            }
+            _ => panic!("expected a polyline"),
+        }
+    }
+}
+
+#[test]
+fn dxb_connected_lines_and_traces_are_extended() {
+    let mut drawing = Drawing::new();
+    drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 0.0, 0.0),
+    ))));
+    drawing.add_entity(Entity::new(EntityType::Line(Line::new(
+        Point::new(1.0, 0.0, 0.0),
+        Point::new(1.0, 1.0, 0.0),
+    ))));
+    drawing.add_entity(Entity::new(EntityType::Trace(Trace::new(
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(0.0, 1.0, 0.0),
+        Point::new(2.0, 0.0, 0.0),
+        Point::new(2.0, 1.0, 0.0),
+    ))));
+    drawing.add_entity(Entity::new(EntityType::Trace(Trace::new(
+        Point::new(2.0, 0.0, 0.0),
+        Point::new(2.0, 1.0, 0.0),
+        Point::new(4.0, 0.0, 0.0),
+        Point::new(4.0, 1.0, 0.0),
+    ))));
+    let (buf, loaded) = dxb_round_trip(&drawing, &DxbWriteOptions::default());
+    assert!(buf.contains(&130)); // line extension
+    assert!(buf.contains(&131)); // trace extension
+    let expected = drawing
+        .entities()
+        .map(|e| e.specific.clone())
+        .collect::<Vec<_>>();
+    let actual = loaded
+        .entities()
+        .map(|e| e.specific.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn dxb_inserts_write_their_blocks() {
+    let mut drawing = Drawing::new();
+    for (name, line) in [
+        ("a", Line::new(Point::origin(), Point::new(1.0, 0.0, 0.0))),
+        ("b", Line::new(Point::origin(), Point::new(0.0, 1.0, 0.0))),
+    ] {
+        let mut block = Block {
+            name: String::from(name),
+            ..Default::default()
+        };
+        block.entities.push(Entity::new(EntityType::Line(line)));
+        drawing.add_block(block);
+        let insert = Insert {
+            name: String::from(name),
+            location: Point::new(10.0, 10.0, 0.0),
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::Insert(insert)));
+    }
+    let (_, loaded) = dxb_round_trip(&drawing, &DxbWriteOptions::default());
+    let ends = loaded
+        .entities()
+        .map(|e| match e.specific {
+            EntityType::Line(ref line) => line.p2.clone(),
+            _ => panic!("expected a line"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![Point::new(11.0, 10.0, 0.0), Point::new(10.0, 11.0, 0.0)],
+        ends
+    );
+}
+
+#[test]
+fn dxb_write_named_block() {
+    let mut drawing = Drawing::new();
+    let mut block = Block {
+        name: String::from("b"),
+        base_point: Point::new(1.0, 2.0, 0.0),
+        ..Default::default()
+    };
+    block
+        .entities
+        .push(Entity::new(EntityType::Circle(Circle::new(
+            Point::origin(),
+            3.0,
+        ))));
+    drawing.add_block(block);
+    drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+    let options = DxbWriteOptions {
+        block_name: Some(String::from("b")),
+        ..Default::default()
+    };
+    let (_, loaded) = dxb_round_trip(&drawing, &options);
+    assert_eq!(0, loaded.entities().count());
+    let block = loaded.blocks().next().unwrap();
+    assert_eq!(Point::new(1.0, 2.0, 0.0), block.base_point);
+    assert_eq!(1, block.entities.len());
+}
+
+#[test]
+fn dxb_layer_per_color() {
+    let mut drawing = Drawing::new();
+    let mut layer = tables::Layer {
+        name: String::from("red-things"),
+        ..Default::default()
+    };
+    layer.color = Color::from_index(1);
+    drawing.add_layer(layer);
+    let mut by_layer = Entity::new(EntityType::Line(Line::default()));
+    by_layer.common.layer = String::from("red-things");
+    drawing.add_entity(by_layer);
+    let mut blue = Entity::new(EntityType::Line(Line::default()));
+    blue.common.color = Color::from_index(5);
+    drawing.add_entity(blue);
+    let options = DxbWriteOptions {
+        layer_strategy: DxbLayerStrategy::Color,
+        ..Default::default()
+    };
+    let (_, loaded) = dxb_round_trip(&drawing, &options);
+    let layers = loaded
+        .entities()
+        .map(|e| e.common.layer.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["1", "5"], layers);
+}
+
+#[test]
+fn dxb_skipped_entities_dont_change_layer_or_color() {
+    let mut drawing = Drawing::new();
+    let mut text = Entity::new(EntityType::Text(Text::default()));
+    text.common.layer = String::from("notes");
+    text.common.color = Color::from_index(1);
+    drawing.add_entity(text);
+    drawing.add_entity(Entity::new(EntityType::Line(Line::default())));
+    let (buf, loaded) = dxb_round_trip(&drawing, &DxbWriteOptions::default());
+    assert_eq!(1, loaded.entities().count());
+
+    // the text leaves nothing behind
+    let mut line_only = Drawing::new();
+    line_only.add_entity(Entity::new(EntityType::Line(Line::default())));
+    let (expected, _) = dxb_round_trip(&line_only, &DxbWriteOptions::default());
+    assert_eq!(expected, buf);
+}
+
+#[test]
+fn dxb_curves_are_written_as_lines() {
+    let mut drawing = Drawing::new();
+    drawing.add_entity(Entity::new(EntityType::Ellipse(Ellipse {
+        major_axis: Vector::new(4.0, 0.0, 0.0),
+        minor_axis_ratio: 0.5,
+        start_parameter: 0.0,
+        end_parameter: std::f64::consts::PI,
+        ..Default::default()
+    })));
+    drawing.add_entity(Entity::new(EntityType::Spline(Spline {
+        degree_of_curve: 2,
+        knot_values: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        control_points: vec![
+            Point::new(10.0, 0.0, 0.0),
+            Point::new(11.0, 2.0, 0.0),
+            Point::new(12.0, 0.0, 0.0),
+        ],
+        ..Default::default()
+    })));
+    let options = DxbWriteOptions {
+        tolerance: 0.001,
+        ..Default::default()
+    };
+    let (_, loaded) = dxb_round_trip(&drawing, &options);
+    let lines = loaded
+        .entities()
+        .map(|e| match e.specific {
+            EntityType::Line(ref line) => line.clone(),
+            _ => panic!("expected a line"),
+        })
+        .collect::<Vec<_>>();
+    assert!(lines.len() > 10);
+    let (ellipse, spline): (Vec<_>, Vec<_>) = lines.iter().partition(|l| l.p1.x < 5.0);
+    for line in &ellipse {
+        let p = &line.p2;
+        assert!(((p.x / 4.0).powi(2) + (p.y / 2.0).powi(2) - 1.0).abs() < 1e-6);
+    }
+    assert_eq!(Point::new(4.0, 0.0, 0.0), ellipse[0].p1);
+    assert_eq!(Point::new(10.0, 0.0, 0.0), spline[0].p1);
+    assert_eq!(Point::new(12.0, 0.0, 0.0), spline.last().unwrap().p2);
+}
+
 #[test]
 fn dont_write_utf8_bom() {
     let drawing = Drawing::new();
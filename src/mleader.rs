@@ -0,0 +1,560 @@
+use crate::code_pair_put_back::CodePairPutBack;
+use crate::helper_functions::*;
+use crate::tables::{BlockRecord, Style};
+use crate::{
+    CodePair, Drawing, DrawingItem, DxfResult, Handle, Point, TransformationMatrix, Vector,
+};
+
+/// The `CONTEXT_DATA{` block of a `MULTILEADER` entity.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MLeaderContextData {
+    pub content_scale: f64,
+    pub content_base_point: Point,
+    pub text_height: f64,
+    pub arrowhead_size: f64,
+    pub landing_gap: f64,
+    pub text_left_attachment: i16,
+    pub text_right_attachment: i16,
+    pub text_alignment_type: i16,
+    pub attachment_type: i16,
+    /// The `MText` content, if any.
+    pub text_content: Option<MLeaderTextContent>,
+    /// The block content, if any.
+    pub block_content: Option<MLeaderBlockContent>,
+    pub plane_origin: Point,
+    pub plane_x_axis: Vector,
+    pub plane_y_axis: Vector,
+    pub is_plane_normal_reversed: bool,
+    pub leader_roots: Vec<MLeaderRoot>,
+    pub text_bottom_attachment: i16,
+    pub text_top_attachment: i16,
+}
+
+impl Default for MLeaderContextData {
+    fn default() -> Self {
+        MLeaderContextData {
+            content_scale: 1.0,
+            content_base_point: Point::origin(),
+            text_height: 0.0,
+            arrowhead_size: 0.0,
+            landing_gap: 0.0,
+            text_left_attachment: 0,
+            text_right_attachment: 0,
+            text_alignment_type: 0,
+            attachment_type: 0,
+            text_content: None,
+            block_content: None,
+            plane_origin: Point::origin(),
+            plane_x_axis: Vector::x_axis(),
+            plane_y_axis: Vector::y_axis(),
+            is_plane_normal_reversed: false,
+            leader_roots: vec![],
+            text_bottom_attachment: 9,
+            text_top_attachment: 9,
+        }
+    }
+}
+
+/// The `MText` content of a `MULTILEADER` entity.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MLeaderTextContent {
+    pub text: String,
+    pub normal: Vector,
+    #[doc(hidden)]
+    pub __text_style_handle: Handle,
+    pub location: Point,
+    pub direction: Vector,
+    pub rotation: f64,
+    pub boundary_width: f64,
+    pub boundary_height: f64,
+    pub line_spacing_factor: f64,
+    pub line_spacing_style: i16,
+    pub color: i32,
+    pub attachment: i16,
+    pub flow_direction: i16,
+    pub background_color: i32,
+    pub background_scale_factor: f64,
+    pub background_transparency: i32,
+    pub is_background_color_on: bool,
+    pub is_background_fill_on: bool,
+    pub column_type: i16,
+    pub use_text_auto_height: bool,
+    pub column_width: f64,
+    pub column_gutter_width: f64,
+    pub is_column_flow_reversed: bool,
+    pub column_sizes: Vec<f64>,
+    pub use_word_break: bool,
+}
+
+impl Default for MLeaderTextContent {
+    fn default() -> Self {
+        MLeaderTextContent {
+            text: String::new(),
+            normal: Vector::z_axis(),
+            __text_style_handle: Handle::empty(),
+            location: Point::origin(),
+            direction: Vector::x_axis(),
+            rotation: 0.0,
+            boundary_width: 0.0,
+            boundary_height: 0.0,
+            line_spacing_factor: 1.0,
+            line_spacing_style: 1,
+            color: 0,
+            attachment: 1,
+            flow_direction: 1,
+            background_color: 0,
+            background_scale_factor: 1.5,
+            background_transparency: 0,
+            is_background_color_on: false,
+            is_background_fill_on: false,
+            column_type: 0,
+            use_text_auto_height: false,
+            column_width: 0.0,
+            column_gutter_width: 0.0,
+            is_column_flow_reversed: false,
+            column_sizes: vec![],
+            use_word_break: false,
+        }
+    }
+}
+
+impl MLeaderTextContent {
+    pub fn text_style<'a>(&self, drawing: &'a Drawing) -> Option<&'a Style> {
+        match drawing.item_by_handle(self.__text_style_handle) {
+            Some(DrawingItem::Style(val)) => Some(val),
+            _ => None,
+        }
+    }
+    pub fn set_text_style(&mut self, item: &Style) {
+        self.__text_style_handle = DrawingItem::Style(item).handle();
+    }
+}
+
+/// The block content of a `MULTILEADER` entity.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MLeaderBlockContent {
+    #[doc(hidden)]
+    pub __block_record_handle: Handle,
+    pub normal: Vector,
+    pub location: Point,
+    pub scale: Vector,
+    pub rotation: f64,
+    pub color: i32,
+    pub transformation_matrix: TransformationMatrix,
+}
+
+impl Default for MLeaderBlockContent {
+    fn default() -> Self {
+        MLeaderBlockContent {
+            __block_record_handle: Handle::empty(),
+            normal: Vector::z_axis(),
+            location: Point::origin(),
+            scale: Vector::new(1.0, 1.0, 1.0),
+            rotation: 0.0,
+            color: 0,
+            transformation_matrix: TransformationMatrix::identity(),
+        }
+    }
+}
+
+impl MLeaderBlockContent {
+    pub fn block_record<'a>(&self, drawing: &'a Drawing) -> Option<&'a BlockRecord> {
+        match drawing.item_by_handle(self.__block_record_handle) {
+            Some(DrawingItem::BlockRecord(val)) => Some(val),
+            _ => None,
+        }
+    }
+    pub fn set_block_record(&mut self, item: &BlockRecord) {
+        self.__block_record_handle = DrawingItem::BlockRecord(item).handle();
+    }
+}
+
+/// A `LEADER{` block of a `MULTILEADER` entity; the point where one or more leader lines connect
+/// to the content.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MLeaderRoot {
+    pub is_content_valid: bool,
+    pub has_dogleg_vector: bool,
+    pub connection_point: Point,
+    pub dogleg_vector: Vector,
+    pub leader_root_index: i32,
+    pub dogleg_length: f64,
+    pub leader_lines: Vec<MLeaderLine>,
+    pub attachment_direction: i16,
+}
+
+impl Default for MLeaderRoot {
+    fn default() -> Self {
+        MLeaderRoot {
+            is_content_valid: true,
+            has_dogleg_vector: true,
+            connection_point: Point::origin(),
+            dogleg_vector: Vector::x_axis(),
+            leader_root_index: 0,
+            dogleg_length: 0.0,
+            leader_lines: vec![],
+            attachment_direction: 0,
+        }
+    }
+}
+
+/// A `LEADER_LINE{` block of a `MULTILEADER` entity.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MLeaderLine {
+    pub vertices: Vec<Point>,
+    pub leader_line_index: i32,
+}
+
+/// An arrowhead override of a `MULTILEADER` entity.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MLeaderArrowhead {
+    pub index: i32,
+    #[doc(hidden)]
+    pub __arrowhead_handle: Handle,
+}
+
+impl MLeaderArrowhead {
+    pub fn arrowhead<'a>(&self, drawing: &'a Drawing) -> Option<&'a BlockRecord> {
+        match drawing.item_by_handle(self.__arrowhead_handle) {
+            Some(DrawingItem::BlockRecord(val)) => Some(val),
+            _ => None,
+        }
+    }
+    pub fn set_arrowhead(&mut self, item: &BlockRecord) {
+        self.__arrowhead_handle = DrawingItem::BlockRecord(item).handle();
+    }
+}
+
+/// A block attribute value of a `MULTILEADER` entity with block content.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MLeaderBlockAttribute {
+    #[doc(hidden)]
+    pub __attribute_definition_handle: Handle,
+    pub index: i16,
+    pub width: f64,
+    pub text: String,
+}
+
+impl Default for MLeaderBlockAttribute {
+    fn default() -> Self {
+        MLeaderBlockAttribute {
+            __attribute_definition_handle: Handle::empty(),
+            index: 0,
+            width: 0.0,
+            text: String::new(),
+        }
+    }
+}
+
+// internal visibility only
+impl MLeaderContextData {
+    pub(crate) const START: &'static str = "CONTEXT_DATA{";
+    /// Reads the values following `300/CONTEXT_DATA{` up to and including `301/}`.
+    pub(crate) fn read(iter: &mut CodePairPutBack) -> DxfResult<MLeaderContextData> {
+        let mut context = MLeaderContextData::default();
+        let mut matrix_values = vec![];
+        loop {
+            let pair = next_block_pair(iter)?;
+            match pair.code {
+                301 => break,
+                40 => context.content_scale = pair.assert_f64()?,
+                10 => context.content_base_point.x = pair.assert_f64()?,
+                20 => context.content_base_point.y = pair.assert_f64()?,
+                30 => context.content_base_point.z = pair.assert_f64()?,
+                41 => context.text_height = pair.assert_f64()?,
+                140 => context.arrowhead_size = pair.assert_f64()?,
+                145 => context.landing_gap = pair.assert_f64()?,
+                174 => context.text_left_attachment = pair.assert_i16()?,
+                175 => context.text_right_attachment = pair.assert_i16()?,
+                176 => context.text_alignment_type = pair.assert_i16()?,
+                177 => context.attachment_type = pair.assert_i16()?,
+                290 => {
+                    context.text_content = if pair.assert_bool()? {
+                        Some(MLeaderTextContent::default())
+                    } else {
+                        None
+                    };
+                }
+                296 => {
+                    context.block_content = if pair.assert_bool()? {
+                        Some(MLeaderBlockContent::default())
+                    } else {
+                        None
+                    };
+                }
+                110 => context.plane_origin.x = pair.assert_f64()?,
+                120 => context.plane_origin.y = pair.assert_f64()?,
+                130 => context.plane_origin.z = pair.assert_f64()?,
+                111 => context.plane_x_axis.x = pair.assert_f64()?,
+                121 => context.plane_x_axis.y = pair.assert_f64()?,
+                131 => context.plane_x_axis.z = pair.assert_f64()?,
+                112 => context.plane_y_axis.x = pair.assert_f64()?,
+                122 => context.plane_y_axis.y = pair.assert_f64()?,
+                132 => context.plane_y_axis.z = pair.assert_f64()?,
+                297 => context.is_plane_normal_reversed = pair.assert_bool()?,
+                302 => context.leader_roots.push(MLeaderRoot::read(iter)?),
+                272 => context.text_bottom_attachment = pair.assert_i16()?,
+                273 => context.text_top_attachment = pair.assert_i16()?,
+                _ => {
+                    if let Some(ref mut text) = context.text_content {
+                        if text.apply_code_pair(&pair)? {
+                            continue;
+                        }
+                    }
+                    if let Some(ref mut block) = context.block_content {
+                        if pair.code == 47 {
+                            matrix_values.push(pair.assert_f64()?);
+                            continue;
+                        }
+                        if block.apply_code_pair(&pair)? {
+                            continue;
+                        }
+                    }
+                    // unsupported code pair
+                }
+            }
+        }
+
+        if let Some(ref mut block) = context.block_content {
+            if !matrix_values.is_empty() {
+                block.transformation_matrix = TransformationMatrix::from_vec(&matrix_values);
+            }
+        }
+
+        Ok(context)
+    }
+    pub(crate) fn add_code_pairs(&self, pairs: &mut Vec<CodePair>) {
+        pairs.push(CodePair::new_str(300, MLeaderContextData::START));
+        pairs.push(CodePair::new_f64(40, self.content_scale));
+        push_point(pairs, 10, &self.content_base_point);
+        pairs.push(CodePair::new_f64(41, self.text_height));
+        pairs.push(CodePair::new_f64(140, self.arrowhead_size));
+        pairs.push(CodePair::new_f64(145, self.landing_gap));
+        pairs.push(CodePair::new_i16(174, self.text_left_attachment));
+        pairs.push(CodePair::new_i16(175, self.text_right_attachment));
+        pairs.push(CodePair::new_i16(176, self.text_alignment_type));
+        pairs.push(CodePair::new_i16(177, self.attachment_type));
+        pairs.push(CodePair::new_bool(290, self.text_content.is_some()));
+        if let Some(ref text) = self.text_content {
+            text.add_code_pairs(pairs);
+        }
+        pairs.push(CodePair::new_bool(296, self.block_content.is_some()));
+        if let Some(ref block) = self.block_content {
+            block.add_code_pairs(pairs);
+        }
+        push_point(pairs, 110, &self.plane_origin);
+        push_vector(pairs, 111, &self.plane_x_axis);
+        push_vector(pairs, 112, &self.plane_y_axis);
+        pairs.push(CodePair::new_bool(297, self.is_plane_normal_reversed));
+        for root in &self.leader_roots {
+            root.add_code_pairs(pairs);
+        }
+        pairs.push(CodePair::new_i16(272, self.text_bottom_attachment));
+        pairs.push(CodePair::new_i16(273, self.text_top_attachment));
+        pairs.push(CodePair::new_str(301, "}"));
+    }
+}
+
+impl MLeaderTextContent {
+    fn apply_code_pair(&mut self, pair: &CodePair) -> DxfResult<bool> {
+        match pair.code {
+            304 => self.text = pair.assert_string()?,
+            11 => self.normal.x = pair.assert_f64()?,
+            21 => self.normal.y = pair.assert_f64()?,
+            31 => self.normal.z = pair.assert_f64()?,
+            340 => self.__text_style_handle = pair.as_handle()?,
+            12 => self.location.x = pair.assert_f64()?,
+            22 => self.location.y = pair.assert_f64()?,
+            32 => self.location.z = pair.assert_f64()?,
+            13 => self.direction.x = pair.assert_f64()?,
+            23 => self.direction.y = pair.assert_f64()?,
+            33 => self.direction.z = pair.assert_f64()?,
+            42 => self.rotation = pair.assert_f64()?,
+            43 => self.boundary_width = pair.assert_f64()?,
+            44 => self.boundary_height = pair.assert_f64()?,
+            45 => self.line_spacing_factor = pair.assert_f64()?,
+            170 => self.line_spacing_style = pair.assert_i16()?,
+            90 => self.color = pair.assert_i32()?,
+            171 => self.attachment = pair.assert_i16()?,
+            172 => self.flow_direction = pair.assert_i16()?,
+            91 => self.background_color = pair.assert_i32()?,
+            141 => self.background_scale_factor = pair.assert_f64()?,
+            92 => self.background_transparency = pair.assert_i32()?,
+            291 => self.is_background_color_on = pair.assert_bool()?,
+            292 => self.is_background_fill_on = pair.assert_bool()?,
+            173 => self.column_type = pair.assert_i16()?,
+            293 => self.use_text_auto_height = pair.assert_bool()?,
+            142 => self.column_width = pair.assert_f64()?,
+            143 => self.column_gutter_width = pair.assert_f64()?,
+            294 => self.is_column_flow_reversed = pair.assert_bool()?,
+            144 => self.column_sizes.push(pair.assert_f64()?),
+            295 => self.use_word_break = pair.assert_bool()?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+    fn add_code_pairs(&self, pairs: &mut Vec<CodePair>) {
+        pairs.push(CodePair::new_string(304, &self.text));
+        push_vector(pairs, 11, &self.normal);
+        pairs.push(CodePair::new_string(
+            340,
+            &self.__text_style_handle.as_string(),
+        ));
+        push_point(pairs, 12, &self.location);
+        push_vector(pairs, 13, &self.direction);
+        pairs.push(CodePair::new_f64(42, self.rotation));
+        pairs.push(CodePair::new_f64(43, self.boundary_width));
+        pairs.push(CodePair::new_f64(44, self.boundary_height));
+        pairs.push(CodePair::new_f64(45, self.line_spacing_factor));
+        pairs.push(CodePair::new_i16(170, self.line_spacing_style));
+        pairs.push(CodePair::new_i32(90, self.color));
+        pairs.push(CodePair::new_i16(171, self.attachment));
+        pairs.push(CodePair::new_i16(172, self.flow_direction));
+        pairs.push(CodePair::new_i32(91, self.background_color));
+        pairs.push(CodePair::new_f64(141, self.background_scale_factor));
+        pairs.push(CodePair::new_i32(92, self.background_transparency));
+        pairs.push(CodePair::new_bool(291, self.is_background_color_on));
+        pairs.push(CodePair::new_bool(292, self.is_background_fill_on));
+        pairs.push(CodePair::new_i16(173, self.column_type));
+        pairs.push(CodePair::new_bool(293, self.use_text_auto_height));
+        pairs.push(CodePair::new_f64(142, self.column_width));
+        pairs.push(CodePair::new_f64(143, self.column_gutter_width));
+        pairs.push(CodePair::new_bool(294, self.is_column_flow_reversed));
+        for size in &self.column_sizes {
+            pairs.push(CodePair::new_f64(144, *size));
+        }
+        pairs.push(CodePair::new_bool(295, self.use_word_break));
+    }
+}
+
+impl MLeaderBlockContent {
+    fn apply_code_pair(&mut self, pair: &CodePair) -> DxfResult<bool> {
+        match pair.code {
+            341 => self.__block_record_handle = pair.as_handle()?,
+            14 => self.normal.x = pair.assert_f64()?,
+            24 => self.normal.y = pair.assert_f64()?,
+            34 => self.normal.z = pair.assert_f64()?,
+            15 => self.location.x = pair.assert_f64()?,
+            25 => self.location.y = pair.assert_f64()?,
+            35 => self.location.z = pair.assert_f64()?,
+            16 => self.scale.x = pair.assert_f64()?,
+            26 => self.scale.y = pair.assert_f64()?,
+            36 => self.scale.z = pair.assert_f64()?,
+            46 => self.rotation = pair.assert_f64()?,
+            93 => self.color = pair.assert_i32()?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+    fn add_code_pairs(&self, pairs: &mut Vec<CodePair>) {
+        pairs.push(CodePair::new_string(
+            341,
+            &self.__block_record_handle.as_string(),
+        ));
+        push_vector(pairs, 14, &self.normal);
+        push_point(pairs, 15, &self.location);
+        push_vector(pairs, 16, &self.scale);
+        pairs.push(CodePair::new_f64(46, self.rotation));
+        pairs.push(CodePair::new_i32(93, self.color));
+        for value in self.transformation_matrix.values() {
+            pairs.push(CodePair::new_f64(47, value));
+        }
+    }
+}
+
+impl MLeaderRoot {
+    /// Reads the values following `302/LEADER{` up to and including `303/}`.
+    fn read(iter: &mut CodePairPutBack) -> DxfResult<MLeaderRoot> {
+        let mut root = MLeaderRoot::default();
+        loop {
+            let pair = next_block_pair(iter)?;
+            match pair.code {
+                303 => break,
+                290 => root.is_content_valid = pair.assert_bool()?,
+                291 => root.has_dogleg_vector = pair.assert_bool()?,
+                10 => root.connection_point.x = pair.assert_f64()?,
+                20 => root.connection_point.y = pair.assert_f64()?,
+                30 => root.connection_point.z = pair.assert_f64()?,
+                11 => root.dogleg_vector.x = pair.assert_f64()?,
+                21 => root.dogleg_vector.y = pair.assert_f64()?,
+                31 => root.dogleg_vector.z = pair.assert_f64()?,
+                90 => root.leader_root_index = pair.assert_i32()?,
+                40 => root.dogleg_length = pair.assert_f64()?,
+                304 => root.leader_lines.push(MLeaderLine::read(iter)?),
+                271 => root.attachment_direction = pair.assert_i16()?,
+                _ => (), // unsupported code pair
+            }
+        }
+
+        Ok(root)
+    }
+    fn add_code_pairs(&self, pairs: &mut Vec<CodePair>) {
+        pairs.push(CodePair::new_str(302, "LEADER{"));
+        pairs.push(CodePair::new_bool(290, self.is_content_valid));
+        pairs.push(CodePair::new_bool(291, self.has_dogleg_vector));
+        push_point(pairs, 10, &self.connection_point);
+        push_vector(pairs, 11, &self.dogleg_vector);
+        pairs.push(CodePair::new_i32(90, self.leader_root_index));
+        pairs.push(CodePair::new_f64(40, self.dogleg_length));
+        for line in &self.leader_lines {
+            line.add_code_pairs(pairs);
+        }
+        pairs.push(CodePair::new_i16(271, self.attachment_direction));
+        pairs.push(CodePair::new_str(303, "}"));
+    }
+}
+
+impl MLeaderLine {
+    /// Reads the values following `304/LEADER_LINE{` up to and including `305/}`.
+    fn read(iter: &mut CodePairPutBack) -> DxfResult<MLeaderLine> {
+        let mut line = MLeaderLine::default();
+        loop {
+            let pair = next_block_pair(iter)?;
+            match pair.code {
+                305 => break,
+                10 => line.vertices.push(Point::new(pair.assert_f64()?, 0.0, 0.0)),
+                20 => {
+                    if let Some(vertex) = line.vertices.last_mut() {
+                        vertex.y = pair.assert_f64()?;
+                    }
+                }
+                30 => {
+                    if let Some(vertex) = line.vertices.last_mut() {
+                        vertex.z = pair.assert_f64()?;
+                    }
+                }
+                91 => line.leader_line_index = pair.assert_i32()?,
+                _ => (), // unsupported code pair
+            }
+        }
+
+        Ok(line)
+    }
+    fn add_code_pairs(&self, pairs: &mut Vec<CodePair>) {
+        pairs.push(CodePair::new_str(304, "LEADER_LINE{"));
+        for vertex in &self.vertices {
+            push_point(pairs, 10, vertex);
+        }
+        pairs.push(CodePair::new_i32(91, self.leader_line_index));
+        pairs.push(CodePair::new_str(305, "}"));
+    }
+}
+
+fn push_point(pairs: &mut Vec<CodePair>, code: i32, point: &Point) {
+    pairs.push(CodePair::new_f64(code, point.x));
+    pairs.push(CodePair::new_f64(code + 10, point.y));
+    pairs.push(CodePair::new_f64(code + 20, point.z));
+}
+
+fn push_vector(pairs: &mut Vec<CodePair>, code: i32, vector: &Vector) {
+    pairs.push(CodePair::new_f64(code, vector.x));
+    pairs.push(CodePair::new_f64(code + 10, vector.y));
+    pairs.push(CodePair::new_f64(code + 20, vector.z));
+}
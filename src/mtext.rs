@@ -0,0 +1,683 @@
+// Parsing and building of `MText` formatting codes
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::entities::MText;
+use crate::Color;
+
+const MAX_CHUNK_LENGTH: usize = 250;
+
+/// How the two parts of stacked `MText` are separated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum MTextStackType {
+    /// `\Supper/lower;`, drawn with a horizontal line.
+    Horizontal,
+    /// `\Supper#lower;`, drawn with a diagonal line.
+    Diagonal,
+    /// `\Supper^lower;`, drawn without a line, as with tolerances.
+    Tolerance,
+}
+
+impl MTextStackType {
+    fn separator(self) -> char {
+        match self {
+            MTextStackType::Horizontal => '/',
+            MTextStackType::Diagonal => '#',
+            MTextStackType::Tolerance => '^',
+        }
+    }
+    fn from_separator(c: char) -> Option<Self> {
+        match c {
+            '/' => Some(MTextStackType::Horizontal),
+            '#' => Some(MTextStackType::Diagonal),
+            '^' => Some(MTextStackType::Tolerance),
+            _ => None,
+        }
+    }
+}
+
+/// The formatting of a run of `MText` content.  Values that are `None` are inherited from the
+/// entity.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct MTextFormat {
+    /// The font from `\f` or the SHX font file from `\F`.
+    pub font_name: Option<String>,
+    pub is_bold: bool,
+    pub is_italic: bool,
+    /// The absolute text height from `\H`.
+    pub height: Option<f64>,
+    /// The product of relative heights from `\H...x;` that applied while `height` was `None`.
+    pub height_factor: f64,
+    /// The color index from `\C`.
+    pub color: Option<Color>,
+    /// The raw 24-bit color value from `\c`.
+    pub true_color: Option<i32>,
+    pub is_underlined: bool,
+    pub is_overlined: bool,
+    pub is_struck_through: bool,
+    /// The width factor from `\W`.
+    pub width_factor: Option<f64>,
+    /// The oblique angle in degrees from `\Q`.
+    pub oblique_angle: Option<f64>,
+    /// The character spacing factor from `\T`.
+    pub character_spacing: Option<f64>,
+}
+
+impl Default for MTextFormat {
+    fn default() -> Self {
+        MTextFormat {
+            font_name: None,
+            is_bold: false,
+            is_italic: false,
+            height: None,
+            height_factor: 1.0,
+            color: None,
+            true_color: None,
+            is_underlined: false,
+            is_overlined: false,
+            is_struck_through: false,
+            width_factor: None,
+            oblique_angle: None,
+            character_spacing: None,
+        }
+    }
+}
+
+impl MTextFormat {
+    /// The height of the text, given the `initial_text_height` of the entity.
+    pub fn text_height(&self, initial_text_height: f64) -> f64 {
+        self.height
+            .unwrap_or(initial_text_height * self.height_factor)
+    }
+    fn set_height(&mut self, value: &str) {
+        let (value, is_relative) = match value.strip_suffix(['x', 'X']) {
+            Some(value) => (value, true),
+            None => (value, false),
+        };
+        if let Ok(value) = value.trim().parse::<f64>() {
+            match (is_relative, self.height) {
+                (true, Some(height)) => self.height = Some(height * value),
+                (true, None) => self.height_factor *= value,
+                (false, _) => {
+                    self.height = Some(value);
+                    self.height_factor = 1.0;
+                }
+            }
+        }
+    }
+    fn set_font(&mut self, value: &str) {
+        let mut parts = value.split('|');
+        self.font_name = parts.next().map(String::from);
+        for part in parts {
+            match part.split_at(part.len().min(1)) {
+                ("b", flag) => self.is_bold = flag == "1",
+                ("i", flag) => self.is_italic = flag == "1",
+                _ => (), // code page, pitch, and family aren't kept
+            }
+        }
+    }
+}
+
+/// A piece of parsed `MText` content.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum MTextRun {
+    /// Text with the same formatting.
+    Text { value: String, format: MTextFormat },
+    /// Two parts drawn on top of each other, e.g., a fraction from `\S1/2;`.
+    Stacked {
+        upper: String,
+        lower: String,
+        stack_type: MTextStackType,
+        format: MTextFormat,
+    },
+    /// A paragraph break from `\P`, `\X`, or a new line.
+    ParagraphBreak,
+    /// A column break from `\N`.
+    ColumnBreak,
+}
+
+impl MTextRun {
+    /// Parses the formatting codes of an `MText` value.
+    pub fn parse(value: &str) -> Vec<MTextRun> {
+        let mut parser = Parser {
+            chars: value.chars().peekable(),
+            format: MTextFormat::default(),
+            groups: vec![],
+            text: String::new(),
+            runs: vec![],
+        };
+        parser.parse();
+        parser.runs
+    }
+}
+
+impl MText {
+    /// Returns the full value, which is `extended_text` followed by `text`.
+    pub fn value(&self) -> String {
+        let mut value = self.extended_text.concat();
+        value.push_str(&self.text);
+        value
+    }
+    /// Sets the full value, splitting it into 250 character chunks across `extended_text` and
+    /// `text`.
+    pub fn set_value(&mut self, value: &str) {
+        let chars = value.chars().collect::<Vec<_>>();
+        let mut chunks = chars
+            .chunks(MAX_CHUNK_LENGTH)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        self.text = chunks.pop().unwrap_or_default();
+        self.extended_text = chunks;
+    }
+    /// Parses the formatting codes of the value.
+    pub fn runs(&self) -> Vec<MTextRun> {
+        MTextRun::parse(&self.value())
+    }
+    /// Returns the value without formatting codes.  Paragraph and column breaks become `\n` and
+    /// stacked text is separated by `/`.
+    pub fn plain_text(&self) -> String {
+        let mut text = String::new();
+        for run in self.runs() {
+            match run {
+                MTextRun::Text { value, .. } => text.push_str(&value),
+                MTextRun::Stacked { upper, lower, .. } => {
+                    text.push_str(&upper);
+                    text.push('/');
+                    text.push_str(&lower);
+                }
+                MTextRun::ParagraphBreak | MTextRun::ColumnBreak => text.push('\n'),
+            }
+        }
+        text
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    format: MTextFormat,
+    groups: Vec<MTextFormat>,
+    text: String,
+    runs: Vec<MTextRun>,
+}
+
+impl Parser<'_> {
+    fn parse(&mut self) {
+        while let Some(c) = self.chars.next() {
+            match c {
+                '{' => {
+                    self.flush();
+                    self.groups.push(self.format.clone());
+                }
+                '}' => {
+                    self.flush();
+                    if let Some(format) = self.groups.pop() {
+                        self.format = format;
+                    }
+                }
+                '\\' => self.parse_code(),
+                '%' => self.parse_percent(),
+                '^' => self.parse_caret(),
+                '\n' => self.push_run(MTextRun::ParagraphBreak),
+                '\r' => (),
+                _ => self.text.push(c),
+            }
+        }
+        self.flush();
+    }
+    fn parse_code(&mut self) {
+        let Some(code) = self.chars.next() else {
+            self.text.push('\\');
+            return;
+        };
+        match code {
+            'P' | 'X' => self.push_run(MTextRun::ParagraphBreak),
+            'N' => self.push_run(MTextRun::ColumnBreak),
+            '~' => self.text.push('\u{a0}'),
+            '\\' | '{' | '}' => self.text.push(code),
+            'L' | 'l' => self.change_format(|f| f.is_underlined = code == 'L'),
+            'O' | 'o' => self.change_format(|f| f.is_overlined = code == 'O'),
+            'K' | 'k' => self.change_format(|f| f.is_struck_through = code == 'K'),
+            'U' if self.chars.peek() == Some(&'+') => {
+                self.chars.next();
+                let hex = self.chars.by_ref().take(4).collect::<String>();
+                if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    self.text.push(c);
+                }
+            }
+            'M' if self.chars.peek() == Some(&'+') => {
+                // multibyte characters depend on the font's code page and can't be decoded
+                for _ in self.chars.by_ref().take(6) {}
+            }
+            'S' => self.parse_stack(),
+            'f' | 'F' => {
+                let value = self.argument();
+                self.change_format(|f| f.set_font(&value));
+            }
+            'H' => {
+                let value = self.argument();
+                self.change_format(|f| f.set_height(&value));
+            }
+            'C' => {
+                let value = self.argument().trim().parse::<i16>().ok();
+                self.change_format(|f| f.color = value.map(Color::from_raw_value));
+            }
+            'c' => {
+                let value = self.argument().trim().parse::<i32>().ok();
+                self.change_format(|f| f.true_color = value);
+            }
+            'W' => {
+                let value = parse_factor(&self.argument());
+                self.change_format(|f| f.width_factor = value);
+            }
+            'Q' => {
+                let value = parse_factor(&self.argument());
+                self.change_format(|f| f.oblique_angle = value);
+            }
+            'T' => {
+                let value = parse_factor(&self.argument());
+                self.change_format(|f| f.character_spacing = value);
+            }
+            'A' | 'p' => {
+                // alignment and paragraph properties aren't kept
+                self.argument();
+            }
+            _ => {
+                self.text.push('\\');
+                self.text.push(code);
+            }
+        }
+    }
+    /// Parses `%%d`, `%%p`, `%%c`, `%%%`, `%%nnn`, and the `%%u`, `%%o`, and `%%k` toggles.
+    fn parse_percent(&mut self) {
+        if self.chars.peek() != Some(&'%') {
+            self.text.push('%');
+            return;
+        }
+
+        self.chars.next();
+        match self.chars.peek().copied() {
+            Some('d' | 'D') => self.text.push('°'),
+            Some('p' | 'P') => self.text.push('±'),
+            Some('c' | 'C') => self.text.push('Ø'),
+            Some('%') => self.text.push('%'),
+            Some('u' | 'U') => self.change_format(|f| f.is_underlined = !f.is_underlined),
+            Some('o' | 'O') => self.change_format(|f| f.is_overlined = !f.is_overlined),
+            Some('k' | 'K') => self.change_format(|f| f.is_struck_through = !f.is_struck_through),
+            Some(c) if c.is_ascii_digit() => {
+                let digits = self
+                    .chars
+                    .clone()
+                    .take(3)
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>();
+                if let Some(c) = digits.parse::<u32>().ok().and_then(char::from_u32) {
+                    self.text.push(c);
+                }
+                for _ in 1..digits.len() {
+                    self.chars.next();
+                }
+            }
+            _ => {
+                self.text.push_str("%%");
+                return;
+            }
+        }
+        self.chars.next();
+    }
+    /// Parses the control characters `^I`, `^J`, and `^M`, and `^ ` for a literal `^`.
+    fn parse_caret(&mut self) {
+        match self.chars.peek() {
+            Some('I') => self.text.push('\t'),
+            Some('J') => self.push_run(MTextRun::ParagraphBreak),
+            Some('M') => (),
+            Some(' ') => self.text.push('^'),
+            _ => {
+                self.text.push('^');
+                return;
+            }
+        }
+        self.chars.next();
+    }
+    fn parse_stack(&mut self) {
+        let mut upper = String::new();
+        let mut lower = String::new();
+        let mut stack_type = None;
+        while let Some(c) = self.chars.next() {
+            let c = match c {
+                ';' => break,
+                '\\' => match self.chars.next() {
+                    Some(c) => c,
+                    None => break,
+                },
+                _ if stack_type.is_none() => match MTextStackType::from_separator(c) {
+                    Some(t) => {
+                        stack_type = Some(t);
+                        continue;
+                    }
+                    None => c,
+                },
+                _ => c,
+            };
+            if stack_type.is_none() {
+                upper.push(c);
+            } else {
+                lower.push(c);
+            }
+        }
+
+        let format = self.format.clone();
+        self.push_run(MTextRun::Stacked {
+            upper,
+            lower,
+            stack_type: stack_type.unwrap_or(MTextStackType::Horizontal),
+            format,
+        });
+    }
+    /// Reads the value of a code up to the terminating `;`.
+    fn argument(&mut self) -> String {
+        self.chars.by_ref().take_while(|&c| c != ';').collect()
+    }
+    fn change_format<F>(&mut self, change: F)
+    where
+        F: FnOnce(&mut MTextFormat),
+    {
+        self.flush();
+        change(&mut self.format);
+    }
+    fn push_run(&mut self, run: MTextRun) {
+        self.flush();
+        self.runs.push(run);
+    }
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            self.runs.push(MTextRun::Text {
+                value: std::mem::take(&mut self.text),
+                format: self.format.clone(),
+            });
+        }
+    }
+}
+
+fn parse_factor(value: &str) -> Option<f64> {
+    value.trim().trim_end_matches(['x', 'X']).parse().ok()
+}
+
+/// Builds an `MText` value, escaping text and emitting formatting codes.
+///
+/// ```
+/// # use dxf::*;
+/// let value = MTextBuilder::new()
+///     .group(|b| b.font("Arial", true, false).text("Title"))
+///     .paragraph_break()
+///     .text("100% {done}")
+///     .build();
+/// assert_eq!("{\\fArial|b1|i0;Title}\\P100%%% \\{done\\}", value);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MTextBuilder {
+    value: String,
+}
+
+impl MTextBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Adds text, escaping characters that would otherwise be read as formatting codes.  New lines
+    /// become paragraph breaks.
+    pub fn text(mut self, text: &str) -> Self {
+        for c in text.chars() {
+            match c {
+                '\\' | '{' | '}' => {
+                    self.value.push('\\');
+                    self.value.push(c);
+                }
+                '\n' => self.value.push_str("\\P"),
+                '\r' => (),
+                '\u{a0}' => self.value.push_str("\\~"),
+                '%' => self.value.push_str("%%%"),
+                '^' => self.value.push_str("^ "),
+                _ => self.value.push(c),
+            }
+        }
+        self
+    }
+    pub fn paragraph_break(self) -> Self {
+        self.code("\\P")
+    }
+    pub fn column_break(self) -> Self {
+        self.code("\\N")
+    }
+    pub fn font(self, name: &str, is_bold: bool, is_italic: bool) -> Self {
+        let code = format!(
+            "\\f{}|b{}|i{};",
+            name,
+            u8::from(is_bold),
+            u8::from(is_italic)
+        );
+        self.code(&code)
+    }
+    pub fn height(self, height: f64) -> Self {
+        self.code(&format!("\\H{height};"))
+    }
+    /// Sets the height as a multiple of the current height.
+    pub fn relative_height(self, factor: f64) -> Self {
+        self.code(&format!("\\H{factor}x;"))
+    }
+    pub fn color(self, color: Color) -> Self {
+        self.code(&format!("\\C{};", color.raw_value()))
+    }
+    /// Sets the raw 24-bit color value.
+    pub fn true_color(self, color: i32) -> Self {
+        self.code(&format!("\\c{color};"))
+    }
+    pub fn width_factor(self, factor: f64) -> Self {
+        self.code(&format!("\\W{factor};"))
+    }
+    /// Sets the oblique angle in degrees.
+    pub fn oblique_angle(self, angle: f64) -> Self {
+        self.code(&format!("\\Q{angle};"))
+    }
+    pub fn character_spacing(self, factor: f64) -> Self {
+        self.code(&format!("\\T{factor};"))
+    }
+    pub fn underline(self, val: bool) -> Self {
+        self.code(if val { "\\L" } else { "\\l" })
+    }
+    pub fn overline(self, val: bool) -> Self {
+        self.code(if val { "\\O" } else { "\\o" })
+    }
+    pub fn strike_through(self, val: bool) -> Self {
+        self.code(if val { "\\K" } else { "\\k" })
+    }
+    /// Adds stacked text, e.g., a fraction.
+    pub fn stack(mut self, upper: &str, lower: &str, stack_type: MTextStackType) -> Self {
+        self.value.push_str("\\S");
+        push_stack_part(&mut self.value, upper);
+        self.value.push(stack_type.separator());
+        push_stack_part(&mut self.value, lower);
+        self.value.push(';');
+        self
+    }
+    /// Adds a `{...}` group; formatting set by `build` ends with the group.
+    pub fn group<F>(mut self, build: F) -> Self
+    where
+        F: FnOnce(MTextBuilder) -> MTextBuilder,
+    {
+        self.value.push('{');
+        self.value.push_str(&build(MTextBuilder::new()).value);
+        self.value.push('}');
+        self
+    }
+    pub fn build(self) -> String {
+        self.value
+    }
+    fn code(mut self, code: &str) -> Self {
+        self.value.push_str(code);
+        self
+    }
+}
+
+fn push_stack_part(value: &mut String, part: &str) {
+    for c in part.chars() {
+        if matches!(c, '\\' | ';' | '/' | '#' | '^') {
+            value.push('\\');
+        }
+        value.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::*;
+
+    fn text(value: &str, format: MTextFormat) -> MTextRun {
+        MTextRun::Text {
+            value: String::from(value),
+            format,
+        }
+    }
+
+    #[test]
+    fn runs_follow_groups_and_codes() {
+        let runs = MTextRun::parse("{\\fArial|b1|i0|c0|p34;\\H2.5;Title}\\P\\C1;red\\Lline\\l");
+        let title = MTextFormat {
+            font_name: Some(String::from("Arial")),
+            is_bold: true,
+            height: Some(2.5),
+            ..Default::default()
+        };
+        let red = MTextFormat {
+            color: Some(Color::from_index(1)),
+            ..Default::default()
+        };
+        let underlined = MTextFormat {
+            is_underlined: true,
+            ..red.clone()
+        };
+        assert_eq!(
+            vec![
+                text("Title", title),
+                MTextRun::ParagraphBreak,
+                text("red", red),
+                text("line", underlined),
+            ],
+            runs
+        );
+    }
+
+    #[test]
+    fn relative_heights_are_applied() {
+        let runs = MTextRun::parse("{\\H0.5x;a{\\H2;\\H1.5x;b}}");
+        let heights = runs
+            .iter()
+            .map(|r| match r {
+                MTextRun::Text { format, .. } => format.text_height(4.0),
+                _ => panic!("expected text"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![2.0, 3.0], heights);
+    }
+
+    #[test]
+    fn stacks_and_symbols_are_parsed() {
+        let runs = MTextRun::parse("1\\S+0.1^-0.2;%%d\\S1\\/2#3;");
+        assert_eq!(4, runs.len());
+        assert_eq!(
+            MTextRun::Stacked {
+                upper: String::from("+0.1"),
+                lower: String::from("-0.2"),
+                stack_type: MTextStackType::Tolerance,
+                format: Default::default(),
+            },
+            runs[1]
+        );
+        assert_eq!(text("°", Default::default()), runs[2]);
+        assert_eq!(
+            MTextRun::Stacked {
+                upper: String::from("1/2"),
+                lower: String::from("3"),
+                stack_type: MTextStackType::Diagonal,
+                format: Default::default(),
+            },
+            runs[3]
+        );
+    }
+
+    #[test]
+    fn plain_text_removes_codes() {
+        let mtext = MText {
+            extended_text: vec![String::from("{\\fArial;Dia")],
+            text: String::from(
+                "meter} %%c10\\P\\A1;\\pxi-3;50%%% x^ 2\\~\\U+00B1\\S1/2;\\{ok\\} C:\\d",
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            "Diameter Ø10\n50% x^2\u{a0}±1/2{ok} C:\\d",
+            mtext.plain_text()
+        );
+    }
+
+    #[test]
+    fn built_values_round_trip() {
+        let raw = "50% {a}\\b ^I %%d\nnext\u{a0}";
+        let value = MTextBuilder::new()
+            .group(|b| {
+                b.font("Times New Roman", false, true)
+                    .height(2.0)
+                    .underline(true)
+                    .text(raw)
+            })
+            .stack("1/2", "3;4", MTextStackType::Horizontal)
+            .color(Color::from_index(3))
+            .text("end")
+            .build();
+
+        let mut mtext = MText::default();
+        mtext.set_value(&value);
+        assert_eq!(
+            "50% {a}\\b ^I %%d\nnext\u{a0}1/2/3;4end",
+            mtext.plain_text()
+        );
+
+        let runs = mtext.runs();
+        match &runs[0] {
+            MTextRun::Text { value, format } => {
+                assert_eq!("50% {a}\\b ^I %%d", value);
+                assert_eq!(Some(String::from("Times New Roman")), format.font_name);
+                assert!(format.is_italic && format.is_underlined);
+                assert_eq!(Some(2.0), format.height);
+            }
+            _ => panic!("expected text"),
+        }
+        match runs.last().unwrap() {
+            MTextRun::Text { value, format } => {
+                assert_eq!("end", value);
+                assert_eq!(
+                    MTextFormat {
+                        color: Some(Color::from_index(3)),
+                        ..Default::default()
+                    },
+                    *format
+                );
+            }
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn long_values_are_split_into_chunks() {
+        let value = "é".repeat(600);
+        let mut mtext = MText::default();
+        mtext.set_value(&value);
+        assert_eq!(2, mtext.extended_text.len());
+        assert_eq!(250, mtext.extended_text[0].chars().count());
+        assert_eq!(100, mtext.text.chars().count());
+        assert_eq!(value, mtext.value());
+    }
+}
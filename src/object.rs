@@ -94,66 +94,95 @@ impl Object {
         // no object-specific values to set
     }
     pub(crate) fn read(iter: &mut CodePairPutBack) -> DxfResult<Option<Object>> {
-        loop {
-            match iter.next() {
-                // first code pair must be 0/object-type
-                Some(Ok(pair @ CodePair { code: 0, .. })) => {
-                    let type_string = pair.assert_string()?;
-                    if type_string == "ENDSEC" || type_string == "ENDBLK" {
-                        iter.put_back(Ok(pair));
-                        return Ok(None);
-                    }
-
-                    match ObjectType::from_type_string(&type_string) {
-                        Some(e) => {
-                            let mut obj = Object::new(e);
-                            if !obj.apply_custom_reader(iter)? {
-                                // no custom reader, use the auto-generated one
-                                loop {
-                                    match iter.next() {
-                                        Some(Ok(pair @ CodePair { code: 0, .. })) => {
-                                            // new object or ENDSEC
-                                            iter.put_back(Ok(pair));
-                                            break;
-                                        }
-                                        Some(Ok(pair)) => obj.apply_code_pair(&pair, iter)?,
-                                        Some(Err(e)) => return Err(e),
-                                        None => return Err(DxfError::UnexpectedEndOfInput),
-                                    }
-                                }
-
-                                obj.post_parse()?;
-                            }
+        match iter.next() {
+            // first code pair must be 0/object-type
+            Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                let type_string = pair.assert_string()?;
+                if type_string == "ENDSEC" || type_string == "ENDBLK" {
+                    iter.put_back(Ok(pair));
+                    return Ok(None);
+                }
 
-                            return Ok(Some(obj));
-                        }
-                        None => {
-                            // swallow unsupported object
+                match ObjectType::from_type_string(&type_string) {
+                    Some(e) => {
+                        let mut obj = Object::new(e);
+                        if !obj.apply_custom_reader(iter)? {
+                            // no custom reader, use the auto-generated one
                             loop {
                                 match iter.next() {
                                     Some(Ok(pair @ CodePair { code: 0, .. })) => {
-                                        // found another object or ENDSEC
+                                        // new object or ENDSEC
                                         iter.put_back(Ok(pair));
                                         break;
                                     }
-                                    Some(Ok(_)) => (), // part of the unsupported object
+                                    Some(Ok(pair)) => {
+                                        if let Err(e) = obj.apply_code_pair(&pair, iter) {
+                                            iter.recover(e)?;
+                                        }
+                                    }
                                     Some(Err(e)) => return Err(e),
                                     None => return Err(DxfError::UnexpectedEndOfInput),
                                 }
                             }
+
+                            obj.post_parse()?;
                         }
+
+                        Ok(Some(obj))
+                    }
+                    None => {
+                        // preserve unsupported object
+                        let obj = Object::read_unknown(type_string, iter)?;
+                        Ok(Some(obj))
                     }
                 }
+            }
+            Some(Ok(pair)) => Err(DxfError::UnexpectedCodePair(
+                pair,
+                String::from("expected 0/object-type or 0/ENDSEC"),
+            )),
+            Some(Err(e)) => Err(e),
+            None => Err(DxfError::UnexpectedEndOfInput),
+        }
+    }
+    fn read_unknown(type_string: String, iter: &mut CodePairPutBack) -> DxfResult<Object> {
+        let mut common = ObjectCommon::default();
+        let mut pairs = vec![];
+        let mut reading_common = true;
+        loop {
+            match iter.next() {
+                Some(Ok(pair @ CodePair { code: 0, .. })) => {
+                    // new object or ENDSEC
+                    iter.put_back(Ok(pair));
+                    break;
+                }
+                Some(Ok(pair @ CodePair { code: 100, .. })) => {
+                    // the first subclass marker starts the object-specific values
+                    reading_common = false;
+                    pairs.push(pair);
+                }
+                Some(Ok(pair @ CodePair { code: 1001, .. })) => {
+                    // XDATA always trails the object
+                    common.apply_individual_pair(&pair, iter)?;
+                }
                 Some(Ok(pair)) => {
-                    return Err(DxfError::UnexpectedCodePair(
-                        pair,
-                        String::from("expected 0/object-type or 0/ENDSEC"),
-                    ))
+                    if !reading_common || !common.apply_individual_pair(&pair, iter)? {
+                        pairs.push(pair);
+                    }
                 }
                 Some(Err(e)) => return Err(e),
                 None => return Err(DxfError::UnexpectedEndOfInput),
             }
         }
+
+        Ok(Object {
+            common,
+            specific: ObjectType::Unknown {
+                type_string,
+                pairs,
+                version: iter.version(),
+            },
+        })
     }
     fn apply_code_pair(&mut self, pair: &CodePair, iter: &mut CodePairPutBack) -> DxfResult<()> {
         if !self.specific.try_apply_code_pair(pair)? {
@@ -414,16 +443,12 @@ impl Object {
                     );
                     current_3d_point = Point::origin();
                 }
-                330 | 331 | 340 | 350 | 360 => {
-                    if read_row_count || read_column_count {
-                        data.set_value(
-                            current_row,
-                            current_column,
-                            DataTableValue::Handle(pair.as_handle()?),
-                        );
-                    } else {
-                        common.apply_individual_pair(&pair, iter)?;
-                    }
+                330 | 331 | 340 | 350 | 360 if read_row_count || read_column_count => {
+                    data.set_value(
+                        current_row,
+                        current_column,
+                        DataTableValue::Handle(pair.as_handle()?),
+                    );
                 }
 
                 _ => {
@@ -1324,7 +1349,7 @@ impl Object {
                         // after the version, 90 pairs come in julian_day/seconds_past_midnight duals
                         match julian_day {
                             Some(jd) => {
-                                let date = as_datetime_local(f64::from(jd));
+                                let date = as_datetime_local(f64::from(jd), pair.offset)?;
                                 let date =
                                     date.add(Duration::seconds(i64::from(pair.assert_i32()?)));
                                 ss.dates.push(date);
@@ -1649,6 +1674,12 @@ impl Object {
                     pairs.push(pair.clone());
                 }
             }
+            ObjectType::Unknown {
+                pairs: ref unknown_pairs,
+                ..
+            } => {
+                pairs.extend(unknown_pairs.iter().cloned());
+            }
             _ => return false, // no custom writer
         }
 
@@ -1694,6 +1725,11 @@ mod tests {
     #[test]
     fn read_unsupported_object() {
         let drawing = drawing_from_pairs(vec![
+            CodePair::new_str(0, "SECTION"),
+            CodePair::new_str(2, "HEADER"),
+            CodePair::new_str(9, "$ACADVER"),
+            CodePair::new_str(1, "AC1015"),
+            CodePair::new_str(0, "ENDSEC"),
             CodePair::new_str(0, "SECTION"),
             CodePair::new_str(2, "OBJECTS"),
             CodePair::new_str(0, "UNSUPPORTED_OBJECT"),
@@ -1701,7 +1737,74 @@ mod tests {
             CodePair::new_str(0, "ENDSEC"),
             CodePair::new_str(0, "EOF"),
         ]);
-        assert_eq!(0, drawing.objects().count());
+        let objects = drawing.objects().collect::<Vec<_>>();
+        assert_eq!(1, objects.len());
+        match objects[0].specific {
+            ObjectType::Unknown {
+                ref type_string,
+                ref pairs,
+                version,
+            } => {
+                assert_eq!("UNSUPPORTED_OBJECT", type_string);
+                assert_eq!(AcadVersion::R2000, version);
+                assert_eq!(vec![CodePair::new_str(1, "unsupported string")], *pairs);
+            }
+            _ => panic!("expected an unknown object"),
+        }
+    }
+
+    #[test]
+    fn read_unsupported_object_common_and_specific_values() {
+        let obj = read_object(
+            "UNSUPPORTED_OBJECT",
+            vec![
+                CodePair::new_str(5, "42"),
+                CodePair::new_str(330, "43"),
+                CodePair::new_str(100, "AcDbUnsupported"),
+                CodePair::new_str(330, "44"),
+                CodePair::new_str(1001, "APP"),
+                CodePair::new_str(1000, "x data"),
+            ],
+        );
+        assert_eq!(Handle(0x42), obj.common.handle);
+        assert_eq!(Handle(0x43), obj.common.__owner_handle);
+        assert_eq!(1, obj.common.x_data.len());
+        match obj.specific {
+            ObjectType::Unknown { ref pairs, .. } => {
+                assert_eq!(
+                    vec![
+                        CodePair::new_str(100, "AcDbUnsupported"),
+                        CodePair::new_str(330, "44"),
+                    ],
+                    *pairs
+                );
+            }
+            _ => panic!("expected an unknown object"),
+        }
+    }
+
+    #[test]
+    fn write_unsupported_object() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let obj = drawing.add_object(Object::new(ObjectType::Unknown {
+            type_string: String::from("UNSUPPORTED_OBJECT"),
+            pairs: vec![
+                CodePair::new_str(100, "AcDbUnsupported"),
+                CodePair::new_str(1, "unsupported string"),
+            ],
+            version: AcadVersion::R2000,
+        }));
+        let handle = obj.common.handle;
+        assert_contains_pairs(
+            &drawing,
+            vec![
+                CodePair::new_str(0, "UNSUPPORTED_OBJECT"),
+                CodePair::new_string(5, &handle.as_string()),
+                CodePair::new_str(100, "AcDbUnsupported"),
+                CodePair::new_str(1, "unsupported string"),
+            ],
+        );
     }
 
     #[test]
@@ -1717,12 +1820,16 @@ mod tests {
             CodePair::new_str(0, "EOF"),
         ]);
         let objects = drawing.objects().collect::<Vec<_>>();
-        assert_eq!(2, objects.len());
+        assert_eq!(3, objects.len());
         match objects[0].specific {
             ObjectType::DictionaryVariable(_) => (),
             _ => panic!("expected a dictionary variable"),
         }
         match objects[1].specific {
+            ObjectType::Unknown { .. } => (),
+            _ => panic!("expected an unknown object"),
+        }
+        match objects[2].specific {
             ObjectType::ImageDefinition(_) => (),
             _ => panic!("expected an image definition"),
         }
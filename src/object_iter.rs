@@ -1,17 +1,39 @@
 use crate::code_pair_put_back::CodePairPutBack;
 use crate::objects::Object;
+use crate::DxfError;
 
-pub(crate) struct ObjectIter<'a> {
-    pub iter: &'a mut CodePairPutBack,
+pub(crate) struct ObjectIter<'a, 'b> {
+    iter: &'a mut CodePairPutBack<'b>,
+    /// The error that stopped reading.
+    pub error: Option<DxfError>,
 }
 
-impl Iterator for ObjectIter<'_> {
+impl<'a, 'b> ObjectIter<'a, 'b> {
+    pub(crate) fn new(iter: &'a mut CodePairPutBack<'b>) -> Self {
+        ObjectIter { iter, error: None }
+    }
+}
+
+impl Iterator for ObjectIter<'_, '_> {
     type Item = Object;
 
     fn next(&mut self) -> Option<Object> {
-        match Object::read(self.iter) {
-            Ok(Some(o)) => Some(o),
-            Ok(None) | Err(_) => None,
+        loop {
+            match Object::read(self.iter) {
+                Ok(Some(o)) => return Some(o),
+                Ok(None) => return None,
+                Err(e) => {
+                    // when recovering, skip the rest of the item and continue with the next one
+                    match self.iter.recover(e).and_then(|_| self.iter.skip_to(&[])) {
+                        Ok(true) => (),
+                        Ok(false) => return None,
+                        Err(e) => {
+                            self.error = Some(e);
+                            return None;
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -0,0 +1,292 @@
+// Conversions from an entity's object coordinate system (OCS) to world coordinates (WCS)
+
+use crate::entities::*;
+use crate::{Point, TransformationMatrix, Vector};
+
+/// The limit below which both the X and Y components of a normal are considered zero by the
+/// arbitrary axis algorithm.
+const ARBITRARY_AXIS_LIMIT: f64 = 1.0 / 64.0;
+
+/// Returns the rotation from the object coordinate system defined by `normal` into world
+/// coordinates, as determined by the DXF arbitrary axis algorithm.
+///
+/// A zero `normal` is treated as the Z axis, giving the identity matrix.
+pub fn ocs_to_wcs_matrix(normal: &Vector) -> TransformationMatrix {
+    let normal = normal.normalized();
+    if normal.length() == 0.0 {
+        return TransformationMatrix::identity();
+    }
+
+    let x_axis = if normal.x.abs() < ARBITRARY_AXIS_LIMIT && normal.y.abs() < ARBITRARY_AXIS_LIMIT {
+        Vector::y_axis().cross(&normal).normalized()
+    } else {
+        Vector::z_axis().cross(&normal).normalized()
+    };
+    let y_axis = normal.cross(&x_axis).normalized();
+    TransformationMatrix {
+        m11: x_axis.x,
+        m12: y_axis.x,
+        m13: normal.x,
+        m21: x_axis.y,
+        m22: y_axis.y,
+        m23: normal.y,
+        m31: x_axis.z,
+        m32: y_axis.z,
+        m33: normal.z,
+        ..TransformationMatrix::identity()
+    }
+}
+
+/// Returns the rotation from world coordinates into the object coordinate system defined by
+/// `normal`; the inverse of `ocs_to_wcs_matrix`.
+pub fn wcs_to_ocs_matrix(normal: &Vector) -> TransformationMatrix {
+    // the rotation is orthonormal, so its inverse is its transpose
+    let m = ocs_to_wcs_matrix(normal);
+    TransformationMatrix {
+        m12: m.m21,
+        m13: m.m31,
+        m21: m.m12,
+        m23: m.m32,
+        m31: m.m13,
+        m32: m.m23,
+        ..m
+    }
+}
+
+/// Returns the world coordinates of the point at `angle` degrees around a circle in the OCS.
+fn point_on_circle(ocs: &TransformationMatrix, center: &Point, radius: f64, angle: f64) -> Point {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    ocs.transform_point(&Point::new(
+        center.x + radius * cos,
+        center.y + radius * sin,
+        center.z,
+    ))
+}
+
+impl Arc {
+    /// Returns the rotation from the arc's object coordinate system into world coordinates.
+    pub fn ocs_to_wcs_matrix(&self) -> TransformationMatrix {
+        ocs_to_wcs_matrix(&self.normal)
+    }
+    /// Returns the center of the arc in world coordinates.
+    pub fn center_wcs(&self) -> Point {
+        self.ocs_to_wcs_matrix().transform_point(&self.center)
+    }
+    /// Returns the point where the arc starts in world coordinates.
+    pub fn start_point_wcs(&self) -> Point {
+        point_on_circle(
+            &self.ocs_to_wcs_matrix(),
+            &self.center,
+            self.radius,
+            self.start_angle,
+        )
+    }
+    /// Returns the point where the arc ends in world coordinates.
+    pub fn end_point_wcs(&self) -> Point {
+        point_on_circle(
+            &self.ocs_to_wcs_matrix(),
+            &self.center,
+            self.radius,
+            self.end_angle,
+        )
+    }
+}
+
+impl Circle {
+    /// Returns the rotation from the circle's object coordinate system into world coordinates.
+    pub fn ocs_to_wcs_matrix(&self) -> TransformationMatrix {
+        ocs_to_wcs_matrix(&self.normal)
+    }
+    /// Returns the center of the circle in world coordinates.
+    pub fn center_wcs(&self) -> Point {
+        self.ocs_to_wcs_matrix().transform_point(&self.center)
+    }
+}
+
+impl LwPolyline {
+    /// Returns the rotation from the polyline's object coordinate system into world coordinates.
+    pub fn ocs_to_wcs_matrix(&self) -> TransformationMatrix {
+        ocs_to_wcs_matrix(&self.extrusion_direction)
+    }
+    /// Returns the vertices in world coordinates.  The polyline's `elevation` is stored on the
+    /// containing `Entity` as `EntityCommon::elevation`.
+    pub fn vertices_wcs(&self, elevation: f64) -> Vec<Point> {
+        let ocs = self.ocs_to_wcs_matrix();
+        self.vertices
+            .iter()
+            .map(|v| ocs.transform_point(&Point::new(v.x, v.y, elevation)))
+            .collect()
+    }
+}
+
+impl Text {
+    /// Returns the rotation from the text's object coordinate system into world coordinates.
+    pub fn ocs_to_wcs_matrix(&self) -> TransformationMatrix {
+        ocs_to_wcs_matrix(&self.normal)
+    }
+    /// Returns the first alignment point in world coordinates.
+    pub fn location_wcs(&self) -> Point {
+        self.ocs_to_wcs_matrix().transform_point(&self.location)
+    }
+    /// Returns the second alignment point in world coordinates.
+    pub fn second_alignment_point_wcs(&self) -> Point {
+        self.ocs_to_wcs_matrix()
+            .transform_point(&self.second_alignment_point)
+    }
+    /// Returns the direction of the text's baseline in world coordinates.
+    pub fn direction_wcs(&self) -> Vector {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        self.ocs_to_wcs_matrix()
+            .transform_vector(&Vector::new(cos, sin, 0.0))
+    }
+}
+
+impl Insert {
+    /// Returns the rotation from the insert's object coordinate system into world coordinates.
+    pub fn ocs_to_wcs_matrix(&self) -> TransformationMatrix {
+        ocs_to_wcs_matrix(&self.extrusion_direction)
+    }
+    /// Returns the insertion point in world coordinates.
+    pub fn location_wcs(&self) -> Point {
+        self.ocs_to_wcs_matrix().transform_point(&self.location)
+    }
+}
+
+impl Solid {
+    /// Returns the rotation from the solid's object coordinate system into world coordinates.
+    pub fn ocs_to_wcs_matrix(&self) -> TransformationMatrix {
+        ocs_to_wcs_matrix(&self.extrusion_direction)
+    }
+    /// Returns the four corners in world coordinates.
+    pub fn corners_wcs(&self) -> [Point; 4] {
+        let ocs = self.ocs_to_wcs_matrix();
+        [
+            ocs.transform_point(&self.first_corner),
+            ocs.transform_point(&self.second_corner),
+            ocs.transform_point(&self.third_corner),
+            ocs.transform_point(&self.fourth_corner),
+        ]
+    }
+}
+
+impl Trace {
+    /// Returns the rotation from the trace's object coordinate system into world coordinates.
+    pub fn ocs_to_wcs_matrix(&self) -> TransformationMatrix {
+        ocs_to_wcs_matrix(&self.extrusion_direction)
+    }
+    /// Returns the four corners in world coordinates.
+    pub fn corners_wcs(&self) -> [Point; 4] {
+        let ocs = self.ocs_to_wcs_matrix();
+        [
+            ocs.transform_point(&self.first_corner),
+            ocs.transform_point(&self.second_corner),
+            ocs.transform_point(&self.third_corner),
+            ocs.transform_point(&self.fourth_corner),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::helper_functions::tests::assert_point;
+    use crate::*;
+
+    #[test]
+    fn z_axis_normal_is_identity() {
+        assert_eq!(
+            TransformationMatrix::identity(),
+            ocs_to_wcs_matrix(&Vector::z_axis())
+        );
+        assert_eq!(
+            TransformationMatrix::identity(),
+            ocs_to_wcs_matrix(&Vector::zero())
+        );
+    }
+
+    #[test]
+    fn negative_z_normal_mirrors_x() {
+        // the arbitrary axis algorithm gives an X axis of Wy x N = (-1, 0, 0)
+        let ocs = ocs_to_wcs_matrix(&Vector::new(0.0, 0.0, -1.0));
+        assert_point(
+            (-1.0, 2.0, -3.0),
+            &ocs.transform_point(&Point::new(1.0, 2.0, 3.0)),
+        );
+    }
+
+    #[test]
+    fn x_axis_normal() {
+        // X axis is Wz x N = (0, 1, 0) and Y axis is N x X = (0, 0, 1)
+        let ocs = ocs_to_wcs_matrix(&Vector::x_axis());
+        assert_point(
+            (3.0, 1.0, 2.0),
+            &ocs.transform_point(&Point::new(1.0, 2.0, 3.0)),
+        );
+    }
+
+    #[test]
+    fn wcs_to_ocs_is_the_inverse() {
+        let normal = Vector::new(1.0, 2.0, 3.0);
+        let p = Point::new(4.0, -5.0, 6.0);
+        let round_tripped = wcs_to_ocs_matrix(&normal)
+            .transform_point(&ocs_to_wcs_matrix(&normal).transform_point(&p));
+        assert_point((4.0, -5.0, 6.0), &round_tripped);
+    }
+
+    #[test]
+    fn arc_points_in_wcs() {
+        let arc = Arc {
+            center: Point::new(1.0, 0.0, 2.0),
+            radius: 1.0,
+            normal: Vector::new(0.0, 0.0, -1.0),
+            start_angle: 0.0,
+            end_angle: 90.0,
+            ..Default::default()
+        };
+        assert_point((-1.0, 0.0, -2.0), &arc.center_wcs());
+        assert_point((-2.0, 0.0, -2.0), &arc.start_point_wcs());
+        assert_point((-1.0, 1.0, -2.0), &arc.end_point_wcs());
+    }
+
+    #[test]
+    fn lw_polyline_vertices_in_wcs() {
+        let poly = LwPolyline {
+            extrusion_direction: Vector::x_axis(),
+            vertices: vec![LwPolylineVertex {
+                x: 1.0,
+                y: 2.0,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let vertices = poly.vertices_wcs(3.0);
+        assert_eq!(1, vertices.len());
+        assert_point((3.0, 1.0, 2.0), &vertices[0]);
+    }
+
+    #[test]
+    fn solid_corners_in_wcs() {
+        let solid = Solid {
+            first_corner: Point::new(1.0, 0.0, 0.0),
+            second_corner: Point::new(2.0, 0.0, 0.0),
+            third_corner: Point::new(1.0, 1.0, 0.0),
+            fourth_corner: Point::new(2.0, 1.0, 0.0),
+            extrusion_direction: Vector::new(0.0, 0.0, -1.0),
+            ..Default::default()
+        };
+        let corners = solid.corners_wcs();
+        assert_point((-1.0, 0.0, 0.0), &corners[0]);
+        assert_point((-2.0, 1.0, 0.0), &corners[3]);
+    }
+
+    #[test]
+    fn text_direction_in_wcs() {
+        let text = Text {
+            rotation: 90.0,
+            normal: Vector::new(0.0, 0.0, -1.0),
+            ..Default::default()
+        };
+        let direction = text.direction_wcs();
+        assert_point((0.0, 1.0, 0.0), &direction.to_point());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{CodePair, DxfError, DxfResult};
+use std::ops::{Add, Sub};
+
+use crate::{CodePair, DxfError, DxfResult, Vector};
 
 /// Represents a simple point in Cartesian space.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -76,6 +78,72 @@ impl Point {
     pub fn tuple(&self) -> (f64, f64, f64) {
         (self.x, self.y, self.z)
     }
+    /// Returns the distance between this point and `other`.
+    pub fn distance(&self, other: &Point) -> f64 {
+        (other - self).length()
+    }
+    /// Returns the point a fraction `t` of the way from this point to `other`, where 0 gives this
+    /// point and 1 gives `other`.
+    pub fn lerp(&self, other: &Point, t: f64) -> Point {
+        Point::new(
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+            self.z + (other.z - self.z) * t,
+        )
+    }
+    /// Returns the vector from the origin to this point.
+    pub fn to_vector(&self) -> Vector {
+        Vector::new(self.x, self.y, self.z)
+    }
+}
+
+impl From<Vector> for Point {
+    fn from(vector: Vector) -> Self {
+        Point::new(vector.x, vector.y, vector.z)
+    }
+}
+
+impl Add<&Vector> for &Point {
+    type Output = Point;
+    fn add(self, vector: &Vector) -> Point {
+        Point::new(self.x + vector.x, self.y + vector.y, self.z + vector.z)
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+    fn add(self, vector: Vector) -> Point {
+        &self + &vector
+    }
+}
+
+impl Sub<&Vector> for &Point {
+    type Output = Point;
+    fn sub(self, vector: &Vector) -> Point {
+        Point::new(self.x - vector.x, self.y - vector.y, self.z - vector.z)
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+    fn sub(self, vector: Vector) -> Point {
+        &self - &vector
+    }
+}
+
+/// Subtracting two points gives the vector from the second to the first.
+impl Sub<&Point> for &Point {
+    type Output = Vector;
+    fn sub(self, other: &Point) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Sub<Point> for Point {
+    type Output = Vector;
+    fn sub(self, other: Point) -> Vector {
+        &self - &other
+    }
 }
 
 #[cfg(test)]
@@ -99,4 +167,15 @@ mod tests {
         assert_eq!(p.y, 2.0);
         assert_eq!(p.z, 3.5);
     }
+    #[test]
+    fn point_arithmetic() {
+        let p = Point::new(1.0, 2.0, 3.0);
+        let v = Vector::new(1.0, 1.0, 1.0);
+        assert_eq!(Point::new(2.0, 3.0, 4.0), &p + &v);
+        assert_eq!(Point::new(0.0, 1.0, 2.0), p.clone() - v);
+        assert_eq!(Vector::new(1.0, 2.0, 3.0), p.clone() - Point::origin());
+        assert_eq!(5.0, Point::origin().distance(&Point::new(3.0, 4.0, 0.0)));
+        assert_eq!(Point::new(0.5, 1.0, 1.5), Point::origin().lerp(&p, 0.5));
+        assert_eq!(p, Point::from(p.to_vector()));
+    }
 }
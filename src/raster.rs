@@ -0,0 +1,315 @@
+// CPU rasterization of the model space of a `Drawing`
+
+use std::borrow::Cow;
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+use crate::entities::*;
+use crate::tables::Layer;
+use crate::{Drawing, Point, TransformationMatrix, RGB};
+
+/// The color behind the entities.
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+
+/// An entity as it's displayed, along with its layer.
+pub(crate) struct DisplayedEntity<'a> {
+    pub entity: Cow<'a, Entity>,
+    pub layer: Option<&'a Layer>,
+}
+
+impl DisplayedEntity<'_> {
+    /// Returns the color the entity is drawn with, assuming a white background.
+    pub fn color(&self) -> RGB {
+        let common = &self.entity.common;
+        if common.color_24_bit != 0 {
+            return RGB::from_i32(common.color_24_bit);
+        }
+
+        let index = if common.color.is_by_layer() {
+            if let Some(rgb) = self.layer.and_then(|l| l.true_color) {
+                return rgb;
+            }
+            self.layer.map(|l| l.color.raw_value().abs()).unwrap_or(7)
+        } else {
+            common.color.raw_value().abs()
+        };
+
+        // color 7 is drawn in the opposite of the background
+        match index {
+            7 | 0 | 256 => RGB::new(0, 0, 0),
+            _ => RGB::from_index(index).unwrap_or(RGB::new(0, 0, 0)),
+        }
+    }
+    /// Returns `true` if the entity is drawn as a filled area instead of its outline.
+    pub fn is_filled(&self) -> bool {
+        matches!(
+            self.entity.specific,
+            EntityType::Hatch(_) | EntityType::Solid(_) | EntityType::Trace(_)
+        )
+    }
+}
+
+impl Drawing {
+    /// Returns the visible model space entities, with `Insert`s expanded into their block's
+    /// entities.
+    pub(crate) fn displayed_entities(&self) -> Vec<DisplayedEntity<'_>> {
+        let mut result = vec![];
+        for entity in self.entities().filter(|e| !e.common.is_in_paper_space) {
+            match entity.specific {
+                EntityType::Insert(ref insert) => {
+                    let mut exploded = vec![];
+                    self.explode_insert_into(
+                        insert,
+                        &TransformationMatrix::identity(),
+                        Some(&entity.common),
+                        true,
+                        &mut vec![],
+                        &mut exploded,
+                    );
+                    result.extend(exploded.into_iter().map(Cow::Owned));
+                }
+                _ => result.push(Cow::Borrowed(entity)),
+            }
+        }
+
+        result
+            .into_iter()
+            .filter(|e| e.common.is_visible)
+            .filter_map(|entity| {
+                let layer = self
+                    .layers()
+                    .find(|l| l.name.eq_ignore_ascii_case(&entity.common.layer));
+                if layer.map(|l| l.is_layer_on).unwrap_or(true) {
+                    Some(DisplayedEntity { entity, layer })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+    /// Renders the model space of the `Drawing` into an image of the specified size.
+    ///
+    /// The drawing extents are scaled to fit the image and centered.  Entities are drawn in plan
+    /// view with their colors on a white background; `Hatch`, `Solid`, and `Trace` entities are
+    /// filled, and text is not drawn.  The result can be assigned to `Drawing::thumbnail`.
+    pub fn render_thumbnail(&self, width: u32, height: u32) -> DynamicImage {
+        let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+        if let (Some(extents), true) = (self.extents(), width > 0 && height > 0) {
+            let available_width = f64::from(width - 1);
+            let available_height = f64::from(height - 1);
+            let scale = [
+                (extents.width(), available_width),
+                (extents.height(), available_height),
+            ]
+            .iter()
+            .filter(|(size, _)| *size > 0.0)
+            .map(|(size, available)| available / size)
+            .fold(f64::INFINITY, f64::min);
+            let scale = if scale.is_finite() { scale } else { 1.0 };
+            let center = extents.center();
+            let rasterizer = Rasterizer {
+                scale,
+                center_x: center.x,
+                center_y: center.y,
+                half_width: available_width / 2.0,
+                half_height: available_height / 2.0,
+            };
+
+            // half a pixel is as close as a curve needs to be
+            let tolerance = 0.5 / scale;
+            for displayed in self.displayed_entities() {
+                let color = displayed.color();
+                let color = Rgb([color.r, color.g, color.b]);
+                let polylines = displayed
+                    .entity
+                    .tessellate(tolerance)
+                    .iter()
+                    .map(|points| {
+                        points
+                            .iter()
+                            .map(|p| rasterizer.project(p))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                if displayed.is_filled() {
+                    fill_polygons(&mut image, &polylines, color);
+                }
+                for points in &polylines {
+                    for segment in points.windows(2) {
+                        draw_line(&mut image, segment[0], segment[1], color);
+                    }
+                }
+            }
+        }
+
+        DynamicImage::ImageRgb8(image)
+    }
+}
+
+struct Rasterizer {
+    scale: f64,
+    center_x: f64,
+    center_y: f64,
+    half_width: f64,
+    half_height: f64,
+}
+
+impl Rasterizer {
+    /// Converts world coordinates to pixel coordinates, where Y increases downward.
+    fn project(&self, point: &Point) -> (f64, f64) {
+        (
+            self.half_width + (point.x - self.center_x) * self.scale,
+            self.half_height - (point.y - self.center_y) * self.scale,
+        )
+    }
+}
+
+fn put_pixel(image: &mut RgbImage, x: i64, y: i64, color: Rgb<u8>) {
+    if x >= 0 && y >= 0 && x < i64::from(image.width()) && y < i64::from(image.height()) {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+fn draw_line(image: &mut RgbImage, start: (f64, f64), end: (f64, f64), color: Rgb<u8>) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let steps = dx.abs().max(dy.abs()).ceil();
+    if !steps.is_finite() || steps > f64::from(u32::MAX) {
+        return;
+    }
+
+    let steps = steps as u64;
+    for i in 0..=steps {
+        let t = if steps == 0 {
+            0.0
+        } else {
+            i as f64 / steps as f64
+        };
+        put_pixel(
+            image,
+            (start.0 + dx * t).round() as i64,
+            (start.1 + dy * t).round() as i64,
+            color,
+        );
+    }
+}
+
+/// Fills the area inside the closed polygons using the even-odd rule.
+fn fill_polygons(image: &mut RgbImage, polygons: &[Vec<(f64, f64)>], color: Rgb<u8>) {
+    let edges = polygons
+        .iter()
+        .filter(|p| p.len() > 2)
+        .flat_map(|p| p.iter().zip(p.iter().cycle().skip(1)).take(p.len()))
+        .collect::<Vec<_>>();
+    let mut crossings = vec![];
+    for y in 0..image.height() {
+        let scan_y = f64::from(y);
+        crossings.clear();
+        for (a, b) in &edges {
+            if (a.1 <= scan_y) != (b.1 <= scan_y) {
+                crossings.push(a.0 + (scan_y - a.1) / (b.1 - a.1) * (b.0 - a.0));
+            }
+        }
+
+        crossings.sort_by(|a, b| a.total_cmp(b));
+        for span in crossings.chunks_exact(2) {
+            let first = span[0].round().max(0.0) as i64;
+            let last = span[1].round().min(f64::from(image.width())) as i64;
+            for x in first..=last {
+                put_pixel(image, x, i64::from(y), color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::helper_functions::tests::*;
+    use crate::tables::Layer;
+    use crate::*;
+    use image::{GenericImageView, Rgba};
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> Entity {
+        Entity::new(EntityType::Line(Line::new(
+            Point::new(x1, y1, 0.0),
+            Point::new(x2, y2, 0.0),
+        )))
+    }
+
+    #[test]
+    fn empty_drawing_renders_background() {
+        let image = Drawing::new().render_thumbnail(8, 4);
+        assert_eq!((8, 4), image.dimensions());
+        assert!(image.pixels().all(|(_, _, p)| p == WHITE));
+    }
+
+    #[test]
+    fn line_is_scaled_to_fit() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line(0.0, 0.0, 100.0, 0.0));
+        drawing.add_entity(line(0.0, 0.0, 0.0, 50.0));
+        let image = drawing.render_thumbnail(11, 11);
+        // the drawing is twice as wide as it is tall, so it's centered vertically
+        assert_eq!(BLACK, image.get_pixel(0, 8));
+        assert_eq!(BLACK, image.get_pixel(10, 8));
+        assert_eq!(BLACK, image.get_pixel(0, 3));
+        assert_eq!(WHITE, image.get_pixel(0, 2));
+        assert_eq!(WHITE, image.get_pixel(5, 5));
+    }
+
+    #[test]
+    fn entity_colors_are_used() {
+        let mut drawing = Drawing::new();
+        let mut red = line(0.0, 0.0, 10.0, 10.0);
+        red.common.color = Color::from_index(1);
+        drawing.add_entity(red);
+        let image = drawing.render_thumbnail(11, 11);
+        assert_eq!(Rgba([255, 0, 0, 255]), image.get_pixel(5, 5));
+    }
+
+    #[test]
+    fn solids_are_filled() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(Entity::new(EntityType::Solid(Solid::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(10.0, 0.0, 0.0),
+            Point::new(0.0, 10.0, 0.0),
+            Point::new(10.0, 10.0, 0.0),
+        ))));
+        let image = drawing.render_thumbnail(11, 11);
+        assert!(image.pixels().all(|(_, _, p)| p == BLACK));
+    }
+
+    #[test]
+    fn entities_on_layers_that_are_off_are_not_drawn() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("hidden"),
+            is_layer_on: false,
+            ..Default::default()
+        });
+        drawing.add_entity(line(0.0, 0.0, 10.0, 10.0));
+        let mut hidden = line(0.0, 10.0, 10.0, 0.0);
+        hidden.common.layer = String::from("hidden");
+        drawing.add_entity(hidden);
+        let image = drawing.render_thumbnail(11, 11);
+        assert_eq!(BLACK, image.get_pixel(0, 10));
+        assert_eq!(WHITE, image.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn thumbnail_is_rendered_on_save() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        drawing.add_entity(line(0.0, 0.0, 10.0, 10.0));
+        drawing.update_thumbnail_on_save = Some((16, 12));
+        let drawing = drawing_from_pairs(drawing.code_pairs().unwrap());
+        let thumbnail = drawing.thumbnail.unwrap();
+        assert_eq!((16, 12), thumbnail.dimensions());
+        assert_eq!(BLACK, thumbnail.get_pixel(2, 11));
+    }
+}
@@ -0,0 +1,449 @@
+// Evaluation and construction of `Spline` entities
+
+use std::error;
+use std::fmt;
+
+use crate::entities::Spline;
+use crate::tessellate::{basis_derivatives, find_span, NurbsCurve};
+use crate::{Point, Vector};
+
+/// Describes why a `Spline`'s control points, knots, or weights don't define a valid curve.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SplineError {
+    /// The degree is less than 1.
+    InvalidDegree(i32),
+    /// There must be more control points than the degree.
+    NotEnoughControlPoints { degree: usize, count: usize },
+    /// There must be as many knots as control points plus the degree plus one.
+    WrongKnotCount { expected: usize, actual: usize },
+    /// The knot at the specified index is less than the knot before it.
+    DecreasingKnots(usize),
+    /// The first and last knots of the valid parameter range are the same.
+    EmptyDomain,
+    /// There must be either no weights or one weight per control point.
+    WrongWeightCount { expected: usize, actual: usize },
+    /// The weight at the specified index isn't positive.
+    NonPositiveWeight(usize),
+    /// Interpolation requires at least two distinct fit points.
+    NotEnoughFitPoints,
+}
+
+impl fmt::Display for SplineError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SplineError::InvalidDegree(degree) => {
+                write!(formatter, "the degree {degree} is less than 1")
+            }
+            SplineError::NotEnoughControlPoints { degree, count } => write!(
+                formatter,
+                "a spline of degree {degree} needs more than {degree} control points, but there are {count}"
+            ),
+            SplineError::WrongKnotCount { expected, actual } => write!(
+                formatter,
+                "expected {expected} knots (control points + degree + 1), but there are {actual}"
+            ),
+            SplineError::DecreasingKnots(index) => {
+                write!(formatter, "the knot at index {index} is less than the previous knot")
+            }
+            SplineError::EmptyDomain => write!(formatter, "the parameter range is empty"),
+            SplineError::WrongWeightCount { expected, actual } => write!(
+                formatter,
+                "expected {expected} weights (one per control point), but there are {actual}"
+            ),
+            SplineError::NonPositiveWeight(index) => {
+                write!(formatter, "the weight at index {index} isn't positive")
+            }
+            SplineError::NotEnoughFitPoints => {
+                write!(formatter, "at least two distinct fit points are required")
+            }
+        }
+    }
+}
+
+impl error::Error for SplineError {}
+
+impl Spline {
+    /// Checks that the degree, control points, knots, and weights define a valid curve.  Weights
+    /// may be omitted, in which case they're all 1.0.
+    pub fn validate(&self) -> Result<(), SplineError> {
+        if self.degree_of_curve < 1 {
+            return Err(SplineError::InvalidDegree(self.degree_of_curve));
+        }
+
+        let degree = self.degree_of_curve as usize;
+        let count = self.control_points.len();
+        if count <= degree {
+            return Err(SplineError::NotEnoughControlPoints { degree, count });
+        }
+
+        if self.knot_values.len() != count + degree + 1 {
+            return Err(SplineError::WrongKnotCount {
+                expected: count + degree + 1,
+                actual: self.knot_values.len(),
+            });
+        }
+
+        if let Some(index) = self.knot_values.windows(2).position(|w| w[1] < w[0]) {
+            return Err(SplineError::DecreasingKnots(index + 1));
+        }
+
+        if self.knot_values[degree] >= self.knot_values[count] {
+            return Err(SplineError::EmptyDomain);
+        }
+
+        if !self.weight_values.is_empty() {
+            if self.weight_values.len() != count {
+                return Err(SplineError::WrongWeightCount {
+                    expected: count,
+                    actual: self.weight_values.len(),
+                });
+            }
+
+            if let Some(index) = self
+                .weight_values
+                .iter()
+                .position(|w| w.is_nan() || *w <= 0.0)
+            {
+                return Err(SplineError::NonPositiveWeight(index));
+            }
+        }
+
+        Ok(())
+    }
+    /// Returns the range of parameters that `point_at` and `derivative_at` accept.
+    pub fn domain(&self) -> Result<(f64, f64), SplineError> {
+        Ok(self.curve()?.domain())
+    }
+    /// Evaluates the curve at the parameter `t`, which is clamped to `domain()`.
+    pub fn point_at(&self, t: f64) -> Result<Point, SplineError> {
+        Ok(self.curve()?.point_at(t))
+    }
+    /// Evaluates the `order`th derivative of the curve with respect to the parameter at `t`,
+    /// which is clamped to `domain()`.  The first derivative is the tangent.
+    pub fn derivative_at(&self, t: f64, order: usize) -> Result<Vector, SplineError> {
+        Ok(self.curve()?.derivatives_at(t, order).swap_remove(order))
+    }
+    /// Approximates the length of the curve.  The result is within roughly `tolerance` of the
+    /// true length.
+    pub fn length(&self, tolerance: f64) -> Result<f64, SplineError> {
+        Ok(self.curve()?.length(tolerance))
+    }
+    /// Replaces the control points, knots, and weights with a non-rational curve that passes
+    /// through the fit points, using chord-length parameterization and averaged knots.  The
+    /// degree is lowered if there are too few fit points for it.  The start and end tangents
+    /// aren't used.
+    pub fn interpolate_fit_points(&mut self) -> Result<(), SplineError> {
+        if self.degree_of_curve < 1 {
+            return Err(SplineError::InvalidDegree(self.degree_of_curve));
+        }
+
+        // repeated points would make the system singular
+        let mut points: Vec<&Point> = vec![];
+        for point in &self.fit_points {
+            if points.last().is_none_or(|last| last.distance(point) > 0.0) {
+                points.push(point);
+            }
+        }
+        if points.len() < 2 {
+            return Err(SplineError::NotEnoughFitPoints);
+        }
+
+        let n = points.len() - 1;
+        let degree = (self.degree_of_curve as usize).min(n);
+
+        // The NURBS Book, algorithm A9.1
+        let chords: Vec<f64> = points.windows(2).map(|w| w[0].distance(w[1])).collect();
+        let total: f64 = chords.iter().sum();
+        let mut parameters = vec![0.0];
+        for chord in &chords {
+            parameters.push(parameters.last().unwrap() + chord / total);
+        }
+        parameters[n] = 1.0;
+
+        let mut knots = vec![0.0; degree + 1];
+        for j in 1..=n - degree {
+            knots.push(parameters[j..j + degree].iter().sum::<f64>() / degree as f64);
+        }
+        knots.extend(vec![1.0; degree + 1]);
+
+        let mut matrix = vec![vec![0.0; n + 1]; n + 1];
+        for (row, &t) in matrix.iter_mut().zip(&parameters) {
+            let span = find_span(&knots, degree, n + 1, t);
+            let basis = &basis_derivatives(&knots, degree, span, t, 0)[0];
+            row[span - degree..=span].copy_from_slice(basis);
+        }
+        let values = points.iter().map(|p| [p.x, p.y, p.z]).collect();
+        let control_points = solve(matrix, values).ok_or(SplineError::NotEnoughFitPoints)?;
+
+        self.degree_of_curve = degree as i32;
+        self.control_points = control_points
+            .into_iter()
+            .map(|[x, y, z]| Point::new(x, y, z))
+            .collect();
+        self.knot_values = knots;
+        self.weight_values.clear();
+        self.set_is_rational(false);
+        Ok(())
+    }
+    /// Replaces the fit points with one point on the curve per control point, evaluated at the
+    /// averages of the knots that influence each control point.  Interpolating the result
+    /// approximates the original curve.
+    pub fn compute_fit_points(&mut self) -> Result<(), SplineError> {
+        let curve = self.curve()?;
+        let degree = self.degree_of_curve as usize;
+        let fit_points = (0..self.control_points.len())
+            .map(|i| {
+                let t = self.knot_values[i + 1..=i + degree].iter().sum::<f64>() / degree as f64;
+                curve.point_at(t)
+            })
+            .collect();
+        self.fit_points = fit_points;
+        Ok(())
+    }
+    fn curve(&self) -> Result<NurbsCurve<'_>, SplineError> {
+        self.validate()?;
+        NurbsCurve::new(
+            self.degree_of_curve as usize,
+            &self.knot_values,
+            &self.control_points,
+            &self.weight_values,
+        )
+        .ok_or(SplineError::NotEnoughControlPoints {
+            degree: self.degree_of_curve as usize,
+            count: self.control_points.len(),
+        })
+    }
+}
+
+// Solves `matrix * x = values` with Gaussian elimination and partial pivoting; returns `None` if
+// the matrix is singular.
+fn solve(mut matrix: Vec<Vec<f64>>, mut values: Vec<[f64; 3]>) -> Option<Vec<[f64; 3]>> {
+    let n = matrix.len();
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        values.swap(column, pivot);
+        let (pivot_rows, rows) = matrix.split_at_mut(column + 1);
+        let pivot_row = &pivot_rows[column];
+        let (pivot_values, row_values) = values.split_at_mut(column + 1);
+        for (row, row_value) in rows.iter_mut().zip(row_values) {
+            let factor = row[column] / pivot_row[column];
+            if factor != 0.0 {
+                for (value, pivot) in row[column..].iter_mut().zip(&pivot_row[column..]) {
+                    *value -= factor * pivot;
+                }
+                for (value, pivot) in row_value.iter_mut().zip(&pivot_values[column]) {
+                    *value -= factor * pivot;
+                }
+            }
+        }
+    }
+
+    let mut result = vec![[0.0; 3]; n];
+    for row in (0..n).rev() {
+        for k in 0..3 {
+            let sum: f64 = (row + 1..n).map(|j| matrix[row][j] * result[j][k]).sum();
+            result[row][k] = (values[row][k] - sum) / matrix[row][row];
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::*;
+    use float_cmp::approx_eq;
+
+    fn parabola() -> Spline {
+        // y = 2x - x^2 from x = 0 to x = 2
+        Spline {
+            degree_of_curve: 2,
+            knot_values: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            control_points: vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 2.0, 0.0),
+                Point::new(2.0, 0.0, 0.0),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn quarter_circle() -> Spline {
+        Spline {
+            degree_of_curve: 2,
+            knot_values: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            weight_values: vec![1.0, std::f64::consts::FRAC_1_SQRT_2, 1.0],
+            control_points: vec![
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(expected: (f64, f64, f64), actual: (f64, f64, f64)) {
+        assert!(
+            approx_eq!(f64, expected.0, actual.0, epsilon = 1e-9),
+            "{expected:?} != {actual:?}"
+        );
+        assert!(
+            approx_eq!(f64, expected.1, actual.1, epsilon = 1e-9),
+            "{expected:?} != {actual:?}"
+        );
+        assert!(
+            approx_eq!(f64, expected.2, actual.2, epsilon = 1e-9),
+            "{expected:?} != {actual:?}"
+        );
+    }
+
+    #[test]
+    fn validate_knots_and_weights() {
+        assert_eq!(Ok(()), parabola().validate());
+
+        let mut spline = parabola();
+        spline.knot_values.pop();
+        assert_eq!(
+            Err(SplineError::WrongKnotCount {
+                expected: 6,
+                actual: 5
+            }),
+            spline.validate()
+        );
+
+        let mut spline = parabola();
+        spline.knot_values[3] = -1.0;
+        assert_eq!(Err(SplineError::DecreasingKnots(3)), spline.validate());
+
+        let mut spline = parabola();
+        spline.weight_values = vec![1.0, 1.0];
+        assert_eq!(
+            Err(SplineError::WrongWeightCount {
+                expected: 3,
+                actual: 2
+            }),
+            spline.validate()
+        );
+
+        let mut spline = parabola();
+        spline.degree_of_curve = 3;
+        assert!(spline.point_at(0.5).is_err());
+    }
+
+    #[test]
+    fn point_and_derivatives_of_polynomial_spline() {
+        // C(t) = (2t, 4t(1 - t))
+        let spline = parabola();
+        assert_eq!((0.0, 1.0), spline.domain().unwrap());
+        assert_close((0.5, 0.75, 0.0), spline.point_at(0.25).unwrap().tuple());
+        let d1 = spline.derivative_at(0.25, 1).unwrap();
+        assert_close((2.0, 2.0, 0.0), (d1.x, d1.y, d1.z));
+        let d2 = spline.derivative_at(0.25, 2).unwrap();
+        assert_close((0.0, -8.0, 0.0), (d2.x, d2.y, d2.z));
+        let d3 = spline.derivative_at(0.25, 3).unwrap();
+        assert_close((0.0, 0.0, 0.0), (d3.x, d3.y, d3.z));
+    }
+
+    #[test]
+    fn derivatives_of_rational_spline() {
+        let spline = quarter_circle();
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            let radius = spline.point_at(t).unwrap().to_vector();
+            let tangent = spline.derivative_at(t, 1).unwrap();
+            assert!(approx_eq!(f64, 1.0, radius.length(), epsilon = 1e-9));
+            assert!(approx_eq!(f64, 0.0, radius.dot(&tangent), epsilon = 1e-9));
+        }
+    }
+
+    #[test]
+    fn arc_length() {
+        let length = quarter_circle().length(1e-9).unwrap();
+        assert!(approx_eq!(
+            f64,
+            std::f64::consts::FRAC_PI_2,
+            length,
+            epsilon = 1e-8
+        ));
+
+        let line = Spline {
+            degree_of_curve: 1,
+            knot_values: vec![0.0, 0.0, 1.0, 2.0, 2.0],
+            control_points: vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(3.0, 4.0, 0.0),
+                Point::new(3.0, 10.0, 0.0),
+            ],
+            ..Default::default()
+        };
+        assert!(approx_eq!(
+            f64,
+            11.0,
+            line.length(1e-9).unwrap(),
+            epsilon = 1e-9
+        ));
+    }
+
+    #[test]
+    fn interpolated_curve_passes_through_fit_points() {
+        let fit_points = vec![
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 2.0, 0.0),
+            Point::new(3.0, 2.0, 1.0),
+            Point::new(4.0, 0.0, 1.0),
+            Point::new(6.0, 1.0, 0.0),
+        ];
+        let mut spline = Spline {
+            degree_of_curve: 3,
+            fit_points: fit_points.clone(),
+            ..Default::default()
+        };
+        spline.interpolate_fit_points().unwrap();
+        assert_eq!(Ok(()), spline.validate());
+        assert_eq!(5, spline.control_points.len());
+        assert_eq!(9, spline.knot_values.len());
+
+        // the fit points are at the chord-length parameters
+        let total: f64 = fit_points.windows(2).map(|w| w[0].distance(&w[1])).sum();
+        let mut t = 0.0;
+        for (i, expected) in fit_points.iter().enumerate() {
+            if i > 0 {
+                t += fit_points[i - 1].distance(expected) / total;
+            }
+            assert_close(expected.tuple(), spline.point_at(t).unwrap().tuple());
+        }
+    }
+
+    #[test]
+    fn interpolation_lowers_the_degree() {
+        let mut spline = Spline {
+            degree_of_curve: 3,
+            fit_points: vec![Point::new(0.0, 0.0, 0.0), Point::new(2.0, 0.0, 0.0)],
+            ..Default::default()
+        };
+        spline.interpolate_fit_points().unwrap();
+        assert_eq!(1, spline.degree_of_curve);
+        assert_close((1.0, 0.0, 0.0), spline.point_at(0.5).unwrap().tuple());
+
+        spline.fit_points = vec![Point::new(1.0, 1.0, 0.0), Point::new(1.0, 1.0, 0.0)];
+        assert_eq!(
+            Err(SplineError::NotEnoughFitPoints),
+            spline.interpolate_fit_points()
+        );
+    }
+
+    #[test]
+    fn fit_points_from_control_points() {
+        let mut spline = parabola();
+        spline.compute_fit_points().unwrap();
+        assert_eq!(3, spline.fit_points.len());
+        assert_close((0.0, 0.0, 0.0), spline.fit_points[0].tuple());
+        assert_close((1.0, 1.0, 0.0), spline.fit_points[1].tuple());
+        assert_close((2.0, 0.0, 0.0), spline.fit_points[2].tuple());
+    }
+}
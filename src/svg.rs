@@ -0,0 +1,512 @@
+// SVG rendering of the model space of a `Drawing`
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::entities::*;
+use crate::enums::*;
+use crate::raster::DisplayedEntity;
+use crate::tables::{Layer, LineType};
+use crate::{ocs_to_wcs_matrix, BoundingBox, Drawing, DxfResult, Point, Vector};
+
+/// The chord tolerance of curves, relative to the size of the drawing.
+const RELATIVE_TOLERANCE: f64 = 1e-4;
+
+/// The line weight used when neither the entity nor its layer specify one, in millimeters.
+const DEFAULT_LINE_WEIGHT: f64 = 0.25;
+
+/// The number of SVG pixels in a millimeter.
+const PIXELS_PER_MILLIMETER: f64 = 96.0 / 25.4;
+
+/// The width of the thinnest line, in pixels.
+const MINIMUM_STROKE_WIDTH: f64 = 1.0;
+
+impl Drawing {
+    /// Writes the model space of the `Drawing` as SVG to anything that implements the `Write`
+    /// trait.
+    ///
+    /// Entities are drawn in plan view with the colors, line types, and line weights of the
+    /// entities or their layers.  Entities on layers that are turned off are skipped, `Insert`s are
+    /// expanded, and `Hatch` boundaries are drawn as filled paths.  Text is drawn with a generic
+    /// font.
+    pub fn save_svg<T>(&self, writer: &mut T) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        let extents = self
+            .extents()
+            .unwrap_or_else(|| BoundingBox::from_point(&Point::origin()));
+        let renderer = SvgRenderer {
+            drawing: self,
+            origin: Point::new(extents.min.x, extents.max.y, 0.0),
+            tolerance: (extents.width().hypot(extents.height()) * RELATIVE_TOLERANCE)
+                .max(f64::EPSILON),
+        };
+        let width = extents.width().max(f64::EPSILON);
+        let height = extents.height().max(f64::EPSILON);
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{w}" height="{h}">"#,
+            w = number(width),
+            h = number(height)
+        )?;
+        for displayed in self.displayed_entities() {
+            renderer.write_entity(writer, &displayed)?;
+        }
+        writeln!(writer, "</svg>")?;
+        Ok(())
+    }
+    /// Writes the model space of the `Drawing` as SVG to disk, using a `BufWriter`.
+    pub fn save_file_svg(&self, path: impl AsRef<Path>) -> DxfResult<()> {
+        let file = File::create(&path)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.save_svg(&mut buf_writer)
+    }
+}
+
+struct SvgRenderer<'a> {
+    drawing: &'a Drawing,
+    /// The world coordinate of the top-left corner of the image.
+    origin: Point,
+    tolerance: f64,
+}
+
+impl SvgRenderer<'_> {
+    fn write_entity<T>(&self, writer: &mut T, displayed: &DisplayedEntity) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        let entity = &*displayed.entity;
+        let layer = displayed.layer;
+        let color = displayed.color().to_hex();
+        match entity.specific {
+            EntityType::Text(ref text) => self.write_text(
+                writer,
+                &color,
+                &text.normal,
+                &text.location,
+                &text.second_alignment_point,
+                text.text_height,
+                text.rotation,
+                text.horizontal_text_justification,
+                text.vertical_text_justification,
+                &[text.value.as_str()],
+            ),
+            EntityType::Attribute(ref att) => self.write_text(
+                writer,
+                &color,
+                &att.normal,
+                &att.location,
+                &att.second_alignment_point,
+                att.text_height,
+                att.rotation,
+                att.horizontal_text_justification,
+                att.vertical_text_justification,
+                &[att.value.as_str()],
+            ),
+            EntityType::AttributeDefinition(ref att) => self.write_text(
+                writer,
+                &color,
+                &att.normal,
+                &att.location,
+                &att.second_alignment_point,
+                att.text_height,
+                att.rotation,
+                att.horizontal_text_justification,
+                att.vertical_text_justification,
+                &[att.text_tag.as_str()],
+            ),
+            EntityType::MText(ref mtext) => self.write_mtext(writer, &color, mtext),
+            _ => {
+                let polylines = entity.tessellate(self.tolerance);
+                if polylines.is_empty() {
+                    return Ok(());
+                }
+
+                let is_filled = displayed.is_filled();
+                let mut data = String::new();
+                for points in polylines.iter().filter(|p| !p.is_empty()) {
+                    for (i, point) in points.iter().enumerate() {
+                        let (x, y) = self.project(point);
+                        data.push_str(if i == 0 { "M" } else { " L" });
+                        data.push_str(&format!("{} {}", number(x), number(y)));
+                    }
+                    if is_filled || points.len() > 2 && points.first() == points.last() {
+                        data.push_str(" Z");
+                    }
+                    data.push(' ');
+                }
+
+                if is_filled {
+                    writeln!(
+                        writer,
+                        r#"  <path d="{}" fill="{}" fill-rule="evenodd" stroke="none" />"#,
+                        data.trim_end(),
+                        color
+                    )?;
+                } else {
+                    let dashes = self.dash_array(entity, layer);
+                    writeln!(
+                        writer,
+                        r#"  <path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round" vector-effect="non-scaling-stroke"{} />"#,
+                        data.trim_end(),
+                        color,
+                        number(
+                            (self.line_weight(entity, layer) * PIXELS_PER_MILLIMETER)
+                                .max(MINIMUM_STROKE_WIDTH)
+                        ),
+                        dashes
+                            .map(|d| format!(r#" stroke-dasharray="{d}""#))
+                            .unwrap_or_default()
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+    /// Converts world coordinates to image coordinates, where Y increases downward.
+    fn project(&self, point: &Point) -> (f64, f64) {
+        (point.x - self.origin.x, self.origin.y - point.y)
+    }
+    /// Returns the line weight in millimeters.
+    fn line_weight(&self, entity: &Entity, layer: Option<&Layer>) -> f64 {
+        let raw_value = match entity.common.lineweight_enum_value {
+            -2 => layer.map(|l| l.line_weight.raw_value()).unwrap_or(-3),
+            value => value,
+        };
+        if raw_value >= 0 {
+            f64::from(raw_value) / 100.0
+        } else {
+            DEFAULT_LINE_WEIGHT
+        }
+    }
+    fn dash_array(&self, entity: &Entity, layer: Option<&Layer>) -> Option<String> {
+        let name = &entity.common.line_type_name;
+        let name = if name.eq_ignore_ascii_case("BYLAYER") {
+            layer
+                .map(|l| l.line_type_name.as_str())
+                .unwrap_or("CONTINUOUS")
+        } else {
+            name.as_str()
+        };
+        let line_type: &LineType = self
+            .drawing
+            .line_types()
+            .find(|lt| lt.name.eq_ignore_ascii_case(name))?;
+        if line_type.dash_dot_space_lengths.is_empty() {
+            return None;
+        }
+
+        // the line type alternates dashes (positive) and spaces (negative); zero is a dot
+        let scale = entity.common.line_type_scale * self.drawing.header.line_type_scale;
+        Some(
+            line_type
+                .dash_dot_space_lengths
+                .iter()
+                .map(|length| number(length.abs() * scale))
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn write_text<T>(
+        &self,
+        writer: &mut T,
+        color: &str,
+        normal: &Vector,
+        location: &Point,
+        second_alignment_point: &Point,
+        height: f64,
+        rotation: f64,
+        horizontal: HorizontalTextJustification,
+        vertical: VerticalTextJustification,
+        lines: &[&str],
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        let anchor = match horizontal {
+            HorizontalTextJustification::Left => "start",
+            HorizontalTextJustification::Right => "end",
+            _ => "middle",
+        };
+        let baseline = match (horizontal, vertical) {
+            (HorizontalTextJustification::Middle, _) | (_, VerticalTextJustification::Middle) => {
+                "middle"
+            }
+            (_, VerticalTextJustification::Top) => "hanging",
+            _ => "alphabetic",
+        };
+        let anchor_point = if horizontal == HorizontalTextJustification::Left
+            && vertical == VerticalTextJustification::Baseline
+        {
+            location
+        } else if horizontal == HorizontalTextJustification::Aligned
+            || horizontal == HorizontalTextJustification::Fit
+        {
+            // centered between the alignment points
+            &Point::new(
+                (location.x + second_alignment_point.x) / 2.0,
+                (location.y + second_alignment_point.y) / 2.0,
+                location.z,
+            )
+        } else {
+            second_alignment_point
+        };
+        let ocs = ocs_to_wcs_matrix(normal);
+        let position = ocs.transform_point(anchor_point);
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let direction = ocs.transform_vector(&Vector::new(cos, sin, 0.0));
+        self.write_text_element(
+            writer,
+            color,
+            &position,
+            direction.y.atan2(direction.x).to_degrees(),
+            height,
+            anchor,
+            baseline,
+            lines,
+        )
+    }
+    fn write_mtext<T>(&self, writer: &mut T, color: &str, mtext: &MText) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        let value = mtext.plain_text();
+        let lines = value.split('\n').collect::<Vec<_>>();
+        let anchor = match mtext.attachment_point {
+            AttachmentPoint::TopLeft
+            | AttachmentPoint::MiddleLeft
+            | AttachmentPoint::BottomLeft => "start",
+            AttachmentPoint::TopRight
+            | AttachmentPoint::MiddleRight
+            | AttachmentPoint::BottomRight => "end",
+            _ => "middle",
+        };
+        let baseline = match mtext.attachment_point {
+            AttachmentPoint::TopLeft | AttachmentPoint::TopCenter | AttachmentPoint::TopRight => {
+                "hanging"
+            }
+            AttachmentPoint::MiddleLeft
+            | AttachmentPoint::MiddleCenter
+            | AttachmentPoint::MiddleRight => "middle",
+            _ => "alphabetic",
+        };
+        let direction = if mtext.x_axis_direction.length() == 0.0 {
+            let (sin, cos) = mtext.rotation_angle.to_radians().sin_cos();
+            ocs_to_wcs_matrix(&mtext.extrusion_direction)
+                .transform_vector(&Vector::new(cos, sin, 0.0))
+        } else {
+            mtext.x_axis_direction.clone()
+        };
+        self.write_text_element(
+            writer,
+            color,
+            &mtext.insertion_point,
+            direction.y.atan2(direction.x).to_degrees(),
+            mtext.initial_text_height,
+            anchor,
+            baseline,
+            &lines,
+        )
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn write_text_element<T>(
+        &self,
+        writer: &mut T,
+        color: &str,
+        position: &Point,
+        rotation: f64,
+        height: f64,
+        anchor: &str,
+        baseline: &str,
+        lines: &[&str],
+    ) -> DxfResult<()>
+    where
+        T: Write + ?Sized,
+    {
+        let (x, y) = self.project(position);
+        let (x, y) = (number(x), number(y));
+        write!(
+            writer,
+            r#"  <text x="{x}" y="{y}" font-family="sans-serif" font-size="{}" fill="{color}" text-anchor="{anchor}" dominant-baseline="{baseline}""#,
+            number(height),
+        )?;
+        if rotation != 0.0 {
+            // image rotations are clockwise
+            write!(
+                writer,
+                r#" transform="rotate({} {x} {y})""#,
+                number(-rotation)
+            )?;
+        }
+        write!(writer, ">")?;
+        match lines {
+            [line] => write!(writer, "{}", escape(line))?,
+            _ => {
+                for (i, line) in lines.iter().enumerate() {
+                    let dy = if i == 0 { 0.0 } else { height * 5.0 / 3.0 };
+                    write!(
+                        writer,
+                        r#"<tspan x="{x}" dy="{}">{}</tspan>"#,
+                        number(dy),
+                        escape(line)
+                    )?;
+                }
+            }
+        }
+        writeln!(writer, "</text>")?;
+        Ok(())
+    }
+}
+
+/// Formats a coordinate without excessive precision.
+fn number(value: f64) -> String {
+    let rounded = (value * 1e6).round() / 1e6;
+    if rounded == 0.0 {
+        String::from("0")
+    } else {
+        format!("{rounded}")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::hatch::*;
+    use crate::tables::{Layer, LineType};
+    use crate::*;
+
+    fn to_svg(drawing: &Drawing) -> String {
+        let mut buf = vec![];
+        drawing.save_svg(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn line_on(layer: &str) -> Entity {
+        let mut line = Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(10.0, 5.0, 0.0),
+        )));
+        line.common.layer = String::from(layer);
+        line
+    }
+
+    #[test]
+    fn line_is_drawn_with_flipped_y() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line_on("0"));
+        let svg = to_svg(&drawing);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"viewBox="0 0 10 5""#));
+        assert!(svg.contains(r#"d="M0 5 L10 0""#));
+        assert!(svg.contains(r##"stroke="#000000""##));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn entities_on_layers_that_are_off_are_skipped() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("hidden"),
+            is_layer_on: false,
+            ..Default::default()
+        });
+        drawing.add_entity(line_on("0"));
+        drawing.add_entity(line_on("hidden"));
+        assert_eq!(1, to_svg(&drawing).matches("<path").count());
+    }
+
+    #[test]
+    fn by_layer_color_and_weight_come_from_the_layer() {
+        let mut drawing = Drawing::new();
+        drawing.add_layer(Layer {
+            name: String::from("red"),
+            color: Color::from_index(1),
+            line_weight: LineWeight::from_raw_value(100),
+            ..Default::default()
+        });
+        let mut line = line_on("red");
+        line.common.lineweight_enum_value = LineWeight::by_layer().raw_value();
+        drawing.add_entity(line);
+        let svg = to_svg(&drawing);
+        assert!(svg.contains(r##"stroke="#FF0000""##));
+        assert!(svg.contains(r#"stroke-width="3.779528""#));
+    }
+
+    #[test]
+    fn true_color_overrides_the_color_index() {
+        let mut drawing = Drawing::new();
+        let mut line = line_on("0");
+        line.common.color = Color::from_index(1);
+        line.common.color_24_bit = 0x12_34_56;
+        drawing.add_entity(line);
+        assert!(to_svg(&drawing).contains(r##"stroke="#123456""##));
+    }
+
+    #[test]
+    fn line_type_pattern_becomes_dash_array() {
+        let mut drawing = Drawing::new();
+        drawing.header.line_type_scale = 2.0;
+        drawing.add_line_type(LineType {
+            name: String::from("DASHED"),
+            total_pattern_length: 0.75,
+            dash_dot_space_lengths: vec![0.5, -0.25],
+            ..Default::default()
+        });
+        let mut line = line_on("0");
+        line.common.line_type_name = String::from("DASHED");
+        drawing.add_entity(line);
+        assert!(to_svg(&drawing).contains(r#"stroke-dasharray="1 0.5""#));
+    }
+
+    #[test]
+    fn hatch_is_drawn_as_filled_path() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line_on("0"));
+        let vertex = |x, y| HatchPolylineVertex {
+            x,
+            y,
+            ..Default::default()
+        };
+        let mut hatch = Hatch::default();
+        hatch.pattern_boundary_data.push(HatchPatternBoundaryData {
+            path: BoundaryPath::Polyline(PolylineBoundaryData {
+                is_closed: true,
+                vertices: vec![vertex(0.0, 0.0), vertex(10.0, 0.0), vertex(10.0, 5.0)],
+            }),
+            ..Default::default()
+        });
+        let mut hatch = Entity::new(EntityType::Hatch(hatch));
+        hatch.common.color = Color::from_index(5);
+        drawing.add_entity(hatch);
+        let svg = to_svg(&drawing);
+        assert!(svg.contains(r##"fill="#0000FF" fill-rule="evenodd""##));
+        assert!(svg.contains(" Z\""));
+    }
+
+    #[test]
+    fn text_is_escaped_and_rotated() {
+        let mut drawing = Drawing::new();
+        drawing.add_entity(line_on("0"));
+        let text = Text {
+            location: Point::new(1.0, 1.0, 0.0),
+            text_height: 2.0,
+            rotation: 90.0,
+            value: String::from("a < b & c"),
+            ..Default::default()
+        };
+        drawing.add_entity(Entity::new(EntityType::Text(text)));
+        let svg = to_svg(&drawing);
+        assert!(svg.contains(">a &lt; b &amp; c</text>"));
+        assert!(svg.contains(r#"transform="rotate(-90 "#));
+    }
+}
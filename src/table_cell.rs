@@ -0,0 +1,239 @@
+use crate::code_pair_put_back::CodePairPutBack;
+use crate::enums::*;
+use crate::helper_functions::*;
+use crate::objects::*;
+use crate::tables::BlockRecord;
+use crate::{CodePair, Drawing, DrawingItem, DxfError, DxfResult, Handle};
+
+use enum_primitive::FromPrimitive;
+
+/// A single cell of a `Table` entity.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableCell {
+    pub cell_type: TableCellType,
+    pub flags: i16,
+    pub is_merged: bool,
+    pub is_autofit: bool,
+    /// The number of columns covered by this cell.
+    pub column_span: i16,
+    /// The number of rows covered by this cell.
+    pub row_span: i16,
+    pub override_flags: i32,
+    pub virtual_edge_flags: i16,
+    pub rotation: f64,
+    #[doc(hidden)]
+    pub __field_handle: Handle,
+    /// The text of a text cell.
+    pub text: String,
+    pub text_style_name: String,
+    #[doc(hidden)]
+    pub __block_record_handle: Handle,
+    /// The scale of the block in a block cell.
+    pub block_scale: f64,
+    /// The attribute values of the block in a block cell.
+    pub attribute_values: Vec<TableCellAttribute>,
+    /// Cell values not otherwise supported by this library, preserved as they were read.
+    pub unknown_pairs: Vec<CodePair>,
+}
+
+impl Default for TableCell {
+    fn default() -> Self {
+        TableCell {
+            cell_type: TableCellType::Text,
+            flags: 0,
+            is_merged: false,
+            is_autofit: false,
+            column_span: 1,
+            row_span: 1,
+            override_flags: 0,
+            virtual_edge_flags: 0,
+            rotation: 0.0,
+            __field_handle: Handle::empty(),
+            text: String::new(),
+            text_style_name: String::new(),
+            __block_record_handle: Handle::empty(),
+            block_scale: 1.0,
+            attribute_values: vec![],
+            unknown_pairs: vec![],
+        }
+    }
+}
+
+impl TableCell {
+    pub fn new_text(text: &str) -> Self {
+        TableCell {
+            text: String::from(text),
+            ..Default::default()
+        }
+    }
+    /// The `FIELD` object that computes the text of this cell, if any.
+    pub fn field<'a>(&self, drawing: &'a Drawing) -> Option<&'a Object> {
+        match drawing.item_by_handle(self.__field_handle) {
+            Some(DrawingItem::Object(val)) => match val.specific {
+                ObjectType::Field(_) => Some(val),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    pub fn set_field(&mut self, item: &Object) -> DxfResult<()> {
+        match item.specific {
+            ObjectType::Field { .. } => self.__field_handle = item.common.handle,
+            _ => return Err(DxfError::WrongItemType),
+        }
+
+        Ok(())
+    }
+    pub fn block_record<'a>(&self, drawing: &'a Drawing) -> Option<&'a BlockRecord> {
+        match drawing.item_by_handle(self.__block_record_handle) {
+            Some(DrawingItem::BlockRecord(val)) => Some(val),
+            _ => None,
+        }
+    }
+    pub fn set_block_record(&mut self, item: &BlockRecord) {
+        self.cell_type = TableCellType::Block;
+        self.__block_record_handle = DrawingItem::BlockRecord(item).handle();
+    }
+}
+
+/// An attribute value of the block in a `TableCell`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableCellAttribute {
+    #[doc(hidden)]
+    pub __attribute_definition_handle: Handle,
+    pub value: String,
+}
+
+impl Default for TableCellAttribute {
+    fn default() -> Self {
+        TableCellAttribute {
+            __attribute_definition_handle: Handle::empty(),
+            value: String::new(),
+        }
+    }
+}
+
+/// A rectangular range of cells in a `Table` that have been merged into one.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableCellRange {
+    pub first_row: usize,
+    pub first_column: usize,
+    pub last_row: usize,
+    pub last_column: usize,
+}
+
+// internal visibility only
+impl TableCell {
+    pub(crate) fn apply_code_pair(&mut self, pair: &CodePair) -> DxfResult<bool> {
+        match pair.code {
+            172 => self.flags = pair.assert_i16()?,
+            173 => self.is_merged = as_bool(pair.assert_i16()?),
+            174 => self.is_autofit = as_bool(pair.assert_i16()?),
+            175 => self.column_span = pair.assert_i16()?,
+            176 => self.row_span = pair.assert_i16()?,
+            91 => self.override_flags = pair.assert_i32()?,
+            178 => self.virtual_edge_flags = pair.assert_i16()?,
+            145 => self.rotation = pair.assert_f64()?,
+            344 => self.__field_handle = pair.as_handle()?,
+            // long text is split with the leading parts in code 2 and the final part in code 1
+            1 | 2 => self.text.push_str(&pair.assert_string()?),
+            7 => self.text_style_name = pair.assert_string()?,
+            340 => self.__block_record_handle = pair.as_handle()?,
+            144 => self.block_scale = pair.assert_f64()?,
+            179 => (), // attribute count; implied by the collection
+            331 => self.attribute_values.push(TableCellAttribute {
+                __attribute_definition_handle: pair.as_handle()?,
+                ..Default::default()
+            }),
+            300 => {
+                if let Some(att) = self.attribute_values.last_mut() {
+                    att.value = pair.assert_string()?;
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+    pub(crate) fn new_from_type(value: i16) -> TableCell {
+        TableCell {
+            cell_type: enum_from_number!(TableCellType, Text, from_i16, value),
+            ..Default::default()
+        }
+    }
+    /// Skips the cached value that follows `301/CELL_VALUE` up to and including `304/ACVALUE_END`.
+    ///
+    /// The displayed text is recovered from the cached value when the cell has no other text.
+    pub(crate) fn read_cell_value(&mut self, iter: &mut CodePairPutBack) -> DxfResult<()> {
+        let mut value_text = String::new();
+        let mut formatted_text = String::new();
+        loop {
+            let pair = next_block_pair(iter)?;
+            match pair.code {
+                304 if pair.assert_string()? == "ACVALUE_END" => break,
+                1 | 2 => value_text.push_str(&pair.assert_string()?),
+                302 => formatted_text = pair.assert_string()?,
+                _ => (),
+            }
+        }
+
+        if self.text.is_empty() {
+            self.text = if value_text.is_empty() {
+                formatted_text
+            } else {
+                value_text
+            };
+        }
+
+        Ok(())
+    }
+    pub(crate) fn add_code_pairs(&self, pairs: &mut Vec<CodePair>) {
+        pairs.push(CodePair::new_i16(171, self.cell_type as i16));
+        pairs.push(CodePair::new_i16(172, self.flags));
+        pairs.push(CodePair::new_i16(173, as_i16(self.is_merged)));
+        pairs.push(CodePair::new_i16(174, as_i16(self.is_autofit)));
+        pairs.push(CodePair::new_i16(175, self.column_span));
+        pairs.push(CodePair::new_i16(176, self.row_span));
+        pairs.push(CodePair::new_i32(91, self.override_flags));
+        pairs.push(CodePair::new_i16(178, self.virtual_edge_flags));
+        pairs.push(CodePair::new_f64(145, self.rotation));
+        match self.cell_type {
+            TableCellType::Text => {
+                if !self.__field_handle.is_empty() {
+                    pairs.push(CodePair::new_string(344, &self.__field_handle.as_string()));
+                }
+                let chars = self.text.chars().collect::<Vec<_>>();
+                let mut chunks = chars.chunks(250).peekable();
+                if chunks.peek().is_none() {
+                    pairs.push(CodePair::new_str(1, ""));
+                }
+                while let Some(chunk) = chunks.next() {
+                    let code = if chunks.peek().is_some() { 2 } else { 1 };
+                    pairs.push(CodePair::new_string(
+                        code,
+                        &chunk.iter().collect::<String>(),
+                    ));
+                }
+                pairs.push(CodePair::new_string(7, &self.text_style_name));
+            }
+            TableCellType::Block => {
+                pairs.push(CodePair::new_string(
+                    340,
+                    &self.__block_record_handle.as_string(),
+                ));
+                pairs.push(CodePair::new_f64(144, self.block_scale));
+                pairs.push(CodePair::new_i16(179, self.attribute_values.len() as i16));
+                for att in &self.attribute_values {
+                    pairs.push(CodePair::new_string(
+                        331,
+                        &att.__attribute_definition_handle.as_string(),
+                    ));
+                    pairs.push(CodePair::new_string(300, &att.value));
+                }
+            }
+        }
+        pairs.extend(self.unknown_pairs.iter().cloned());
+    }
+}
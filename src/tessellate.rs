@@ -0,0 +1,1015 @@
+use std::f64::consts::PI;
+
+use crate::entities::*;
+use crate::hatch::*;
+use crate::{ocs_to_wcs_matrix, Point, TransformationMatrix, Vector};
+
+/// The most segments used to approximate a single curve.
+const MAX_SEGMENTS: usize = 10_000;
+
+/// The deepest interval subdivision used when approximating a spline.
+const MAX_SPLINE_DEPTH: u32 = 16;
+
+impl Entity {
+    /// Approximates the entity with line segments, returned as a collection of polylines in world
+    /// coordinates.
+    ///
+    /// No point of a curve is farther than `tolerance` from its approximation.  Closed shapes
+    /// repeat their first point at the end.  Entities without line work (e.g., text, `Insert`s, or
+    /// infinite lines) return an empty collection; use `Drawing::explode_insert()` to tessellate
+    /// the contents of a block.
+    pub fn tessellate(&self, tolerance: f64) -> Vec<Vec<Point>> {
+        let tolerance = if tolerance.is_finite() && tolerance > 0.0 {
+            tolerance
+        } else {
+            f64::EPSILON
+        };
+        match self.specific {
+            EntityType::Face3D(ref face) => vec![closed(vec![
+                face.first_corner.clone(),
+                face.second_corner.clone(),
+                face.third_corner.clone(),
+                face.fourth_corner.clone(),
+            ])],
+            EntityType::Arc(ref arc) => {
+                let ocs = ocs_to_wcs_matrix(&arc.normal);
+                let start = arc.start_angle.to_radians();
+                let sweep = arc_sweep(start, arc.end_angle.to_radians());
+                vec![circular_arc(
+                    &ocs,
+                    &arc.center,
+                    arc.radius,
+                    start,
+                    sweep,
+                    tolerance,
+                )]
+            }
+            EntityType::Circle(ref circle) => {
+                let ocs = ocs_to_wcs_matrix(&circle.normal);
+                vec![circular_arc(
+                    &ocs,
+                    &circle.center,
+                    circle.radius,
+                    0.0,
+                    PI * 2.0,
+                    tolerance,
+                )]
+            }
+            EntityType::Ellipse(ref ellipse) => {
+                let minor_axis = ellipse.normal.normalized().cross(&ellipse.major_axis);
+                let minor_axis = scaled(&minor_axis, ellipse.minor_axis_ratio);
+                vec![elliptic_arc(
+                    &ellipse.center,
+                    &ellipse.major_axis,
+                    &minor_axis,
+                    ellipse.start_parameter,
+                    arc_sweep(ellipse.start_parameter, ellipse.end_parameter),
+                    tolerance,
+                )]
+            }
+            EntityType::Hatch(ref hatch) => hatch_boundaries(hatch, tolerance),
+            EntityType::Helix(ref helix) => vec![helix_points(helix, tolerance)],
+            EntityType::Leader(ref leader) => vec![leader.vertices.clone()],
+            EntityType::Line(ref line) => vec![vec![line.p1.clone(), line.p2.clone()]],
+            EntityType::LwPolyline(ref poly) => {
+                let ocs = ocs_to_wcs_matrix(&poly.extrusion_direction);
+                let vertices = poly
+                    .vertices
+                    .iter()
+                    .map(|v| (Point::new(v.x, v.y, self.common.elevation), v.bulge))
+                    .collect::<Vec<_>>();
+                vec![bulged_polyline(
+                    &ocs,
+                    &vertices,
+                    poly.is_closed(),
+                    tolerance,
+                )]
+            }
+            EntityType::MLine(ref mline) => {
+                if mline.is_closed() {
+                    vec![closed(mline.vertices.clone())]
+                } else {
+                    vec![mline.vertices.clone()]
+                }
+            }
+            EntityType::Polyline(ref poly) => polyline(poly, tolerance),
+            EntityType::Solid(ref solid) => vec![ocs_quadrilateral(
+                &solid.extrusion_direction,
+                [
+                    &solid.first_corner,
+                    &solid.second_corner,
+                    &solid.third_corner,
+                    &solid.fourth_corner,
+                ],
+            )],
+            EntityType::Spline(ref spline) => vec![spline_points(spline, tolerance)],
+            EntityType::Trace(ref trace) => vec![ocs_quadrilateral(
+                &trace.extrusion_direction,
+                [
+                    &trace.first_corner,
+                    &trace.second_corner,
+                    &trace.third_corner,
+                    &trace.fourth_corner,
+                ],
+            )],
+            _ => vec![],
+        }
+    }
+}
+
+/// Returns the counter-clockwise angle from `start` to `end`, both in radians.
+fn arc_sweep(start: f64, end: f64) -> f64 {
+    if (end - start).abs() >= PI * 2.0 {
+        PI * 2.0
+    } else {
+        let sweep = (end - start).rem_euclid(PI * 2.0);
+        if sweep == 0.0 {
+            PI * 2.0
+        } else {
+            sweep
+        }
+    }
+}
+
+/// The number of segments needed to keep the chords of an arc within `tolerance` of the arc.
+fn segment_count(radius: f64, sweep: f64, tolerance: f64) -> usize {
+    let radius = radius.abs();
+    let step = if tolerance >= radius {
+        PI
+    } else {
+        // `2 * acos(1 - x)`, written so that tiny ratios don't round to a step of zero
+        4.0 * (tolerance / radius / 2.0).sqrt().asin()
+    };
+
+    // never use fewer than one segment per quarter turn
+    let count = (sweep.abs() / step.min(PI / 2.0)).ceil();
+    if count.is_nan() {
+        1
+    } else {
+        // a step too small to represent needs as many segments as allowed
+        (count as usize).clamp(1, MAX_SEGMENTS)
+    }
+}
+
+fn scaled(v: &Vector, factor: f64) -> Vector {
+    Vector::new(v.x * factor, v.y * factor, v.z * factor)
+}
+
+fn closed(mut points: Vec<Point>) -> Vec<Point> {
+    points.dedup();
+    if let Some(first) = points.first().cloned() {
+        points.push(first);
+    }
+    points
+}
+
+/// Returns the points of `center + u * cos(t) + v * sin(t)` from `start` through `start + sweep`.
+fn elliptic_arc(
+    center: &Point,
+    u: &Vector,
+    v: &Vector,
+    start: f64,
+    sweep: f64,
+    tolerance: f64,
+) -> Vec<Point> {
+    // for a given parameter step, the chord error is no more than that of a circle with the
+    // radius of the major axis
+    let radius = u.length().max(v.length());
+    let count = segment_count(radius, sweep, tolerance);
+    let mut points = (0..=count)
+        .map(|i| {
+            let t = start + sweep * i as f64 / count as f64;
+            let (sin, cos) = t.sin_cos();
+            Point::new(
+                center.x + u.x * cos + v.x * sin,
+                center.y + u.y * cos + v.y * sin,
+                center.z + u.z * cos + v.z * sin,
+            )
+        })
+        .collect::<Vec<_>>();
+    if sweep.abs() >= PI * 2.0 {
+        points[count] = points[0].clone();
+    }
+    points
+}
+
+/// Returns the points of an arc specified in an object coordinate system.
+fn circular_arc(
+    ocs: &TransformationMatrix,
+    center: &Point,
+    radius: f64,
+    start: f64,
+    sweep: f64,
+    tolerance: f64,
+) -> Vec<Point> {
+    elliptic_arc(
+        &ocs.transform_point(center),
+        &ocs.transform_vector(&Vector::new(radius, 0.0, 0.0)),
+        &ocs.transform_vector(&Vector::new(0.0, radius, 0.0)),
+        start,
+        sweep,
+        tolerance,
+    )
+}
+
+/// Returns the points of a polyline in an object coordinate system whose segments may be bulged.
+fn bulged_polyline(
+    ocs: &TransformationMatrix,
+    vertices: &[(Point, f64)],
+    is_closed: bool,
+    tolerance: f64,
+) -> Vec<Point> {
+    let mut result = vec![];
+    for (i, (point, bulge)) in vertices.iter().enumerate() {
+        let next = match vertices.get(i + 1) {
+            Some((next, _)) => next,
+            None if is_closed && vertices.len() > 1 => &vertices[0].0,
+            None => {
+                result.push(ocs.transform_point(point));
+                break;
+            }
+        };
+        let segment = bulged_segment(ocs, point, next, *bulge, tolerance);
+        // the last point of each segment is the first point of the next
+        result.extend_from_slice(&segment[..segment.len() - 1]);
+        if i == vertices.len() - 1 {
+            result.push(segment[segment.len() - 1].clone());
+        }
+    }
+    result
+}
+
+fn bulged_segment(
+    ocs: &TransformationMatrix,
+    start: &Point,
+    end: &Point,
+    bulge: f64,
+    tolerance: f64,
+) -> Vec<Point> {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let chord = dx.hypot(dy);
+    if bulge == 0.0 || chord == 0.0 {
+        return vec![ocs.transform_point(start), ocs.transform_point(end)];
+    }
+
+    // the center is offset from the midpoint of the chord to the left for counter-clockwise arcs
+    let offset = chord / 2.0 * (1.0 - bulge * bulge) / (2.0 * bulge);
+    let center = Point::new(
+        (start.x + end.x) / 2.0 - dy / chord * offset,
+        (start.y + end.y) / 2.0 + dx / chord * offset,
+        start.z,
+    );
+    let radius = (start.x - center.x).hypot(start.y - center.y);
+    let start_angle = (start.y - center.y).atan2(start.x - center.x);
+    let sweep = 4.0 * bulge.atan();
+    let mut points = circular_arc(ocs, &center, radius, start_angle, sweep, tolerance);
+
+    // avoid accumulated error at the vertices
+    let last = points.len() - 1;
+    points[0] = ocs.transform_point(start);
+    points[last] = ocs.transform_point(end);
+    points
+}
+
+fn polyline(poly: &Polyline, tolerance: f64) -> Vec<Vec<Point>> {
+    let vertices = poly.vertices().collect::<Vec<_>>();
+    if poly.is_polyface_mesh() {
+        let locations = vertices
+            .iter()
+            .filter(|v| v.is_3d_polygon_mesh())
+            .map(|v| &v.location)
+            .collect::<Vec<_>>();
+        vertices
+            .iter()
+            .filter(|v| v.is_polyface_mesh_vertex() && !v.is_3d_polygon_mesh())
+            .map(|face| {
+                let indices = [
+                    face.polyface_mesh_vertex_index1,
+                    face.polyface_mesh_vertex_index2,
+                    face.polyface_mesh_vertex_index3,
+                    face.polyface_mesh_vertex_index4,
+                ];
+                // indices are 1-based and negative for invisible edges; zero is unused
+                closed(
+                    indices
+                        .iter()
+                        .filter(|&&index| index != 0)
+                        .filter_map(|index| locations.get(index.unsigned_abs() as usize - 1))
+                        .map(|&location| location.clone())
+                        .collect(),
+                )
+            })
+            .filter(|face| face.len() > 1)
+            .collect()
+    } else if poly.is_3d_polygon_mesh() {
+        let m = poly.polygon_mesh_m_vertex_count.max(0) as usize;
+        let n = poly.polygon_mesh_n_vertex_count.max(0) as usize;
+        if m * n == 0 || m * n > vertices.len() {
+            return vec![];
+        }
+
+        let at = |row: usize, column: usize| vertices[row * n + column].location.clone();
+        let mut result = vec![];
+        for row in 0..m {
+            let line = (0..n).map(|column| at(row, column)).collect::<Vec<_>>();
+            result.push(if poly.is_polygon_mesh_closed_in_n_direction() {
+                closed(line)
+            } else {
+                line
+            });
+        }
+        for column in 0..n {
+            let line = (0..m).map(|row| at(row, column)).collect::<Vec<_>>();
+            result.push(if poly.is_closed() { closed(line) } else { line });
+        }
+        result
+    } else if poly.is_3d_polyline() {
+        let points = vertices.iter().map(|v| v.location.clone()).collect();
+        vec![if poly.is_closed() {
+            closed(points)
+        } else {
+            points
+        }]
+    } else {
+        let ocs = ocs_to_wcs_matrix(&poly.normal);
+        let vertices = vertices
+            .iter()
+            .map(|v| {
+                (
+                    Point::new(v.location.x, v.location.y, poly.location.z),
+                    v.bulge,
+                )
+            })
+            .collect::<Vec<_>>();
+        vec![bulged_polyline(
+            &ocs,
+            &vertices,
+            poly.is_closed(),
+            tolerance,
+        )]
+    }
+}
+
+fn ocs_quadrilateral(normal: &Vector, corners: [&Point; 4]) -> Vec<Point> {
+    // the third and fourth corners are stored in the opposite order of the outline
+    let ocs = ocs_to_wcs_matrix(normal);
+    closed(
+        [corners[0], corners[1], corners[3], corners[2]]
+            .iter()
+            .map(|&p| ocs.transform_point(p))
+            .collect(),
+    )
+}
+
+fn helix_points(helix: &Helix, tolerance: f64) -> Vec<Point> {
+    let axis = helix.axis_vector.normalized();
+    let base = &helix.axis_base_point;
+    let start = Vector::new(
+        helix.start_point.x - base.x,
+        helix.start_point.y - base.y,
+        helix.start_point.z - base.z,
+    );
+    let along = start.dot(&axis);
+    let radial = Vector::new(
+        start.x - axis.x * along,
+        start.y - axis.y * along,
+        start.z - axis.z * along,
+    );
+    let radius = radial.length();
+    let u = radial;
+    let v = if helix.is_right_handed {
+        axis.cross(&u)
+    } else {
+        u.cross(&axis)
+    };
+    let sweep = PI * 2.0 * helix.number_of_turns;
+    let count = segment_count(radius, sweep, tolerance);
+    (0..=count)
+        .map(|i| {
+            let fraction = i as f64 / count as f64;
+            let (sin, cos) = (sweep * fraction).sin_cos();
+            let height = along + helix.turn_height * helix.number_of_turns * fraction;
+            Point::new(
+                base.x + u.x * cos + v.x * sin + axis.x * height,
+                base.y + u.y * cos + v.y * sin + axis.y * height,
+                base.z + u.z * cos + v.z * sin + axis.z * height,
+            )
+        })
+        .collect()
+}
+
+fn hatch_boundaries(hatch: &Hatch, tolerance: f64) -> Vec<Vec<Point>> {
+    let z = hatch.elevation_point.z;
+    let identity = TransformationMatrix::identity();
+    hatch
+        .pattern_boundary_data
+        .iter()
+        .map(|boundary| match boundary.path {
+            BoundaryPath::Polyline(ref poly) => {
+                let vertices = poly
+                    .vertices
+                    .iter()
+                    .map(|v| (Point::new(v.x, v.y, z), v.bulge))
+                    .collect::<Vec<_>>();
+                bulged_polyline(&identity, &vertices, poly.is_closed, tolerance)
+            }
+            BoundaryPath::Edge(ref edges) => {
+                let mut result: Vec<Point> = vec![];
+                for edge in &edges.edges {
+                    let points = hatch_edge(edge, z, tolerance);
+                    let skip = match (result.last(), points.first()) {
+                        (Some(last), Some(first)) if last == first => 1,
+                        _ => 0,
+                    };
+                    result.extend(points.into_iter().skip(skip));
+                }
+                result
+            }
+        })
+        .filter(|points| !points.is_empty())
+        .collect()
+}
+
+fn hatch_edge(edge: &EdgePath, z: f64, tolerance: f64) -> Vec<Point> {
+    let at_z = |p: &Point| Point::new(p.x, p.y, z);
+
+    // clockwise arcs store their angles mirrored across the X axis and are traced backwards
+    let range = |start: f64, end: f64, is_counter_clockwise: bool| {
+        if is_counter_clockwise {
+            let start = start.to_radians();
+            (start, arc_sweep(start, end.to_radians()))
+        } else {
+            let start = -start.to_radians();
+            (start, -arc_sweep(-end.to_radians(), start))
+        }
+    };
+    match edge {
+        EdgePath::Line(ref line) => vec![at_z(&line.p1), at_z(&line.p2)],
+        EdgePath::CircularArc(ref arc) => {
+            let (start, sweep) = range(arc.start_angle, arc.end_angle, arc.is_counter_clockwise);
+            circular_arc(
+                &TransformationMatrix::identity(),
+                &at_z(&arc.center),
+                arc.radius,
+                start,
+                sweep,
+                tolerance,
+            )
+        }
+        EdgePath::EllipticArc(ref arc) => {
+            let (start, sweep) = range(arc.start_angle, arc.end_angle, arc.is_counter_clockwise);
+            let minor_axis = Vector::new(
+                -arc.major_axis.y * arc.minor_axis_ratio,
+                arc.major_axis.x * arc.minor_axis_ratio,
+                0.0,
+            );
+            elliptic_arc(
+                &at_z(&arc.center),
+                &arc.major_axis,
+                &minor_axis,
+                start,
+                sweep,
+                tolerance,
+            )
+        }
+        EdgePath::Spline(ref spline) => {
+            let weights = spline
+                .weights
+                .iter()
+                .map(|w| w.unwrap_or(1.0))
+                .collect::<Vec<_>>();
+            let control_points = spline.control_points.iter().map(at_z).collect::<Vec<_>>();
+            match NurbsCurve::new(
+                spline.degree.max(1) as usize,
+                &spline.knots,
+                &control_points,
+                &weights,
+            ) {
+                Some(curve) => curve.tessellate(tolerance),
+                None => spline.fit_points.iter().map(at_z).collect(),
+            }
+        }
+    }
+}
+
+fn spline_points(spline: &Spline, tolerance: f64) -> Vec<Point> {
+    match NurbsCurve::new(
+        spline.degree_of_curve.max(1) as usize,
+        &spline.knot_values,
+        &spline.control_points,
+        &spline.weight_values,
+    ) {
+        Some(curve) => curve.tessellate(tolerance),
+        // without control points, the fit points are the best available approximation
+        None => spline.fit_points.clone(),
+    }
+}
+
+/// A non-uniform rational B-spline curve.
+pub(crate) struct NurbsCurve<'a> {
+    degree: usize,
+    knots: Vec<f64>,
+    control_points: &'a [Point],
+    weights: Vec<f64>,
+}
+
+impl<'a> NurbsCurve<'a> {
+    /// Returns `None` if there aren't enough control points for the degree.  Missing or mismatched
+    /// knots are replaced with a clamped uniform knot vector and missing weights default to 1.0.
+    pub(crate) fn new(
+        degree: usize,
+        knots: &[f64],
+        control_points: &'a [Point],
+        weights: &[f64],
+    ) -> Option<Self> {
+        let count = control_points.len();
+        if count <= degree {
+            return None;
+        }
+
+        let knots = if knots.len() == count + degree + 1 && knots.windows(2).all(|w| w[0] <= w[1]) {
+            knots.to_vec()
+        } else {
+            let spans = (count - degree) as f64;
+            (0..count + degree + 1)
+                .map(|i| (i.saturating_sub(degree) as f64 / spans).min(1.0))
+                .collect()
+        };
+        let weights = if weights.len() == count {
+            weights.to_vec()
+        } else {
+            vec![1.0; count]
+        };
+        Some(NurbsCurve {
+            degree,
+            knots,
+            control_points,
+            weights,
+        })
+    }
+    /// The valid range of the curve parameter.
+    pub(crate) fn domain(&self) -> (f64, f64) {
+        (
+            self.knots[self.degree],
+            self.knots[self.control_points.len()],
+        )
+    }
+    /// Evaluates the curve with de Boor's algorithm.
+    pub(crate) fn point_at(&self, t: f64) -> Point {
+        let p = self.degree;
+        let n = self.control_points.len();
+        let (min, max) = self.domain();
+        let t = t.clamp(min, max);
+        let span = find_span(&self.knots, p, n, t);
+
+        // homogeneous coordinates
+        let mut d = (0..=p)
+            .map(|j| {
+                let point = &self.control_points[span - p + j];
+                let w = self.weights[span - p + j];
+                [point.x * w, point.y * w, point.z * w, w]
+            })
+            .collect::<Vec<_>>();
+        for r in 1..=p {
+            for j in (r..=p).rev() {
+                let i = span - p + j;
+                let denominator = self.knots[i + p + 1 - r] - self.knots[i];
+                let alpha = if denominator == 0.0 {
+                    0.0
+                } else {
+                    (t - self.knots[i]) / denominator
+                };
+                let previous = d[j - 1];
+                for (value, previous) in d[j].iter_mut().zip(previous) {
+                    *value = (1.0 - alpha) * previous + alpha * *value;
+                }
+            }
+        }
+
+        let [x, y, z, w] = d[p];
+        if w == 0.0 {
+            Point::new(x, y, z)
+        } else {
+            Point::new(x / w, y / w, z / w)
+        }
+    }
+    /// Returns the position followed by the first `order` derivatives of the curve at `t`.
+    pub(crate) fn derivatives_at(&self, t: f64, order: usize) -> Vec<Vector> {
+        let p = self.degree;
+        let (min, max) = self.domain();
+        let t = t.clamp(min, max);
+        let span = find_span(&self.knots, p, self.control_points.len(), t);
+        let basis = basis_derivatives(&self.knots, p, span, t, order);
+
+        // derivatives of the curve in homogeneous coordinates
+        let mut homogeneous = vec![[0.0; 4]; order + 1];
+        for (derivative, basis) in homogeneous.iter_mut().zip(&basis) {
+            for (j, b) in basis.iter().enumerate() {
+                let point = &self.control_points[span - p + j];
+                let w = self.weights[span - p + j];
+                derivative[0] += b * point.x * w;
+                derivative[1] += b * point.y * w;
+                derivative[2] += b * point.z * w;
+                derivative[3] += b * w;
+            }
+        }
+
+        // project back with the quotient rule
+        let mut result: Vec<Vector> = vec![];
+        for k in 0..=order {
+            let [x, y, z, _] = homogeneous[k];
+            let mut v = Vector::new(x, y, z);
+            for i in 1..=k {
+                v = v - result[k - i].clone() * (binomial(k, i) * homogeneous[i][3]);
+            }
+            let w = homogeneous[0][3];
+            result.push(if w == 0.0 { v } else { v * (1.0 / w) });
+        }
+        result
+    }
+    /// Approximates the arc length with adaptive Gauss-Legendre quadrature over each knot span.
+    pub(crate) fn length(&self, tolerance: f64) -> f64 {
+        let mut breaks = self.knots[self.degree..=self.control_points.len()].to_vec();
+        breaks.dedup();
+        breaks
+            .windows(2)
+            .map(|span| {
+                let estimate = self.gauss_length(span[0], span[1]);
+                self.adaptive_length(span[0], span[1], estimate, tolerance, 0)
+            })
+            .sum()
+    }
+    fn adaptive_length(&self, t0: f64, t1: f64, estimate: f64, tolerance: f64, depth: u32) -> f64 {
+        let mid = (t0 + t1) / 2.0;
+        let left = self.gauss_length(t0, mid);
+        let right = self.gauss_length(mid, t1);
+        if depth >= MAX_SPLINE_DEPTH || (left + right - estimate).abs() <= tolerance {
+            left + right
+        } else {
+            self.adaptive_length(t0, mid, left, tolerance / 2.0, depth + 1)
+                + self.adaptive_length(mid, t1, right, tolerance / 2.0, depth + 1)
+        }
+    }
+    fn gauss_length(&self, t0: f64, t1: f64) -> f64 {
+        // 5-point Gauss-Legendre nodes and weights on [-1, 1]
+        const NODES: [(f64, f64); 5] = [
+            (0.0, 0.568_888_888_888_888_9),
+            (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+            (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+            (-0.906_179_845_938_664, 0.236_926_885_056_189_1),
+            (0.906_179_845_938_664, 0.236_926_885_056_189_1),
+        ];
+        let half = (t1 - t0) / 2.0;
+        NODES
+            .iter()
+            .map(|(x, w)| w * self.derivatives_at(t0 + half * (x + 1.0), 1)[1].length())
+            .sum::<f64>()
+            * half
+    }
+    /// Approximates the curve by subdividing each knot span until the chords are within
+    /// `tolerance`.
+    pub(crate) fn tessellate(&self, tolerance: f64) -> Vec<Point> {
+        let (min, max) = self.domain();
+        let mut result = vec![self.point_at(min)];
+        let mut breaks = self.knots[self.degree..=self.control_points.len()].to_vec();
+        breaks.dedup();
+        for span in breaks.windows(2) {
+            // sample each span a few times up front so inflections aren't missed
+            let samples = (self.degree * 2).max(2);
+            for i in 0..samples {
+                let t0 = span[0] + (span[1] - span[0]) * i as f64 / samples as f64;
+                let t1 = span[0] + (span[1] - span[0]) * (i + 1) as f64 / samples as f64;
+                self.subdivide(
+                    t0,
+                    &self.point_at(t0),
+                    t1,
+                    &self.point_at(t1),
+                    tolerance,
+                    0,
+                    &mut result,
+                );
+            }
+        }
+        if breaks.len() < 2 {
+            result.push(self.point_at(max));
+        }
+        result
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn subdivide(
+        &self,
+        t0: f64,
+        p0: &Point,
+        t1: f64,
+        p1: &Point,
+        tolerance: f64,
+        depth: u32,
+        result: &mut Vec<Point>,
+    ) {
+        let t = (t0 + t1) / 2.0;
+        let mid = self.point_at(t);
+        if depth < MAX_SPLINE_DEPTH && distance_to_segment(&mid, p0, p1) > tolerance {
+            self.subdivide(t0, p0, t, &mid, tolerance, depth + 1, result);
+            self.subdivide(t, &mid, t1, p1, tolerance, depth + 1, result);
+        } else {
+            result.push(p1.clone());
+        }
+    }
+}
+
+/// Returns the index of the knot span containing `t` for a curve with `count` control points.
+pub(crate) fn find_span(knots: &[f64], degree: usize, count: usize, t: f64) -> usize {
+    let mut span = degree;
+    while span < count - 1 && knots[span + 1] <= t {
+        span += 1;
+    }
+    span
+}
+
+/// Returns the values of the `degree + 1` basis functions that are non-zero in `span` and their
+/// first `order` derivatives, indexed by derivative and then function.
+pub(crate) fn basis_derivatives(
+    knots: &[f64],
+    degree: usize,
+    span: usize,
+    t: f64,
+    order: usize,
+) -> Vec<Vec<f64>> {
+    // The NURBS Book, algorithm A2.3
+    let p = degree;
+    let mut ndu = vec![vec![0.0; p + 1]; p + 1];
+    let mut left = vec![0.0; p + 1];
+    let mut right = vec![0.0; p + 1];
+    ndu[0][0] = 1.0;
+    for j in 1..=p {
+        left[j] = t - knots[span + 1 - j];
+        right[j] = knots[span + j] - t;
+        let mut saved = 0.0;
+        for r in 0..j {
+            ndu[j][r] = right[r + 1] + left[j - r];
+            let temp = if ndu[j][r] == 0.0 {
+                0.0
+            } else {
+                ndu[r][j - 1] / ndu[j][r]
+            };
+            ndu[r][j] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        ndu[j][j] = saved;
+    }
+
+    let mut derivatives = vec![vec![0.0; p + 1]; order + 1];
+    for j in 0..=p {
+        derivatives[0][j] = ndu[j][p];
+    }
+
+    // derivatives above the degree are zero
+    let divide = |a: f64, b: f64| if b == 0.0 { 0.0 } else { a / b };
+    let mut a = [vec![0.0; p + 1], vec![0.0; p + 1]];
+    for r in 0..=p {
+        let (mut s1, mut s2) = (0, 1);
+        a[0][0] = 1.0;
+        for k in 1..=order.min(p) {
+            let mut d = 0.0;
+            let pk = p - k;
+            if r >= k {
+                let rk = r - k;
+                a[s2][0] = divide(a[s1][0], ndu[pk + 1][rk]);
+                d = a[s2][0] * ndu[rk][pk];
+            }
+            let j1 = if r + 1 >= k { 1 } else { k - r };
+            let j2 = if r <= pk + 1 { k - 1 } else { p - r };
+            for j in j1..=j2 {
+                let rkj = r + j - k;
+                a[s2][j] = divide(a[s1][j] - a[s1][j - 1], ndu[pk + 1][rkj]);
+                d += a[s2][j] * ndu[rkj][pk];
+            }
+            if r <= pk {
+                a[s2][k] = divide(-a[s1][k - 1], ndu[pk + 1][r]);
+                d += a[s2][k] * ndu[r][pk];
+            }
+            derivatives[k][r] = d;
+            std::mem::swap(&mut s1, &mut s2);
+        }
+    }
+
+    let mut factor = p as f64;
+    for (k, derivative) in derivatives.iter_mut().enumerate().take(p + 1).skip(1) {
+        for value in derivative.iter_mut() {
+            *value *= factor;
+        }
+        factor *= (p - k) as f64;
+    }
+    derivatives
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |result, i| result * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn distance_to_segment(point: &Point, start: &Point, end: &Point) -> f64 {
+    let segment = end - start;
+    let offset = point - start;
+    let length_squared = segment.dot(&segment);
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (offset.dot(&segment) / length_squared).clamp(0.0, 1.0)
+    };
+    (offset - segment * t).length()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAX_SEGMENTS;
+    use crate::entities::*;
+    use crate::helper_functions::tests::assert_point;
+    use crate::*;
+
+    /// Asserts that the midpoint of every chord is within `tolerance` of a circle.
+    fn assert_on_circle(points: &[Point], center: (f64, f64), radius: f64, tolerance: f64) {
+        for pair in points.windows(2) {
+            for p in &pair[..1] {
+                let distance = (p.x - center.0).hypot(p.y - center.1);
+                assert!((distance - radius).abs() < 1e-9);
+            }
+            let mid = ((pair[0].x + pair[1].x) / 2.0, (pair[0].y + pair[1].y) / 2.0);
+            let distance = (mid.0 - center.0).hypot(mid.1 - center.1);
+            assert!(radius - distance <= tolerance + 1e-12);
+        }
+    }
+
+    #[test]
+    fn tessellate_line() {
+        let line = Entity::new(EntityType::Line(Line::new(
+            Point::new(1.0, 2.0, 3.0),
+            Point::new(4.0, 5.0, 6.0),
+        )));
+        let polylines = line.tessellate(0.1);
+        assert_eq!(1, polylines.len());
+        assert_eq!(
+            vec![Point::new(1.0, 2.0, 3.0), Point::new(4.0, 5.0, 6.0)],
+            polylines[0]
+        );
+    }
+
+    #[test]
+    fn tessellate_circle_within_tolerance() {
+        let circle = Entity::new(EntityType::Circle(Circle::new(
+            Point::new(1.0, 1.0, 0.0),
+            10.0,
+        )));
+        let polylines = circle.tessellate(0.01);
+        let points = &polylines[0];
+        assert!(points.len() > 20);
+        assert_eq!(points.first(), points.last());
+        assert_on_circle(points, (1.0, 1.0), 10.0, 0.01);
+
+        // a looser tolerance needs fewer points
+        assert!(circle.tessellate(1.0)[0].len() < points.len());
+    }
+
+    #[test]
+    fn tessellate_circle_with_tiny_tolerance() {
+        let circle = Entity::new(EntityType::Circle(Circle::new(Point::origin(), 1.0)));
+        assert_eq!(MAX_SEGMENTS + 1, circle.tessellate(1e-300)[0].len());
+
+        let circle = Entity::new(EntityType::Circle(Circle::new(Point::origin(), 1e12)));
+        let points = &circle.tessellate(1e-5)[0];
+        assert_eq!(MAX_SEGMENTS + 1, points.len());
+        assert_eq!(points.first(), points.last());
+    }
+
+    #[test]
+    fn tessellate_arc_ends_at_its_angles() {
+        let arc = Entity::new(EntityType::Arc(Arc::new(Point::origin(), 2.0, 270.0, 90.0)));
+        let points = &arc.tessellate(0.001)[0];
+        assert_point((0.0, -2.0, 0.0), &points[0]);
+        assert_point((0.0, 2.0, 0.0), points.last().unwrap());
+        assert!(points.iter().all(|p| p.x >= -1e-9));
+        assert_on_circle(points, (0.0, 0.0), 2.0, 0.001);
+    }
+
+    #[test]
+    fn tessellate_ellipse() {
+        let ellipse = Entity::new(EntityType::Ellipse(Ellipse {
+            major_axis: Vector::new(4.0, 0.0, 0.0),
+            minor_axis_ratio: 0.5,
+            start_parameter: 0.0,
+            end_parameter: std::f64::consts::PI,
+            ..Default::default()
+        }));
+        let points = &ellipse.tessellate(0.01)[0];
+        assert_point((4.0, 0.0, 0.0), &points[0]);
+        assert_point((-4.0, 0.0, 0.0), points.last().unwrap());
+        for p in points {
+            assert!(((p.x / 4.0).powi(2) + (p.y / 2.0).powi(2) - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn tessellate_bulged_lw_polyline() {
+        let mut poly = LwPolyline::default();
+        poly.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            y: 0.0,
+            bulge: 1.0,
+            ..Default::default()
+        });
+        poly.vertices.push(LwPolylineVertex {
+            x: -1.0,
+            y: 0.0,
+            ..Default::default()
+        });
+        let ent = Entity::new(EntityType::LwPolyline(poly));
+        let points = &ent.tessellate(0.01)[0];
+        assert_point((1.0, 0.0, 0.0), &points[0]);
+        assert_point((-1.0, 0.0, 0.0), points.last().unwrap());
+        assert!(points.iter().all(|p| p.y >= -1e-9));
+        assert_on_circle(points, (0.0, 0.0), 1.0, 0.01);
+    }
+
+    #[test]
+    fn tessellate_closed_lw_polyline() {
+        let mut poly = LwPolyline::default();
+        poly.set_is_closed(true);
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)] {
+            poly.vertices.push(LwPolylineVertex {
+                x,
+                y,
+                ..Default::default()
+            });
+        }
+        let points = &Entity::new(EntityType::LwPolyline(poly)).tessellate(0.01)[0];
+        assert_eq!(4, points.len());
+        assert_eq!(points.first(), points.last());
+    }
+
+    #[test]
+    fn tessellate_quadratic_spline() {
+        let spline = Spline {
+            degree_of_curve: 2,
+            knot_values: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            control_points: vec![
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(1.0, 2.0, 0.0),
+                Point::new(2.0, 0.0, 0.0),
+            ],
+            ..Default::default()
+        };
+        let points = &Entity::new(EntityType::Spline(spline)).tessellate(0.001)[0];
+        assert_point((0.0, 0.0, 0.0), &points[0]);
+        assert_point((2.0, 0.0, 0.0), points.last().unwrap());
+
+        // the curve is the parabola y = 2x - x^2
+        for p in points {
+            assert!((p.y - (2.0 * p.x - p.x * p.x)).abs() < 1e-9);
+        }
+        assert!(points
+            .iter()
+            .any(|p| (p.x - 1.0).abs() < 1e-9 && (p.y - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn tessellate_rational_spline_circle() {
+        // a quarter circle as a rational quadratic curve
+        let spline = Spline {
+            degree_of_curve: 2,
+            knot_values: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            weight_values: vec![1.0, std::f64::consts::FRAC_1_SQRT_2, 1.0],
+            control_points: vec![
+                Point::new(1.0, 0.0, 0.0),
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(0.0, 1.0, 0.0),
+            ],
+            ..Default::default()
+        };
+        let points = &Entity::new(EntityType::Spline(spline)).tessellate(0.001)[0];
+        assert_on_circle(points, (0.0, 0.0), 1.0, 0.001);
+    }
+
+    #[test]
+    fn tessellate_helix() {
+        let helix = Helix {
+            axis_base_point: Point::origin(),
+            start_point: Point::new(1.0, 0.0, 0.0),
+            axis_vector: Vector::z_axis(),
+            radius: 1.0,
+            number_of_turns: 2.0,
+            turn_height: 3.0,
+            is_right_handed: true,
+            ..Default::default()
+        };
+        let points = &Entity::new(EntityType::Helix(helix)).tessellate(0.01)[0];
+        assert_point((1.0, 0.0, 0.0), &points[0]);
+        assert_point((1.0, 0.0, 6.0), points.last().unwrap());
+        // counter-clockwise about the axis
+        assert!(points[1].y > 0.0);
+    }
+
+    #[test]
+    fn tessellate_text_is_empty() {
+        let text = Entity::new(EntityType::Text(Default::default()));
+        assert!(text.tessellate(0.1).is_empty());
+    }
+}
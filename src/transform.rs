@@ -0,0 +1,1170 @@
+// `Entity::transform` and the per-type geometry updates it relies on
+
+use std::f64::consts::PI;
+
+use crate::entities::*;
+use crate::hatch::*;
+use crate::{
+    ocs_to_wcs_matrix, wcs_to_ocs_matrix, MLeaderContextData, Point, TransformationMatrix, Vector,
+};
+
+impl Entity {
+    /// Applies the transformation to every geometric value of the entity.
+    ///
+    /// Values stored in an object coordinate system (e.g., `Arc`, `LwPolyline` and `Text`) are
+    /// moved into the object coordinate system of the transformed plane.  An `Arc` or `Circle`
+    /// that is no longer circular after a non-uniform scale is replaced with an `Ellipse`.  Opaque
+    /// data, like the ACIS content of a `Solid3D`, is left untouched.
+    pub fn transform(&mut self, matrix: &TransformationMatrix) {
+        if let Some(replacement) = self.specific.transform(matrix, &mut self.common.elevation) {
+            self.specific = replacement;
+        }
+    }
+}
+
+impl EntityType {
+    fn transform(
+        &mut self,
+        matrix: &TransformationMatrix,
+        elevation: &mut f64,
+    ) -> Option<EntityType> {
+        match self {
+            EntityType::Face3D(ref mut face) => {
+                face.first_corner = matrix.transform_point(&face.first_corner);
+                face.second_corner = matrix.transform_point(&face.second_corner);
+                face.third_corner = matrix.transform_point(&face.third_corner);
+                face.fourth_corner = matrix.transform_point(&face.fourth_corner);
+            }
+            EntityType::Arc(ref mut arc) => {
+                let plane = PlaneTransform::new(matrix, &arc.normal);
+                if !plane.is_conformal() {
+                    let ocs = ocs_to_wcs_matrix(&arc.normal);
+                    return Some(EntityType::Ellipse(ellipse_from_conjugates(
+                        matrix.transform_point(&ocs.transform_point(&arc.center)),
+                        matrix.transform_vector(
+                            &ocs.transform_vector(&Vector::new(arc.radius, 0.0, 0.0)),
+                        ),
+                        matrix.transform_vector(
+                            &ocs.transform_vector(&Vector::new(0.0, arc.radius, 0.0)),
+                        ),
+                        arc.start_angle.to_radians(),
+                        arc.end_angle.to_radians(),
+                    )));
+                }
+
+                let sweep = arc.end_angle - arc.start_angle;
+                arc.center = plane.point(&arc.center);
+                arc.radius *= plane.scale();
+                arc.start_angle = normalize_degrees(plane.direction(arc.start_angle).0);
+                arc.end_angle = if sweep.abs() >= 360.0 {
+                    arc.start_angle + sweep
+                } else {
+                    normalize_degrees(arc.start_angle + sweep)
+                };
+                arc.thickness *= plane.normal_scale;
+                arc.normal = plane.normal;
+            }
+            EntityType::ArcAlignedText(ref mut text) => {
+                let plane = PlaneTransform::new(matrix, &text.extrusion_direction);
+                let scale = plane.scale();
+                text.center_point = plane.point(&text.center_point);
+                text.arc_radius *= scale;
+                text.text_height *= scale;
+                text.character_spacing *= scale;
+                text.offset_from_arc *= scale;
+                text.right_offset *= scale;
+                text.left_offset *= scale;
+                text.start_angle = plane
+                    .direction(text.start_angle.to_degrees())
+                    .0
+                    .to_radians();
+                text.end_angle = plane.direction(text.end_angle.to_degrees()).0.to_radians();
+                text.extrusion_direction = plane.normal;
+            }
+            EntityType::AttributeDefinition(ref mut att) => {
+                let plane = PlaneTransform::new(matrix, &att.normal);
+                att.location = plane.point(&att.location);
+                att.second_alignment_point = plane.point(&att.second_alignment_point);
+                att.alignment_point = plane.point(&att.alignment_point);
+                plane.text(
+                    &mut att.rotation,
+                    &mut att.text_height,
+                    &mut att.relative_x_scale_factor,
+                );
+                att.thickness *= plane.normal_scale;
+                att.normal = plane.normal;
+                transform_mtext(&mut att.m_text, matrix);
+            }
+            EntityType::Attribute(ref mut att) => transform_attribute(att, matrix),
+            EntityType::Circle(ref mut circle) => {
+                let plane = PlaneTransform::new(matrix, &circle.normal);
+                if !plane.is_conformal() {
+                    let ocs = ocs_to_wcs_matrix(&circle.normal);
+                    return Some(EntityType::Ellipse(ellipse_from_conjugates(
+                        matrix.transform_point(&ocs.transform_point(&circle.center)),
+                        matrix.transform_vector(&ocs.transform_vector(&Vector::new(
+                            circle.radius,
+                            0.0,
+                            0.0,
+                        ))),
+                        matrix.transform_vector(&ocs.transform_vector(&Vector::new(
+                            0.0,
+                            circle.radius,
+                            0.0,
+                        ))),
+                        0.0,
+                        PI * 2.0,
+                    )));
+                }
+
+                circle.center = plane.point(&circle.center);
+                circle.radius *= plane.scale();
+                circle.thickness *= plane.normal_scale;
+                circle.normal = plane.normal;
+            }
+            EntityType::RotatedDimension(ref mut dim) => {
+                let plane = transform_dimension_base(&mut dim.dimension_base, matrix, true);
+                dim.insertion_point = matrix.transform_point(&dim.insertion_point);
+                dim.definition_point_2 = matrix.transform_point(&dim.definition_point_2);
+                dim.definition_point_3 = matrix.transform_point(&dim.definition_point_3);
+                dim.rotation_angle = plane.direction(dim.rotation_angle).0;
+                dim.extension_line_angle = plane.direction(dim.extension_line_angle).0;
+            }
+            EntityType::RadialDimension(ref mut dim) => {
+                let plane = transform_dimension_base(&mut dim.dimension_base, matrix, true);
+                dim.definition_point_2 = matrix.transform_point(&dim.definition_point_2);
+                dim.leader_length *= plane.scale();
+            }
+            EntityType::DiameterDimension(ref mut dim) => {
+                let plane = transform_dimension_base(&mut dim.dimension_base, matrix, true);
+                dim.definition_point_2 = matrix.transform_point(&dim.definition_point_2);
+                dim.leader_length *= plane.scale();
+            }
+            EntityType::AngularThreePointDimension(ref mut dim) => {
+                transform_dimension_base(&mut dim.dimension_base, matrix, false);
+                dim.definition_point_2 = matrix.transform_point(&dim.definition_point_2);
+                dim.definition_point_3 = matrix.transform_point(&dim.definition_point_3);
+                dim.definition_point_4 = matrix.transform_point(&dim.definition_point_4);
+                dim.definition_point_5 = matrix.transform_point(&dim.definition_point_5);
+            }
+            EntityType::OrdinateDimension(ref mut dim) => {
+                transform_dimension_base(&mut dim.dimension_base, matrix, true);
+                dim.definition_point_2 = matrix.transform_point(&dim.definition_point_2);
+                dim.definition_point_3 = matrix.transform_point(&dim.definition_point_3);
+            }
+            EntityType::Ellipse(ref mut ellipse) => {
+                let minor_axis = ellipse.normal.normalized().cross(&ellipse.major_axis);
+                let minor_axis = Vector::new(
+                    minor_axis.x * ellipse.minor_axis_ratio,
+                    minor_axis.y * ellipse.minor_axis_ratio,
+                    minor_axis.z * ellipse.minor_axis_ratio,
+                );
+                *ellipse = ellipse_from_conjugates(
+                    matrix.transform_point(&ellipse.center),
+                    matrix.transform_vector(&ellipse.major_axis),
+                    matrix.transform_vector(&minor_axis),
+                    ellipse.start_parameter,
+                    ellipse.end_parameter,
+                );
+            }
+            EntityType::Hatch(ref mut hatch) => transform_hatch(hatch, matrix),
+            EntityType::Helix(ref mut helix) => {
+                let axis_length = helix.axis_vector.length();
+                let axis_base_point = matrix.transform_point(&helix.axis_base_point);
+                let start_point = matrix.transform_point(&helix.start_point);
+                let axis_vector = matrix.transform_vector(&helix.axis_vector);
+                if axis_length != 0.0 {
+                    helix.turn_height *= axis_vector.length() / axis_length;
+                }
+
+                // the radius is the distance from the start point to the axis
+                let axis = axis_vector.normalized();
+                let offset = Vector::new(
+                    start_point.x - axis_base_point.x,
+                    start_point.y - axis_base_point.y,
+                    start_point.z - axis_base_point.z,
+                );
+                let along = offset.dot(&axis);
+                let radial = Vector::new(
+                    offset.x - axis.x * along,
+                    offset.y - axis.y * along,
+                    offset.z - axis.z * along,
+                );
+                helix.radius = radial.length();
+                if determinant(matrix) < 0.0 {
+                    helix.is_right_handed = !helix.is_right_handed;
+                }
+
+                helix.axis_base_point = axis_base_point;
+                helix.start_point = start_point;
+                helix.axis_vector = axis_vector;
+            }
+            EntityType::Image(ref mut image) => {
+                image.location = matrix.transform_point(&image.location);
+                image.u_vector = matrix.transform_vector(&image.u_vector);
+                image.v_vector = matrix.transform_vector(&image.v_vector);
+            }
+            EntityType::Insert(ref mut insert) => {
+                let plane = PlaneTransform::new(matrix, &insert.extrusion_direction);
+                let (rotation, x_factor) = plane.direction(insert.rotation);
+                let (_, y_factor) = plane.direction(insert.rotation + 90.0);
+                insert.location = plane.point(&insert.location);
+                insert.rotation = rotation;
+                insert.x_scale_factor *= x_factor;
+                insert.y_scale_factor *= y_factor;
+                insert.z_scale_factor *= plane.normal_scale;
+                insert.column_spacing *= x_factor;
+                insert.row_spacing *= y_factor;
+                insert.extrusion_direction = plane.normal;
+                for att in insert.attributes_mut() {
+                    transform_attribute(att, matrix);
+                }
+            }
+            EntityType::Leader(ref mut leader) => {
+                let plane = PlaneTransform::new(matrix, &leader.normal);
+                for vertex in leader.vertices.iter_mut() {
+                    *vertex = matrix.transform_point(vertex);
+                }
+                leader.text_annotation_height *= plane.scale();
+                leader.text_annotation_width *= plane.scale();
+                leader.right = matrix.transform_vector(&leader.right).normalized();
+                leader.block_offset = matrix.transform_vector(&leader.block_offset);
+                leader.annotation_offset = matrix.transform_vector(&leader.annotation_offset);
+                leader.normal = plane.normal;
+            }
+            EntityType::Light(ref mut light) => {
+                let scale = PlaneTransform::new(matrix, &Vector::z_axis()).scale();
+                light.position = matrix.transform_point(&light.position);
+                light.target_location = matrix.transform_point(&light.target_location);
+                light.attenuation_start_limit *= scale;
+                light.attenuation_end_limit *= scale;
+            }
+            EntityType::Line(ref mut line) => {
+                let plane = PlaneTransform::new(matrix, &line.extrusion_direction);
+                line.p1 = matrix.transform_point(&line.p1);
+                line.p2 = matrix.transform_point(&line.p2);
+                line.thickness *= plane.normal_scale;
+                line.extrusion_direction = plane.normal;
+            }
+            EntityType::LwPolyline(ref mut poly) => {
+                let plane = PlaneTransform::new(matrix, &poly.extrusion_direction);
+                let mut new_elevation = *elevation;
+                for vertex in poly.vertices.iter_mut() {
+                    let p = plane.point(&Point::new(vertex.x, vertex.y, *elevation));
+                    vertex.x = p.x;
+                    vertex.y = p.y;
+                    vertex.starting_width *= plane.scale();
+                    vertex.ending_width *= plane.scale();
+                    new_elevation = p.z;
+                }
+                *elevation = new_elevation;
+                poly.constant_width *= plane.scale();
+                poly.thickness *= plane.normal_scale;
+                poly.extrusion_direction = plane.normal;
+            }
+            EntityType::MLeader(ref mut mleader) => {
+                let scale = transform_mleader_context(&mut mleader.context_data, matrix);
+                mleader.dogleg_length *= scale;
+                mleader.arrowhead_size *= scale;
+                mleader.block_content_scale = Vector::new(
+                    mleader.block_content_scale.x * scale,
+                    mleader.block_content_scale.y * scale,
+                    mleader.block_content_scale.z * scale,
+                );
+            }
+            EntityType::MLine(ref mut mline) => {
+                let plane = PlaneTransform::new(matrix, &mline.normal);
+                let scale = plane.scale();
+                mline.start_point = matrix.transform_point(&mline.start_point);
+                for vertex in mline.vertices.iter_mut() {
+                    *vertex = matrix.transform_point(vertex);
+                }
+                for direction in mline.segment_directions.iter_mut() {
+                    *direction = matrix.transform_vector(direction).normalized();
+                }
+                for direction in mline.miter_directions.iter_mut() {
+                    *direction = matrix.transform_vector(direction).normalized();
+                }
+                mline.scale_factor *= scale;
+                for parameter in mline.parameters.iter_mut() {
+                    *parameter *= scale;
+                }
+                for parameter in mline.area_fill_parameters.iter_mut() {
+                    *parameter *= scale;
+                }
+                mline.normal = plane.normal;
+            }
+            EntityType::MText(ref mut mtext) => transform_mtext(mtext, matrix),
+            EntityType::Ole2Frame(ref mut ole) => {
+                ole.upper_left_corner = matrix.transform_point(&ole.upper_left_corner);
+                ole.lower_right_corner = matrix.transform_point(&ole.lower_right_corner);
+            }
+            EntityType::ModelPoint(ref mut point) => {
+                let plane = PlaneTransform::new(matrix, &point.extrusion_direction);
+                point.location = matrix.transform_point(&point.location);
+                point.angle = plane.direction(point.angle).0;
+                point.thickness *= plane.normal_scale;
+                point.extrusion_direction = plane.normal;
+            }
+            EntityType::Polyline(ref mut poly) => {
+                let plane = PlaneTransform::new(matrix, &poly.normal);
+                let is_planar = !(poly.is_3d_polyline()
+                    || poly.is_3d_polygon_mesh()
+                    || poly.is_polyface_mesh());
+                let elevation = poly.location.z;
+                let scale = plane.scale();
+                for vertex in poly.vertices_mut() {
+                    if is_planar {
+                        vertex.location = plane.point(&Point::new(
+                            vertex.location.x,
+                            vertex.location.y,
+                            elevation,
+                        ));
+                        vertex.curve_fit_tangent_direction =
+                            plane.direction(vertex.curve_fit_tangent_direction).0;
+                    } else {
+                        vertex.location = matrix.transform_point(&vertex.location);
+                    }
+                    vertex.starting_width *= scale;
+                    vertex.ending_width *= scale;
+                }
+                if is_planar {
+                    poly.location = Point::new(0.0, 0.0, plane.point(&poly.location).z);
+                    poly.thickness *= plane.normal_scale;
+                    poly.normal = plane.normal;
+                }
+                poly.default_starting_width *= scale;
+                poly.default_ending_width *= scale;
+            }
+            EntityType::Ray(ref mut ray) => {
+                ray.start_point = matrix.transform_point(&ray.start_point);
+                ray.unit_direction_vector = matrix
+                    .transform_vector(&ray.unit_direction_vector)
+                    .normalized();
+            }
+            EntityType::RText(ref mut text) => {
+                let plane = PlaneTransform::new(matrix, &text.extrusion_direction);
+                let mut x_scale = 1.0;
+                text.insertion_point = plane.point(&text.insertion_point);
+                plane.text(
+                    &mut text.rotation_angle,
+                    &mut text.text_height,
+                    &mut x_scale,
+                );
+                text.extrusion_direction = plane.normal;
+            }
+            EntityType::Section(ref mut section) => {
+                let vertical_length = section.vertical_direction.length();
+                let vertical_direction = matrix.transform_vector(&section.vertical_direction);
+                if vertical_length != 0.0 {
+                    let factor = vertical_direction.length() / vertical_length;
+                    section.top_height *= factor;
+                    section.bottom_height *= factor;
+                }
+                section.vertical_direction = vertical_direction.normalized();
+                for vertex in section.vertices.iter_mut() {
+                    *vertex = matrix.transform_point(vertex);
+                }
+                for vertex in section.back_line_vertices.iter_mut() {
+                    *vertex = matrix.transform_point(vertex);
+                }
+            }
+            EntityType::Shape(ref mut shape) => {
+                let plane = PlaneTransform::new(matrix, &shape.extrusion_direction);
+                shape.location = plane.point(&shape.location);
+                plane.text(
+                    &mut shape.rotation_angle,
+                    &mut shape.size,
+                    &mut shape.relative_x_scale_factor,
+                );
+                shape.thickness *= plane.normal_scale;
+                shape.extrusion_direction = plane.normal;
+            }
+            EntityType::Solid(ref mut solid) => {
+                let plane = PlaneTransform::new(matrix, &solid.extrusion_direction);
+                solid.first_corner = plane.point(&solid.first_corner);
+                solid.second_corner = plane.point(&solid.second_corner);
+                solid.third_corner = plane.point(&solid.third_corner);
+                solid.fourth_corner = plane.point(&solid.fourth_corner);
+                solid.thickness *= plane.normal_scale;
+                solid.extrusion_direction = plane.normal;
+            }
+            EntityType::Spline(ref mut spline) => {
+                let plane = PlaneTransform::new(matrix, &spline.normal);
+                for point in spline.control_points.iter_mut() {
+                    *point = matrix.transform_point(point);
+                }
+                for point in spline.fit_points.iter_mut() {
+                    *point = matrix.transform_point(point);
+                }
+                spline.start_tangent = transform_point_as_vector(matrix, &spline.start_tangent);
+                spline.end_tangent = transform_point_as_vector(matrix, &spline.end_tangent);
+                spline.normal = plane.normal;
+            }
+            EntityType::Table(ref mut table) => {
+                let x_axis = table.horizontal_direction.normalized();
+                let y_axis = Vector::z_axis().cross(&x_axis);
+                let new_x_axis = matrix.transform_vector(&x_axis);
+                let column_factor = new_x_axis.length();
+                let row_factor = matrix.transform_vector(&y_axis).length();
+                table.insertion_point = matrix.transform_point(&table.insertion_point);
+                table.horizontal_direction = new_x_axis.normalized();
+                for width in table.column_widths.iter_mut() {
+                    *width *= column_factor;
+                }
+                for height in table.row_heights.iter_mut() {
+                    *height *= row_factor;
+                }
+            }
+            EntityType::Text(ref mut text) => {
+                let plane = PlaneTransform::new(matrix, &text.normal);
+                text.location = plane.point(&text.location);
+                text.second_alignment_point = plane.point(&text.second_alignment_point);
+                plane.text(
+                    &mut text.rotation,
+                    &mut text.text_height,
+                    &mut text.relative_x_scale_factor,
+                );
+                text.thickness *= plane.normal_scale;
+                text.normal = plane.normal;
+            }
+            EntityType::Tolerance(ref mut tol) => {
+                let plane = PlaneTransform::new(matrix, &tol.extrusion_direction);
+                tol.insertion_point = matrix.transform_point(&tol.insertion_point);
+                tol.direction_vector = matrix.transform_vector(&tol.direction_vector);
+                tol.extrusion_direction = plane.normal;
+            }
+            EntityType::Trace(ref mut trace) => {
+                let plane = PlaneTransform::new(matrix, &trace.extrusion_direction);
+                trace.first_corner = plane.point(&trace.first_corner);
+                trace.second_corner = plane.point(&trace.second_corner);
+                trace.third_corner = plane.point(&trace.third_corner);
+                trace.fourth_corner = plane.point(&trace.fourth_corner);
+                trace.thickness *= plane.normal_scale;
+                trace.extrusion_direction = plane.normal;
+            }
+            EntityType::DgnUnderlay(ref mut underlay) => transform_underlay(
+                matrix,
+                &mut underlay.insertion_point,
+                &mut underlay.normal,
+                &mut underlay.rotation_angle,
+                &mut underlay.x_scale,
+                &mut underlay.y_scale,
+                &mut underlay.z_scale,
+            ),
+            EntityType::DwfUnderlay(ref mut underlay) => transform_underlay(
+                matrix,
+                &mut underlay.insertion_point,
+                &mut underlay.normal,
+                &mut underlay.rotation_angle,
+                &mut underlay.x_scale,
+                &mut underlay.y_scale,
+                &mut underlay.z_scale,
+            ),
+            EntityType::PdfUnderlay(ref mut underlay) => transform_underlay(
+                matrix,
+                &mut underlay.insertion_point,
+                &mut underlay.normal,
+                &mut underlay.rotation_angle,
+                &mut underlay.x_scale,
+                &mut underlay.y_scale,
+                &mut underlay.z_scale,
+            ),
+            EntityType::Vertex(ref mut vertex) => {
+                let scale = PlaneTransform::new(matrix, &Vector::z_axis()).scale();
+                vertex.location = matrix.transform_point(&vertex.location);
+                vertex.starting_width *= scale;
+                vertex.ending_width *= scale;
+            }
+            EntityType::Viewport(ref mut viewport) => {
+                let scale = PlaneTransform::new(matrix, &Vector::z_axis()).scale();
+                viewport.center = matrix.transform_point(&viewport.center);
+                viewport.width *= scale;
+                viewport.height *= scale;
+            }
+            EntityType::Wipeout(ref mut wipeout) => {
+                wipeout.location = matrix.transform_point(&wipeout.location);
+                wipeout.u_vector = matrix.transform_vector(&wipeout.u_vector);
+                wipeout.v_vector = matrix.transform_vector(&wipeout.v_vector);
+            }
+            EntityType::XLine(ref mut xline) => {
+                xline.first_point = matrix.transform_point(&xline.first_point);
+                xline.unit_direction_vector = matrix
+                    .transform_vector(&xline.unit_direction_vector)
+                    .normalized();
+            }
+            // opaque or non-geometric data
+            EntityType::Solid3D(_)
+            | EntityType::ProxyEntity(_)
+            | EntityType::Body(_)
+            | EntityType::OleFrame(_)
+            | EntityType::Region(_)
+            | EntityType::Seqend(_)
+            | EntityType::Unknown { .. } => (),
+        }
+
+        None
+    }
+}
+
+/// Maps coordinates from the object coordinate system of a planar entity into the object
+/// coordinate system of the transformed plane.
+struct PlaneTransform {
+    matrix: TransformationMatrix,
+    /// The normal of the transformed plane.
+    normal: Vector,
+    /// The signed factor by which lengths along the normal, like thickness, are scaled.
+    normal_scale: f64,
+}
+
+impl PlaneTransform {
+    fn new(transform: &TransformationMatrix, normal: &Vector) -> Self {
+        let to_wcs = ocs_to_wcs_matrix(normal);
+        let x_axis = transform.transform_vector(&to_wcs.transform_vector(&Vector::x_axis()));
+        let y_axis = transform.transform_vector(&to_wcs.transform_vector(&Vector::y_axis()));
+        let mapped_normal = transform.transform_vector(&normal.normalized());
+
+        // deriving the normal from the in-plane axes keeps arcs counter-clockwise after a mirror
+        let mut new_normal = x_axis.cross(&y_axis).normalized();
+        if new_normal.length() == 0.0 {
+            new_normal = mapped_normal.normalized();
+        }
+        if new_normal.length() == 0.0 {
+            new_normal = Vector::z_axis();
+        }
+
+        let to_new_ocs = wcs_to_ocs_matrix(&new_normal);
+        PlaneTransform {
+            matrix: to_wcs.compose(transform).compose(&to_new_ocs),
+            normal_scale: mapped_normal.dot(&new_normal),
+            normal: new_normal,
+        }
+    }
+    fn point(&self, point: &Point) -> Point {
+        self.matrix.transform_point(point)
+    }
+    /// Transforms an in-plane direction specified in degrees and returns the new direction along
+    /// with the factor by which lengths in that direction are scaled.
+    fn direction(&self, angle: f64) -> (f64, f64) {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let v = self.matrix.transform_vector(&Vector::new(cos, sin, 0.0));
+        (v.y.atan2(v.x).to_degrees(), v.x.hypot(v.y))
+    }
+    /// The factor by which in-plane lengths are scaled; exact for conformal transformations.
+    fn scale(&self) -> f64 {
+        let x_axis = self.matrix.transform_vector(&Vector::x_axis());
+        let y_axis = self.matrix.transform_vector(&Vector::y_axis());
+        (x_axis.x * y_axis.y - x_axis.y * y_axis.x).abs().sqrt()
+    }
+    /// Returns `true` if circles in the plane remain circles.
+    fn is_conformal(&self) -> bool {
+        let x_axis = self.matrix.transform_vector(&Vector::x_axis());
+        let y_axis = self.matrix.transform_vector(&Vector::y_axis());
+        let x_length = x_axis.x.hypot(x_axis.y);
+        let y_length = y_axis.x.hypot(y_axis.y);
+        let tolerance = 1e-9 * x_length.max(y_length);
+        (x_length - y_length).abs() <= tolerance
+            && (x_axis.x * y_axis.x + x_axis.y * y_axis.y).abs() <= tolerance * x_length.max(1.0)
+    }
+    /// Updates the rotation (in degrees), height, and relative width of text-like values.
+    fn text(&self, rotation: &mut f64, height: &mut f64, relative_x_scale_factor: &mut f64) {
+        let (new_rotation, x_factor) = self.direction(*rotation);
+        let (_, y_factor) = self.direction(*rotation + 90.0);
+        *rotation = new_rotation;
+        *height *= y_factor;
+        if y_factor != 0.0 {
+            *relative_x_scale_factor *= x_factor / y_factor;
+        }
+    }
+}
+
+fn transform_attribute(att: &mut Attribute, matrix: &TransformationMatrix) {
+    let plane = PlaneTransform::new(matrix, &att.normal);
+    att.location = plane.point(&att.location);
+    att.second_alignment_point = plane.point(&att.second_alignment_point);
+    att.alignment_point = plane.point(&att.alignment_point);
+    plane.text(
+        &mut att.rotation,
+        &mut att.text_height,
+        &mut att.relative_x_scale_factor,
+    );
+    att.thickness *= plane.normal_scale;
+    att.normal = plane.normal;
+    transform_mtext(&mut att.m_text, matrix);
+}
+
+fn transform_mtext(mtext: &mut MText, matrix: &TransformationMatrix) {
+    let plane = PlaneTransform::new(matrix, &mtext.extrusion_direction);
+
+    // an explicit x-axis direction takes precedence over the rotation angle
+    let has_x_axis = mtext.x_axis_direction.length() != 0.0;
+    let rotation = if has_x_axis {
+        let x_axis =
+            wcs_to_ocs_matrix(&mtext.extrusion_direction).transform_vector(&mtext.x_axis_direction);
+        x_axis.y.atan2(x_axis.x).to_degrees()
+    } else {
+        mtext.rotation_angle
+    };
+    let (new_rotation, x_factor) = plane.direction(rotation);
+    let (_, y_factor) = plane.direction(rotation + 90.0);
+
+    mtext.insertion_point = matrix.transform_point(&mtext.insertion_point);
+    mtext.rotation_angle = new_rotation;
+    if has_x_axis {
+        let (sin, cos) = new_rotation.to_radians().sin_cos();
+        mtext.x_axis_direction =
+            ocs_to_wcs_matrix(&plane.normal).transform_vector(&Vector::new(cos, sin, 0.0));
+    }
+    mtext.initial_text_height *= y_factor;
+    mtext.vertical_height *= y_factor;
+    for height in mtext.column_heights.iter_mut() {
+        *height *= y_factor;
+    }
+    mtext.reference_rectangle_width *= x_factor;
+    mtext.horizontal_width *= x_factor;
+    mtext.column_width *= x_factor;
+    mtext.column_gutter *= x_factor;
+    mtext.extrusion_direction = plane.normal;
+}
+
+fn transform_dimension_base(
+    dim: &mut DimensionBase,
+    matrix: &TransformationMatrix,
+    is_linear: bool,
+) -> PlaneTransform {
+    let plane = PlaneTransform::new(matrix, &dim.normal);
+    let rotation = plane.direction(0.0).0;
+    dim.definition_point_1 = matrix.transform_point(&dim.definition_point_1);
+    dim.text_mid_point = matrix.transform_point(&dim.text_mid_point);
+    dim.text_rotation_angle += rotation;
+    dim.horizontal_direction_angle += rotation;
+    if is_linear {
+        dim.actual_measurement *= plane.scale();
+    }
+    dim.normal = plane.normal.clone();
+    plane
+}
+
+fn transform_underlay(
+    matrix: &TransformationMatrix,
+    insertion_point: &mut Point,
+    normal: &mut Vector,
+    rotation_angle: &mut f64,
+    x_scale: &mut f64,
+    y_scale: &mut f64,
+    z_scale: &mut f64,
+) {
+    let plane = PlaneTransform::new(matrix, normal);
+    let (rotation, x_factor) = plane.direction(*rotation_angle);
+    let (_, y_factor) = plane.direction(*rotation_angle + 90.0);
+    *insertion_point = matrix.transform_point(insertion_point);
+    *rotation_angle = rotation;
+    *x_scale *= x_factor;
+    *y_scale *= y_factor;
+    *z_scale *= plane.normal_scale;
+    *normal = plane.normal;
+}
+
+/// Returns the scale factor applied to the content.
+fn transform_mleader_context(
+    context: &mut MLeaderContextData,
+    matrix: &TransformationMatrix,
+) -> f64 {
+    let normal = context.plane_x_axis.cross(&context.plane_y_axis);
+    let plane = PlaneTransform::new(matrix, &normal);
+    let scale = plane.scale();
+    let rotation = plane.direction(0.0).0;
+    context.content_scale *= scale;
+    context.content_base_point = matrix.transform_point(&context.content_base_point);
+    context.text_height *= scale;
+    context.arrowhead_size *= scale;
+    context.landing_gap *= scale;
+    if let Some(ref mut text) = context.text_content {
+        text.location = matrix.transform_point(&text.location);
+        text.direction = matrix.transform_vector(&text.direction).normalized();
+        text.normal = plane.normal.clone();
+        text.boundary_width *= scale;
+        text.boundary_height *= scale;
+        text.column_width *= scale;
+        text.column_gutter_width *= scale;
+        for size in text.column_sizes.iter_mut() {
+            *size *= scale;
+        }
+    }
+    if let Some(ref mut block) = context.block_content {
+        block.location = matrix.transform_point(&block.location);
+        block.normal = plane.normal.clone();
+        block.scale = Vector::new(
+            block.scale.x * scale,
+            block.scale.y * scale,
+            block.scale.z * scale,
+        );
+        block.rotation += rotation.to_radians();
+        block.transformation_matrix = block.transformation_matrix.compose(matrix);
+    }
+    context.plane_origin = matrix.transform_point(&context.plane_origin);
+    context.plane_x_axis = matrix.transform_vector(&context.plane_x_axis).normalized();
+    context.plane_y_axis = matrix.transform_vector(&context.plane_y_axis).normalized();
+    for root in context.leader_roots.iter_mut() {
+        root.connection_point = matrix.transform_point(&root.connection_point);
+        root.dogleg_vector = matrix.transform_vector(&root.dogleg_vector).normalized();
+        root.dogleg_length *= scale;
+        for line in root.leader_lines.iter_mut() {
+            for vertex in line.vertices.iter_mut() {
+                *vertex = matrix.transform_point(vertex);
+            }
+        }
+    }
+
+    scale
+}
+
+/// Hatch boundaries are stored in two dimensions at the elevation of the hatch; components of the
+/// transformation that leave the XY plane are projected back onto it.
+fn transform_hatch(hatch: &mut Hatch, matrix: &TransformationMatrix) {
+    let elevation = hatch.elevation_point.z;
+    let map = |x: f64, y: f64| {
+        let p = matrix.transform_point(&Point::new(x, y, elevation));
+        (p.x, p.y)
+    };
+    let x_axis = matrix.transform_vector(&Vector::x_axis());
+    let y_axis = matrix.transform_vector(&Vector::y_axis());
+    let area_scale = x_axis.x * y_axis.y - x_axis.y * y_axis.x;
+    let is_mirrored = area_scale < 0.0;
+    let scale = area_scale.abs().sqrt();
+    let rotation = x_axis.y.atan2(x_axis.x);
+
+    for boundary in hatch.pattern_boundary_data.iter_mut() {
+        match boundary.path {
+            BoundaryPath::Polyline(ref mut poly) => {
+                for vertex in poly.vertices.iter_mut() {
+                    let (x, y) = map(vertex.x, vertex.y);
+                    vertex.x = x;
+                    vertex.y = y;
+                    if is_mirrored {
+                        vertex.bulge = -vertex.bulge;
+                    }
+                }
+            }
+            BoundaryPath::Edge(ref mut edges) => {
+                for edge in edges.edges.iter_mut() {
+                    transform_hatch_edge(edge, matrix, elevation, is_mirrored);
+                }
+            }
+        }
+    }
+    for point in hatch.seed_points.iter_mut() {
+        let (x, y) = map(point.x, point.y);
+        point.x = x;
+        point.y = y;
+    }
+    hatch.elevation_point = Point::new(
+        0.0,
+        0.0,
+        matrix.transform_point(&Point::new(0.0, 0.0, elevation)).z,
+    );
+    hatch.pattern_angle += rotation;
+    hatch.pattern_scale *= scale;
+    hatch.pixel_size *= scale;
+    for line in hatch.pattern_line_data.iter_mut() {
+        let (base_x, base_y) = map(line.base_point_x, line.base_point_y);
+        let offset = matrix.transform_vector(&Vector::new(line.offset_x, line.offset_y, 0.0));
+        line.angle += rotation.to_degrees();
+        line.base_point_x = base_x;
+        line.base_point_y = base_y;
+        line.offset_x = offset.x;
+        line.offset_y = offset.y;
+        for dash in line.dash_lengths.iter_mut() {
+            *dash *= scale;
+        }
+    }
+}
+
+fn transform_hatch_edge(
+    edge: &mut EdgePath,
+    matrix: &TransformationMatrix,
+    elevation: f64,
+    is_mirrored: bool,
+) {
+    let map_point = |p: &Point| {
+        let p = matrix.transform_point(&Point::new(p.x, p.y, elevation));
+        Point::new(p.x, p.y, 0.0)
+    };
+    let map_vector = |v: &Vector| {
+        let v = matrix.transform_vector(&Vector::new(v.x, v.y, 0.0));
+        Vector::new(v.x, v.y, 0.0)
+    };
+
+    // clockwise arcs store their angles mirrored across the X axis
+    let actual = |angle: f64, is_counter_clockwise: bool| {
+        if is_counter_clockwise {
+            angle
+        } else {
+            -angle
+        }
+    };
+    let replacement = match edge {
+        EdgePath::Line(ref mut line) => {
+            line.p1 = map_point(&line.p1);
+            line.p2 = map_point(&line.p2);
+            None
+        }
+        EdgePath::CircularArc(ref mut arc) => {
+            let ccw = arc.is_counter_clockwise;
+            let start = actual(arc.start_angle, ccw).to_radians();
+            let end = actual(arc.end_angle, ccw).to_radians();
+            let u = map_vector(&Vector::new(arc.radius, 0.0, 0.0));
+            let v = map_vector(&Vector::new(0.0, arc.radius, 0.0));
+            let is_conformal = (u.length() - v.length()).abs() <= 1e-9 * u.length().max(v.length())
+                && u.dot(&v).abs() <= 1e-9 * u.length().max(1.0);
+            if is_conformal {
+                let direction = |angle: f64| {
+                    let d = map_vector(&Vector::new(angle.cos(), angle.sin(), 0.0));
+                    d.y.atan2(d.x).to_degrees()
+                };
+                let is_full_circle = (arc.end_angle - arc.start_angle).abs() >= 360.0;
+                let new_ccw = ccw != is_mirrored;
+                arc.center = map_point(&arc.center);
+                arc.radius = u.length();
+                if !is_full_circle {
+                    arc.start_angle = normalize_degrees(actual(direction(start), new_ccw));
+                    arc.end_angle = normalize_degrees(actual(direction(end), new_ccw));
+                }
+                arc.is_counter_clockwise = new_ccw;
+                None
+            } else {
+                Some(elliptic_edge_from_conjugates(
+                    map_point(&arc.center),
+                    u,
+                    v,
+                    start,
+                    end,
+                    ccw,
+                    is_mirrored,
+                ))
+            }
+        }
+        EdgePath::EllipticArc(ref mut arc) => {
+            let ccw = arc.is_counter_clockwise;
+            let u = map_vector(&arc.major_axis);
+            let minor = Vector::new(
+                -arc.major_axis.y * arc.minor_axis_ratio,
+                arc.major_axis.x * arc.minor_axis_ratio,
+                0.0,
+            );
+            let v = map_vector(&minor);
+            Some(elliptic_edge_from_conjugates(
+                map_point(&arc.center),
+                u,
+                v,
+                actual(arc.start_angle, ccw).to_radians(),
+                actual(arc.end_angle, ccw).to_radians(),
+                ccw,
+                is_mirrored,
+            ))
+        }
+        EdgePath::Spline(ref mut spline) => {
+            for point in spline.control_points.iter_mut() {
+                *point = map_point(point);
+            }
+            for point in spline.fit_points.iter_mut() {
+                *point = map_point(point);
+            }
+            spline.start_tangent = map_vector(&spline.start_tangent);
+            spline.end_tangent = map_vector(&spline.end_tangent);
+            None
+        }
+    };
+
+    if let Some(replacement) = replacement {
+        *edge = replacement;
+    }
+}
+
+fn elliptic_edge_from_conjugates(
+    center: Point,
+    u: Vector,
+    v: Vector,
+    start: f64,
+    end: f64,
+    is_counter_clockwise: bool,
+    is_mirrored: bool,
+) -> EdgePath {
+    // negating both the second conjugate axis and the parameter traces the same points with a
+    // positively oriented basis, but in the opposite direction
+    let (v, start, end) = if is_mirrored {
+        (Vector::new(-v.x, -v.y, -v.z), -start, -end)
+    } else {
+        (v, start, end)
+    };
+    let is_counter_clockwise = is_counter_clockwise != is_mirrored;
+    let is_full_ellipse = (end - start).abs() >= PI * 2.0;
+    let ellipse = ellipse_from_conjugates(center, u, v, start, end);
+    let store = |angle: f64| {
+        let degrees = normalize_degrees(angle.to_degrees());
+        if is_counter_clockwise {
+            degrees
+        } else {
+            normalize_degrees(-degrees)
+        }
+    };
+    let (start_angle, end_angle) = if is_full_ellipse {
+        (0.0, 360.0)
+    } else {
+        (store(ellipse.start_parameter), store(ellipse.end_parameter))
+    };
+    EdgePath::EllipticArc(EdgeEllipticArcData {
+        center: ellipse.center,
+        major_axis: ellipse.major_axis,
+        minor_axis_ratio: ellipse.minor_axis_ratio,
+        start_angle,
+        end_angle,
+        is_counter_clockwise,
+    })
+}
+
+/// Builds the ellipse traced by `center + u * cos(t) + v * sin(t)` for `t` in `[start, end]`.
+fn ellipse_from_conjugates(center: Point, u: Vector, v: Vector, start: f64, end: f64) -> Ellipse {
+    // rotate the parameter so the conjugate axes become the principal axes
+    let t0 = 0.5 * (2.0 * u.dot(&v)).atan2(u.dot(&u) - v.dot(&v));
+    let (sin, cos) = t0.sin_cos();
+    let mut major = Vector::new(
+        u.x * cos + v.x * sin,
+        u.y * cos + v.y * sin,
+        u.z * cos + v.z * sin,
+    );
+    let mut minor = Vector::new(
+        v.x * cos - u.x * sin,
+        v.y * cos - u.y * sin,
+        v.z * cos - u.z * sin,
+    );
+    let mut offset = t0;
+    if minor.length() > major.length() {
+        let previous_major = major;
+        major = minor;
+        minor = Vector::new(-previous_major.x, -previous_major.y, -previous_major.z);
+        offset += PI / 2.0;
+    }
+
+    let (start_parameter, end_parameter) = if (end - start).abs() >= PI * 2.0 {
+        (0.0, PI * 2.0)
+    } else {
+        (
+            normalize_radians(start - offset),
+            normalize_radians(end - offset),
+        )
+    };
+    let ratio = if major.length() == 0.0 {
+        1.0
+    } else {
+        minor.length() / major.length()
+    };
+    Ellipse {
+        center,
+        normal: major.cross(&minor).normalized(),
+        major_axis: major,
+        minor_axis_ratio: ratio,
+        start_parameter,
+        end_parameter,
+    }
+}
+
+fn transform_point_as_vector(matrix: &TransformationMatrix, point: &Point) -> Point {
+    let v = matrix.transform_vector(&Vector::new(point.x, point.y, point.z));
+    Point::new(v.x, v.y, v.z)
+}
+
+fn determinant(m: &TransformationMatrix) -> f64 {
+    m.m11 * (m.m22 * m.m33 - m.m23 * m.m32) - m.m12 * (m.m21 * m.m33 - m.m23 * m.m31)
+        + m.m13 * (m.m21 * m.m32 - m.m22 * m.m31)
+}
+
+fn normalize_degrees(angle: f64) -> f64 {
+    angle.rem_euclid(360.0)
+}
+
+fn normalize_radians(angle: f64) -> f64 {
+    angle.rem_euclid(PI * 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::helper_functions::tests::assert_point;
+    use crate::{Point, TransformationMatrix, Vector};
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-9,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    fn assert_vector(expected: (f64, f64, f64), actual: &Vector) {
+        assert_close(expected.0, actual.x);
+        assert_close(expected.1, actual.y);
+        assert_close(expected.2, actual.z);
+    }
+
+    #[test]
+    fn compose_and_invert_matrix() {
+        let matrix = TransformationMatrix::rotation(&Vector::z_axis(), 90.0).compose(
+            &TransformationMatrix::translation(&Vector::new(1.0, 2.0, 3.0)),
+        );
+        assert_point(
+            (1.0, 3.0, 3.0),
+            &matrix.transform_point(&Point::new(1.0, 0.0, 0.0)),
+        );
+
+        let inverse = matrix.inverse().unwrap();
+        assert_point(
+            (1.0, 0.0, 0.0),
+            &inverse.transform_point(&Point::new(1.0, 3.0, 3.0)),
+        );
+        assert!(TransformationMatrix::scale(1.0, 0.0, 1.0)
+            .inverse()
+            .is_none());
+    }
+
+    #[test]
+    fn mirror_matrix() {
+        let matrix = TransformationMatrix::mirror(&Point::new(1.0, 0.0, 0.0), &Vector::x_axis());
+        assert_point(
+            (0.0, 5.0, 0.0),
+            &matrix.transform_point(&Point::new(2.0, 5.0, 0.0)),
+        );
+    }
+
+    #[test]
+    fn translate_line() {
+        let mut ent = Entity::new(EntityType::Line(Line::new(
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(1.0, 1.0, 0.0),
+        )));
+        ent.transform(&TransformationMatrix::translation(&Vector::new(
+            2.0, 3.0, 4.0,
+        )));
+        match ent.specific {
+            EntityType::Line(ref line) => {
+                assert_point((2.0, 3.0, 4.0), &line.p1);
+                assert_point((3.0, 4.0, 4.0), &line.p2);
+            }
+            _ => panic!("expected a line"),
+        }
+    }
+
+    #[test]
+    fn rotate_and_scale_arc() {
+        let mut ent = Entity::new(EntityType::Arc(Arc::new(
+            Point::new(1.0, 0.0, 0.0),
+            1.0,
+            0.0,
+            90.0,
+        )));
+        ent.transform(
+            &TransformationMatrix::rotation(&Vector::z_axis(), 90.0)
+                .compose(&TransformationMatrix::scale(2.0, 2.0, 2.0)),
+        );
+        match ent.specific {
+            EntityType::Arc(ref arc) => {
+                assert_point((0.0, 2.0, 0.0), &arc.center);
+                assert_close(2.0, arc.radius);
+                assert_close(90.0, arc.start_angle);
+                assert_close(180.0, arc.end_angle);
+                assert_vector((0.0, 0.0, 1.0), &arc.normal);
+            }
+            _ => panic!("expected an arc"),
+        }
+    }
+
+    #[test]
+    fn mirror_arc_flips_normal() {
+        let mut ent = Entity::new(EntityType::Arc(Arc::new(
+            Point::new(1.0, 0.0, 0.0),
+            1.0,
+            0.0,
+            90.0,
+        )));
+        ent.transform(&TransformationMatrix::mirror(
+            &Point::origin(),
+            &Vector::x_axis(),
+        ));
+        match ent.specific {
+            EntityType::Arc(ref arc) => {
+                // the OCS of a -Z normal has its X axis along -X in world coordinates
+                assert_vector((0.0, 0.0, -1.0), &arc.normal);
+                assert_point((1.0, 0.0, 0.0), &arc.center);
+                assert_close(0.0, arc.start_angle);
+                assert_close(90.0, arc.end_angle);
+            }
+            _ => panic!("expected an arc"),
+        }
+    }
+
+    #[test]
+    fn non_uniform_scale_turns_circle_into_ellipse() {
+        let mut ent = Entity::new(EntityType::Circle(Circle::new(
+            Point::new(1.0, 1.0, 0.0),
+            1.0,
+        )));
+        ent.transform(&TransformationMatrix::scale(1.0, 3.0, 1.0));
+        match ent.specific {
+            EntityType::Ellipse(ref ellipse) => {
+                assert_point((1.0, 3.0, 0.0), &ellipse.center);
+                assert_close(3.0, ellipse.major_axis.length());
+                assert_close(0.0, ellipse.major_axis.x);
+                assert_close(1.0 / 3.0, ellipse.minor_axis_ratio);
+                assert_close(1.0, ellipse.normal.z.abs());
+                assert_close(0.0, ellipse.start_parameter);
+                assert_close(std::f64::consts::PI * 2.0, ellipse.end_parameter);
+            }
+            _ => panic!("expected an ellipse"),
+        }
+    }
+
+    #[test]
+    fn rotate_insert() {
+        let insert = Insert {
+            location: Point::new(1.0, 0.0, 0.0),
+            rotation: 10.0,
+            x_scale_factor: 2.0,
+            ..Default::default()
+        };
+        let mut ent = Entity::new(EntityType::Insert(insert));
+        ent.transform(&TransformationMatrix::rotation(&Vector::z_axis(), 90.0));
+        match ent.specific {
+            EntityType::Insert(ref insert) => {
+                assert_point((0.0, 1.0, 0.0), &insert.location);
+                assert_close(100.0, insert.rotation);
+                assert_close(2.0, insert.x_scale_factor);
+                assert_close(1.0, insert.y_scale_factor);
+            }
+            _ => panic!("expected an insert"),
+        }
+    }
+
+    #[test]
+    fn transform_lw_polyline_uses_elevation() {
+        let mut poly = LwPolyline::default();
+        poly.vertices.push(crate::LwPolylineVertex {
+            x: 1.0,
+            y: 2.0,
+            ..Default::default()
+        });
+        let mut ent = Entity::new(EntityType::LwPolyline(poly));
+        ent.common.elevation = 5.0;
+        ent.transform(&TransformationMatrix::translation(&Vector::new(
+            1.0, 1.0, 1.0,
+        )));
+        assert_close(6.0, ent.common.elevation);
+        match ent.specific {
+            EntityType::LwPolyline(ref poly) => {
+                assert_close(2.0, poly.vertices[0].x);
+                assert_close(3.0, poly.vertices[0].y);
+            }
+            _ => panic!("expected a polyline"),
+        }
+    }
+}
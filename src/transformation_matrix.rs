@@ -1,3 +1,7 @@
+use std::ops::Mul;
+
+use crate::{Point, Vector};
+
 /// Applies a transformation to a point.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
@@ -31,6 +35,188 @@ impl TransformationMatrix {
             ..Default::default()
         }
     }
+    /// Creates a matrix that moves points by the specified offset.
+    pub fn translation(offset: &Vector) -> Self {
+        TransformationMatrix {
+            m14: offset.x,
+            m24: offset.y,
+            m34: offset.z,
+            ..TransformationMatrix::identity()
+        }
+    }
+    /// Creates a matrix that scales about the origin by the specified factor along each axis.
+    pub fn scale(x: f64, y: f64, z: f64) -> Self {
+        TransformationMatrix {
+            m11: x,
+            m22: y,
+            m33: z,
+            ..TransformationMatrix::identity()
+        }
+    }
+    /// Creates a matrix that scales uniformly about the specified point.
+    pub fn uniform_scale_about(center: &Point, factor: f64) -> Self {
+        let to_origin =
+            TransformationMatrix::translation(&Vector::new(-center.x, -center.y, -center.z));
+        let back = TransformationMatrix::translation(&Vector::new(center.x, center.y, center.z));
+        to_origin
+            .compose(&TransformationMatrix::scale(factor, factor, factor))
+            .compose(&back)
+    }
+    /// Creates a matrix that rotates counter-clockwise (when looking down the axis toward the
+    /// origin) about an axis through the origin.  The angle is in degrees.
+    pub fn rotation(axis: &Vector, angle: f64) -> Self {
+        let length = (axis.x * axis.x + axis.y * axis.y + axis.z * axis.z).sqrt();
+        if length == 0.0 {
+            return TransformationMatrix::identity();
+        }
+
+        let (x, y, z) = (axis.x / length, axis.y / length, axis.z / length);
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        TransformationMatrix {
+            m11: t * x * x + cos,
+            m12: t * x * y - sin * z,
+            m13: t * x * z + sin * y,
+            m21: t * x * y + sin * z,
+            m22: t * y * y + cos,
+            m23: t * y * z - sin * x,
+            m31: t * x * z - sin * y,
+            m32: t * y * z + sin * x,
+            m33: t * z * z + cos,
+            ..TransformationMatrix::identity()
+        }
+    }
+    /// Creates a matrix that rotates about an axis through the specified point.  The angle is in
+    /// degrees.
+    pub fn rotation_about(center: &Point, axis: &Vector, angle: f64) -> Self {
+        let to_origin =
+            TransformationMatrix::translation(&Vector::new(-center.x, -center.y, -center.z));
+        let back = TransformationMatrix::translation(&Vector::new(center.x, center.y, center.z));
+        to_origin
+            .compose(&TransformationMatrix::rotation(axis, angle))
+            .compose(&back)
+    }
+    /// Creates a matrix that reflects points across the plane through `point` with the specified
+    /// normal.
+    pub fn mirror(point: &Point, normal: &Vector) -> Self {
+        let length = (normal.x * normal.x + normal.y * normal.y + normal.z * normal.z).sqrt();
+        if length == 0.0 {
+            return TransformationMatrix::identity();
+        }
+
+        let (a, b, c) = (normal.x / length, normal.y / length, normal.z / length);
+        let d = -(a * point.x + b * point.y + c * point.z);
+        TransformationMatrix {
+            m11: 1.0 - 2.0 * a * a,
+            m12: -2.0 * a * b,
+            m13: -2.0 * a * c,
+            m14: -2.0 * a * d,
+            m21: -2.0 * a * b,
+            m22: 1.0 - 2.0 * b * b,
+            m23: -2.0 * b * c,
+            m24: -2.0 * b * d,
+            m31: -2.0 * a * c,
+            m32: -2.0 * b * c,
+            m33: 1.0 - 2.0 * c * c,
+            m34: -2.0 * c * d,
+            ..TransformationMatrix::identity()
+        }
+    }
+    /// Returns a matrix that applies `self` followed by `next`.
+    pub fn compose(&self, next: &TransformationMatrix) -> Self {
+        let a = next.rows();
+        let b = self.rows();
+        let mut result = [[0.0; 4]; 4];
+        for (i, row) in result.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+            }
+        }
+
+        TransformationMatrix::from_rows(&result)
+    }
+    /// Returns the matrix that undoes this transformation, or `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan elimination with partial pivoting
+        let mut m = self.rows();
+        let mut inv = TransformationMatrix::identity().rows();
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))
+                .unwrap_or(col);
+            if m[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+
+            m.swap(col, pivot);
+            inv.swap(col, pivot);
+            let scale = m[col][col];
+            for j in 0..4 {
+                m[col][j] /= scale;
+                inv[col][j] /= scale;
+            }
+
+            for row in 0..4 {
+                if row != col {
+                    let factor = m[row][col];
+                    for j in 0..4 {
+                        m[row][j] -= factor * m[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+
+        Some(TransformationMatrix::from_rows(&inv))
+    }
+    /// Applies the full transformation, including translation, to a point.
+    pub fn transform_point(&self, point: &Point) -> Point {
+        let x = self.m11 * point.x + self.m12 * point.y + self.m13 * point.z + self.m14;
+        let y = self.m21 * point.x + self.m22 * point.y + self.m23 * point.z + self.m24;
+        let z = self.m31 * point.x + self.m32 * point.y + self.m33 * point.z + self.m34;
+        let w = self.m41 * point.x + self.m42 * point.y + self.m43 * point.z + self.m44;
+        if w != 0.0 && w != 1.0 {
+            Point::new(x / w, y / w, z / w)
+        } else {
+            Point::new(x, y, z)
+        }
+    }
+    /// Applies the transformation to a direction; translation is ignored.
+    pub fn transform_vector(&self, vector: &Vector) -> Vector {
+        Vector::new(
+            self.m11 * vector.x + self.m12 * vector.y + self.m13 * vector.z,
+            self.m21 * vector.x + self.m22 * vector.y + self.m23 * vector.z,
+            self.m31 * vector.x + self.m32 * vector.y + self.m33 * vector.z,
+        )
+    }
+}
+
+impl Mul<&Point> for &TransformationMatrix {
+    type Output = Point;
+    fn mul(self, point: &Point) -> Point {
+        self.transform_point(point)
+    }
+}
+
+impl Mul<Point> for TransformationMatrix {
+    type Output = Point;
+    fn mul(self, point: Point) -> Point {
+        self.transform_point(&point)
+    }
+}
+
+impl Mul<&Vector> for &TransformationMatrix {
+    type Output = Vector;
+    fn mul(self, vector: &Vector) -> Vector {
+        self.transform_vector(vector)
+    }
+}
+
+impl Mul<Vector> for TransformationMatrix {
+    type Output = Vector;
+    fn mul(self, vector: Vector) -> Vector {
+        self.transform_vector(&vector)
+    }
 }
 
 // internal visibility only
@@ -71,6 +257,34 @@ impl TransformationMatrix {
 
 // private implementation
 impl TransformationMatrix {
+    fn rows(&self) -> [[f64; 4]; 4] {
+        [
+            [self.m11, self.m12, self.m13, self.m14],
+            [self.m21, self.m22, self.m23, self.m24],
+            [self.m31, self.m32, self.m33, self.m34],
+            [self.m41, self.m42, self.m43, self.m44],
+        ]
+    }
+    fn from_rows(rows: &[[f64; 4]; 4]) -> Self {
+        TransformationMatrix {
+            m11: rows[0][0],
+            m12: rows[0][1],
+            m13: rows[0][2],
+            m14: rows[0][3],
+            m21: rows[1][0],
+            m22: rows[1][1],
+            m23: rows[1][2],
+            m24: rows[1][3],
+            m31: rows[2][0],
+            m32: rows[2][1],
+            m33: rows[2][2],
+            m34: rows[2][3],
+            m41: rows[3][0],
+            m42: rows[3][1],
+            m43: rows[3][2],
+            m44: rows[3][3],
+        }
+    }
     fn value_or_default(values: &[f64], index: usize) -> f64 {
         if values.len() > index {
             values[index]
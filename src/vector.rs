@@ -1,4 +1,6 @@
-use crate::{CodePair, DxfError, DxfResult};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::{CodePair, DxfError, DxfResult, Point};
 
 /// Represents a simple vector in Cartesian space.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -33,6 +35,47 @@ impl Vector {
     pub fn z_axis() -> Vector {
         Vector::new(0.0, 0.0, 1.0)
     }
+    /// Returns the dot product of this vector and `other`.
+    pub fn dot(&self, other: &Vector) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+    /// Returns the cross product of this vector and `other`.
+    pub fn cross(&self, other: &Vector) -> Vector {
+        Vector::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+    /// Returns the length of the vector.
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+    /// Returns a vector in the same direction with a length of 1, or the zero vector unchanged.
+    pub fn normalized(&self) -> Vector {
+        let length = self.length();
+        if length == 0.0 {
+            self.clone()
+        } else {
+            Vector::new(self.x / length, self.y / length, self.z / length)
+        }
+    }
+    /// Returns the angle between this vector and `other`, in degrees from 0 to 180.
+    pub fn angle_between(&self, other: &Vector) -> f64 {
+        self.cross(other)
+            .length()
+            .atan2(self.dot(other))
+            .to_degrees()
+    }
+    /// Returns the vector a fraction `t` of the way from this vector to `other`, where 0 gives
+    /// this vector and 1 gives `other`.
+    pub fn lerp(&self, other: &Vector, t: f64) -> Vector {
+        self + &((other - self) * t)
+    }
+    /// Returns the point at the end of this vector when it starts at the origin.
+    pub fn to_point(&self) -> Point {
+        Point::new(self.x, self.y, self.z)
+    }
     pub(crate) fn set(&mut self, pair: &CodePair) -> DxfResult<()> {
         match pair.code {
             10 => self.x = pair.assert_f64()?,
@@ -49,3 +92,124 @@ impl Vector {
         Ok(())
     }
 }
+
+impl From<Point> for Vector {
+    fn from(point: Point) -> Self {
+        Vector::new(point.x, point.y, point.z)
+    }
+}
+
+impl Add<&Vector> for &Vector {
+    type Output = Vector;
+    fn add(self, other: &Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Add<Vector> for Vector {
+    type Output = Vector;
+    fn add(self, other: Vector) -> Vector {
+        &self + &other
+    }
+}
+
+impl Sub<&Vector> for &Vector {
+    type Output = Vector;
+    fn sub(self, other: &Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Sub<Vector> for Vector {
+    type Output = Vector;
+    fn sub(self, other: Vector) -> Vector {
+        &self - &other
+    }
+}
+
+impl Neg for &Vector {
+    type Output = Vector;
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+    fn neg(self) -> Vector {
+        -&self
+    }
+}
+
+impl Mul<f64> for &Vector {
+    type Output = Vector;
+    fn mul(self, scale: f64) -> Vector {
+        Vector::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl Mul<f64> for Vector {
+    type Output = Vector;
+    fn mul(self, scale: f64) -> Vector {
+        &self * scale
+    }
+}
+
+impl Mul<&Vector> for f64 {
+    type Output = Vector;
+    fn mul(self, vector: &Vector) -> Vector {
+        vector * self
+    }
+}
+
+impl Mul<Vector> for f64 {
+    type Output = Vector;
+    fn mul(self, vector: Vector) -> Vector {
+        &vector * self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn vector_arithmetic() {
+        let a = Vector::new(1.0, 2.0, 3.0);
+        let b = Vector::new(4.0, 5.0, 6.0);
+        assert_eq!(Vector::new(5.0, 7.0, 9.0), &a + &b);
+        assert_eq!(Vector::new(3.0, 3.0, 3.0), b.clone() - a.clone());
+        assert_eq!(Vector::new(-1.0, -2.0, -3.0), -&a);
+        assert_eq!(Vector::new(2.0, 4.0, 6.0), &a * 2.0);
+        assert_eq!(Vector::new(2.0, 4.0, 6.0), 2.0 * a.clone());
+        assert_eq!(32.0, a.dot(&b));
+        assert_eq!(Vector::z_axis(), Vector::x_axis().cross(&Vector::y_axis()));
+        assert_eq!(Vector::new(2.5, 3.5, 4.5), a.lerp(&b, 0.5));
+        assert_eq!(a, Vector::from(a.to_point()));
+    }
+
+    #[test]
+    fn vector_length_and_angle() {
+        let v = Vector::new(3.0, 0.0, 4.0);
+        assert_eq!(5.0, v.length());
+        assert_eq!(Vector::new(0.6, 0.0, 0.8), v.normalized());
+        assert_eq!(Vector::zero(), Vector::zero().normalized());
+        assert_eq!(90.0, Vector::x_axis().angle_between(&Vector::y_axis()));
+        assert_eq!(180.0, Vector::x_axis().angle_between(&-Vector::x_axis()));
+        assert!((45.0 - Vector::x_axis().angle_between(&Vector::new(1.0, 1.0, 0.0))).abs() < 1e-12);
+    }
+
+    #[test]
+    fn multiply_by_matrix() {
+        let matrix = TransformationMatrix::translation(&Vector::new(1.0, 2.0, 3.0))
+            .compose(&TransformationMatrix::scale(2.0, 2.0, 2.0));
+        assert_eq!(
+            Point::new(4.0, 6.0, 8.0),
+            &matrix * &Point::new(1.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            Vector::new(2.0, 2.0, 2.0),
+            matrix * Vector::new(1.0, 1.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,498 @@
+// Conversion of entities between drawing versions
+
+use std::fmt;
+
+use crate::audit::unsupported_fields;
+use crate::bounding_box::MTEXT_LINE_SPACING;
+use crate::drawing::AUTO_REPLACE_HANDLE;
+use crate::entities::*;
+use crate::enums::*;
+use crate::{ocs_to_wcs_matrix, wcs_to_ocs_matrix, Drawing, Handle, Point, Vector};
+
+/// Information that was lost by `Drawing::convert_to_version`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConversionLoss {
+    /// The entity was replaced by 3D `Polyline`s approximating its curves.
+    Approximated { item: Handle, type_string: String },
+    /// The `MText` was replaced by a `Text` for each line, losing its formatting and wrapping.
+    TextFormattingLost { item: Handle },
+    /// The `Hatch` was replaced by `Polyline`s of its boundaries, losing its fill.
+    FillLost { item: Handle },
+    /// The entity has no equivalent in the new version and was removed.
+    Dropped { item: Handle, type_string: String },
+    /// An entity property that can't be saved with the new version was reset to its default.
+    FieldCleared { item: Handle, field: &'static str },
+}
+
+impl fmt::Display for ConversionLoss {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConversionLoss::Approximated {
+                item,
+                ref type_string,
+            } => write!(
+                formatter,
+                "entity {} of type {type_string} was approximated with polylines",
+                item.as_string()
+            ),
+            ConversionLoss::TextFormattingLost { item } => write!(
+                formatter,
+                "the formatting of MTEXT {} was lost",
+                item.as_string()
+            ),
+            ConversionLoss::FillLost { item } => {
+                write!(formatter, "the fill of HATCH {} was lost", item.as_string())
+            }
+            ConversionLoss::Dropped {
+                item,
+                ref type_string,
+            } => write!(
+                formatter,
+                "entity {} of type {type_string} was removed",
+                item.as_string()
+            ),
+            ConversionLoss::FieldCleared { item, field } => write!(
+                formatter,
+                "{field} of entity {} was cleared",
+                item.as_string()
+            ),
+        }
+    }
+}
+
+impl Drawing {
+    /// Sets `$ACADVER` to `version` and replaces the entities that can't be saved with that
+    /// version with equivalents that can, e.g., `LwPolyline`s become `Polyline`s, curves such as
+    /// `Ellipse`s and `Spline`s become 3D `Polyline`s within `tolerance` of the original, `MText`
+    /// becomes a `Text` per line, and `Hatch`es become the `Polyline`s of their boundaries.
+    /// Entities without an equivalent are removed and entity properties that can't be saved are
+    /// reset.
+    ///
+    /// The entities of blocks are converted as well.  Returns whatever information was lost;
+    /// converting a `LwPolyline` is lossless and isn't reported.
+    pub fn convert_to_version(
+        &mut self,
+        version: AcadVersion,
+        tolerance: f64,
+    ) -> Vec<ConversionLoss> {
+        self.header.version = version;
+        let mut losses = vec![];
+
+        let entities = self.take_entities();
+        for mut entity in entities {
+            match convert_entity(&mut entity, version, tolerance, &mut losses) {
+                Some(replacements) => {
+                    for replacement in replacements {
+                        self.add_entity(replacement);
+                    }
+                }
+                None => {
+                    self.add_entity_no_handle_set(entity);
+                }
+            }
+        }
+
+        for index in 0..self.blocks().count() {
+            let entities = match self.blocks_mut().nth(index) {
+                Some(block) => std::mem::take(&mut block.entities),
+                None => break,
+            };
+            let mut converted = vec![];
+            for mut entity in entities {
+                match convert_entity(&mut entity, version, tolerance, &mut losses) {
+                    Some(replacements) => {
+                        for mut replacement in replacements {
+                            self.set_entity_handles(&mut replacement);
+                            converted.push(replacement);
+                        }
+                    }
+                    None => converted.push(entity),
+                }
+            }
+            if let Some(block) = self.blocks_mut().nth(index) {
+                block.entities = converted;
+            }
+        }
+
+        losses
+    }
+}
+
+/// Clears the properties of `entity` that can't be saved with `version`, and if the entity itself
+/// can't be saved, returns the entities that replace it, which need new handles.
+fn convert_entity(
+    entity: &mut Entity,
+    version: AcadVersion,
+    tolerance: f64,
+    losses: &mut Vec<ConversionLoss>,
+) -> Option<Vec<Entity>> {
+    let item = entity.common.handle;
+    for (field, _) in unsupported_fields(&entity.common, version) {
+        clear_field(&mut entity.common, field);
+        losses.push(ConversionLoss::FieldCleared { item, field });
+    }
+
+    if entity.specific.is_supported_on_version(version) {
+        return None;
+    }
+
+    let mut replacements = match entity.specific {
+        EntityType::LwPolyline(ref poly) => vec![polyline_from_lw_polyline(&entity.common, poly)],
+        EntityType::MText(ref mtext) => {
+            losses.push(ConversionLoss::TextFormattingLost { item });
+            text_from_mtext(mtext)
+                .into_iter()
+                .map(EntityType::Text)
+                .map(|specific| Entity {
+                    common: entity.common.clone(),
+                    specific,
+                })
+                .collect()
+        }
+        _ => {
+            let polylines = entity
+                .tessellate(tolerance)
+                .into_iter()
+                .filter(|points| points.len() > 1)
+                .map(|points| EntityType::Polyline(polyline_from_points(points)))
+                .map(|specific| Entity {
+                    common: entity.common.clone(),
+                    specific,
+                })
+                .collect::<Vec<_>>();
+            let type_string = String::from(entity.specific.to_type_string());
+            losses.push(match entity.specific {
+                EntityType::Hatch(_) if !polylines.is_empty() => ConversionLoss::FillLost { item },
+                _ if !polylines.is_empty() => ConversionLoss::Approximated { item, type_string },
+                _ => ConversionLoss::Dropped { item, type_string },
+            });
+            polylines
+        }
+    };
+
+    for replacement in &mut replacements {
+        replacement.common.__owner_handle = Handle::empty();
+        if let EntityType::Polyline(_) = replacement.specific {
+            // the polyline's location carries the elevation
+            replacement.common.elevation = 0.0;
+        }
+    }
+
+    Some(replacements)
+}
+
+/// Resets an entity property returned by `unsupported_fields()` to its default.
+fn clear_field(common: &mut EntityCommon, field: &str) {
+    match field {
+        "is_visible" => common.is_visible = true,
+        "line_type_scale" => common.line_type_scale = 1.0,
+        "lineweight_enum_value" => common.lineweight_enum_value = 0,
+        "color_24_bit" => common.color_24_bit = 0,
+        "color_name" => common.color_name.clear(),
+        "transparency" => common.transparency = 0,
+        _ => (),
+    }
+}
+
+fn polyline_from_lw_polyline(common: &EntityCommon, lw: &LwPolyline) -> Entity {
+    let mut poly = Polyline {
+        location: Point::new(0.0, 0.0, common.elevation),
+        thickness: lw.thickness,
+        default_starting_width: lw.constant_width,
+        default_ending_width: lw.constant_width,
+        normal: lw.extrusion_direction.clone(),
+        ..Default::default()
+    };
+    poly.set_is_closed(lw.is_closed());
+    for v in &lw.vertices {
+        let (starting_width, ending_width) = if lw.constant_width != 0.0 {
+            (lw.constant_width, lw.constant_width)
+        } else {
+            (v.starting_width, v.ending_width)
+        };
+        let vertex = Vertex {
+            location: Point::new(v.x, v.y, 0.0),
+            starting_width,
+            ending_width,
+            bulge: v.bulge,
+            ..Default::default()
+        };
+        poly.__vertices_and_handles
+            .push((vertex, AUTO_REPLACE_HANDLE));
+    }
+    Entity {
+        common: common.clone(),
+        specific: EntityType::Polyline(poly),
+    }
+}
+
+/// Creates a 3D polyline through `points`, closing it if the last point repeats the first.
+fn polyline_from_points(mut points: Vec<Point>) -> Polyline {
+    let mut poly = Polyline::default();
+    poly.set_is_3d_polyline(true);
+    if points.len() > 2 && points.first() == points.last() {
+        points.pop();
+        poly.set_is_closed(true);
+    }
+    for location in points {
+        let mut vertex = Vertex::new(location);
+        vertex.set_is_3d_polyline_vertex(true);
+        poly.__vertices_and_handles
+            .push((vertex, AUTO_REPLACE_HANDLE));
+    }
+    poly
+}
+
+/// Creates a `Text` for each line of `mtext`, placed as the lines of the `MText` would be.
+fn text_from_mtext(mtext: &MText) -> Vec<Text> {
+    let lines = mtext_lines(mtext);
+    let height = mtext.initial_text_height;
+    let step = height * MTEXT_LINE_SPACING * mtext.line_spacing_factor;
+    let total_height = height + (lines.len().max(1) - 1) as f64 * step;
+    let (justification, anchor_y) = match mtext.attachment_point {
+        AttachmentPoint::TopLeft => (HorizontalTextJustification::Left, 1.0),
+        AttachmentPoint::TopCenter => (HorizontalTextJustification::Center, 1.0),
+        AttachmentPoint::TopRight => (HorizontalTextJustification::Right, 1.0),
+        AttachmentPoint::MiddleLeft => (HorizontalTextJustification::Left, 0.5),
+        AttachmentPoint::MiddleCenter => (HorizontalTextJustification::Center, 0.5),
+        AttachmentPoint::MiddleRight => (HorizontalTextJustification::Right, 0.5),
+        AttachmentPoint::BottomLeft => (HorizontalTextJustification::Left, 0.0),
+        AttachmentPoint::BottomCenter => (HorizontalTextJustification::Center, 0.0),
+        AttachmentPoint::BottomRight => (HorizontalTextJustification::Right, 0.0),
+    };
+
+    let normal = &mtext.extrusion_direction;
+    let x_axis = if mtext.x_axis_direction.length() == 0.0 {
+        let (sin, cos) = mtext.rotation_angle.to_radians().sin_cos();
+        ocs_to_wcs_matrix(normal).transform_vector(&Vector::new(cos, sin, 0.0))
+    } else {
+        mtext.x_axis_direction.normalized()
+    };
+    let y_axis = normal.normalized().cross(&x_axis);
+    let to_ocs = wcs_to_ocs_matrix(normal);
+    let ocs_x_axis = to_ocs.transform_vector(&x_axis);
+    let rotation = ocs_x_axis.y.atan2(ocs_x_axis.x).to_degrees();
+
+    let top = total_height * (1.0 - anchor_y);
+    lines
+        .into_iter()
+        .enumerate()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(i, value)| {
+            let baseline = top - height - i as f64 * step;
+            let location = to_ocs
+                .transform_point(&(mtext.insertion_point.clone() + y_axis.clone() * baseline));
+            Text {
+                location: location.clone(),
+                text_height: height,
+                value,
+                rotation,
+                text_style_name: mtext.text_style_name.clone(),
+                horizontal_text_justification: justification,
+                second_alignment_point: location,
+                normal: normal.clone(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Returns the lines of the value of `mtext` without formatting codes.
+fn mtext_lines(mtext: &MText) -> Vec<String> {
+    mtext
+        .plain_text()
+        .replace('\u{a0}', " ")
+        .split('\n')
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::*;
+    use crate::enums::*;
+    use crate::*;
+
+    fn drawing_with(specific: EntityType) -> Drawing {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        drawing.add_entity(Entity::new(specific));
+        drawing
+    }
+
+    fn polylines(drawing: &Drawing) -> Vec<&Polyline> {
+        drawing
+            .entities()
+            .filter_map(|e| match e.specific {
+                EntityType::Polyline(ref p) => Some(p),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lw_polyline_becomes_polyline_without_loss() {
+        let mut lw = LwPolyline::default();
+        lw.vertices.push(LwPolylineVertex {
+            x: 1.0,
+            y: 2.0,
+            bulge: 0.5,
+            ..Default::default()
+        });
+        lw.vertices.push(LwPolylineVertex {
+            x: 3.0,
+            y: 4.0,
+            ..Default::default()
+        });
+        lw.set_is_closed(true);
+        let mut drawing = drawing_with(EntityType::LwPolyline(lw));
+        let losses = drawing.convert_to_version(AcadVersion::R12, 0.01);
+        assert!(losses.is_empty());
+        assert_eq!(AcadVersion::R12, drawing.header.version);
+
+        let polys = polylines(&drawing);
+        assert_eq!(1, polys.len());
+        let poly = polys[0];
+        assert!(poly.is_closed());
+        let vertices = poly.vertices().collect::<Vec<_>>();
+        assert_eq!(2, vertices.len());
+        assert_eq!(Point::new(1.0, 2.0, 0.0), vertices[0].location);
+        assert_eq!(0.5, vertices[0].bulge);
+        assert!(poly
+            .__vertices_and_handles
+            .iter()
+            .all(|(_, h)| !h.is_empty()));
+        assert!(drawing.audit().is_empty());
+    }
+
+    #[test]
+    fn ellipse_is_approximated() {
+        let mut drawing = drawing_with(EntityType::Ellipse(Ellipse {
+            major_axis: Vector::new(2.0, 0.0, 0.0),
+            minor_axis_ratio: 0.5,
+            start_parameter: 0.0,
+            end_parameter: std::f64::consts::PI * 2.0,
+            ..Default::default()
+        }));
+        let item = drawing.entities().next().unwrap().common.handle;
+        let losses = drawing.convert_to_version(AcadVersion::R12, 0.01);
+        assert_eq!(
+            vec![ConversionLoss::Approximated {
+                item,
+                type_string: String::from("ELLIPSE"),
+            }],
+            losses
+        );
+
+        let polys = polylines(&drawing);
+        assert_eq!(1, polys.len());
+        assert!(polys[0].is_3d_polyline());
+        assert!(polys[0].is_closed());
+        assert!(polys[0].vertices().count() > 4);
+    }
+
+    #[test]
+    fn mtext_becomes_text_per_line() {
+        let mut mtext = MText {
+            insertion_point: Point::new(10.0, 20.0, 0.0),
+            initial_text_height: 1.0,
+            text: String::from("{\\fArial|b1;first}\\Psecond \\S1/2;"),
+            attachment_point: AttachmentPoint::TopLeft,
+            ..Default::default()
+        };
+        mtext.line_spacing_factor = 1.0;
+        let mut drawing = drawing_with(EntityType::MText(mtext));
+        let item = drawing.entities().next().unwrap().common.handle;
+        let losses = drawing.convert_to_version(AcadVersion::R12, 0.01);
+        assert_eq!(vec![ConversionLoss::TextFormattingLost { item }], losses);
+
+        let texts = drawing
+            .entities()
+            .filter_map(|e| match e.specific {
+                EntityType::Text(ref t) => Some(t),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(2, texts.len());
+        assert_eq!("first", texts[0].value);
+        assert_eq!("second 1/2", texts[1].value);
+        assert_eq!(Point::new(10.0, 19.0, 0.0), texts[0].location);
+        assert!(texts[1].location.y < texts[0].location.y);
+    }
+
+    #[test]
+    fn entities_without_equivalent_are_dropped() {
+        let mut drawing = drawing_with(EntityType::Ray(Ray::default()));
+        let item = drawing.entities().next().unwrap().common.handle;
+        let losses = drawing.convert_to_version(AcadVersion::R12, 0.01);
+        assert_eq!(
+            vec![ConversionLoss::Dropped {
+                item,
+                type_string: String::from("RAY"),
+            }],
+            losses
+        );
+        assert_eq!(0, drawing.entities().count());
+    }
+
+    #[test]
+    fn block_entities_are_converted() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        let mut block = Block {
+            name: String::from("b"),
+            ..Default::default()
+        };
+        block.entities.push(Entity::new(EntityType::Spline(Spline {
+            degree_of_curve: 1,
+            control_points: vec![Point::origin(), Point::new(1.0, 1.0, 0.0)],
+            knot_values: vec![0.0, 0.0, 1.0, 1.0],
+            ..Default::default()
+        })));
+        drawing.add_block(block);
+        let losses = drawing.convert_to_version(AcadVersion::R12, 0.01);
+        assert_eq!(1, losses.len());
+
+        let block = drawing.blocks().next().unwrap();
+        assert_eq!(1, block.entities.len());
+        let entity = &block.entities[0];
+        assert!(!entity.common.handle.is_empty());
+        match entity.specific {
+            EntityType::Polyline(ref poly) => {
+                let vertices = poly.vertices().collect::<Vec<_>>();
+                assert_eq!(Point::origin(), vertices[0].location);
+                assert_eq!(
+                    Point::new(1.0, 1.0, 0.0),
+                    vertices[vertices.len() - 1].location
+                );
+            }
+            _ => panic!("expected a polyline"),
+        }
+    }
+
+    #[test]
+    fn unsupported_fields_are_cleared() {
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2018;
+        let mut line = Entity::new(EntityType::Line(Line::default()));
+        line.common.lineweight_enum_value = 30;
+        let item = drawing.add_entity(line).common.handle;
+        let losses = drawing.convert_to_version(AcadVersion::R14, 0.01);
+        assert_eq!(
+            vec![ConversionLoss::FieldCleared {
+                item,
+                field: "lineweight_enum_value",
+            }],
+            losses
+        );
+        assert_eq!(
+            0,
+            drawing
+                .entities()
+                .next()
+                .unwrap()
+                .common
+                .lineweight_enum_value
+        );
+    }
+}
@@ -0,0 +1,497 @@
+// Typed access to the data of `XRecordObject`s
+
+use crate::extension_data::EXTENSION_DATA_GROUP;
+use crate::objects::*;
+use crate::{CodePair, DxfError, DxfResult, Handle, Point};
+
+/// Represents a named list of `XRecordObject` data, written as `102/{name` ... `102/}`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct XRecordGroup {
+    pub name: String,
+    pub items: Vec<XRecordItem>,
+}
+
+/// Represents a single piece of `XRecordObject` data or a named list.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum XRecordItem {
+    CodePair(CodePair),
+    Group(XRecordGroup),
+}
+
+/// Maps a type to and from the data of an `XRecordObject`.
+///
+/// ```
+/// # use dxf::*;
+/// # use dxf::objects::XRecordObject;
+/// struct Settings {
+///     name: String,
+///     scale: f64,
+/// }
+///
+/// impl XRecordData for Settings {
+///     fn write_xrecord(&self, builder: XRecordBuilder) -> XRecordBuilder {
+///         builder.string(1, &self.name).f64(40, self.scale)
+///     }
+///     fn read_xrecord(reader: &XRecordReader) -> DxfResult<Self> {
+///         Ok(Settings {
+///             name: reader.string(1)?,
+///             scale: reader.f64(40)?,
+///         })
+///     }
+/// }
+///
+/// let xrecord = XRecordObject::from_data(&Settings { name: String::from("a"), scale: 2.0 });
+/// let settings: Settings = xrecord.to_data().unwrap();
+/// assert_eq!(2.0, settings.scale);
+/// ```
+pub trait XRecordData: Sized {
+    /// Adds the values of `self` to `builder`.
+    fn write_xrecord(&self, builder: XRecordBuilder) -> XRecordBuilder;
+    /// Creates a value from the data of an `XRecordObject`.
+    fn read_xrecord(reader: &XRecordReader) -> DxfResult<Self>;
+}
+
+/// Builds the data of an `XRecordObject`.  The code of each value should be one that holds the
+/// value's type, e.g., 1-9 for strings, 40-59 for `f64` and 70-79 for `i16`.
+#[derive(Clone, Debug, Default)]
+pub struct XRecordBuilder {
+    pairs: Vec<CodePair>,
+}
+
+impl XRecordBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn pair(mut self, pair: CodePair) -> Self {
+        self.pairs.push(pair);
+        self
+    }
+    pub fn string(self, code: i32, val: &str) -> Self {
+        self.pair(CodePair::new_str(code, val))
+    }
+    pub fn f64(self, code: i32, val: f64) -> Self {
+        self.pair(CodePair::new_f64(code, val))
+    }
+    pub fn i16(self, code: i32, val: i16) -> Self {
+        self.pair(CodePair::new_i16(code, val))
+    }
+    pub fn i32(self, code: i32, val: i32) -> Self {
+        self.pair(CodePair::new_i32(code, val))
+    }
+    pub fn i64(self, code: i32, val: i64) -> Self {
+        self.pair(CodePair::new_i64(code, val))
+    }
+    pub fn bool(self, code: i32, val: bool) -> Self {
+        self.pair(CodePair::new_bool(code, val))
+    }
+    pub fn binary(self, code: i32, val: Vec<u8>) -> Self {
+        self.pair(CodePair::new_binary(code, val))
+    }
+    pub fn handle(self, code: i32, val: Handle) -> Self {
+        self.pair(CodePair::new_string(code, &val.as_string()))
+    }
+    /// Adds a point as the codes `code`, `code + 10`, and `code + 20`.
+    pub fn point(self, code: i32, val: &Point) -> Self {
+        self.f64(code, val.x)
+            .f64(code + 10, val.y)
+            .f64(code + 20, val.z)
+    }
+    /// Adds a value with `XRecordData`, without any surrounding group.
+    pub fn data<T: XRecordData>(self, val: &T) -> Self {
+        val.write_xrecord(self)
+    }
+    /// Adds a named list whose items are added by `build`.
+    pub fn group<F>(self, name: &str, build: F) -> Self
+    where
+        F: FnOnce(XRecordBuilder) -> XRecordBuilder,
+    {
+        let group = build(XRecordBuilder::new());
+        let mut result = self.string(EXTENSION_DATA_GROUP, &format!("{{{name}"));
+        result.pairs.extend(group.pairs);
+        result.string(EXTENSION_DATA_GROUP, "}")
+    }
+    /// Returns the code pairs that have been added.
+    pub fn into_pairs(self) -> Vec<CodePair> {
+        self.pairs
+    }
+    /// Creates an `XRecordObject` containing the added data.
+    pub fn build(self) -> XRecordObject {
+        XRecordObject {
+            data_pairs: self.pairs,
+            ..Default::default()
+        }
+    }
+}
+
+/// Queries the data of an `XRecordObject`.  Values are found by the first pair with the
+/// requested code, ignoring pairs in nested groups.
+#[derive(Clone, Copy, Debug)]
+pub struct XRecordReader<'a> {
+    items: &'a [XRecordItem],
+}
+
+impl<'a> XRecordReader<'a> {
+    pub fn new(items: &'a [XRecordItem]) -> Self {
+        XRecordReader { items }
+    }
+    /// The items being read.
+    pub fn items(&self) -> &'a [XRecordItem] {
+        self.items
+    }
+    /// Returns all code pairs, excluding those in nested groups.
+    pub fn pairs(&self) -> impl Iterator<Item = &'a CodePair> {
+        self.items.iter().filter_map(|item| match item {
+            XRecordItem::CodePair(pair) => Some(pair),
+            XRecordItem::Group(_) => None,
+        })
+    }
+    /// Returns all code pairs with the specified code, excluding those in nested groups.
+    pub fn pairs_with_code(&self, code: i32) -> impl Iterator<Item = &'a CodePair> {
+        self.pairs().filter(move |pair| pair.code == code)
+    }
+    /// Returns the first code pair with the specified code.
+    pub fn pair(&self, code: i32) -> Option<&'a CodePair> {
+        self.pairs_with_code(code).next()
+    }
+    pub fn contains(&self, code: i32) -> bool {
+        self.pair(code).is_some()
+    }
+    pub fn string(&self, code: i32) -> DxfResult<String> {
+        self.required(code)?.assert_string()
+    }
+    pub fn f64(&self, code: i32) -> DxfResult<f64> {
+        self.required(code)?.assert_f64()
+    }
+    pub fn i16(&self, code: i32) -> DxfResult<i16> {
+        self.required(code)?.assert_i16()
+    }
+    pub fn i32(&self, code: i32) -> DxfResult<i32> {
+        self.required(code)?.assert_i32()
+    }
+    pub fn i64(&self, code: i32) -> DxfResult<i64> {
+        self.required(code)?.assert_i64()
+    }
+    pub fn bool(&self, code: i32) -> DxfResult<bool> {
+        self.required(code)?.assert_bool()
+    }
+    pub fn binary(&self, code: i32) -> DxfResult<Vec<u8>> {
+        self.required(code)?.assert_binary()
+    }
+    pub fn handle(&self, code: i32) -> DxfResult<Handle> {
+        self.required(code)?.as_handle()
+    }
+    /// Reads a point from the first pair with `code` and the `code + 10` and `code + 20` pairs
+    /// that follow it.  A missing `code + 20` pair is read as a Z value of 0.
+    pub fn point(&self, code: i32) -> DxfResult<Point> {
+        let mut pairs = self.pairs().skip_while(|pair| pair.code != code);
+        let x = pairs
+            .next()
+            .ok_or(DxfError::MissingCode(code))?
+            .assert_f64()?;
+        let y = match pairs.next() {
+            Some(pair) if pair.code == code + 10 => pair.assert_f64()?,
+            Some(pair) => return Err(DxfError::UnexpectedCode(pair.code, pair.offset)),
+            None => return Err(DxfError::MissingCode(code + 10)),
+        };
+        let z = match pairs.next() {
+            Some(pair) if pair.code == code + 20 => pair.assert_f64()?,
+            _ => 0.0,
+        };
+        Ok(Point::new(x, y, z))
+    }
+    /// Reads a value with `XRecordData` from all of the items.
+    pub fn data<T: XRecordData>(&self) -> DxfResult<T> {
+        T::read_xrecord(self)
+    }
+    /// Returns all nested groups with the specified name.
+    pub fn groups(&self, name: &'a str) -> impl Iterator<Item = XRecordReader<'a>> {
+        self.items.iter().filter_map(move |item| match item {
+            XRecordItem::Group(group) if group.name == name => {
+                Some(XRecordReader::new(&group.items))
+            }
+            _ => None,
+        })
+    }
+    /// Returns the first nested group with the specified name.
+    pub fn group(&self, name: &str) -> DxfResult<XRecordReader<'a>> {
+        self.items
+            .iter()
+            .find_map(|item| match item {
+                XRecordItem::Group(group) if group.name == name => {
+                    Some(XRecordReader::new(&group.items))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| DxfError::MissingGroup(String::from(name)))
+    }
+    fn required(&self, code: i32) -> DxfResult<&'a CodePair> {
+        self.pair(code).ok_or(DxfError::MissingCode(code))
+    }
+}
+
+impl XRecordObject {
+    /// Creates an `XRecordObject` from a value with `XRecordData`.
+    pub fn from_data<T: XRecordData>(val: &T) -> Self {
+        val.write_xrecord(XRecordBuilder::new()).build()
+    }
+    /// Reads a value with `XRecordData` from the data pairs.
+    pub fn to_data<T: XRecordData>(&self) -> DxfResult<T> {
+        let items = self.items();
+        T::read_xrecord(&XRecordReader::new(&items))
+    }
+    /// Returns the data pairs with each `102/{name` ... `102/}` list collected into an
+    /// `XRecordItem::Group`.  Other `102` pairs, unmatched `102/}` pairs, and lists that aren't
+    /// closed are returned as they are.
+    pub fn items(&self) -> Vec<XRecordItem> {
+        let mut stack: Vec<(usize, XRecordGroup)> = vec![];
+        let mut items = vec![];
+        for (index, pair) in self.data_pairs.iter().enumerate() {
+            if pair.code == EXTENSION_DATA_GROUP {
+                if let Ok(value) = pair.assert_string() {
+                    if let Some(name) = value.strip_prefix('{') {
+                        let group = XRecordGroup {
+                            name: String::from(name),
+                            items: vec![],
+                        };
+                        stack.push((index, group));
+                        continue;
+                    } else if value == "}" {
+                        if let Some((_, group)) = stack.pop() {
+                            let item = XRecordItem::Group(group);
+                            match stack.last_mut() {
+                                Some((_, parent)) => parent.items.push(item),
+                                None => items.push(item),
+                            }
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            let item = XRecordItem::CodePair(pair.clone());
+            match stack.last_mut() {
+                Some((_, group)) => group.items.push(item),
+                None => items.push(item),
+            }
+        }
+
+        if let Some((start, _)) = stack.first() {
+            // unclosed lists are kept as plain pairs
+            items.extend(
+                self.data_pairs[*start..]
+                    .iter()
+                    .map(|pair| XRecordItem::CodePair(pair.clone())),
+            );
+        }
+        items
+    }
+    /// Replaces the data pairs with the specified items.
+    pub fn set_items(&mut self, items: &[XRecordItem]) {
+        self.data_pairs.clear();
+        add_item_pairs(items, &mut self.data_pairs);
+    }
+}
+
+fn add_item_pairs(items: &[XRecordItem], pairs: &mut Vec<CodePair>) {
+    for item in items {
+        match item {
+            XRecordItem::CodePair(pair) => pairs.push(pair.clone()),
+            XRecordItem::Group(group) => {
+                pairs.push(CodePair::new_string(
+                    EXTENSION_DATA_GROUP,
+                    &format!("{{{}", group.name),
+                ));
+                add_item_pairs(&group.items, pairs);
+                pairs.push(CodePair::new_str(EXTENSION_DATA_GROUP, "}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::AcadVersion;
+    use crate::objects::*;
+    use crate::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Viewport {
+        name: String,
+        center: Point,
+        zoom: f64,
+        frozen: bool,
+    }
+
+    impl XRecordData for Viewport {
+        fn write_xrecord(&self, builder: XRecordBuilder) -> XRecordBuilder {
+            builder
+                .string(1, &self.name)
+                .point(10, &self.center)
+                .f64(40, self.zoom)
+                .bool(290, self.frozen)
+        }
+        fn read_xrecord(reader: &XRecordReader) -> DxfResult<Self> {
+            Ok(Viewport {
+                name: reader.string(1)?,
+                center: reader.point(10)?,
+                zoom: reader.f64(40)?,
+                frozen: reader.bool(290)?,
+            })
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Settings {
+        version: i16,
+        viewports: Vec<Viewport>,
+    }
+
+    impl XRecordData for Settings {
+        fn write_xrecord(&self, builder: XRecordBuilder) -> XRecordBuilder {
+            self.viewports
+                .iter()
+                .fold(builder.i16(70, self.version), |builder, viewport| {
+                    builder.group("VIEWPORT", |group| group.data(viewport))
+                })
+        }
+        fn read_xrecord(reader: &XRecordReader) -> DxfResult<Self> {
+            Ok(Settings {
+                version: reader.i16(70)?,
+                viewports: reader
+                    .groups("VIEWPORT")
+                    .map(|group| group.data())
+                    .collect::<DxfResult<_>>()?,
+            })
+        }
+    }
+
+    #[test]
+    fn nested_lists_are_grouped() {
+        let xrecord = XRecordBuilder::new()
+            .string(1, "outer")
+            .group("A", |a| a.i16(70, 1).group("B", |b| b.f64(40, 2.0)))
+            .string(102, "VTR_NOT_A_LIST")
+            .build();
+        assert_eq!(8, xrecord.data_pairs.len());
+
+        let items = xrecord.items();
+        assert_eq!(
+            vec![
+                XRecordItem::CodePair(CodePair::new_str(1, "outer")),
+                XRecordItem::Group(XRecordGroup {
+                    name: String::from("A"),
+                    items: vec![
+                        XRecordItem::CodePair(CodePair::new_i16(70, 1)),
+                        XRecordItem::Group(XRecordGroup {
+                            name: String::from("B"),
+                            items: vec![XRecordItem::CodePair(CodePair::new_f64(40, 2.0))],
+                        }),
+                    ],
+                }),
+                XRecordItem::CodePair(CodePair::new_str(102, "VTR_NOT_A_LIST")),
+            ],
+            items
+        );
+
+        let mut copy = XRecordObject::default();
+        copy.set_items(&items);
+        assert_eq!(xrecord.data_pairs, copy.data_pairs);
+
+        let reader = XRecordReader::new(&items);
+        assert_eq!(
+            2.0,
+            reader
+                .group("A")
+                .unwrap()
+                .group("B")
+                .unwrap()
+                .f64(40)
+                .unwrap()
+        );
+        assert!(!reader.contains(70));
+    }
+
+    #[test]
+    fn unbalanced_lists_are_kept_as_pairs() {
+        let xrecord = XRecordBuilder::new()
+            .string(102, "}")
+            .string(1, "a")
+            .string(102, "{OPEN")
+            .i16(70, 1)
+            .build();
+        let items = xrecord.items();
+        assert_eq!(4, items.len());
+        assert!(items
+            .iter()
+            .all(|item| matches!(item, XRecordItem::CodePair(_))));
+    }
+
+    #[test]
+    fn data_round_trips_through_a_drawing() {
+        let settings = Settings {
+            version: 3,
+            viewports: vec![
+                Viewport {
+                    name: String::from("top"),
+                    center: Point::new(1.0, 2.0, 0.0),
+                    zoom: 1.5,
+                    frozen: false,
+                },
+                Viewport {
+                    name: String::from("side"),
+                    center: Point::new(-1.0, 0.0, 4.0),
+                    zoom: 0.5,
+                    frozen: true,
+                },
+            ],
+        };
+
+        let mut drawing = Drawing::new();
+        drawing.header.version = AcadVersion::R2000;
+        let dictionary = drawing.ensure_dictionary_path(&["MY_APP"]).unwrap();
+        let xrecord = Object::new(ObjectType::XRecordObject(XRecordObject::from_data(
+            &settings,
+        )));
+        drawing
+            .add_dictionary_entry(dictionary, "SETTINGS", xrecord)
+            .unwrap();
+
+        let mut buf = vec![];
+        drawing.save(&mut buf).unwrap();
+        let drawing = Drawing::load(&mut buf.as_slice()).unwrap();
+        match drawing
+            .dictionary_path(&["MY_APP", "SETTINGS"])
+            .unwrap()
+            .specific
+        {
+            ObjectType::XRecordObject(ref xr) => {
+                assert_eq!(settings, xr.to_data::<Settings>().unwrap())
+            }
+            _ => panic!("expected an xrecord"),
+        }
+    }
+
+    #[test]
+    fn missing_values_are_errors() {
+        let xrecord = XRecordBuilder::new()
+            .string(1, "a")
+            .f64(10, 1.0)
+            .f64(40, 2.0)
+            .build();
+        let items = xrecord.items();
+        let reader = XRecordReader::new(&items);
+        assert!(matches!(reader.f64(41), Err(DxfError::MissingCode(41))));
+        assert!(matches!(reader.i16(1), Err(DxfError::WrongValueType(_))));
+        assert!(matches!(
+            reader.point(10),
+            Err(DxfError::UnexpectedCode(40, _))
+        ));
+        assert!(matches!(
+            reader.group("X"),
+            Err(DxfError::MissingGroup(ref name)) if name == "X"
+        ));
+        assert!(xrecord.to_data::<Viewport>().is_err());
+    }
+}